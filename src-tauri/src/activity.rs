@@ -0,0 +1,94 @@
+// File: src-tauri/src/activity.rs
+// Description: Per-conversation activity timeline (message counts and gift volume per day / week).
+// Changes:
+// - Created file. Computed from the local message store only, so rendering a timeline never hits the daemon.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Runtime, State};
+use super::formatting::normalize_timestamp_secs;
+use super::message_store::MessageStore;
+use super::settings::SettingsError;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+// The Unix epoch was a Thursday; weeks start on Monday
+const EPOCH_WEEKDAY_OFFSET_DAYS: u64 = 3;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityBucket {
+    Day,
+    Week,
+}
+
+impl ActivityBucket {
+    // Start (UTC, unix seconds) of the bucket containing the timestamp
+    fn start_of(self, secs: u64) -> u64 {
+        let day = secs / SECS_PER_DAY;
+        match self {
+            ActivityBucket::Day => day * SECS_PER_DAY,
+            ActivityBucket::Week => {
+                let shifted = day + EPOCH_WEEKDAY_OFFSET_DAYS;
+                (shifted - shifted % 7).saturating_sub(EPOCH_WEEKDAY_OFFSET_DAYS) * SECS_PER_DAY
+            }
+        }
+    }
+
+    fn length_secs(self) -> u64 {
+        match self {
+            ActivityBucket::Day => SECS_PER_DAY,
+            ActivityBucket::Week => 7 * SECS_PER_DAY,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ActivityPoint {
+    pub bucket_start: u64, // Unix seconds (UTC)
+    pub received_count: u32,
+    pub sent_count: u32,
+    pub gifts_received: f64,
+    pub gifts_sent: f64,
+}
+
+// --- Tauri Commands ---
+
+// Activity per bucket from the first to the last message; empty buckets are included so the series is continuous
+#[tauri::command]
+pub async fn get_conversation_activity<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    conversation_id: String,
+    bucket: ActivityBucket,
+) -> Result<Vec<ActivityPoint>, SettingsError> {
+    log::debug!("get_conversation_activity command received for {} (user {}, {:?})", conversation_id, identity_i_address, bucket);
+
+    let mut points: BTreeMap<u64, ActivityPoint> = BTreeMap::new();
+    for message in store.load_conversation(&app, &identity_i_address, &conversation_id) {
+        let secs = normalize_timestamp_secs(message.timestamp);
+        if secs == 0 {
+            continue; // No timestamp yet (e.g., unconfirmed history entry)
+        }
+        let start = bucket.start_of(secs);
+        let point = points.entry(start).or_insert_with(|| ActivityPoint { bucket_start: start, ..Default::default() });
+        if message.direction == "sent" {
+            point.sent_count += 1;
+            point.gifts_sent += message.amount;
+        } else {
+            point.received_count += 1;
+            point.gifts_received += message.amount;
+        }
+    }
+
+    let (Some(&first), Some(&last)) = (points.keys().next(), points.keys().next_back()) else {
+        return Ok(Vec::new());
+    };
+    let mut start = first;
+    while start < last {
+        points.entry(start).or_insert_with(|| ActivityPoint { bucket_start: start, ..Default::default() });
+        start += bucket.length_secs();
+    }
+    Ok(points.into_values().collect())
+}
@@ -0,0 +1,134 @@
+// File: src-tauri/src/address.rs
+// Description: Chain-aware validation of recipient private addresses.
+// Changes:
+// - Created file. Recipient z-addresses are checked against the connected chain before anything is signed or sent:
+//   the bech32 prefix must match the chain's network and z_validateaddress (which decodes with the chain's own
+//   parameters) must accept it as a sapling address. Results are cached per RPC port.
+// - Added validate_transparent_address (R-address recipients of amount-only gifts, checked with validateaddress).
+
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+
+// Addresses already accepted, per RPC port
+static VALIDATED: LazyLock<Mutex<HashSet<(u16, String)>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// Transparent gift recipients already accepted, per RPC port (kept apart: they must never pass as message recipients)
+static VALIDATED_TRANSPARENT: LazyLock<Mutex<HashSet<(u16, String)>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// Network of the chain served on each RPC port (from getblockchaininfo)
+static NETWORKS: LazyLock<Mutex<HashMap<u16, Network>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Network {
+    Main,
+    Test,
+    Regtest,
+}
+
+impl Network {
+    fn from_chain(chain: &str) -> Option<Self> {
+        match chain {
+            "main" => Some(Network::Main),
+            "test" => Some(Network::Test),
+            "regtest" => Some(Network::Regtest),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Network::Main => "mainnet",
+            Network::Test => "testnet",
+            Network::Regtest => "regtest",
+        }
+    }
+}
+
+// Network a sapling address belongs to, from its bech32 human-readable part.
+// Longer prefixes first: "zs1" would otherwise never be reached for the others.
+fn address_network(address: &str) -> Option<Network> {
+    if address.starts_with("zregtestsapling1") {
+        Some(Network::Regtest)
+    } else if address.starts_with("ztestsapling1") {
+        Some(Network::Test)
+    } else if address.starts_with("zs1") {
+        Some(Network::Main)
+    } else {
+        None
+    }
+}
+
+async fn chain_network(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Result<Option<Network>, VerusRpcError> {
+    if let Some(network) = NETWORKS.lock().unwrap_or_else(|e| e.into_inner()).get(&rpc_port) {
+        return Ok(Some(*network));
+    }
+    let info: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockchaininfo", vec![]).await?;
+    let network = info.get("chain").and_then(|v| v.as_str()).and_then(Network::from_chain);
+    if let Some(network) = network {
+        NETWORKS.lock().unwrap_or_else(|e| e.into_inner()).insert(rpc_port, network);
+    }
+    Ok(network)
+}
+
+// Reject recipient addresses that aren't sapling addresses of the connected chain.
+// The prefix check only exists to give a clear error; z_validateaddress is the authority.
+pub async fn validate_recipient_address(rpc_user: &str, rpc_pass: &str, rpc_port: u16, address: &str) -> Result<(), VerusRpcError> {
+    let address = address.trim();
+    if VALIDATED.lock().unwrap_or_else(|e| e.into_inner()).contains(&(rpc_port, address.to_string())) {
+        return Ok(());
+    }
+
+    let Some(address_network) = address_network(address) else {
+        return Err(VerusRpcError::InvalidAddress(format!("{} is not a private (sapling) address", address)));
+    };
+    match chain_network(rpc_user, rpc_pass, rpc_port).await {
+        Ok(Some(chain)) if chain != address_network => {
+            log::warn!("Rejected {} address {} on a {} chain", address_network.label(), address, chain.label());
+            return Err(VerusRpcError::InvalidAddress(format!(
+                "{} is a {} address but the connected chain is on {}",
+                address,
+                address_network.label(),
+                chain.label()
+            )));
+        }
+        Ok(_) => {}
+        Err(e) => log::debug!("Chain network unknown, relying on z_validateaddress: {:?}", e),
+    }
+
+    let result: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "z_validateaddress", vec![json!(address)]).await?;
+    let valid = result.get("isvalid").and_then(|v| v.as_bool()).unwrap_or(false);
+    let address_type = result.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+    if !valid {
+        return Err(VerusRpcError::InvalidAddress(format!("{} is not a valid address on the connected chain", address)));
+    }
+    if address_type != "sapling" {
+        return Err(VerusRpcError::InvalidAddress(format!("{} is a {} address; only sapling addresses can receive messages", address, address_type)));
+    }
+
+    VALIDATED.lock().unwrap_or_else(|e| e.into_inner()).insert((rpc_port, address.to_string()));
+    Ok(())
+}
+
+// Reject gift recipients that aren't transparent R-addresses of the connected chain (validateaddress decodes
+// with the chain's parameters, so a wrong-network address is invalid)
+pub async fn validate_transparent_address(rpc_user: &str, rpc_pass: &str, rpc_port: u16, address: &str) -> Result<(), VerusRpcError> {
+    let address = address.trim();
+    if VALIDATED_TRANSPARENT.lock().unwrap_or_else(|e| e.into_inner()).contains(&(rpc_port, address.to_string())) {
+        return Ok(());
+    }
+    if !address.starts_with('R') {
+        return Err(VerusRpcError::InvalidAddress(format!("{} is not a transparent R-address", address)));
+    }
+
+    let result: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "validateaddress", vec![json!(address)]).await?;
+    let valid = result.get("isvalid").and_then(|v| v.as_bool()).unwrap_or(false);
+    let is_script = result.get("isscript").and_then(|v| v.as_bool()).unwrap_or(false);
+    if !valid || is_script {
+        return Err(VerusRpcError::InvalidAddress(format!("{} is not a valid transparent address on the connected chain", address)));
+    }
+
+    VALIDATED_TRANSPARENT.lock().unwrap_or_else(|e| e.into_inner()).insert((rpc_port, address.to_string()));
+    Ok(())
+}
@@ -0,0 +1,103 @@
+// File: src-tauri/src/api_version.rs
+// Description: Command API versioning: handshake with the frontend bundle and compatibility shims for older bundles.
+// Changes:
+// - Created file. The frontend reports the API version it was built against; bundles down to
+//   MIN_SUPPORTED_API_VERSION are served through legacy_* shim commands during staged updates.
+// - API v3: shim for load_messages_for_conversation (now returns the last-read marker).
+// - legacy_save_credentials saves without a cookie path (password auth).
+// - Removed the legacy_* shims: shipped bundles only know the original command names, and the v1 shapes they
+//   served never existed. Older shapes are now served by the original commands themselves (frontend_api_version);
+//   the version history is rebuilt from the changes the shipped frontend actually sees.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+// Bump when a command's parameters, result shape or semantics change incompatibly.
+// 1: the command set the shipped frontend was built against (it doesn't call api_handshake)
+// 2: load_conversations without a filter returns active (non-archived) conversations only;
+//    get_new_received_messages returns only messages newer than the address's sync cursor (v1 frontends merge
+//    polled messages by id, so they need no shim for it)
+pub const API_VERSION: u32 = 2;
+
+// Oldest frontend API still served (older shapes are served by the original commands)
+pub const MIN_SUPPORTED_API_VERSION: u32 = 1;
+
+// Version agreed in the last handshake (None until the frontend has called api_handshake)
+static NEGOTIATED_VERSION: Mutex<Option<u32>> = Mutex::new(None);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Compatibility {
+    Full,        // Frontend and backend speak the same API
+    Shimmed,     // Older frontend; the commands listed in the handshake answer in its older shape
+    Unsupported, // Frontend is too old or newer than this backend; it should ask the user to update
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ApiHandshake {
+    pub backend_api_version: u32,
+    pub min_supported_api_version: u32,
+    pub app_version: String,
+    pub compatibility: Compatibility,
+    pub shimmed_commands: Vec<ShimmedCommand>, // Commands that answer an older frontend in its older shape
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ShimmedCommand {
+    pub command: &'static str,
+    pub changed_in: u32,
+}
+
+// Commands that keep serving their older shape to older frontends
+const SHIMS: &[ShimmedCommand] = &[
+    ShimmedCommand { command: "load_conversations", changed_in: 2 },
+];
+
+pub fn negotiated_version() -> Option<u32> {
+    *NEGOTIATED_VERSION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// API the current frontend was built against. Bundles that never called api_handshake predate it (v1).
+pub fn frontend_api_version() -> u32 {
+    negotiated_version().unwrap_or(MIN_SUPPORTED_API_VERSION)
+}
+
+fn compatibility_for(frontend_api_version: u32) -> Compatibility {
+    if frontend_api_version == API_VERSION {
+        Compatibility::Full
+    } else if (MIN_SUPPORTED_API_VERSION..API_VERSION).contains(&frontend_api_version) {
+        Compatibility::Shimmed
+    } else {
+        Compatibility::Unsupported
+    }
+}
+
+// --- Tauri Commands ---
+
+// First call of every frontend bundle: reports the API it was built against and learns how to talk to this backend
+#[tauri::command]
+pub fn api_handshake(frontend_api_version: u32, frontend_build: Option<String>) -> ApiHandshake {
+    let compatibility = compatibility_for(frontend_api_version);
+    log::info!(
+        "api_handshake: frontend API v{} (build {}), backend API v{} -> {:?}",
+        frontend_api_version,
+        frontend_build.as_deref().unwrap_or("unknown"),
+        API_VERSION,
+        compatibility
+    );
+    if compatibility != Compatibility::Unsupported {
+        *NEGOTIATED_VERSION.lock().unwrap_or_else(|e| e.into_inner()) = Some(frontend_api_version);
+    }
+
+    let shimmed_commands = match compatibility {
+        Compatibility::Shimmed => SHIMS.iter().filter(|s| s.changed_in > frontend_api_version).cloned().collect(),
+        _ => Vec::new(),
+    };
+    ApiHandshake {
+        backend_api_version: API_VERSION,
+        min_supported_api_version: MIN_SUPPORTED_API_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        compatibility,
+        shimmed_commands,
+    }
+}
@@ -0,0 +1,310 @@
+// File: src-tauri/src/auto_topup.rs
+// Description: Automatic top-ups of the messaging (private) address from the wallet's transparent funds.
+// Changes:
+// - Created file. Opt-in per identity: after polls (throttled, never while a previous top-up is in flight) the
+//   private balance is compared against the floor; if it is below and transparent funds exist, the configured
+//   amount is shielded into the address. Every top-up gets an audit entry (kept in the store, newest last) and
+//   auto-top-up events for its start and outcome. A daily limit caps how often the rule can spend.
+// - AutoTopUpError goes into the error log when a command returns it.
+// - Uses clock::now_secs instead of a local copy.
+// - Top-ups spend only the identity's own transparent addresses (not every wallet address via "R*"), and the
+//   audit entry is reserved before sending so the daily limit holds even if recording the outcome fails.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use super::capabilities::{supports, Feature};
+use super::error_log::recorded_command_error;
+use super::events::{emit_event, AUTO_TOPUP_EVENT};
+use super::message_rpc::DEFAULT_TX_FEE;
+use super::rpc_client::VerusRpcError;
+use super::shielding::{shield_amount, source_addresses, spendable_by_source, ShieldError};
+use super::storage::{load_value, save_value, StorageError};
+use super::wallet_rpc::{get_pending_balance, wait_for_operation, DUST_THRESHOLD};
+use super::clock::now_secs;
+
+const TOPUP_STORE_PATH: &str = "auto_topup.json";
+
+// Background checks after polls run at most this often per identity
+const CHECK_INTERVAL_SECS: u64 = 10 * 60;
+
+// Audit entries kept per identity
+const MAX_AUDIT_ENTRIES: usize = 200;
+
+const TOPUP_OPERATION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutoTopUpSettings {
+    pub enabled: bool,
+    pub floor: f64,              // Top up when the private balance (including unconfirmed) is below this
+    pub amount: f64,             // Shielded per top-up (less if the transparent balance is smaller)
+    pub max_top_ups_per_day: u32,
+}
+
+impl Default for AutoTopUpSettings {
+    fn default() -> Self {
+        AutoTopUpSettings { enabled: false, floor: 0.01, amount: 0.05, max_top_ups_per_day: 3 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TopUpStatus {
+    Pending,
+    Success,
+    Failed,
+}
+
+// Audit entry, also the payload of the auto-top-up event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TopUpRecord {
+    pub timestamp: u64,
+    pub private_address: String,
+    pub private_balance: f64,     // Before the top-up
+    pub transparent_balance: f64, // Of the identity's own transparent addresses, before the top-up
+    pub floor: f64,
+    pub amount: f64,
+    pub opid: String,
+    pub status: TopUpStatus,
+    pub txid: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum AutoTopUpError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Invalid top-up settings: {0}")]
+    InvalidSettings(String),
+}
+
+recorded_command_error!(AutoTopUpError, "auto_topup");
+
+impl From<VerusRpcError> for AutoTopUpError {
+    fn from(error: VerusRpcError) -> Self {
+        AutoTopUpError::Rpc(error)
+    }
+}
+
+impl From<StorageError> for AutoTopUpError {
+    fn from(error: StorageError) -> Self {
+        AutoTopUpError::Storage(error.to_string())
+    }
+}
+
+#[derive(Default)]
+struct TopUpState {
+    last_check: u64,
+    running: bool, // Check or top-up operation in flight
+}
+
+// Identity -> top-up state (in memory)
+static STATE: LazyLock<Mutex<HashMap<String, TopUpState>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn get_audit_key(identity_i_address: &str) -> String {
+    format!("audit_{}", identity_i_address)
+}
+
+pub fn read_topup_settings<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> AutoTopUpSettings {
+    load_value(app, TOPUP_STORE_PATH, identity_i_address)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read auto top-up settings for {}: {}", identity_i_address, e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn read_audit<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Result<Vec<TopUpRecord>, StorageError> {
+    Ok(load_value(app, TOPUP_STORE_PATH, &get_audit_key(identity_i_address))?.unwrap_or_default())
+}
+
+// Insert or update (by opid) an audit entry
+fn write_audit_entry<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str, record: &TopUpRecord) -> Result<(), StorageError> {
+    update_audit_entry(app, identity_i_address, &record.opid, record)
+}
+
+// Replace the entry stored under `opid` (e.g. a reservation once the operation id is known), or add it
+fn update_audit_entry<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str, opid: &str, record: &TopUpRecord) -> Result<(), StorageError> {
+    let mut audit = read_audit(app, identity_i_address)?;
+    match audit.iter_mut().find(|r| r.opid == opid) {
+        Some(existing) => *existing = record.clone(),
+        None => audit.push(record.clone()),
+    }
+    if audit.len() > MAX_AUDIT_ENTRIES {
+        audit.drain(..audit.len() - MAX_AUDIT_ENTRIES);
+    }
+    save_value(app, TOPUP_STORE_PATH, &get_audit_key(identity_i_address), &audit)
+}
+
+fn validate_settings(settings: &AutoTopUpSettings) -> Result<(), AutoTopUpError> {
+    if !settings.floor.is_finite() || settings.floor <= 0.0 {
+        return Err(AutoTopUpError::InvalidSettings("floor must be above 0".to_string()));
+    }
+    if !settings.amount.is_finite() || settings.amount < DUST_THRESHOLD {
+        return Err(AutoTopUpError::InvalidSettings(format!("amount must be at least {}", DUST_THRESHOLD)));
+    }
+    if settings.max_top_ups_per_day == 0 {
+        return Err(AutoTopUpError::InvalidSettings("daily limit must be at least 1".to_string()));
+    }
+    Ok(())
+}
+
+// Apply the rule once. Returns the started top-up, or None if none was needed or possible.
+async fn run_top_up<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity_i_address: &str,
+    private_address: &str,
+    settings: &AutoTopUpSettings,
+) -> Result<Option<TopUpRecord>, AutoTopUpError> {
+    // Unconfirmed funds count, so a top-up on its way isn't repeated
+    let private_balance = get_pending_balance(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, private_address.to_string()).await?;
+    if private_balance >= settings.floor {
+        return Ok(None);
+    }
+    let now = now_secs();
+    let recent = read_audit(app, identity_i_address)?
+        .iter()
+        .filter(|r| r.status != TopUpStatus::Failed && now.saturating_sub(r.timestamp) < DAY_SECS)
+        .count();
+    if recent >= settings.max_top_ups_per_day as usize {
+        log::info!("Auto top-up for {} skipped: daily limit of {} reached", identity_i_address, settings.max_top_ups_per_day);
+        return Ok(None);
+    }
+    // Only the identity's own transparent addresses are spent, never other wallet funds
+    let sources = match source_addresses(rpc_user, rpc_pass, rpc_port, private_address).await {
+        Ok(sources) => sources,
+        Err(ShieldError::Rpc(e)) => return Err(e.into()),
+        Err(e) => {
+            log::debug!("Auto top-up for {} skipped: {}", identity_i_address, e);
+            return Ok(None);
+        }
+    };
+    let funds = spendable_by_source(rpc_user, rpc_pass, rpc_port, &sources).await?;
+    let transparent_balance: f64 = funds.iter().map(|(_, value, _)| value).sum();
+    // sendcurrency spends from one address; take the one that can cover the most
+    let Some((source, source_value, _)) = funds.into_iter().max_by(|(_, a, _), (_, b, _)| a.total_cmp(b)) else {
+        return Ok(None);
+    };
+    let amount = settings.amount.min(source_value - DEFAULT_TX_FEE);
+    if amount < DUST_THRESHOLD {
+        log::debug!("Auto top-up for {} skipped: no transparent funds ({})", identity_i_address, transparent_balance);
+        return Ok(None);
+    }
+
+    // The entry is written before sending, so the top-up counts towards the daily limit even if recording the
+    // outcome fails later; nothing is sent if it can't be written
+    let reservation = format!("reserved-{}", now);
+    let mut record = TopUpRecord {
+        timestamp: now,
+        private_address: private_address.to_string(),
+        private_balance,
+        transparent_balance,
+        floor: settings.floor,
+        amount,
+        opid: reservation.clone(),
+        status: TopUpStatus::Pending,
+        txid: None,
+        error: None,
+    };
+    write_audit_entry(app, identity_i_address, &record)?;
+    match shield_amount(rpc_user, rpc_pass, rpc_port, &source, private_address, amount).await {
+        Ok(operation) => record.opid = operation.opid,
+        Err(e) => {
+            record.status = TopUpStatus::Failed;
+            record.error = Some(e.to_string());
+            if let Err(storage_error) = write_audit_entry(app, identity_i_address, &record) {
+                log::warn!("Failed to record failed auto top-up for {}: {}", identity_i_address, storage_error);
+            }
+            return Err(e.into());
+        }
+    }
+    // The reservation stays pending (and counted) if this fails; the operation is still tracked
+    if let Err(e) = update_audit_entry(app, identity_i_address, &reservation, &record) {
+        log::warn!("Failed to record auto top-up {}: {}", record.opid, e);
+    }
+    log::info!("Auto top-up of {} from {} into {} started as {}", amount, source, private_address, record.opid);
+    Ok(Some(record))
+}
+
+// Background check after a poll: only if enabled, supported and not checked recently
+pub fn spawn_top_up_check<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, identity_i_address: String, private_address: String) {
+    let settings = read_topup_settings(app, &identity_i_address);
+    if !settings.enabled || !supports(rpc_port, Feature::SendCurrency) {
+        return;
+    }
+    {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state.entry(identity_i_address.clone()).or_default();
+        let now = now_secs();
+        if entry.running || now.saturating_sub(entry.last_check) < CHECK_INTERVAL_SECS {
+            return;
+        }
+        entry.running = true;
+        entry.last_check = now;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match run_top_up(&app, &rpc_user, &rpc_pass, rpc_port, &identity_i_address, &private_address, &settings).await {
+            Ok(Some(mut record)) => {
+                emit_event(&app, AUTO_TOPUP_EVENT, record.clone());
+                match wait_for_operation(&rpc_user, &rpc_pass, rpc_port, &record.opid, TOPUP_OPERATION_TIMEOUT).await {
+                    Ok(txid) => {
+                        log::info!("Auto top-up {} succeeded: {}", record.opid, txid);
+                        record.status = TopUpStatus::Success;
+                        record.txid = Some(txid);
+                    }
+                    Err(e) => {
+                        log::warn!("Auto top-up {} failed: {}", record.opid, e);
+                        record.status = TopUpStatus::Failed;
+                        record.error = Some(e.to_string());
+                    }
+                }
+                if let Err(e) = write_audit_entry(&app, &identity_i_address, &record) {
+                    log::warn!("Failed to record auto top-up {}: {}", record.opid, e);
+                }
+                emit_event(&app, AUTO_TOPUP_EVENT, record);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Auto top-up check for {} failed: {}", identity_i_address, e),
+        }
+        if let Some(entry) = STATE.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&identity_i_address) {
+            entry.running = false;
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_auto_topup_settings<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> AutoTopUpSettings {
+    log::debug!("get_auto_topup_settings command received for {}", identity_i_address);
+    read_topup_settings(&app, &identity_i_address)
+}
+
+#[tauri::command]
+pub fn set_auto_topup_settings<R: Runtime>(app: AppHandle<R>, identity_i_address: String, settings: AutoTopUpSettings) -> Result<(), AutoTopUpError> {
+    log::info!("set_auto_topup_settings command received for {}: {:?}", identity_i_address, settings);
+    validate_settings(&settings)?;
+    save_value(&app, TOPUP_STORE_PATH, &identity_i_address, &settings)?;
+    Ok(())
+}
+
+// Audit log of automatic top-ups, newest first
+#[tauri::command]
+pub fn get_auto_topup_history<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> Result<Vec<TopUpRecord>, AutoTopUpError> {
+    log::debug!("get_auto_topup_history command received for {}", identity_i_address);
+    let mut audit = read_audit(&app, &identity_i_address)?;
+    audit.reverse();
+    Ok(audit)
+}
@@ -0,0 +1,194 @@
+// File: src-tauri/src/balance_alerts.rs
+// Description: Low-balance alerts: warns before an identity's private address runs out of funds for messages.
+// Changes:
+// - Created file. Thresholds (private balance and/or Fast Messages remaining) are set per identity. After polls
+//   (throttled) the private address is compared against them; while below, low-balance events are emitted, and a
+//   low-balance notification is sent once per drop (again only after the balance recovered in between).
+// - BalanceAlertError goes into the error log when a command returns it.
+// - Dropped the private now_secs in favour of clock::now_secs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::error_log::recorded_command_error;
+use super::events::{emit_event, LOW_BALANCE_EVENT, LOW_BALANCE_NOTIFICATION_EVENT};
+use super::rpc_client::VerusRpcError;
+use super::storage::{load_value, save_value, StorageError};
+use super::wallet_rpc::{get_private_balance, get_utxo_info};
+use super::clock::now_secs;
+
+const BALANCE_ALERT_STORE_PATH: &str = "balance_alerts.json";
+
+// Background checks after polls run at most this often per identity
+const CHECK_INTERVAL_SECS: u64 = 5 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BalanceAlertSettings {
+    pub enabled: bool,
+    pub min_balance: Option<f64>,       // Warn below this private balance
+    pub min_fast_messages: Option<u32>, // Warn below this many usable notes (Fast Messages)
+}
+
+// Payload of the low-balance and low-balance-notification events
+#[derive(Serialize, Debug, Clone)]
+pub struct LowBalanceWarning {
+    pub identity_i_address: String,
+    pub address: String,
+    pub balance: f64,
+    pub fast_messages_remaining: u32,
+    pub below_min_balance: bool,
+    pub below_min_fast_messages: bool,
+    pub message: String, // e.g. "Below 10 fast messages remaining"
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum BalanceAlertError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Invalid alert settings: {0}")]
+    InvalidSettings(String),
+}
+
+recorded_command_error!(BalanceAlertError, "balance_alerts");
+
+impl From<VerusRpcError> for BalanceAlertError {
+    fn from(error: VerusRpcError) -> Self {
+        BalanceAlertError::Rpc(error)
+    }
+}
+
+impl From<StorageError> for BalanceAlertError {
+    fn from(error: StorageError) -> Self {
+        BalanceAlertError::Storage(error.to_string())
+    }
+}
+
+#[derive(Default)]
+struct AlertState {
+    last_check: u64,
+    notified: bool, // Notification sent for the current drop
+}
+
+// Identity -> alert state (in memory)
+static STATE: LazyLock<Mutex<HashMap<String, AlertState>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn read_alert_settings<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> BalanceAlertSettings {
+    load_value(app, BALANCE_ALERT_STORE_PATH, identity_i_address)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read balance alert settings for {}: {}", identity_i_address, e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn validate_settings(settings: &BalanceAlertSettings) -> Result<(), BalanceAlertError> {
+    if let Some(min_balance) = settings.min_balance {
+        if !min_balance.is_finite() || min_balance <= 0.0 {
+            return Err(BalanceAlertError::InvalidSettings("minimum balance must be above 0".to_string()));
+        }
+    }
+    if settings.min_fast_messages == Some(0) {
+        return Err(BalanceAlertError::InvalidSettings("Fast Messages threshold must be at least 1".to_string()));
+    }
+    if settings.enabled && settings.min_balance.is_none() && settings.min_fast_messages.is_none() {
+        return Err(BalanceAlertError::InvalidSettings("set a balance or Fast Messages threshold".to_string()));
+    }
+    Ok(())
+}
+
+// Compare the private address against the thresholds. None while it is above all of them.
+pub async fn check_balance(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity_i_address: &str,
+    address: &str,
+    settings: &BalanceAlertSettings,
+) -> Result<Option<LowBalanceWarning>, BalanceAlertError> {
+    let balance = get_private_balance(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, address.to_string()).await?;
+    let info = get_utxo_info(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, address.to_string()).await?;
+
+    let below_min_balance = settings.min_balance.is_some_and(|min| balance < min);
+    let below_min_fast_messages = settings.min_fast_messages.is_some_and(|min| info.usable_utxos < min);
+    let message = match (settings.min_fast_messages, settings.min_balance) {
+        (Some(min), _) if below_min_fast_messages => format!("Below {} fast messages remaining", min),
+        (_, Some(min)) if below_min_balance => format!("Private balance below {}", min),
+        _ => return Ok(None),
+    };
+    Ok(Some(LowBalanceWarning {
+        identity_i_address: identity_i_address.to_string(),
+        address: address.to_string(),
+        balance,
+        fast_messages_remaining: info.usable_utxos,
+        below_min_balance,
+        below_min_fast_messages,
+        message,
+    }))
+}
+
+// Background check after a poll: only if enabled and not checked recently
+pub fn spawn_balance_check<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, identity_i_address: String, address: String) {
+    let settings = read_alert_settings(app, &identity_i_address);
+    if !settings.enabled {
+        return;
+    }
+    {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state.entry(identity_i_address.clone()).or_default();
+        let now = now_secs();
+        if now.saturating_sub(entry.last_check) < CHECK_INTERVAL_SECS {
+            return;
+        }
+        entry.last_check = now;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let warning = match check_balance(&rpc_user, &rpc_pass, rpc_port, &identity_i_address, &address, &settings).await {
+            Ok(warning) => warning,
+            Err(e) => {
+                log::warn!("Balance check for {} failed: {}", identity_i_address, e);
+                return;
+            }
+        };
+        let notify = {
+            let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = state.entry(identity_i_address.clone()).or_default();
+            // Recovering above the thresholds re-arms the notification
+            let notify = warning.is_some() && !entry.notified;
+            entry.notified = warning.is_some();
+            notify
+        };
+        let Some(warning) = warning else { return };
+        log::info!("Low balance for {}: {}", identity_i_address, warning.message);
+        if notify {
+            emit_event(&app, LOW_BALANCE_NOTIFICATION_EVENT, warning.clone());
+        }
+        emit_event(&app, LOW_BALANCE_EVENT, warning);
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_balance_alert_settings<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> BalanceAlertSettings {
+    log::debug!("get_balance_alert_settings command received for {}", identity_i_address);
+    read_alert_settings(&app, &identity_i_address)
+}
+
+#[tauri::command]
+pub fn set_balance_alert_settings<R: Runtime>(app: AppHandle<R>, identity_i_address: String, settings: BalanceAlertSettings) -> Result<(), BalanceAlertError> {
+    log::info!("set_balance_alert_settings command received for {}: {:?}", identity_i_address, settings);
+    validate_settings(&settings)?;
+    save_value(&app, BALANCE_ALERT_STORE_PATH, &identity_i_address, &settings)?;
+    // New thresholds take effect on the next poll
+    if let Some(entry) = STATE.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&identity_i_address) {
+        entry.last_check = 0;
+        entry.notified = false;
+    }
+    Ok(())
+}
@@ -0,0 +1,176 @@
+// File: src-tauri/src/blocklist.rs
+// Description: Persisted sender blocklist (blocklist.json).
+// Changes:
+// - Created file. Memos from blocked VerusIDs are dropped before signature verification, so they cost no
+//   verifymessage call and never reach the UI.
+// - Blocked senders are resolved to their i-address when blocking (entries from before are resolved when the list
+//   is read). Claimed senders match by name or i-address before verification, and verified senders are resolved
+//   to their i-address, so a blocked identity can't get through by writing its name differently.
+// - BlocklistError goes into the error log when a command returns it.
+// - Timestamps come from clock::now_secs (the local helper is gone).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::credentials::load_credentials;
+use super::error_log::recorded_command_error;
+use super::response_cache::make_cached_rpc_call;
+use super::storage::{load_value, save_value};
+use super::clock::now_secs;
+
+const BLOCKLIST_STORE_PATH: &str = "blocklist.json";
+const BLOCKLIST_KEY: &str = "blocked";
+
+// Normalized ids of blocked senders, consulted by the memo parser
+static BLOCKED_SENDERS: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// i-addresses of blocked senders (case-sensitive, not normalized)
+static BLOCKED_ADDRESSES: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockedSender {
+    pub verus_id: String, // As entered by the user, e.g. "spammer@"
+    pub blocked_at: u64,
+    #[serde(default)]
+    pub i_address: Option<String>, // None until the daemon resolved the name
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum BlocklistError {
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Invalid VerusID: {0}")]
+    InvalidId(String),
+}
+
+recorded_command_error!(BlocklistError, "blocklist");
+
+impl From<super::storage::StorageError> for BlocklistError {
+    fn from(error: super::storage::StorageError) -> Self {
+        BlocklistError::Storage(error.to_string())
+    }
+}
+
+// VerusID names are case-insensitive and the trailing '@' is optional when entered
+fn normalize_id(verus_id: &str) -> String {
+    let id = verus_id.trim().to_lowercase();
+    if id.ends_with('@') { id } else { format!("{}@", id) }
+}
+
+// Claimed sender (name or i-address) is blocked, without asking the daemon
+pub fn is_blocked(sender_id: &str) -> bool {
+    BLOCKED_ADDRESSES.lock().unwrap_or_else(|e| e.into_inner()).contains(sender_id.trim().trim_end_matches('@'))
+        || BLOCKED_SENDERS.lock().unwrap_or_else(|e| e.into_inner()).contains(&normalize_id(sender_id))
+}
+
+async fn resolve_i_address(rpc_user: &str, rpc_pass: &str, rpc_port: u16, verus_id: &str) -> Option<String> {
+    match make_cached_rpc_call::<Value>(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(verus_id)]).await {
+        Ok(identity) => identity.pointer("/identity/identityaddress").and_then(|v| v.as_str()).map(String::from),
+        Err(e) => {
+            log::debug!("Could not resolve {} for the blocklist: {:?}", verus_id, e);
+            None
+        }
+    }
+}
+
+// A verified sender is blocked: by name, or by the i-address its name resolves to
+pub async fn is_blocked_identity(rpc_user: &str, rpc_pass: &str, rpc_port: u16, sender_id: &str) -> bool {
+    if is_blocked(sender_id) {
+        return true;
+    }
+    if BLOCKED_ADDRESSES.lock().unwrap_or_else(|e| e.into_inner()).is_empty() {
+        return false;
+    }
+    resolve_i_address(rpc_user, rpc_pass, rpc_port, sender_id)
+        .await
+        .is_some_and(|i_address| BLOCKED_ADDRESSES.lock().unwrap_or_else(|e| e.into_inner()).contains(&i_address))
+}
+
+fn set_blocked(entries: &[BlockedSender]) {
+    *BLOCKED_SENDERS.lock().unwrap_or_else(|e| e.into_inner()) = entries.iter().map(|b| normalize_id(&b.verus_id)).collect();
+    *BLOCKED_ADDRESSES.lock().unwrap_or_else(|e| e.into_inner()) = entries.iter().filter_map(|b| b.i_address.clone()).collect();
+}
+
+// Fill in the i-address of entries that don't have one yet. Returns true if any was resolved.
+async fn resolve_entries<R: Runtime>(app: &AppHandle<R>, entries: &mut [BlockedSender]) -> bool {
+    if entries.iter().all(|b| b.i_address.is_some()) {
+        return false;
+    }
+    let creds = match load_credentials(app.clone()).await {
+        Ok(creds) => creds,
+        Err(e) => {
+            log::debug!("Blocked senders not resolved, no credentials: {}", e);
+            return false;
+        }
+    };
+    let mut resolved = false;
+    for entry in entries.iter_mut().filter(|b| b.i_address.is_none()) {
+        entry.i_address = resolve_i_address(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &entry.verus_id).await;
+        resolved |= entry.i_address.is_some();
+    }
+    resolved
+}
+
+fn read_blocklist<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<BlockedSender>, BlocklistError> {
+    Ok(load_value(app, BLOCKLIST_STORE_PATH, BLOCKLIST_KEY)?.unwrap_or_default())
+}
+
+fn write_blocklist<R: Runtime>(app: &AppHandle<R>, entries: &[BlockedSender]) -> Result<(), BlocklistError> {
+    save_value(app, BLOCKLIST_STORE_PATH, BLOCKLIST_KEY, &entries)?;
+    set_blocked(entries);
+    Ok(())
+}
+
+// Load the persisted blocklist at startup
+pub fn load_blocklist<R: Runtime>(app: &AppHandle<R>) {
+    match read_blocklist(app) {
+        Ok(entries) => set_blocked(&entries),
+        Err(e) => log::warn!("Failed to load sender blocklist: {}", e),
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn get_blocked_senders<R: Runtime>(app: AppHandle<R>) -> Result<Vec<BlockedSender>, BlocklistError> {
+    log::debug!("get_blocked_senders command received");
+    let mut entries = read_blocklist(&app)?;
+    if resolve_entries(&app, &mut entries).await {
+        write_blocklist(&app, &entries)?;
+    }
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn block_sender<R: Runtime>(app: AppHandle<R>, verus_id: String) -> Result<Vec<BlockedSender>, BlocklistError> {
+    log::info!("block_sender command received for {}", verus_id);
+    let id = verus_id.trim();
+    if id.is_empty() || id.trim_end_matches('@').is_empty() {
+        return Err(BlocklistError::InvalidId(verus_id));
+    }
+
+    let mut entries = read_blocklist(&app)?;
+    let added = entries.iter().all(|b| normalize_id(&b.verus_id) != normalize_id(id));
+    if added {
+        entries.push(BlockedSender { verus_id: id.to_string(), blocked_at: now_secs(), i_address: None });
+    }
+    if resolve_entries(&app, &mut entries).await || added {
+        write_blocklist(&app, &entries)?;
+    }
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn unblock_sender<R: Runtime>(app: AppHandle<R>, verus_id: String) -> Result<Vec<BlockedSender>, BlocklistError> {
+    log::info!("unblock_sender command received for {}", verus_id);
+    let mut entries = read_blocklist(&app)?;
+    let count = entries.len();
+    entries.retain(|b| normalize_id(&b.verus_id) != normalize_id(&verus_id));
+    if entries.len() != count {
+        write_blocklist(&app, &entries)?;
+    }
+    Ok(entries)
+}
@@ -0,0 +1,169 @@
+// File: src-tauri/src/capabilities.rs
+// Description: Per-chain feature capability matrix, probed when connecting to a daemon.
+// Changes:
+// - Created file. Capabilities are detected via `help <method>`, cached per chain (capabilities.json, keyed by
+//   chain id and re-probed when the daemon version changes) and consulted by commands through require_feature.
+// - method_available is public (used to pick between alternative RPC methods, e.g. for wallet rescans).
+// - Uses clock::now_secs instead of a local copy.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::storage::{load_value, save_value};
+use super::clock::now_secs;
+
+const CAPABILITIES_STORE_PATH: &str = "capabilities.json";
+const CAPABILITIES_KEY: &str = "chains";
+
+// Prefix of the daemon's `help` output for methods it doesn't know
+const UNKNOWN_COMMAND_TEXT: &str = "help: unknown command";
+
+// Capabilities of the chain served on each RPC port (filled at connect time)
+static ACTIVE_CAPABILITIES: LazyLock<Mutex<HashMap<u16, ChainCapabilities>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    SendCurrency,    // sendcurrency (multi-currency sends)
+    ViewTransaction, // z_viewtransaction (sent history recovery, payment proofs, integrity audit)
+    Offers,          // makeoffer / getoffers
+    Conversions,     // Basket currency state and conversion estimates
+}
+
+impl Feature {
+    fn label(self) -> &'static str {
+        match self {
+            Feature::SendCurrency => "Currency sends",
+            Feature::ViewTransaction => "Viewing shielded transactions",
+            Feature::Offers => "Offers",
+            Feature::Conversions => "Currency conversions",
+        }
+    }
+
+    // RPC methods that must all exist for the feature to be usable
+    fn required_methods(self) -> &'static [&'static str] {
+        match self {
+            Feature::SendCurrency => &["sendcurrency"],
+            Feature::ViewTransaction => &["z_viewtransaction"],
+            Feature::Offers => &["makeoffer", "getoffers"],
+            Feature::Conversions => &["getcurrencystate", "estimateconversion"],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChainCapabilities {
+    pub chain_id: String,
+    pub chain_name: String,
+    pub daemon_version: u64,
+    pub send_currency: bool,
+    pub view_transaction: bool,
+    pub offers: bool,
+    pub conversions: bool,
+    pub probed_at: u64,
+}
+
+impl ChainCapabilities {
+    pub fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::SendCurrency => self.send_currency,
+            Feature::ViewTransaction => self.view_transaction,
+            Feature::Offers => self.offers,
+            Feature::Conversions => self.conversions,
+        }
+    }
+}
+
+pub async fn method_available(rpc_user: &str, rpc_pass: &str, rpc_port: u16, method: &str) -> Result<bool, VerusRpcError> {
+    match make_rpc_call::<String>(rpc_user, rpc_pass, rpc_port, "help", vec![json!(method)]).await {
+        Ok(help) => Ok(!help.starts_with(UNKNOWN_COMMAND_TEXT)),
+        Err(VerusRpcError::Rpc { code: -32601, .. }) => Ok(false), // RPC_METHOD_NOT_FOUND
+        Err(e) => Err(e),
+    }
+}
+
+async fn feature_available(rpc_user: &str, rpc_pass: &str, rpc_port: u16, feature: Feature) -> Result<bool, VerusRpcError> {
+    for method in feature.required_methods() {
+        if !method_available(rpc_user, rpc_pass, rpc_port, method).await? {
+            log::info!("{} not available on this chain (missing {})", feature.label(), method);
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Probe the daemon (or reuse the cached result for its chain) and make it the active capability set for the port
+pub async fn probe_chain_capabilities<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+) -> Result<ChainCapabilities, VerusRpcError> {
+    let info: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "getinfo", vec![]).await?;
+    let chain_name = info.get("name").and_then(|v| v.as_str()).unwrap_or("unknown").to_string();
+    let chain_id = info.get("chainid").and_then(|v| v.as_str()).map(str::to_string).unwrap_or_else(|| chain_name.clone());
+    let daemon_version = info.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+
+    let mut cached: HashMap<String, ChainCapabilities> = load_value(app, CAPABILITIES_STORE_PATH, CAPABILITIES_KEY)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load capability cache: {}", e);
+            None
+        })
+        .unwrap_or_default();
+
+    let capabilities = match cached.get(&chain_id) {
+        Some(known) if known.daemon_version == daemon_version => {
+            log::debug!("Using cached capabilities for chain {} ({})", chain_name, chain_id);
+            known.clone()
+        }
+        _ => {
+            log::info!("Probing capabilities of chain {} ({}), daemon version {}", chain_name, chain_id, daemon_version);
+            let capabilities = ChainCapabilities {
+                chain_id: chain_id.clone(),
+                chain_name,
+                daemon_version,
+                send_currency: feature_available(rpc_user, rpc_pass, rpc_port, Feature::SendCurrency).await?,
+                view_transaction: feature_available(rpc_user, rpc_pass, rpc_port, Feature::ViewTransaction).await?,
+                offers: feature_available(rpc_user, rpc_pass, rpc_port, Feature::Offers).await?,
+                conversions: feature_available(rpc_user, rpc_pass, rpc_port, Feature::Conversions).await?,
+                probed_at: now_secs(),
+            };
+            cached.insert(chain_id, capabilities.clone());
+            if let Err(e) = save_value(app, CAPABILITIES_STORE_PATH, CAPABILITIES_KEY, &cached) {
+                log::warn!("Failed to persist capability cache: {}", e);
+            }
+            capabilities
+        }
+    };
+
+    ACTIVE_CAPABILITIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(rpc_port, capabilities.clone());
+    Ok(capabilities)
+}
+
+// Capabilities of the chain on this port, if it has been probed
+pub fn active_capabilities(rpc_port: u16) -> Option<ChainCapabilities> {
+    ACTIVE_CAPABILITIES.lock().unwrap_or_else(|e| e.into_inner()).get(&rpc_port).cloned()
+}
+
+// Whether a feature may be used. Unprobed chains are assumed to support everything.
+pub fn supports(rpc_port: u16, feature: Feature) -> bool {
+    active_capabilities(rpc_port).is_none_or(|c| c.supports(feature))
+}
+
+// Fail fast with a clear error when the connected chain lacks a feature
+pub fn require_feature(rpc_port: u16, feature: Feature) -> Result<(), VerusRpcError> {
+    match active_capabilities(rpc_port) {
+        Some(capabilities) if !capabilities.supports(feature) => {
+            log::warn!("{} is not supported on chain {}", feature.label(), capabilities.chain_name);
+            Err(VerusRpcError::NotSupported(feature.label().to_string()))
+        }
+        _ => Ok(()),
+    }
+}
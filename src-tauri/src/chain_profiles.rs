@@ -0,0 +1,322 @@
+// File: src-tauri/src/chain_profiles.rs
+// Description: Community-maintained chain profile bundles, so new PBaaS chains can be added without an app release.
+// Changes:
+// - Created file. Bundles are loaded from a local file or URL, their signature is checked against the
+//   publisher's VerusID via the connected daemon, and installed profiles (chain_profiles.json) extend
+//   get_blockchain_configs and config path discovery.
+// - Only bundles from the built-in TRUSTED_PUBLISHERS are accepted (a valid signature alone only proves who
+//   published a bundle). Downloads are read in chunks and abort once they pass MAX_BUNDLE_BYTES.
+// - ChainProfileError goes into the error log when a command returns it.
+// - Dropped the private now_secs in favour of clock::now_secs.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use super::credentials::{load_credentials, BlockchainConfig};
+use super::error_log::recorded_command_error;
+use super::rpc_client::{verify_message, VerusRpcError};
+use super::storage::{load_value, save_value};
+use super::clock::now_secs;
+
+const PROFILES_STORE_PATH: &str = "chain_profiles.json";
+const PROFILES_KEY: &str = "bundles";
+
+// Highest bundle format this client understands
+const SUPPORTED_BUNDLE_VERSION: u32 = 1;
+
+const BUNDLE_FETCH_TIMEOUT_SECS: u64 = 15;
+
+// Guards against accidentally loading something that isn't a bundle
+const MAX_BUNDLE_BYTES: usize = 512 * 1024;
+
+// VerusIDs whose signed bundles may be installed
+const TRUSTED_PUBLISHERS: [&str; 1] = ["chainprofiles@"];
+
+// Profiles of all installed bundles (read by get_blockchain_configs)
+static INSTALLED_PROFILES: LazyLock<Mutex<Vec<ChainProfile>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+// Config directory per platform: relative to the home directory (Linux / macOS) or %APPDATA% (Windows)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PlatformConfigDirs {
+    pub linux: Option<String>,
+    pub macos: Option<String>,
+    pub windows: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChainProfile {
+    pub id: String,
+    pub name: String,
+    pub chain_string: Option<String>, // PBaaS chain id; used for the standard PBaaS config layout
+    pub config_file_name: String,
+    pub ticker: String,
+    #[serde(default = "default_decimals")]
+    pub decimals: u8,
+    #[serde(default)]
+    pub config_dirs: Option<PlatformConfigDirs>, // Overrides the standard PBaaS layout
+    #[serde(default)]
+    pub rpc_ports: Vec<u16>, // Informational; the port is always read from the config file
+    #[serde(default)]
+    pub explorers: Vec<String>,
+    #[serde(default)]
+    pub bootstrap_urls: Vec<String>,
+}
+
+fn default_decimals() -> u8 {
+    8
+}
+
+// Signed envelope: `payload` is the profile document as a JSON string, signed verbatim by `publisher`
+#[derive(Deserialize, Debug)]
+struct SignedBundle {
+    publisher: String, // VerusID, e.g. "chainprofiles@"
+    payload: String,
+    signature: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BundlePayload {
+    bundle_version: u32,
+    profiles: Vec<ChainProfile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstalledBundle {
+    pub source: String, // File path or URL it was loaded from
+    pub publisher: String,
+    pub loaded_at: u64,
+    pub profiles: Vec<ChainProfile>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum ChainProfileError {
+    #[error("Failed to read bundle: {0}")]
+    Fetch(String),
+    #[error("Invalid bundle: {0}")]
+    Invalid(String),
+    #[error("Bundle signature is not valid for {0}")]
+    SignatureInvalid(String),
+    #[error("{0} is not a trusted bundle publisher")]
+    UntrustedPublisher(String),
+    #[error("A connected daemon is required to check the bundle signature: {0}")]
+    VerificationUnavailable(String),
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Bundle not installed: {0}")]
+    NotFound(String),
+}
+
+recorded_command_error!(ChainProfileError, "chain_profiles");
+
+impl From<super::storage::StorageError> for ChainProfileError {
+    fn from(error: super::storage::StorageError) -> Self {
+        ChainProfileError::Storage(error.to_string())
+    }
+}
+
+impl From<&ChainProfile> for BlockchainConfig {
+    fn from(profile: &ChainProfile) -> Self {
+        BlockchainConfig {
+            id: profile.id.clone(),
+            name: profile.name.clone(),
+            chain_string: profile.chain_string.clone(),
+            config_file_name: profile.config_file_name.clone(),
+            ticker: profile.ticker.clone(),
+            decimals: profile.decimals,
+        }
+    }
+}
+
+// Relative paths only; bundles must not point discovery outside the chain data directories
+fn is_safe_relative_path(path: &str) -> bool {
+    Path::new(path).components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn validate_profile(profile: &ChainProfile) -> Result<(), String> {
+    if profile.id.trim().is_empty() || profile.name.trim().is_empty() || profile.ticker.trim().is_empty() {
+        return Err("profile is missing id, name or ticker".to_string());
+    }
+    if !is_safe_relative_path(&profile.config_file_name) || profile.config_file_name.contains(['/', '\\']) {
+        return Err(format!("invalid config file name '{}' in profile {}", profile.config_file_name, profile.id));
+    }
+    if let Some(chain_string) = &profile.chain_string {
+        if !chain_string.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("invalid chain string in profile {}", profile.id));
+        }
+    }
+    if let Some(dirs) = &profile.config_dirs {
+        for dir in [&dirs.linux, &dirs.macos, &dirs.windows].into_iter().flatten() {
+            if !is_safe_relative_path(dir) {
+                return Err(format!("config directory '{}' in profile {} must be relative", dir, profile.id));
+            }
+        }
+    }
+    if profile.chain_string.is_none() && profile.config_dirs.is_none() {
+        return Err(format!("profile {} needs a chain string or config directories", profile.id));
+    }
+    Ok(())
+}
+
+// All profiles from installed bundles
+pub fn installed_profiles() -> Vec<ChainProfile> {
+    INSTALLED_PROFILES.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+// Config file locations for an installed profile (None if no profile has this id)
+pub fn profile_config_paths(blockchain_config: &BlockchainConfig, home_dir: &Path) -> Option<Vec<PathBuf>> {
+    let profile = installed_profiles().into_iter().find(|p| p.id == blockchain_config.id)?;
+    let Some(dirs) = &profile.config_dirs else {
+        return Some(super::credentials::pbaas_config_paths(blockchain_config, home_dir));
+    };
+
+    let base = if cfg!(target_os = "windows") {
+        dirs.windows.as_ref().and_then(|dir| std::env::var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join(dir)))
+    } else if cfg!(target_os = "macos") {
+        dirs.macos.as_ref().map(|dir| home_dir.join(dir))
+    } else {
+        dirs.linux.as_ref().map(|dir| home_dir.join(dir))
+    };
+    Some(base.map(|dir| vec![dir.join(&profile.config_file_name)]).unwrap_or_default())
+}
+
+fn set_installed(bundles: &[InstalledBundle]) {
+    let profiles = bundles.iter().flat_map(|b| b.profiles.iter().cloned()).collect();
+    *INSTALLED_PROFILES.lock().unwrap_or_else(|e| e.into_inner()) = profiles;
+}
+
+fn read_bundles<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<InstalledBundle>, ChainProfileError> {
+    Ok(load_value(app, PROFILES_STORE_PATH, PROFILES_KEY)?.unwrap_or_default())
+}
+
+// Load installed bundles at startup
+pub fn load_installed<R: Runtime>(app: &AppHandle<R>) {
+    match read_bundles(app) {
+        Ok(bundles) => {
+            set_installed(&bundles);
+            log::info!("Loaded {} installed chain profile bundles", bundles.len());
+        }
+        Err(e) => log::warn!("Failed to load chain profile bundles: {}", e),
+    }
+}
+
+fn too_large() -> ChainProfileError {
+    ChainProfileError::Invalid(format!("bundle is larger than {} bytes", MAX_BUNDLE_BYTES))
+}
+
+fn is_trusted_publisher(publisher: &str) -> bool {
+    TRUSTED_PUBLISHERS.iter().any(|trusted| trusted.eq_ignore_ascii_case(publisher.trim()))
+}
+
+// Read a bundle, never holding more than MAX_BUNDLE_BYTES of it
+async fn fetch_bundle(source: &str) -> Result<String, ChainProfileError> {
+    let contents = if source.starts_with("https://") || source.starts_with("http://") {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(BUNDLE_FETCH_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| ChainProfileError::Fetch(e.to_string()))?;
+        let mut response = client
+            .get(source)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ChainProfileError::Fetch(e.to_string()))?;
+        if response.content_length().is_some_and(|length| length > MAX_BUNDLE_BYTES as u64) {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| ChainProfileError::Fetch(e.to_string()))? {
+            if body.len() + chunk.len() > MAX_BUNDLE_BYTES {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        String::from_utf8(body).map_err(|e| ChainProfileError::Invalid(e.to_string()))?
+    } else {
+        let metadata = tokio::fs::metadata(source).await.map_err(|e| ChainProfileError::Fetch(e.to_string()))?;
+        if metadata.len() > MAX_BUNDLE_BYTES as u64 {
+            return Err(too_large());
+        }
+        tokio::fs::read_to_string(source).await.map_err(|e| ChainProfileError::Fetch(e.to_string()))?
+    };
+    if contents.len() > MAX_BUNDLE_BYTES {
+        return Err(too_large());
+    }
+    Ok(contents)
+}
+
+// --- Tauri Commands ---
+
+// Load, verify and install a bundle. Re-loading from the same source replaces the previous version.
+#[tauri::command]
+pub async fn load_chain_profile_bundle<R: Runtime>(app: AppHandle<R>, source: String) -> Result<InstalledBundle, ChainProfileError> {
+    log::info!("load_chain_profile_bundle command received from {}", source);
+
+    let contents = fetch_bundle(&source).await?;
+    let signed: SignedBundle = serde_json::from_str(&contents).map_err(|e| ChainProfileError::Invalid(e.to_string()))?;
+    if !is_trusted_publisher(&signed.publisher) {
+        log::warn!("Rejected chain profile bundle from {}: {} is not a trusted publisher", source, signed.publisher);
+        return Err(ChainProfileError::UntrustedPublisher(signed.publisher));
+    }
+
+    let creds = load_credentials(app.clone())
+        .await
+        .map_err(|e| ChainProfileError::VerificationUnavailable(e.to_string()))?;
+    let valid = verify_message(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &signed.publisher, &signed.signature, &signed.payload)
+        .await
+        .map_err(ChainProfileError::Rpc)?;
+    if !valid {
+        log::warn!("Rejected chain profile bundle from {}: signature invalid for {}", source, signed.publisher);
+        return Err(ChainProfileError::SignatureInvalid(signed.publisher));
+    }
+
+    let payload: BundlePayload = serde_json::from_str(&signed.payload).map_err(|e| ChainProfileError::Invalid(e.to_string()))?;
+    if payload.bundle_version == 0 || payload.bundle_version > SUPPORTED_BUNDLE_VERSION {
+        return Err(ChainProfileError::Invalid(format!("unsupported bundle version {}", payload.bundle_version)));
+    }
+    if payload.profiles.is_empty() {
+        return Err(ChainProfileError::Invalid("bundle contains no profiles".to_string()));
+    }
+    for profile in &payload.profiles {
+        validate_profile(profile).map_err(ChainProfileError::Invalid)?;
+    }
+
+    let bundle = InstalledBundle {
+        source: source.clone(),
+        publisher: signed.publisher,
+        loaded_at: now_secs(),
+        profiles: payload.profiles,
+    };
+    let mut bundles = read_bundles(&app)?;
+    bundles.retain(|b| b.source != source);
+    bundles.push(bundle.clone());
+    save_value(&app, PROFILES_STORE_PATH, PROFILES_KEY, &bundles)?;
+    set_installed(&bundles);
+
+    log::info!("Installed {} chain profiles published by {}", bundle.profiles.len(), bundle.publisher);
+    Ok(bundle)
+}
+
+#[tauri::command]
+pub async fn get_chain_profile_bundles<R: Runtime>(app: AppHandle<R>) -> Result<Vec<InstalledBundle>, ChainProfileError> {
+    log::debug!("get_chain_profile_bundles command received");
+    read_bundles(&app)
+}
+
+#[tauri::command]
+pub async fn remove_chain_profile_bundle<R: Runtime>(app: AppHandle<R>, source: String) -> Result<(), ChainProfileError> {
+    log::info!("remove_chain_profile_bundle command received for {}", source);
+    let mut bundles = read_bundles(&app)?;
+    let count = bundles.len();
+    bundles.retain(|b| b.source != source);
+    if bundles.len() == count {
+        return Err(ChainProfileError::NotFound(source));
+    }
+    save_value(&app, PROFILES_STORE_PATH, PROFILES_KEY, &bundles)?;
+    set_installed(&bundles);
+    Ok(())
+}
@@ -0,0 +1,205 @@
+// File: src-tauri/src/clock.rs
+// Description: Guard against a skewed local clock affecting signed message timestamps.
+// Changes:
+// - Created file. The local clock is compared with the chain tip's block time on connect and (when the last check
+//   is stale) before sends; outgoing timestamps can optionally be derived from chain time instead.
+// - now_secs is public, so other modules share it instead of keeping their own copy.
+// - Added now_nanos and new_id (short time-salted hash ids) for the modules that built their own.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Runtime};
+use super::events::{emit_event, CLOCK_SKEW_EVENT};
+use super::rpc_client::{make_background_rpc_call, VerusRpcError};
+use super::storage::{load_value, save_value, StorageError};
+
+const CLOCK_STORE_PATH: &str = "store.json";
+const CLOCK_SETTINGS_KEY: &str = "clock_settings";
+
+// A local clock behind the last block is certainly slow; allow for block timestamp leeway
+const MAX_BEHIND_SECS: i64 = 5 * 60;
+
+// The tip is normally a few minutes old; beyond this the local clock is likely fast
+// (only judged while the daemon is synced, since a stale tip looks the same)
+const MAX_AHEAD_SECS: i64 = 2 * 60 * 60;
+
+// Sends re-check the clock when the last check is older than this
+const RECHECK_INTERVAL_SECS: u64 = 10 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockStatus {
+    Ok,
+    Behind, // Local clock is slow: our messages appear older than they are
+    Ahead,  // Local clock is fast: our messages appear to come from the future
+    Unknown, // Daemon not synced, so the tip time isn't a reliable reference
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ClockSkewReport {
+    pub status: ClockStatus,
+    pub local_time: u64,
+    pub chain_tip_time: u64,
+    pub median_time: u64,
+    pub skew_secs: i64, // local - tip time
+    pub correction_enabled: bool,
+    pub checked_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ClockSettings {
+    #[serde(default)]
+    pub correct_outgoing_timestamps: bool,
+}
+
+// Last check: report plus the monotonic instant it was taken at
+static LAST_CHECK: LazyLock<Mutex<Option<(ClockSkewReport, Instant)>>> = LazyLock::new(|| Mutex::new(None));
+
+// Mirrors the persisted setting so signing doesn't need the store
+static CORRECTION_ENABLED: Mutex<bool> = Mutex::new(false);
+
+// Local unix time in seconds; every module reads the wall clock through this or now_nanos
+pub fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Local unix time in nanoseconds (for ids and seeds, not timestamps)
+pub fn now_nanos() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+// 16 hex character id from the given parts and the current time, so equal parts still get distinct ids
+pub fn new_id(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.update(now_nanos().to_le_bytes());
+    hex::encode(&hasher.finalize()[..8])
+}
+
+fn classify(skew_secs: i64, synced: bool) -> ClockStatus {
+    if skew_secs < -MAX_BEHIND_SECS {
+        ClockStatus::Behind
+    } else if !synced {
+        ClockStatus::Unknown
+    } else if skew_secs > MAX_AHEAD_SECS {
+        ClockStatus::Ahead
+    } else {
+        ClockStatus::Ok
+    }
+}
+
+// Compare the local clock with the chain tip and remember the result
+pub async fn check_clock_skew(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Result<ClockSkewReport, VerusRpcError> {
+    let info: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockchaininfo", vec![]).await?;
+    let best_hash = info.get("bestblockhash").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let median_time = info.get("mediantime").and_then(|v| v.as_u64()).unwrap_or(0);
+    let blocks = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let headers = info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0);
+
+    let tip: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblock", vec![json!(best_hash), json!(1)]).await?;
+    let chain_tip_time = tip.get("time").and_then(|v| v.as_u64()).unwrap_or(median_time);
+
+    let local_time = now_secs();
+    let skew_secs = local_time as i64 - chain_tip_time as i64;
+    let status = classify(skew_secs, headers > 0 && blocks >= headers);
+    match status {
+        ClockStatus::Behind | ClockStatus::Ahead => {
+            log::warn!("Local clock looks skewed ({:?}): local {} vs chain tip {} ({}s)", status, local_time, chain_tip_time, skew_secs)
+        }
+        _ => log::debug!("Clock check: local {} vs chain tip {} ({}s, {:?})", local_time, chain_tip_time, skew_secs, status),
+    }
+
+    let report = ClockSkewReport {
+        status,
+        local_time,
+        chain_tip_time,
+        median_time,
+        skew_secs,
+        correction_enabled: correction_enabled(),
+        checked_at: local_time,
+    };
+    *LAST_CHECK.lock().unwrap_or_else(|e| e.into_inner()) = Some((report.clone(), Instant::now()));
+    Ok(report)
+}
+
+// Re-check before a send when the last result is stale. Best effort: failures are only logged.
+pub async fn refresh_if_stale(rpc_user: &str, rpc_pass: &str, rpc_port: u16) {
+    let stale = LAST_CHECK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_none_or(|(_, at)| at.elapsed().as_secs() >= RECHECK_INTERVAL_SECS);
+    if stale {
+        if let Err(e) = check_clock_skew(rpc_user, rpc_pass, rpc_port).await {
+            log::debug!("Clock check before send failed: {:?}", e);
+        }
+    }
+}
+
+fn correction_enabled() -> bool {
+    *CORRECTION_ENABLED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Timestamp for outgoing signed memos. With correction enabled and a skewed clock, chain time
+// (tip time plus the time elapsed since the check) is used instead of the local clock.
+pub fn outgoing_timestamp() -> u64 {
+    let local = now_secs();
+    if !correction_enabled() {
+        return local;
+    }
+    match LAST_CHECK.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some((report, at)) if matches!(report.status, ClockStatus::Behind | ClockStatus::Ahead) => {
+            let corrected = report.chain_tip_time + at.elapsed().as_secs();
+            log::debug!("Using chain-derived timestamp {} instead of local {}", corrected, local);
+            corrected
+        }
+        _ => local,
+    }
+}
+
+pub fn load_clock_settings<R: Runtime>(app: &AppHandle<R>) {
+    match load_value::<R, ClockSettings>(app, CLOCK_STORE_PATH, CLOCK_SETTINGS_KEY) {
+        Ok(settings) => *CORRECTION_ENABLED.lock().unwrap_or_else(|e| e.into_inner()) = settings.unwrap_or_default().correct_outgoing_timestamps,
+        Err(e) => log::warn!("Failed to load clock settings: {}", e),
+    }
+}
+
+// Check on connect and tell the frontend if the clock is off
+pub async fn check_on_connect<R: Runtime>(app: &AppHandle<R>, rpc_user: &str, rpc_pass: &str, rpc_port: u16) {
+    match check_clock_skew(rpc_user, rpc_pass, rpc_port).await {
+        Ok(report) if matches!(report.status, ClockStatus::Behind | ClockStatus::Ahead) => emit_event(app, CLOCK_SKEW_EVENT, report),
+        Ok(_) => {}
+        Err(e) => log::warn!("Clock check failed: {:?}", e),
+    }
+}
+
+// --- Tauri Commands ---
+
+// Result of the last clock check (None before connecting)
+#[tauri::command]
+pub fn get_clock_status() -> Option<ClockSkewReport> {
+    log::debug!("get_clock_status command received");
+    LAST_CHECK.lock().unwrap_or_else(|e| e.into_inner()).as_ref().map(|(report, _)| ClockSkewReport {
+        correction_enabled: correction_enabled(),
+        ..report.clone()
+    })
+}
+
+#[tauri::command]
+pub async fn set_timestamp_correction<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), StorageError> {
+    log::info!("set_timestamp_correction command received: {}", enabled);
+    save_value(&app, CLOCK_STORE_PATH, CLOCK_SETTINGS_KEY, &ClockSettings { correct_outgoing_timestamps: enabled })?;
+    *CORRECTION_ENABLED.lock().unwrap_or_else(|e| e.into_inner()) = enabled;
+    Ok(())
+}
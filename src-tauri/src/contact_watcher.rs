@@ -0,0 +1,171 @@
+// File: src-tauri/src/contact_watcher.rs
+// Description: Watches conversation partners' identities for changes to their receiving and controlling addresses.
+// Changes:
+// - Created file. The watcher re-resolves every contact of the logged-in identity periodically. A changed private
+//   address is written to the conversation (sends must go to the new one); changed primary addresses mean the
+//   identity is controlled by other keys now. Both are reported as contact-identity-changed events, which the
+//   identity warmup emits too when it finds a change.
+// - Changed private addresses are written with update_conversations (only those fields, on the current list).
+
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use super::events::{emit_event, CONTACT_IDENTITY_CHANGED_EVENT};
+use super::identity_cache::IdentityCache;
+use super::identity_rpc::{check_identity_eligibility, FormattedIdentity};
+use super::settings::{read_conversations, update_conversations};
+
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const MIN_WATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+// Identity -> generation of the running watcher; bumping it stops the old one
+static WATCHERS: LazyLock<Mutex<HashMap<String, u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Payload of the contact-identity-changed event
+#[derive(Serialize, Debug, Clone)]
+pub struct ContactIdentityChange {
+    pub identity_i_address: String, // Logged-in identity whose conversation changed
+    pub conversation_id: String,
+    pub private_address_changed: bool,
+    pub old_private_address: String,
+    pub new_private_address: String,
+    pub primary_addresses_changed: bool,
+    pub old_primary_addresses: Vec<String>,
+    pub new_primary_addresses: Vec<String>,
+    pub revoked: bool,
+}
+
+// Compare a fresh lookup against the conversation and the previously cached identity. Primary addresses are only
+// compared when the cached entry had them (older cache entries don't).
+pub fn detect_change(
+    identity_i_address: &str,
+    conversation_id: &str,
+    conversation_private_address: &str,
+    previous: Option<&FormattedIdentity>,
+    current: &FormattedIdentity,
+) -> Option<ContactIdentityChange> {
+    let private_address_changed = conversation_private_address != current.private_address;
+    let old_primary_addresses = previous.map(|p| p.primary_addresses.clone()).unwrap_or_default();
+    let primary_addresses_changed = !old_primary_addresses.is_empty()
+        && old_primary_addresses.iter().collect::<BTreeSet<_>>() != current.primary_addresses.iter().collect::<BTreeSet<_>>();
+    if !private_address_changed && !primary_addresses_changed {
+        return None;
+    }
+    Some(ContactIdentityChange {
+        identity_i_address: identity_i_address.to_string(),
+        conversation_id: conversation_id.to_string(),
+        private_address_changed,
+        old_private_address: conversation_private_address.to_string(),
+        new_private_address: current.private_address.clone(),
+        primary_addresses_changed,
+        old_primary_addresses,
+        new_primary_addresses: current.primary_addresses.clone(),
+        revoked: current.revoked,
+    })
+}
+
+// One pass over all contacts. Returns the number of changes found.
+async fn check_contacts<R: Runtime>(app: &AppHandle<R>, rpc_user: &str, rpc_pass: &str, rpc_port: u16, identity_i_address: &str) -> usize {
+    let conversations = match read_conversations(app, identity_i_address) {
+        Ok(conversations) => conversations,
+        Err(e) => {
+            log::warn!("Contact watcher: failed to read conversations: {}", e);
+            return 0;
+        }
+    };
+    let cache = app.state::<IdentityCache>().inner().clone();
+    let mut changes = 0;
+    let mut address_updates: Vec<(String, String)> = Vec::new(); // Conversation id, new private address
+    for conversation in &conversations {
+        let previous = cache.get(&conversation.id).map(|cached| cached.identity);
+        let current = match check_identity_eligibility(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, conversation.id.clone()).await {
+            Ok(identity) => identity,
+            Err(e) => {
+                log::debug!("Contact watcher: failed to resolve {}: {:?}", conversation.id, e);
+                continue;
+            }
+        };
+        cache.insert(&conversation.id, current.clone());
+        let Some(change) = detect_change(identity_i_address, &conversation.id, &conversation.recipient_private_address, previous.as_ref(), &current) else {
+            continue;
+        };
+        log::warn!(
+            "Identity of contact {} changed (private address: {}, primary addresses: {})",
+            conversation.id, change.private_address_changed, change.primary_addresses_changed
+        );
+        if change.private_address_changed {
+            address_updates.push((conversation.id.clone(), current.private_address.clone()));
+        }
+        changes += 1;
+        emit_event(app, CONTACT_IDENTITY_CHANGED_EVENT, change);
+    }
+    if !address_updates.is_empty() {
+        let result = update_conversations(app, identity_i_address, |conversations| {
+            for (id, private_address) in address_updates {
+                if let Some(conversation) = conversations.iter_mut().find(|c| c.id == id) {
+                    conversation.recipient_private_address = private_address;
+                }
+            }
+        });
+        if let Err(e) = result {
+            log::warn!("Contact watcher: failed to save updated conversations: {}", e);
+        }
+    }
+    if let Err(e) = cache.persist(app) {
+        log::warn!("Failed to persist identity cache: {}", e);
+    }
+    changes
+}
+
+// Check the contacts of an identity periodically. Starting again for the same identity replaces the watcher.
+pub fn start_contact_watcher<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    identity_i_address: String,
+    interval: Option<Duration>,
+) {
+    let interval = interval.unwrap_or(DEFAULT_WATCH_INTERVAL).max(MIN_WATCH_INTERVAL);
+    let generation = {
+        let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = watchers.get(&identity_i_address).copied().unwrap_or(0) + 1;
+        watchers.insert(identity_i_address.clone(), generation);
+        generation
+    };
+    let key = identity_i_address.clone();
+    let is_current = move || WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).get(&key) == Some(&generation);
+    log::info!("Starting contact watcher for {} (every {}s)", identity_i_address, interval.as_secs());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // The first pass waits too; the login warmup has just resolved everyone
+            tokio::time::sleep(interval).await;
+            if !is_current() {
+                break;
+            }
+            let changes = check_contacts(&app, &rpc_user, &rpc_pass, rpc_port, &identity_i_address).await;
+            log::debug!("Contact watcher for {}: {} changes", identity_i_address, changes);
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+// Stop watching the contacts of one identity, or of all identities
+#[tauri::command]
+pub fn stop_contact_watcher(identity_i_address: Option<String>) {
+    log::info!("stop_contact_watcher command received");
+    let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+    match identity_i_address {
+        Some(identity) => {
+            if let Some(generation) = watchers.get_mut(&identity) {
+                *generation += 1;
+            }
+        }
+        None => watchers.values_mut().for_each(|generation| *generation += 1),
+    }
+}
@@ -0,0 +1,209 @@
+// File: src-tauri/src/conversion.rs
+// Description: Convert-and-send gifts: the recipient receives a different currency than the sender spends.
+// Changes:
+// - Created file. One sendcurrency operation carries the conversion output (convertto / via, delivered to the
+//   recipient's VerusID; conversions can't pay out to a z-address) and the signed memo to the recipient's private
+//   address. The operation is tracked in the background and reported with conversion-gift events; the gift is
+//   recorded in the message store once the transaction exists.
+// - The recipient identity is resolved with getidentity and must own the recipient z-address (the memo is signed
+//   for that address, so converted funds must go to its owner); the output is sent to the identity's i-address.
+// - RPC failures are classified by error code instead of message text.
+// - The record's timestamp comes from clock::now_secs.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use super::address::validate_recipient_address;
+use super::events::{emit_event, CONVERSION_GIFT_EVENT};
+use super::message_rpc::{build_signed_memo_hex, resolve_fee, resolve_gift_currency};
+use super::message_store::MessageStore;
+use super::price::estimate_conversion;
+use super::protocol::PROTOCOL_VERSION;
+use super::response_cache::make_cached_rpc_call;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::settings::{ChatMessage, MessageKind};
+use super::wallet_rpc::{wait_for_operation, WalletOperationStatus};
+use super::clock::now_secs;
+
+// Conversion proofs take longer than plain sends
+const CONVERSION_OPERATION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+const RPC_WALLET_INSUFFICIENT_FUNDS: i32 = -6;
+const RPC_INVALID_PARAMETER: i32 = -8; // sendcurrency's answer to unknown currencies and impossible routes
+
+#[derive(Debug, Clone, thiserror::Error, Serialize)]
+pub enum ConversionError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Insufficient funds for the conversion: {0}")]
+    InsufficientFunds(String),
+    #[error("Conversion route not available: {0}")]
+    InvalidRoute(String),
+    #[error("{0} does not own the recipient's private address")]
+    RecipientMismatch(String),
+}
+
+impl From<VerusRpcError> for ConversionError {
+    fn from(error: VerusRpcError) -> Self {
+        match error {
+            VerusRpcError::Rpc { code: RPC_WALLET_INSUFFICIENT_FUNDS, message } => ConversionError::InsufficientFunds(message),
+            VerusRpcError::Rpc { code: RPC_INVALID_PARAMETER, message } => ConversionError::InvalidRoute(message),
+            error => ConversionError::Rpc(error),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversionGiftStarted {
+    pub opid: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub estimated_output: f64, // Preview at submission time; the actual amount depends on the block it settles in
+}
+
+// Payload of the conversion-gift event
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversionGiftUpdate {
+    pub status: WalletOperationStatus,
+    pub error: Option<ConversionError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConversionGiftRequest {
+    pub sender_z_address: String,
+    pub sender_identity: String,
+    pub recipient_z_address: String, // Receives the signed memo
+    pub recipient_identity: String,  // Receives the converted funds
+    pub memo_text: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub via: Option<String>,
+    pub amount: f64,
+    pub fee: Option<f64>,
+    pub identity_i_address: Option<String>, // Where to record the gift once sent
+    pub conversation_id: Option<String>,
+}
+
+// i-address of the recipient identity, which must own the z-address the memo is signed for
+async fn resolve_recipient(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    recipient_identity: &str,
+    recipient_z_address: &str,
+) -> Result<String, ConversionError> {
+    let response: Value = make_cached_rpc_call(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(recipient_identity)]).await?;
+    let identity = response.get("identity");
+    let field = |name: &str| identity.and_then(|i| i.get(name)).and_then(|v| v.as_str());
+    match (field("identityaddress"), field("privateaddress")) {
+        (Some(i_address), Some(private_address)) if private_address == recipient_z_address => Ok(i_address.to_string()),
+        _ => {
+            log::warn!("Conversion gift refused: {} does not own {}", recipient_identity, recipient_z_address);
+            Err(ConversionError::RecipientMismatch(recipient_identity.to_string()))
+        }
+    }
+}
+
+// Start a convert-and-send gift. Returns once the operation is submitted; completion arrives as an event.
+pub async fn send_conversion_gift<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    request: ConversionGiftRequest,
+) -> Result<ConversionGiftStarted, ConversionError> {
+    // Everything is checked before signing, like send_private_message
+    let fee = resolve_fee(request.fee, None)?;
+    let from_currency = resolve_gift_currency(&rpc_user, &rpc_pass, rpc_port, &request.from_currency).await?;
+    let to_currency = resolve_gift_currency(&rpc_user, &rpc_pass, rpc_port, &request.to_currency).await?;
+    validate_recipient_address(&rpc_user, &rpc_pass, rpc_port, &request.recipient_z_address).await?;
+    let recipient_i_address =
+        resolve_recipient(&rpc_user, &rpc_pass, rpc_port, &request.recipient_identity, &request.recipient_z_address).await?;
+    // The estimate doubles as a route check: it fails the same way the conversion would
+    let estimate = estimate_conversion(&rpc_user, &rpc_pass, rpc_port, &from_currency, &to_currency, request.via.as_deref(), request.amount)
+        .await
+        .map_err(|e| match e {
+            VerusRpcError::Rpc { message, .. } => ConversionError::InvalidRoute(message),
+            e => ConversionError::from(e),
+        })?;
+
+    let memo_hex = build_signed_memo_hex(
+        &rpc_user,
+        &rpc_pass,
+        rpc_port,
+        &request.recipient_z_address,
+        &request.memo_text,
+        &request.sender_identity,
+    )
+    .await?;
+    let mut conversion_output = json!({
+        "address": recipient_i_address,
+        "currency": from_currency,
+        "convertto": to_currency,
+        "amount": request.amount,
+    });
+    if let Some(via) = &request.via {
+        conversion_output["via"] = json!(via);
+    }
+    // sendcurrency takes memos as text; a leading '#' marks hex
+    let memo_output = json!({ "address": request.recipient_z_address, "amount": 0, "memo": format!("#{}", memo_hex) });
+    let mut params = vec![json!(request.sender_z_address), json!([conversion_output, memo_output]), json!(1)]; // minconf
+    if let Some(fee) = fee {
+        params.push(json!(fee));
+    }
+    log::info!(
+        "Sending conversion gift: {} {} -> ~{} {} for {}",
+        request.amount, from_currency, estimate.estimated_output, to_currency, request.recipient_identity
+    );
+    let opid: String = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "sendcurrency", params).await?;
+
+    let started = ConversionGiftStarted { opid: opid.clone(), from_currency: from_currency.clone(), to_currency, estimated_output: estimate.estimated_output };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let update = match wait_for_operation(&rpc_user, &rpc_pass, rpc_port, &opid, CONVERSION_OPERATION_TIMEOUT).await {
+            Ok(txid) => {
+                log::info!("Conversion gift {} sent in {}", opid, txid);
+                record_gift(&app, &request, &from_currency, &txid);
+                ConversionGiftUpdate {
+                    status: WalletOperationStatus { opid, status: "success".to_string(), txid: Some(txid), error: None },
+                    error: None,
+                }
+            }
+            Err(e) => {
+                log::warn!("Conversion gift {} failed: {}", opid, e);
+                let error = ConversionError::from(e);
+                ConversionGiftUpdate {
+                    status: WalletOperationStatus { opid, status: "failed".to_string(), txid: None, error: Some(error.to_string()) },
+                    error: Some(error),
+                }
+            }
+        };
+        emit_event(&app, CONVERSION_GIFT_EVENT, update);
+    });
+    Ok(started)
+}
+
+fn record_gift<R: Runtime>(app: &AppHandle<R>, request: &ConversionGiftRequest, from_currency: &str, txid: &str) {
+    let (Some(identity), Some(conversation_id)) = (&request.identity_i_address, &request.conversation_id) else { return };
+    let sent = ChatMessage {
+        id: txid.to_string(),
+        sender: request.sender_identity.clone(),
+        text: request.memo_text.clone(),
+        timestamp: now_secs(),
+        amount: request.amount, // What we spent; the recipient's amount depends on the conversion
+        confirmations: 0,
+        direction: "sent".to_string(),
+        status: Some("sent".to_string()),
+        protocol_version: Some(PROTOCOL_VERSION),
+        recipient_bound: Some(true),
+        signature: None,
+        fee: None,
+        size_bytes: None,
+        kind: Some(MessageKind::ConversionGift),
+        currency: Some(from_currency.to_string()),
+    };
+    if let Err(e) = app.state::<MessageStore>().merge(app, identity, conversation_id, vec![sent]) {
+        log::warn!("Failed to record conversion gift {} in message store: {}", txid, e);
+    }
+}
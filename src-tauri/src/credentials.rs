@@ -12,14 +12,26 @@
 // - MAJOR: Added parallel blockchain detection system with enhanced error reporting
 // - Added folder selection dialog for manual configuration discovery
 // - Added detection result structures for comprehensive status reporting
+// - Added ticker and decimals to BlockchainConfig for backend amount formatting
+// - detect_all_blockchains accepts an optional task_id and can be cancelled via cancel_task
+// - Chains from installed chain profile bundles are appended to get_blockchain_configs; PBaaS path layout moved to pbaas_config_paths
+// - Connection tests use the shared RPC HTTP client of the port (rpc_client::rpc_http_client)
+// - Cookie-file authentication: configs without rpcuser/rpcpassword use the daemon's .cookie next to the config;
+//   Credentials remember the cookie path and the cookie is re-read on load and when the daemon rejects it (rotation)
+// - CredentialError and DiscoveryError go into the error log when a command returns them.
+// - Tests for cookie parsing, the cookie fallback of parse_config_file and cookie rotation.
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Runtime};
 use tauri_plugin_store::{StoreExt, Error as StoreError};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::sync::{LazyLock, Mutex};
 use tokio::task::JoinSet;
 use std::time::Duration;
+use super::error_log::recorded_command_error;
+use super::tasks::{run_cancellable, TaskError};
 
 
 // Path for the store file relative to AppData directory
@@ -31,13 +43,21 @@ const CREDENTIALS_KEY: &str = "verus_rpc_credentials";
 // Detection timeout in seconds
 const DETECTION_TIMEOUT_SECS: u64 = 8;
 
+// Auth cookie the daemon writes into its data directory when no rpcpassword is configured
+const COOKIE_FILE_NAME: &str = ".cookie";
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Credentials {
     pub rpc_user: String,
     pub rpc_pass: String,
     pub rpc_port: u16, // NEW: Port support for different blockchains
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookie_path: Option<String>, // Cookie auth: user and password are read from this file (it changes on daemon restart)
 }
 
+// RPC port -> cookie file of daemons using cookie auth
+static COOKIE_FILES: LazyLock<Mutex<HashMap<u16, PathBuf>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
 // NEW: Blockchain configuration structure
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockchainConfig {
@@ -45,6 +65,8 @@ pub struct BlockchainConfig {
     pub name: String,
     pub chain_string: Option<String>,
     pub config_file_name: String,
+    pub ticker: String,   // Native currency ticker (e.g., VRSC)
+    pub decimals: u8,     // Native currency precision
 }
 
 // NEW: Enhanced detection result for individual blockchains
@@ -88,6 +110,7 @@ pub struct DiscoveryResult {
 }
 
 #[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
 pub enum DiscoveryError {
     #[error("Config file not found in standard locations")]
     NotFound,
@@ -97,42 +120,70 @@ pub enum DiscoveryError {
     PermissionDenied,
     #[error("IO error: {0}")]
     IoError(String),
+    #[error("Detection was cancelled")]
+    Cancelled,
+}
+
+recorded_command_error!(DiscoveryError, "credentials");
+
+impl From<TaskError> for DiscoveryError {
+    fn from(_: TaskError) -> Self {
+        DiscoveryError::Cancelled
+    }
 }
 
 // NEW: Get blockchain configurations in the specified order
 pub fn get_blockchain_configs() -> Vec<BlockchainConfig> {
-    vec![
+    let mut configs = vec![
         BlockchainConfig {
             id: "verus".to_string(),
             name: "Verus".to_string(),
             chain_string: None,
             config_file_name: "VRSC.conf".to_string(),
+            ticker: "VRSC".to_string(),
+            decimals: 8,
         },
         BlockchainConfig {
             id: "chips".to_string(),
             name: "CHIPS".to_string(),
             chain_string: Some("f315367528394674d45277e369629605a1c3ce9f".to_string()),
             config_file_name: "f315367528394674d45277e369629605a1c3ce9f.conf".to_string(),
+            ticker: "CHIPS".to_string(),
+            decimals: 8,
         },
         BlockchainConfig {
             id: "vdex".to_string(),
             name: "vDEX".to_string(),
             chain_string: Some("53fe39eea8c06bba32f1a4e20db67e5524f0309d".to_string()),
             config_file_name: "53fe39eea8c06bba32f1a4e20db67e5524f0309d.conf".to_string(),
+            ticker: "vDEX".to_string(),
+            decimals: 8,
         },
         BlockchainConfig {
             id: "varrr".to_string(),
             name: "vARRR".to_string(),
             chain_string: Some("e9e10955b7d16031e3d6f55d9c908a038e3ae47d".to_string()),
             config_file_name: "e9e10955b7d16031e3d6f55d9c908a038e3ae47d.conf".to_string(),
+            ticker: "vARRR".to_string(),
+            decimals: 8,
         },
         BlockchainConfig {
             id: "verus-testnet".to_string(),
             name: "Verus Testnet".to_string(),
             chain_string: None,
             config_file_name: "vrsctest.conf".to_string(),
+            ticker: "VRSCTEST".to_string(),
+            decimals: 8,
         },
-    ]
+    ];
+
+    // Chains from installed profile bundles; built-in ids always take precedence
+    for profile in super::chain_profiles::installed_profiles() {
+        if configs.iter().all(|c| c.id != profile.id) {
+            configs.push(BlockchainConfig::from(&profile));
+        }
+    }
+    configs
 }
 
 // NEW: Get standard config paths for a blockchain
@@ -177,24 +228,14 @@ pub fn get_standard_config_paths(blockchain_config: &BlockchainConfig) -> Vec<Pa
                 }
             },
             "chips" | "varrr" | "vdex" => {
-                // PBaaS chains use different paths
-                if let Some(chain_string) = &blockchain_config.chain_string {
-                    if cfg!(target_os = "windows") {
-                        if let Some(appdata) = std::env::var_os("APPDATA") {
-                            let path = PathBuf::from(appdata).join("Verus").join("pbaas").join(chain_string).join(config_file);
-                            paths.push(path);
-                        }
-                    } else if cfg!(target_os = "macos") {
-                        let path = home_dir.join("Library").join("Application Support").join("Verus").join("PBAAS").join(chain_string).join(config_file);
-                        paths.push(path);
-                    } else {
-                        let path = home_dir.join(".verus").join("pbaas").join(chain_string).join(config_file);
-                        paths.push(path);
-                    }
-                }
+                paths.extend(pbaas_config_paths(blockchain_config, &home_dir));
             },
             _ => {
-                log::warn!("Unknown blockchain configuration: {}", blockchain_config.id);
+                // Chains added through installed chain profile bundles
+                match super::chain_profiles::profile_config_paths(blockchain_config, &home_dir) {
+                    Some(profile_paths) => paths.extend(profile_paths),
+                    None => log::warn!("Unknown blockchain configuration: {}", blockchain_config.id),
+                }
             }
         }
     }
@@ -202,6 +243,63 @@ pub fn get_standard_config_paths(blockchain_config: &BlockchainConfig) -> Vec<Pa
     paths
 }
 
+// Standard config location of a PBaaS chain
+pub fn pbaas_config_paths(blockchain_config: &BlockchainConfig, home_dir: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let config_file = &blockchain_config.config_file_name;
+
+    // PBaaS chains use different paths
+    if let Some(chain_string) = &blockchain_config.chain_string {
+        if cfg!(target_os = "windows") {
+            if let Some(appdata) = std::env::var_os("APPDATA") {
+                let path = PathBuf::from(appdata).join("Verus").join("pbaas").join(chain_string).join(config_file);
+                paths.push(path);
+            }
+        } else if cfg!(target_os = "macos") {
+            let path = home_dir.join("Library").join("Application Support").join("Verus").join("PBAAS").join(chain_string).join(config_file);
+            paths.push(path);
+        } else {
+            let path = home_dir.join(".verus").join("pbaas").join(chain_string).join(config_file);
+            paths.push(path);
+        }
+    }
+
+    paths
+}
+
+// Read a daemon auth cookie ("user:password")
+pub fn read_cookie_file(path: &Path) -> Result<(String, String), DiscoveryError> {
+    let content = fs::read_to_string(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::PermissionDenied => DiscoveryError::PermissionDenied,
+        _ => DiscoveryError::IoError(format!("{}: {}", path.display(), e)),
+    })?;
+    match content.trim().split_once(':') {
+        Some((user, pass)) if !user.is_empty() && !pass.is_empty() => Ok((user.to_string(), pass.to_string())),
+        _ => Err(DiscoveryError::ParseError(format!("Malformed cookie file {}", path.display()))),
+    }
+}
+
+fn register_cookie_file(rpc_port: u16, path: PathBuf) {
+    COOKIE_FILES.lock().unwrap_or_else(|e| e.into_inner()).insert(rpc_port, path);
+}
+
+// Called when the daemon rejects a cookie password: the cookie's current user and password if it has changed since
+// (the daemon was restarted). None for ports without cookie auth.
+pub fn refreshed_cookie_auth(rpc_port: u16, used_pass: &str) -> Option<(String, String)> {
+    let path = COOKIE_FILES.lock().unwrap_or_else(|e| e.into_inner()).get(&rpc_port).cloned()?;
+    match read_cookie_file(&path) {
+        Ok((user, pass)) if pass != used_pass => {
+            log::info!("Auth cookie for port {} has rotated, using the new one", rpc_port);
+            Some((user, pass))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            log::warn!("Failed to re-read auth cookie {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
 // NEW: Parse config file to extract credentials
 pub fn parse_config_file(file_path: &PathBuf) -> Result<Credentials, DiscoveryError> {
     log::info!("Attempting to parse config file: {:?}", file_path);
@@ -254,6 +352,26 @@ pub fn parse_config_file(file_path: &PathBuf) -> Result<Credentials, DiscoveryEr
                 rpc_user: user,
                 rpc_pass: pass,
                 rpc_port: port,
+                cookie_path: None,
+            })
+        },
+        // No rpcuser/rpcpassword: the daemon authenticates with the cookie in its data directory
+        (None, None, Some(port)) => {
+            let cookie_path = file_path.with_file_name(COOKIE_FILE_NAME);
+            if !cookie_path.exists() {
+                log::error!("Config file has no rpcuser/rpcpassword and no {} next to it", COOKIE_FILE_NAME);
+                return Err(DiscoveryError::ParseError(format!(
+                    "Missing rpcuser and rpcpassword, and no {} file (is the daemon running?)", COOKIE_FILE_NAME
+                )));
+            }
+            let (user, pass) = read_cookie_file(&cookie_path)?;
+            log::info!("Using cookie authentication from {:?}. Port: {}", cookie_path, port);
+            register_cookie_file(port, cookie_path.clone());
+            Ok(Credentials {
+                rpc_user: user,
+                rpc_pass: pass,
+                rpc_port: port,
+                cookie_path: Some(cookie_path.to_string_lossy().to_string()),
             })
         },
         (Some(_), Some(_), None) => {
@@ -269,7 +387,15 @@ pub fn parse_config_file(file_path: &PathBuf) -> Result<Credentials, DiscoveryEr
 
 // NEW: Parallel blockchain detection with timeout and error handling
 #[tauri::command]
-pub async fn detect_all_blockchains() -> Result<ParallelDetectionResult, DiscoveryError> {
+pub async fn detect_all_blockchains<R: Runtime>(
+    app: AppHandle<R>,
+    task_id: Option<String>,
+) -> Result<ParallelDetectionResult, DiscoveryError> {
+    run_cancellable(&app, task_id, "detection", run_parallel_detection()).await
+}
+
+// Detection body shared by the command (dropping it aborts all spawned detection tasks)
+pub async fn run_parallel_detection() -> Result<ParallelDetectionResult, DiscoveryError> {
     let start_time = std::time::Instant::now();
     log::info!("Starting parallel blockchain detection for all supported chains");
     
@@ -438,10 +564,10 @@ async fn detect_single_blockchain(config: BlockchainConfig) -> BlockchainDetecti
 
 // NEW: Test daemon connection (simplified version for detection)
 async fn test_daemon_connection(credentials: &Credentials) -> Result<u64, String> {
-    use reqwest::{Client, StatusCode};
+    use reqwest::StatusCode;
     use serde_json::json;
     
-    let client = Client::new();
+    let client = crate::rpc_client::rpc_http_client(credentials.rpc_port);
     let url = format!("http://127.0.0.1:{}", credentials.rpc_port);
     
     log::info!("Testing connection to {} with user: {} (pass length: {})", 
@@ -628,6 +754,7 @@ pub async fn detect_blockchain_from_path(path: String) -> Result<ParallelDetecti
 
 // Custom error type for credential operations
 #[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
 pub enum CredentialError {
     #[error("Store plugin error: {0}")]
     Store(String),
@@ -637,8 +764,12 @@ pub enum CredentialError {
     Serialization(String),
     #[error("Deserialization error: {0}")]
     Deserialization(String),
+    #[error("Auth cookie error: {0}")]
+    Cookie(String),
 }
 
+recorded_command_error!(CredentialError, "credentials");
+
 // Convert StoreError to CredentialError
 impl From<StoreError> for CredentialError {
     fn from(error: StoreError) -> Self {
@@ -653,9 +784,19 @@ pub async fn save_credentials<R: Runtime>(
     rpc_user: String,
     rpc_pass: String,
     rpc_port: u16,
+    cookie_path: Option<String>, // Cookie auth: rpc_user / rpc_pass are taken from the cookie
 ) -> Result<(), CredentialError> {
     log::info!("Attempting to save credentials to store...");
-    let credentials = Credentials { rpc_user, rpc_pass, rpc_port };
+    let cookie_path = cookie_path.filter(|path| !path.trim().is_empty());
+    let (rpc_user, rpc_pass) = match &cookie_path {
+        Some(path) => {
+            let auth = read_cookie_file(Path::new(path)).map_err(|e| CredentialError::Cookie(e.to_string()))?;
+            register_cookie_file(rpc_port, PathBuf::from(path));
+            auth
+        }
+        None => (rpc_user, rpc_pass),
+    };
+    let credentials = Credentials { rpc_user, rpc_pass, rpc_port, cookie_path };
     let credentials_json = serde_json::to_value(credentials)
         .map_err(|e| CredentialError::Serialization(e.to_string()))?;
 
@@ -688,8 +829,20 @@ pub async fn load_credentials<R: Runtime>(
             
             // Try to deserialize into the new format first
             match serde_json::from_value::<Credentials>(value.clone()) {
-                Ok(credentials) => {
+                Ok(mut credentials) => {
                     log::info!("Successfully loaded credentials with port: {}", credentials.rpc_port);
+                    // The cookie changes with every daemon restart; the stored copy is only a fallback
+                    if let Some(path) = credentials.cookie_path.clone() {
+                        let path = PathBuf::from(path);
+                        match read_cookie_file(&path) {
+                            Ok((user, pass)) => {
+                                credentials.rpc_user = user;
+                                credentials.rpc_pass = pass;
+                            }
+                            Err(e) => log::warn!("Failed to read auth cookie, using the last known one: {}", e),
+                        }
+                        register_cookie_file(credentials.rpc_port, path);
+                    }
                     Ok(credentials)
                 }
                 Err(e) => {
@@ -757,4 +910,80 @@ pub async fn clear_credentials<R: Runtime>(app: AppHandle<R>) -> Result<(), Cred
     }
     
     Ok(())
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Fresh directory under the system temp dir for one test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nymia-credentials-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reads_cookie_user_and_password() {
+        let dir = test_dir("cookie");
+        let path = dir.join(COOKIE_FILE_NAME);
+        fs::write(&path, "__cookie__:s3cr3t\n").unwrap();
+        assert_eq!(read_cookie_file(&path).unwrap(), ("__cookie__".to_string(), "s3cr3t".to_string()));
+        // Only the first colon separates user and password
+        fs::write(&path, "__cookie__:a:b").unwrap();
+        assert_eq!(read_cookie_file(&path).unwrap().1, "a:b");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rejects_malformed_cookies() {
+        let dir = test_dir("malformed");
+        let path = dir.join(COOKIE_FILE_NAME);
+        for content in ["", "no-separator", ":password", "__cookie__:", "  \n"] {
+            fs::write(&path, content).unwrap();
+            assert!(matches!(read_cookie_file(&path), Err(DiscoveryError::ParseError(_))), "accepted {:?}", content);
+        }
+        assert!(matches!(read_cookie_file(&dir.join("missing")), Err(DiscoveryError::IoError(_))));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_without_password_uses_cookie() {
+        let dir = test_dir("config");
+        let config = dir.join("VRSC.conf");
+        fs::write(&config, "# comment\nrpcport=27486\nserver=1\n").unwrap();
+        assert!(matches!(parse_config_file(&config), Err(DiscoveryError::ParseError(_))));
+
+        let cookie = dir.join(COOKIE_FILE_NAME);
+        fs::write(&cookie, "__cookie__:first").unwrap();
+        let credentials = parse_config_file(&config).unwrap();
+        assert_eq!(credentials.rpc_port, 27486);
+        assert_eq!(credentials.rpc_user, "__cookie__");
+        assert_eq!(credentials.rpc_pass, "first");
+        assert_eq!(credentials.cookie_path, Some(cookie.to_string_lossy().to_string()));
+
+        // Configured credentials win over a cookie
+        fs::write(&config, "rpcuser=user\nrpcpassword=pass\nrpcport=27486\n").unwrap();
+        let credentials = parse_config_file(&config).unwrap();
+        assert_eq!((credentials.rpc_user.as_str(), credentials.rpc_pass.as_str()), ("user", "pass"));
+        assert_eq!(credentials.cookie_path, None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotated_cookie_is_picked_up() {
+        let dir = test_dir("rotation");
+        let cookie = dir.join(COOKIE_FILE_NAME);
+        fs::write(&cookie, "__cookie__:first").unwrap();
+        // Port 1 is only used by this test
+        assert_eq!(refreshed_cookie_auth(1, "first"), None);
+        register_cookie_file(1, cookie.clone());
+        assert_eq!(refreshed_cookie_auth(1, "first"), None);
+        fs::write(&cookie, "__cookie__:second").unwrap();
+        assert_eq!(refreshed_cookie_auth(1, "first"), Some(("__cookie__".to_string(), "second".to_string())));
+        fs::remove_file(&cookie).unwrap();
+        assert_eq!(refreshed_cookie_auth(1, "first"), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,99 @@
+// File: src-tauri/src/currency.rs
+// Description: Currency metadata (name, fully qualified name, decimals) resolved from getcurrency.
+// Changes:
+// - Created file. Currencies are looked up by i-address or name and cached under both for the session (currency
+//   definitions don't change). Used wherever a currency i-address would otherwise reach the UI: balances,
+//   reserve outputs of transactions, gift summaries, and price lookups (currency ids).
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use super::rpc_client::{make_background_rpc_call, VerusRpcError};
+
+// Precision of Verus currencies unless the definition says otherwise
+const DEFAULT_DECIMALS: u8 = 8;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CurrencyInfo {
+    pub currency_id: String,          // i-address
+    pub name: String,                 // Short name / ticker (e.g. "vETH")
+    pub fully_qualified_name: String, // e.g. "vETH" or "DAI.vETH"; what the UI shows
+    pub decimals: u8,
+    pub system_id: Option<String>,    // Chain the currency lives on
+}
+
+// (RPC port, lowercased i-address or name) -> currency
+static CURRENCIES: LazyLock<Mutex<HashMap<(u16, String), CurrencyInfo>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Currency i-addresses look like identity addresses: 34 characters starting with 'i'
+pub fn is_currency_id(currency: &str) -> bool {
+    currency.len() == 34 && currency.starts_with('i') && currency.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn parse_definition(definition: &Value, requested: &str) -> Result<CurrencyInfo, VerusRpcError> {
+    let currency_id = definition
+        .get("currencyid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VerusRpcError::ParseError(format!("getcurrency response for {} missing currencyid", requested)))?
+        .to_string();
+    let name = definition.get("name").and_then(|v| v.as_str()).unwrap_or(requested).to_string();
+    let fully_qualified_name = definition
+        .get("fullyqualifiedname")
+        .and_then(|v| v.as_str())
+        .map(String::from)
+        .unwrap_or_else(|| name.clone());
+    Ok(CurrencyInfo {
+        currency_id,
+        name,
+        fully_qualified_name,
+        decimals: definition.get("decimals").and_then(|v| v.as_u64()).map(|d| d as u8).unwrap_or(DEFAULT_DECIMALS),
+        system_id: definition.get("systemid").and_then(|v| v.as_str()).map(String::from),
+    })
+}
+
+// Metadata of a currency by i-address or name (cached; failed lookups aren't)
+pub async fn get_currency_info(rpc_user: &str, rpc_pass: &str, rpc_port: u16, currency: &str) -> Result<CurrencyInfo, VerusRpcError> {
+    let currency = currency.trim();
+    let key = (rpc_port, currency.to_lowercase());
+    if let Some(info) = CURRENCIES.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(info.clone());
+    }
+    let definition: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getcurrency", vec![json!(currency)]).await?;
+    let info = parse_definition(&definition, currency)?;
+    let mut currencies = CURRENCIES.lock().unwrap_or_else(|e| e.into_inner());
+    for alias in [&info.currency_id, &info.fully_qualified_name, &info.name] {
+        currencies.entry((rpc_port, alias.to_lowercase())).or_insert_with(|| info.clone());
+    }
+    currencies.insert(key, info.clone());
+    Ok(info)
+}
+
+// Name to show for a currency: i-addresses become their fully qualified name, anything else (names, and
+// currencies the daemon can't resolve) is returned unchanged
+pub async fn currency_display_name(rpc_user: &str, rpc_pass: &str, rpc_port: u16, currency: &str) -> String {
+    if !is_currency_id(currency) {
+        return currency.to_string();
+    }
+    match get_currency_info(rpc_user, rpc_pass, rpc_port, currency).await {
+        Ok(info) => info.fully_qualified_name,
+        Err(e) => {
+            log::debug!("Failed to resolve currency {}: {:?}", currency, e);
+            currency.to_string()
+        }
+    }
+}
+
+// Already resolved name of a currency i-address, without asking the daemon (i-addresses are unique across chains)
+pub fn cached_display_name(currency: &str) -> Option<String> {
+    if !is_currency_id(currency) {
+        return None;
+    }
+    let key = currency.to_lowercase();
+    CURRENCIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|((_, alias), _)| *alias == key)
+        .map(|(_, info)| info.fully_qualified_name.clone())
+}
@@ -0,0 +1,213 @@
+// File: src-tauri/src/error_log.rs
+// Description: Local capture of backend panics and command failures for troubleshooting.
+// Changes:
+// - Created file. A panic hook and the CommandError conversions record redacted entries in memory and in
+//   crash.log (JSON lines, app log directory); entries from earlier runs are loaded at startup so a crash
+//   that killed the app can still be inspected via get_recent_errors.
+// - Command errors are recorded when they are serialized for the frontend (recorded_command_error), for every
+//   command error type instead of only the CommandError conversions.
+// - Timestamps come from clock::now_secs (the local helper is gone).
+// - Only the outermost serialization of a command error records it; errors nested in another error (serialized
+//   as part of it) are not recorded a second time.
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use tauri::{AppHandle, Manager, Runtime};
+use super::clock::now_secs;
+
+const CRASH_LOG_FILE: &str = "crash.log";
+
+// Rotated to crash.log.1 when larger
+const MAX_CRASH_LOG_BYTES: u64 = 1024 * 1024;
+
+// Entries kept in memory (and loaded from disk at startup)
+const MAX_RECENT_ERRORS: usize = 200;
+
+static RECENT_ERRORS: LazyLock<Mutex<VecDeque<ErrorRecord>>> = LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+// Set once the app log directory is known; entries before that are kept in memory only
+static CRASH_LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Panic,
+    Command,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorRecord {
+    pub timestamp: u64,
+    pub kind: ErrorKind,
+    pub source: String, // Panic location or error category
+    pub message: String, // Redacted
+    pub thread: Option<String>,
+}
+
+// Mask shielded addresses and spending keys so logs can be shared
+fn redact(message: &str) -> String {
+    message
+        .split_inclusive(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+        .map(|token| {
+            let word = token.trim_end_matches(|c: char| !c.is_ascii_alphanumeric() && c != '-');
+            let is_secret = word.starts_with("secret-extended-key") || (word.starts_with("zs1") && word.len() > 40);
+            if is_secret {
+                token.replacen(word, &format!("{}<redacted>", &word[..3]), 1)
+            } else {
+                token.to_string()
+            }
+        })
+        .collect()
+}
+
+fn append_to_crash_log(record: &ErrorRecord) {
+    let Some(path) = CRASH_LOG_PATH.get() else { return };
+    if std::fs::metadata(path).is_ok_and(|m| m.len() > MAX_CRASH_LOG_BYTES) {
+        let _ = std::fs::rename(path, path.with_extension("log.1"));
+    }
+    let Ok(line) = serde_json::to_string(record) else { return };
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(e) = result {
+        // Logging only; capturing an error must never fail the caller
+        log::warn!("Failed to write crash log: {}", e);
+    }
+}
+
+fn record(kind: ErrorKind, source: &str, message: &str) {
+    let record = ErrorRecord {
+        timestamp: now_secs(),
+        kind,
+        source: source.to_string(),
+        message: redact(message),
+        thread: std::thread::current().name().map(str::to_string),
+    };
+    append_to_crash_log(&record);
+
+    let mut recent = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() >= MAX_RECENT_ERRORS {
+        recent.pop_front();
+    }
+    recent.push_back(record);
+}
+
+// Record a failed command
+pub fn record_command_error(category: &str, message: &str) {
+    record(ErrorKind::Command, category, message);
+}
+
+thread_local! {
+    // Set while a recorded error is being serialized, so errors nested in it aren't recorded again
+    static SERIALIZING_ERROR: Cell<bool> = const { Cell::new(false) };
+}
+
+// Clears SERIALIZING_ERROR when the outermost serialization ends (also if the serializer panics)
+struct OutermostSerialization;
+
+impl Drop for OutermostSerialization {
+    fn drop(&mut self) {
+        SERIALIZING_ERROR.with(|serializing| serializing.set(false));
+    }
+}
+
+// Record a command error and serialize it, unless it is serialized as part of another recorded error
+pub fn serialize_recorded<T>(category: &str, message: impl FnOnce() -> String, serialize: impl FnOnce() -> T) -> T {
+    if SERIALIZING_ERROR.with(|serializing| serializing.replace(true)) {
+        return serialize();
+    }
+    let _outermost = OutermostSerialization;
+    record_command_error(category, &message());
+    serialize()
+}
+
+// Serialize for an error type that commands return, derived with #[serde(remote = "Self")] so the derived
+// serializer is still used. Errors are only serialized when a command hands them to the frontend, so every failed
+// command is recorded here (and errors handled inside the backend are not). An error wrapped in another one is
+// recorded once, by the outer error.
+macro_rules! recorded_command_error {
+    ($error:ty, $category:expr) => {
+        impl serde::Serialize for $error {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $crate::error_log::serialize_recorded($category, || self.to_string(), || <$error>::serialize(self, serializer))
+            }
+        }
+    };
+}
+pub(crate) use recorded_command_error;
+
+// Record panics before handing over to the default hook (which prints them)
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        record(ErrorKind::Panic, &location, &message);
+        previous(info);
+    }));
+}
+
+// Point the crash log at the app log directory and load entries from earlier runs
+pub fn init_crash_log<R: Runtime>(app: &AppHandle<R>) {
+    let dir = match app.path().app_log_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("No log directory for the crash log: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("Failed to create log directory {:?}: {}", dir, e);
+        return;
+    }
+    let path = dir.join(CRASH_LOG_FILE);
+
+    let previous: Vec<ErrorRecord> = std::fs::read_to_string(&path)
+        .map(|contents| contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default();
+    {
+        let mut recent = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+        let captured_this_run: Vec<ErrorRecord> = recent.drain(..).collect();
+        recent.extend(previous.into_iter().chain(captured_this_run));
+        while recent.len() > MAX_RECENT_ERRORS {
+            recent.pop_front();
+        }
+    }
+    let _ = CRASH_LOG_PATH.set(path);
+}
+
+// --- Tauri Commands ---
+
+// Most recent captured errors, newest first
+#[tauri::command]
+pub fn get_recent_errors(limit: Option<usize>) -> Vec<ErrorRecord> {
+    log::debug!("get_recent_errors command received");
+    let recent = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    recent.iter().rev().take(limit.unwrap_or(MAX_RECENT_ERRORS)).cloned().collect()
+}
+
+#[tauri::command]
+pub fn clear_recent_errors() -> Result<(), String> {
+    log::info!("clear_recent_errors command received");
+    RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    if let Some(path) = CRASH_LOG_PATH.get() {
+        if path.exists() {
+            std::fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+// File: src-tauri/src/events.rs
+// Description: Central place for backend -> frontend event names and emission.
+// Changes:
+// - Created file with task lifecycle event name and emit_event helper.
+// - Added message notification event.
+// - Added message store update event.
+// - Added conversation prefetch event.
+// - Added message requests update event.
+// - Added conversation auto-accepted event.
+// - Added clock skew event.
+// - Added conversations read event.
+// - Added verification backlog progress event.
+// - Added conversations recovered event.
+// - Added UTXO split event.
+// - Added shield operation event.
+// - Added sync progress event.
+// - Added wallet rescan event.
+// - Added conversion gift event.
+// - Added low balance warning and notification events.
+// - Added automatic top-up event.
+// - Added identity registration event.
+// - Added profile publish event.
+// - Added contact identity changed event.
+// - Added identity session event.
+// - Added wallet identities changed event.
+// - Added daemon notification and ZMQ status events.
+// - Added integrity report event.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+
+// Task lifecycle updates (started / completed / cancelled / failed)
+pub const TASK_LIFECYCLE_EVENT: &str = "task-lifecycle";
+
+// A received message that should produce a user-facing notification
+pub const MESSAGE_NOTIFICATION_EVENT: &str = "message-notification";
+
+// A conversation's canonical message list changed in the message store
+pub const MESSAGE_STORE_UPDATED_EVENT: &str = "message-store-updated";
+
+// Data prefetched in the background for the open conversation
+pub const CONVERSATION_PREFETCH_EVENT: &str = "conversation-prefetch";
+
+// The held "message requests" (spam-filtered messages) of an identity changed
+pub const MESSAGE_REQUESTS_UPDATED_EVENT: &str = "message-requests-updated";
+
+// A conversation was created from an unknown sender's gift without going through message requests
+pub const CONVERSATION_AUTO_ACCEPTED_EVENT: &str = "conversation-auto-accepted";
+
+// The local clock disagrees with chain time (signed timestamps would be off)
+pub const CLOCK_SKEW_EVENT: &str = "clock-skew";
+
+// Conversations were marked as read in bulk (mark_all_read)
+pub const CONVERSATIONS_READ_EVENT: &str = "conversations-read";
+
+// Progress of the background verification of a first-sync backlog
+pub const VERIFICATION_BACKLOG_EVENT: &str = "verification-backlog";
+
+// Conversations were created for recipients found in outgoing memos (sent from another client)
+pub const CONVERSATIONS_RECOVERED_EVENT: &str = "conversations-recovered";
+
+// A background self-send split a large note to restore Fast Messages capacity
+pub const UTXO_SPLIT_EVENT: &str = "utxo-split";
+
+// A background shielding operation (transparent -> private address) finished
+pub const SHIELD_OPERATION_EVENT: &str = "shield-operation";
+
+// Periodic daemon sync progress while the sync watcher runs
+pub const SYNC_PROGRESS_EVENT: &str = "sync-progress";
+
+// Wallet rescan started / still running / completed / failed
+pub const WALLET_RESCAN_EVENT: &str = "wallet-rescan";
+
+// A convert-and-send gift operation finished (or failed)
+pub const CONVERSION_GIFT_EVENT: &str = "conversion-gift";
+
+// Private balance below the identity's alert thresholds (every check while it stays below)
+pub const LOW_BALANCE_EVENT: &str = "low-balance";
+
+// First low-balance check after the balance dropped below the thresholds
+pub const LOW_BALANCE_NOTIFICATION_EVENT: &str = "low-balance-notification";
+
+// An automatic top-up started, succeeded or failed (payload: the audit entry)
+pub const AUTO_TOPUP_EVENT: &str = "auto-top-up";
+
+// VerusID registration progress (commitment, registration, completed / failed)
+pub const IDENTITY_REGISTRATION_EVENT: &str = "identity-registration";
+
+// A published profile update is confirming / confirmed / failed
+pub const PROFILE_PUBLISH_EVENT: &str = "profile-publish";
+
+// A contact's private (receiving) address or primary addresses changed (security relevant)
+pub const CONTACT_IDENTITY_CHANGED_EVENT: &str = "contact-identity-changed";
+
+// An identity session was opened or switched to, or an inactive one received messages
+pub const IDENTITY_SESSION_EVENT: &str = "identity-session-updated";
+
+// Identities became eligible in the wallet (or stopped being eligible, or changed) while the app is open
+pub const WALLET_IDENTITIES_CHANGED_EVENT: &str = "wallet-identities-changed";
+
+// The daemon pushed a new block or transaction over ZMQ (refresh balances and messages now)
+pub const DAEMON_NOTIFICATION_EVENT: &str = "daemon-notification";
+
+// The ZMQ listener connected, disconnected or found ZMQ unavailable (polling is timer-based while not connected)
+pub const ZMQ_STATUS_EVENT: &str = "zmq-status";
+
+// The startup integrity audit of an identity finished (payload: IntegrityReport)
+pub const INTEGRITY_REPORT_EVENT: &str = "integrity-report";
+
+// Emit an event to all webviews, logging (not propagating) failures
+pub fn emit_event<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        log::warn!("Failed to emit event '{}': {}", event, e);
+    }
+}
@@ -0,0 +1,530 @@
+// File: src-tauri/src/export.rs
+// Description: Export of a conversation's message history to a user-chosen JSON, CSV, Matrix or transcript file, and import of JSON archives.
+// Changes:
+// - Created file with the export_conversation command (save dialog via the dialog plugin).
+// - pick_save_path is shared (title and file type filter are parameters).
+// - Added import_conversation: validates a JSON archive, optionally re-verifies signatures and merges it
+//   into the settings store, skipping txids that are already stored.
+// - Added the Matrix exporter (m.room.message events in Element's export layout; gifts under io.nymia.gift).
+// - Added printable Markdown and HTML transcripts (grouped by day, local time, sender names, gift amounts).
+// - CSV text fields starting with = + - @ tab or CR are prefixed with ' (spreadsheet formula injection).
+// - The imported conversation is added with update_conversations.
+// - ExportError goes into the error log when a command returns it.
+// - Uses clock::now_secs instead of a local copy.
+// - A transcript UTC offset out of range fails the export (InvalidOption) before anything is rendered.
+// - Gifts in a non-native currency show that currency in transcripts instead of the chain's ticker.
+
+use chrono::{FixedOffset, Offset, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::{AppHandle, Runtime, State};
+use super::error_log::recorded_command_error;
+use super::formatting::{build_formatted_currency_amount, build_formatted_timestamp, normalize_timestamp_secs, utc_offset, FormattingError};
+use super::message_store::MessageStore;
+use super::protocol::signed_payload;
+use super::rpc_client::{verify_message, VerusRpcError};
+use super::settings::{read_conversation_messages, read_conversations, update_conversations, write_conversation_messages, ChatMessage, Conversation};
+use super::clock::now_secs;
+
+// Version of the JSON archive layout
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Matrix, // Matrix room events (Element export layout) for bridging / migrating
+    Markdown, // Printable transcript
+    Html,     // Printable transcript (self-contained page)
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json | ExportFormat::Matrix => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+// Server part of the Matrix user ids VerusIDs are mapped to; bridges rewrite it on import
+const MATRIX_SERVER_NAME: &str = "verus.local";
+
+// Namespaced content key for gift details (not part of the Matrix spec)
+const MATRIX_GIFT_KEY: &str = "io.nymia.gift";
+
+// JSON archive of one conversation
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConversationArchive {
+    pub format_version: u32,
+    pub exported_at: u64,
+    pub identity_i_address: String,
+    pub conversation: Conversation,
+    pub messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum ExportError {
+    #[error("Settings error: {0}")]
+    Settings(String),
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+    #[error("Failed to write file: {0}")]
+    Io(String),
+    #[error("Invalid file path: {0}")]
+    InvalidPath(String),
+    #[error("Invalid export option: {0}")]
+    InvalidOption(String),
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(String),
+    #[error("Archive belongs to identity {0}")]
+    IdentityMismatch(String),
+    #[error("Credential error: {0}")]
+    Credentials(String),
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+}
+
+recorded_command_error!(ExportError, "export");
+
+impl From<FormattingError> for ExportError {
+    fn from(error: FormattingError) -> Self {
+        ExportError::InvalidOption(error.to_string())
+    }
+}
+
+impl From<super::settings::SettingsError> for ExportError {
+    fn from(error: super::settings::SettingsError) -> Self {
+        ExportError::Settings(error.to_string())
+    }
+}
+
+impl From<VerusRpcError> for ExportError {
+    fn from(error: VerusRpcError) -> Self {
+        ExportError::Rpc(error)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportReport {
+    pub conversation_id: String,
+    pub total: usize,      // Messages in the archive
+    pub imported: usize,   // Newly added
+    pub duplicates: usize, // Txid already stored
+    pub invalid: usize,    // Structurally invalid, skipped
+    pub unverified: usize, // Missing or failing signature, skipped (only when verifying)
+}
+
+// Quote a CSV field when needed (RFC 4180). Text starting like a formula gets a leading ' so spreadsheets show it
+// instead of evaluating it (message text and names come from other people).
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) { format!("'{}", value) } else { value.to_string() };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn render_csv(messages: &[ChatMessage]) -> String {
+    let mut csv = String::from("txid,direction,sender,timestamp,time_utc,amount,confirmations,status,protocol_version,signature,text\n");
+    for m in messages {
+        let time_utc = build_formatted_timestamp(m.timestamp, Some("en-US"), Utc.fix(), now_secs()).iso;
+        let row = [
+            csv_field(&m.id),
+            csv_field(&m.direction),
+            csv_field(&m.sender),
+            normalize_timestamp_secs(m.timestamp).to_string(),
+            time_utc,
+            format!("{:.8}", m.amount),
+            m.confirmations.to_string(),
+            csv_field(m.status.as_deref().unwrap_or("")),
+            m.protocol_version.map(|v| v.to_string()).unwrap_or_default(),
+            csv_field(m.signature.as_deref().unwrap_or("")),
+            csv_field(&m.text),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+// Map a VerusID to a Matrix user id (localparts only allow a-z, 0-9 and ._=-/)
+fn matrix_user_id(verus_id: &str) -> String {
+    let localpart: String = verus_id
+        .trim_end_matches('@')
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "._=-/".contains(c) { c } else { '_' })
+        .collect();
+    format!("@{}:{}", localpart, MATRIX_SERVER_NAME)
+}
+
+fn render_matrix(conversation: &Conversation, identity_i_address: &str, messages: &[ChatMessage]) -> serde_json::Value {
+    let room_id = format!("!{}:{}", conversation.id.trim_end_matches('@').to_lowercase(), MATRIX_SERVER_NAME);
+    let events: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| {
+            // Sent messages are stored with our identity (or "self") as the sender
+            let sender = if m.direction == "sent" && m.sender == "self" { identity_i_address } else { m.sender.as_str() };
+            let mut content = serde_json::json!({ "msgtype": "m.text", "body": m.text });
+            if m.amount > 0.0 {
+                content[MATRIX_GIFT_KEY] = serde_json::json!({ "amount": m.amount, "txid": m.id });
+            }
+            serde_json::json!({
+                "type": "m.room.message",
+                "event_id": format!("${}", m.id),
+                "room_id": room_id,
+                "sender": matrix_user_id(sender),
+                "origin_server_ts": normalize_timestamp_secs(m.timestamp) * 1000,
+                "content": content,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "room_name": conversation.name,
+        "room_creator": matrix_user_id(identity_i_address),
+        "topic": format!("Nymia conversation with {}", conversation.id),
+        "export_date": build_formatted_timestamp(now_secs(), Some("en-US"), Utc.fix(), now_secs()).iso,
+        "exported_by": matrix_user_id(identity_i_address),
+        "messages": events,
+    })
+}
+
+// Locale and offset the transcript's dates and times are rendered in
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TranscriptOptions {
+    pub locale: Option<String>,
+    pub utc_offset_minutes: Option<i32>,
+}
+
+// One rendered transcript line
+struct TranscriptEntry {
+    date: String,
+    time: String,
+    sender: String,
+    text: String,
+    gift: Option<String>, // Formatted amount with ticker
+}
+
+fn transcript_entries(messages: &[ChatMessage], options: &TranscriptOptions, offset: FixedOffset) -> Vec<TranscriptEntry> {
+    let locale = options.locale.as_deref();
+    messages
+        .iter()
+        .map(|m| {
+            let formatted = build_formatted_timestamp(m.timestamp, locale, offset, now_secs());
+            // Locale dates never contain spaces, so this splits "date time"
+            let (date, time) = formatted.absolute.split_once(' ').unwrap_or((formatted.absolute.as_str(), ""));
+            TranscriptEntry {
+                date: date.to_string(),
+                time: time.to_string(),
+                sender: if m.sender == "self" { "You".to_string() } else { m.sender.clone() },
+                text: m.text.clone(),
+                gift: (m.amount > 0.0).then(|| build_formatted_currency_amount(m.amount, m.currency.as_deref(), locale).display),
+            }
+        })
+        .collect()
+}
+
+fn render_markdown(conversation: &Conversation, messages: &[ChatMessage], options: &TranscriptOptions, offset: FixedOffset) -> String {
+    let mut md = format!("# Conversation with {}\n\n", conversation.name);
+    let mut current_date = String::new();
+    for entry in transcript_entries(messages, options, offset) {
+        if entry.date != current_date {
+            md.push_str(&format!("## {}\n\n", entry.date));
+            current_date = entry.date;
+        }
+        md.push_str(&format!("**{}** · {}", entry.sender, entry.time));
+        if let Some(gift) = &entry.gift {
+            md.push_str(&format!(" · Gift: {}", gift));
+        }
+        md.push_str("\n\n");
+        // Quote every line so multi-line messages stay one block
+        for line in entry.text.lines() {
+            md.push_str(&format!("> {}\n", line));
+        }
+        md.push('\n');
+    }
+    md
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+const TRANSCRIPT_STYLE: &str = "body{font-family:sans-serif;max-width:48rem;margin:2rem auto;color:#111}\
+h2{font-size:1rem;border-bottom:1px solid #ccc;margin-top:2rem}\
+.meta{color:#555;font-size:.85rem}.gift{font-weight:bold}\
+p.text{white-space:pre-wrap;margin:.25rem 0 1rem}\
+@media print{body{margin:0}}";
+
+fn render_html(conversation: &Conversation, messages: &[ChatMessage], options: &TranscriptOptions, offset: FixedOffset) -> String {
+    let title = html_escape(&format!("Conversation with {}", conversation.name));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        title, TRANSCRIPT_STYLE, title
+    );
+    let mut current_date = String::new();
+    for entry in transcript_entries(messages, options, offset) {
+        if entry.date != current_date {
+            html.push_str(&format!("<h2>{}</h2>\n", html_escape(&entry.date)));
+            current_date = entry.date;
+        }
+        html.push_str(&format!("<div class=\"meta\"><strong>{}</strong> · {}", html_escape(&entry.sender), html_escape(&entry.time)));
+        if let Some(gift) = &entry.gift {
+            html.push_str(&format!(" · <span class=\"gift\">Gift: {}</span>", html_escape(gift)));
+        }
+        html.push_str(&format!("</div>\n<p class=\"text\">{}</p>\n", html_escape(&entry.text)));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// Ask the user where to save; None if the dialog was cancelled
+pub async fn pick_save_path<R: Runtime>(
+    app: &AppHandle<R>,
+    title: &str,
+    default_name: String,
+    extension: Option<&str>, // Restricts the dialog to this file type
+) -> Result<Option<PathBuf>, ExportError> {
+    use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
+    use tokio::sync::oneshot;
+
+    let (tx, rx) = oneshot::channel();
+    let mut dialog = FileDialogBuilder::new(app.dialog().clone())
+        .set_title(title)
+        .set_file_name(default_name);
+    if let Some(extension) = extension {
+        dialog = dialog.add_filter(extension.to_uppercase(), &[extension]);
+    }
+    dialog.save_file(move |file_path| {
+        let _ = tx.send(file_path);
+    });
+
+    match rx.await {
+        Ok(Some(file_path)) => file_path
+            .into_path()
+            .map(Some)
+            .map_err(|e| ExportError::InvalidPath(e.to_string())),
+        _ => Ok(None),
+    }
+}
+
+// Ask the user for a file to open; None if the dialog was cancelled
+async fn pick_open_path<R: Runtime>(app: &AppHandle<R>, title: &str, extension: &str) -> Result<Option<PathBuf>, ExportError> {
+    use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
+    use tokio::sync::oneshot;
+
+    let (tx, rx) = oneshot::channel();
+    FileDialogBuilder::new(app.dialog().clone())
+        .set_title(title)
+        .add_filter(extension.to_uppercase(), &[extension])
+        .pick_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+
+    match rx.await {
+        Ok(Some(file_path)) => file_path
+            .into_path()
+            .map(Some)
+            .map_err(|e| ExportError::InvalidPath(e.to_string())),
+        _ => Ok(None),
+    }
+}
+
+// Structural checks for an archived message; returns the reason it is invalid
+fn validate_message(message: &ChatMessage, conversation_id: &str) -> Result<(), String> {
+    if message.id.trim().is_empty() {
+        return Err("missing txid".to_string());
+    }
+    if message.sender.trim().is_empty() {
+        return Err("missing sender".to_string());
+    }
+    match message.direction.as_str() {
+        "received" if message.sender != conversation_id => Err(format!("received message from {} in conversation {}", message.sender, conversation_id)),
+        "received" | "sent" => Ok(()),
+        other => Err(format!("unknown direction '{}'", other)),
+    }?;
+    if !message.amount.is_finite() || message.amount < 0.0 {
+        return Err(format!("invalid amount {}", message.amount));
+    }
+    Ok(())
+}
+
+// Re-verify an archived message's signature. Received memos are bound to our address, sent ones to the peer's.
+async fn verify_archived_message(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    message: &ChatMessage,
+    own_private_address: &str,
+    peer_private_address: &str,
+) -> Result<bool, VerusRpcError> {
+    let Some(signature) = &message.signature else { return Ok(false) };
+    let recipient_address = if message.direction == "sent" { peer_private_address } else { own_private_address };
+    let payload = signed_payload(&message.text, &message.sender, message.timestamp, message.protocol_version.unwrap_or(1), recipient_address);
+    verify_message(rpc_user, rpc_pass, rpc_port, &message.sender, signature, &payload).await
+}
+
+// --- Tauri Commands ---
+
+// Export a conversation's full history. Returns the written path, or None if the user cancelled.
+#[tauri::command]
+pub async fn export_conversation<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    conversation_id: String,
+    format: ExportFormat,
+    transcript_options: Option<TranscriptOptions>, // Markdown / HTML only
+) -> Result<Option<String>, ExportError> {
+    log::info!("export_conversation command received for {} (user {}) as {:?}", conversation_id, identity_i_address, format);
+    let transcript_options = transcript_options.unwrap_or_default();
+    let transcript_offset = utc_offset(transcript_options.utc_offset_minutes)?;
+
+    let conversation = read_conversations(&app, &identity_i_address)?
+        .into_iter()
+        .find(|c| c.id == conversation_id)
+        .unwrap_or_else(|| Conversation {
+            id: conversation_id.clone(),
+            name: conversation_id.clone(),
+            recipient_private_address: String::new(),
+            unread: None,
+            archived: false,
+        });
+    let messages = store.load_conversation(&app, &identity_i_address, &conversation_id);
+
+    let contents = match format {
+        ExportFormat::Json => {
+            let archive = ConversationArchive {
+                format_version: ARCHIVE_FORMAT_VERSION,
+                exported_at: now_secs(),
+                identity_i_address,
+                conversation,
+                messages,
+            };
+            serde_json::to_string_pretty(&archive).map_err(|e| ExportError::Serialization(e.to_string()))?
+        }
+        ExportFormat::Csv => render_csv(&messages),
+        ExportFormat::Matrix => serde_json::to_string_pretty(&render_matrix(&conversation, &identity_i_address, &messages))
+            .map_err(|e| ExportError::Serialization(e.to_string()))?,
+        ExportFormat::Markdown => render_markdown(&conversation, &messages, &transcript_options, transcript_offset),
+        ExportFormat::Html => render_html(&conversation, &messages, &transcript_options, transcript_offset),
+    };
+
+    let default_name = format!("nymia-{}.{}", conversation_id.trim_end_matches('@'), format.extension());
+    let Some(path) = pick_save_path(&app, "Export conversation", default_name, Some(format.extension())).await? else {
+        log::info!("Export cancelled by user");
+        return Ok(None);
+    };
+
+    tokio::fs::write(&path, contents).await.map_err(|e| ExportError::Io(e.to_string()))?;
+    log::info!("Exported conversation {} to {}", conversation_id, path.display());
+    Ok(Some(path.display().to_string()))
+}
+
+// Import a JSON archive created by export_conversation. Returns None if the user cancelled the file dialog.
+#[tauri::command]
+pub async fn import_conversation<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    own_private_address: String, // Needed to rebuild the signed payload of received messages
+    verify_signatures: bool,     // Drop messages whose signature doesn't verify against the daemon
+) -> Result<Option<ImportReport>, ExportError> {
+    log::info!("import_conversation command received for {} (verify signatures: {})", identity_i_address, verify_signatures);
+
+    let Some(path) = pick_open_path(&app, "Import conversation", ExportFormat::Json.extension()).await? else {
+        log::info!("Import cancelled by user");
+        return Ok(None);
+    };
+    let contents = tokio::fs::read_to_string(&path).await.map_err(|e| ExportError::Io(e.to_string()))?;
+    let archive: ConversationArchive = serde_json::from_str(&contents).map_err(|e| ExportError::InvalidArchive(e.to_string()))?;
+
+    if archive.format_version == 0 || archive.format_version > ARCHIVE_FORMAT_VERSION {
+        return Err(ExportError::InvalidArchive(format!("unsupported format version {}", archive.format_version)));
+    }
+    if archive.identity_i_address != identity_i_address {
+        return Err(ExportError::IdentityMismatch(archive.identity_i_address));
+    }
+    let conversation_id = archive.conversation.id.clone();
+    if conversation_id.trim().is_empty() {
+        return Err(ExportError::InvalidArchive("missing conversation id".to_string()));
+    }
+
+    let credentials = if verify_signatures {
+        Some(super::credentials::load_credentials(app.clone()).await.map_err(|e| ExportError::Credentials(e.to_string()))?)
+    } else {
+        None
+    };
+
+    let mut stored = read_conversation_messages(&app, &identity_i_address, &conversation_id)?;
+    let mut known: HashSet<String> = stored.iter().map(|m| m.id.clone()).collect();
+    let mut report = ImportReport {
+        conversation_id: conversation_id.clone(),
+        total: archive.messages.len(),
+        imported: 0,
+        duplicates: 0,
+        invalid: 0,
+        unverified: 0,
+    };
+
+    let mut imported = Vec::new();
+    for message in archive.messages {
+        if let Err(reason) = validate_message(&message, &conversation_id) {
+            log::warn!("Skipping invalid archived message {}: {}", message.id, reason);
+            report.invalid += 1;
+            continue;
+        }
+        if known.contains(&message.id) {
+            report.duplicates += 1;
+            continue;
+        }
+        if let Some(creds) = &credentials {
+            let peer_address = &archive.conversation.recipient_private_address;
+            if !verify_archived_message(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &message, &own_private_address, peer_address).await? {
+                log::warn!("Skipping archived message {}: signature missing or invalid", message.id);
+                report.unverified += 1;
+                continue;
+            }
+        }
+        known.insert(message.id.clone());
+        imported.push(message);
+    }
+    report.imported = imported.len();
+
+    if !imported.is_empty() {
+        stored.extend(imported.iter().cloned());
+        stored.sort_by(|a, b| {
+            normalize_timestamp_secs(a.timestamp)
+                .cmp(&normalize_timestamp_secs(b.timestamp))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        write_conversation_messages(&app, &identity_i_address, &conversation_id, &stored)?;
+        store.merge(&app, &identity_i_address, &conversation_id, imported)?;
+    }
+
+    // Make the conversation visible if it isn't in the list yet
+    update_conversations(&app, &identity_i_address, |conversations| {
+        if !conversations.iter().any(|c| c.id == conversation_id) {
+            conversations.push(archive.conversation);
+        }
+    })?;
+
+    log::info!(
+        "Imported {} of {} messages into {} ({} duplicates, {} invalid, {} unverified)",
+        report.imported, report.total, conversation_id, report.duplicates, report.invalid, report.unverified
+    );
+    Ok(Some(report))
+}
@@ -0,0 +1,201 @@
+// File: src-tauri/src/external_signer.rs
+// Description: Sending with a signature produced outside the connected daemon (offline machine, future hardware).
+// Changes:
+// - Created file. A send is split in two: prepare_external_send exports a signing request (the exact payload for
+//   signmessage), complete_external_send imports the signature, checks it with verifymessage and submits the
+//   transaction. Only the identity's signing key needs to live elsewhere; the z-address still spends from this wallet.
+// - The recipient address is validated against the connected chain before the request is exported.
+// - complete_external_send takes the pending send out before submitting (a concurrent completion of the same
+//   request finds nothing) and puts it back if the send fails. Request ids also cover amount, fee and the time
+//   of preparation, so identical texts prepared back to back no longer replace each other.
+// - Dropped the private now_secs in favour of clock::now_secs.
+// - Request ids come from clock::new_id.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use super::address::validate_recipient_address;
+use super::message_rpc::{assemble_signed_memo_hex, prepare_memo, resolve_fee, submit_signed_memo, UnsignedMemo};
+use super::rpc_client::{verify_message, VerusRpcError};
+use super::clock::{new_id, now_secs};
+
+// Pending requests are dropped after this long (the signed timestamp would be stale by then)
+const REQUEST_TTL_SECS: u64 = 60 * 60;
+
+// Stand-in signature used to check that the memo will fit before the request is exported
+const PLACEHOLDER_SIGNATURE_CHARS: usize = 96;
+
+// Exported to the external signer. Sign `message` with the identity (e.g. `signmessage <identity> <message>`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SigningRequest {
+    pub request_id: String,
+    pub identity: String,
+    pub message: String,
+    pub message_sha256: String, // For comparing what the offline machine shows with what was exported
+    pub expires_at: u64,
+}
+
+// Everything needed to finish the send once the signature arrives
+#[derive(Debug, Clone)]
+pub struct PendingExternalSend {
+    pub unsigned: UnsignedMemo,
+    pub sender_z_address: String,
+    pub amount: f64,
+    pub fee: Option<f64>,
+    pub identity_i_address: Option<String>, // Where to record the sent message once submitted
+    pub conversation_id: Option<String>,
+    pub expires_at: u64,
+}
+
+// Request id -> pending send (in memory; a restart abandons unsigned sends)
+static PENDING: LazyLock<Mutex<HashMap<String, PendingExternalSend>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum ExternalSignerError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Unknown signing request: {0}")]
+    UnknownRequest(String),
+    #[error("Signing request {0} expired; prepare the send again")]
+    Expired(String),
+    #[error("Signature is not valid for this request")]
+    InvalidSignature,
+}
+
+impl From<VerusRpcError> for ExternalSignerError {
+    fn from(error: VerusRpcError) -> Self {
+        ExternalSignerError::Rpc(error)
+    }
+}
+
+fn new_request_id(message_sha256: &str, sender_z_address: &str, amount: f64, fee: Option<f64>) -> String {
+    new_id(&[
+        message_sha256.as_bytes(),
+        sender_z_address.as_bytes(),
+        &amount.to_le_bytes(),
+        &fee.unwrap_or(0.0).to_le_bytes(),
+    ])
+}
+
+fn prune_expired(pending: &mut HashMap<String, PendingExternalSend>) {
+    let now = now_secs();
+    pending.retain(|_, send| send.expires_at > now);
+}
+
+// Prepare a message/gift for external signing and keep it pending. Fee and memo size are checked now so the
+// offline round trip isn't wasted on a send that would be rejected.
+#[allow(clippy::too_many_arguments)]
+pub async fn prepare_external_send(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    memo_text: String,
+    sender_identity: String,
+    amount: f64,
+    fee: Option<f64>,
+    identity_i_address: Option<String>,
+    conversation_id: Option<String>,
+) -> Result<SigningRequest, ExternalSignerError> {
+    let fee = resolve_fee(fee, None)?;
+    validate_recipient_address(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address).await?;
+    let unsigned = prepare_memo(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address, &memo_text, &sender_identity).await;
+    assemble_signed_memo_hex(&unsigned, &"A".repeat(PLACEHOLDER_SIGNATURE_CHARS))?;
+
+    let message_sha256 = hex::encode(Sha256::digest(unsigned.payload.as_bytes()));
+    let request_id = new_request_id(&message_sha256, &sender_z_address, amount, fee);
+    let expires_at = now_secs() + REQUEST_TTL_SECS;
+    let request = SigningRequest {
+        request_id: request_id.clone(),
+        identity: sender_identity,
+        message: unsigned.payload.clone(),
+        message_sha256,
+        expires_at,
+    };
+
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    prune_expired(&mut pending);
+    pending.insert(request_id.clone(), PendingExternalSend {
+        unsigned,
+        sender_z_address,
+        amount,
+        fee,
+        identity_i_address,
+        conversation_id,
+        expires_at,
+    });
+    log::info!("Prepared external signing request {} ({} pending)", request_id, pending.len());
+    Ok(request)
+}
+
+// Check the imported signature and submit the pending send. Returns the txid and the completed request.
+// The request is taken out of the pending list while this runs, so it can only be submitted once; a rejected
+// signature or failed send puts it back so a corrected signature can be imported or the send retried.
+pub async fn complete_external_send(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    request_id: String,
+    signature: String,
+) -> Result<(String, PendingExternalSend), ExternalSignerError> {
+    let send = PENDING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&request_id)
+        .ok_or_else(|| ExternalSignerError::UnknownRequest(request_id.clone()))?;
+    if send.expires_at <= now_secs() {
+        return Err(ExternalSignerError::Expired(request_id));
+    }
+
+    match submit_external_send(&rpc_user, &rpc_pass, rpc_port, &request_id, signature.trim(), &send).await {
+        Ok(txid) => {
+            log::info!("Externally signed request {} sent in {}", request_id, txid);
+            Ok((txid, send))
+        }
+        Err(e) => {
+            PENDING.lock().unwrap_or_else(|e| e.into_inner()).insert(request_id, send);
+            Err(e)
+        }
+    }
+}
+
+async fn submit_external_send(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    request_id: &str,
+    signature: &str,
+    send: &PendingExternalSend,
+) -> Result<String, ExternalSignerError> {
+    // Recipients would reject a bad signature anyway; catching it here keeps the fee
+    let valid = verify_message(rpc_user, rpc_pass, rpc_port, &send.unsigned.sender_identity, signature, &send.unsigned.payload).await?;
+    if !valid {
+        log::warn!("Imported signature for request {} did not verify", request_id);
+        return Err(ExternalSignerError::InvalidSignature);
+    }
+
+    let memo_hex = assemble_signed_memo_hex(&send.unsigned, signature)?;
+    let txid = submit_signed_memo(
+        rpc_user,
+        rpc_pass,
+        rpc_port,
+        &send.sender_z_address,
+        &send.unsigned.recipient_z_address,
+        send.amount,
+        &memo_hex,
+        send.fee,
+    )
+    .await?;
+    Ok(txid)
+}
+
+// --- Tauri Commands ---
+
+// Abandon a pending externally signed send
+#[tauri::command]
+pub fn cancel_external_send(request_id: String) -> bool {
+    log::info!("cancel_external_send command received for {}", request_id);
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).remove(&request_id).is_some()
+}
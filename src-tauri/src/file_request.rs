@@ -0,0 +1,418 @@
+// File: src-tauri/src/file_request.rs
+// Description: Identity-to-identity file request protocol (request a document, answer with an attachment).
+// Changes:
+// - Created file. Requests, responses and attachment chunks travel as signed memos whose text is a prefixed
+//   JSON payload; request/response linkage is tracked by request id in the message index.
+// - CHUNK_BYTES is shared with voice memos.
+// - Responses and chunks are only accepted for requests we sent (no record is created for them), chunk indices
+//   beyond the inline limit are ignored, and reassembly never allocates more than the inline limit (the announced
+//   size comes from the peer). Chunking and reassembly are shared helpers (split_into_chunks, reassemble_chunks).
+// - FileRequestError goes into the error log when a command returns it.
+// - Timestamps come from clock::now_secs (the local helper is gone).
+// - Request ids come from clock::new_id.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{AppHandle, Runtime, State};
+use super::error_log::recorded_command_error;
+use super::message_index::MessageIndex;
+use super::message_rpc::{send_private_message, send_signed_memos, ChatMessage};
+use super::rpc_client::VerusRpcError;
+use super::clock::{new_id, now_secs};
+
+// Memo text prefix marking a file protocol payload
+const FILE_MEMO_PREFIX: &str = "nymia-file:";
+
+// Attachment bytes per chunk memo (hex encoded, so twice as many characters)
+pub const CHUNK_BYTES: usize = 120;
+
+// Larger files have to be shared via IPFS
+pub const MAX_INLINE_ATTACHMENT_BYTES: usize = 4096;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "src", rename_all = "snake_case")]
+pub enum AttachmentSource {
+    Ipfs { cid: String },   // Pinned by the responder; the frontend fetches it
+    Inline { chunks: u32 }, // Sent as chunk memos in the response transaction
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub name: String,
+    pub size: u64,
+    pub sha256: String, // Hex digest of the file contents
+    #[serde(flatten)]
+    pub source: AttachmentSource,
+}
+
+// Payload carried in the memo text. Field names are short to leave room in the 512-byte memo.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "t", rename_all = "snake_case")]
+pub enum FileMemo {
+    Request {
+        id: String,
+        #[serde(rename = "d")]
+        description: String,
+    },
+    Response {
+        id: String,
+        #[serde(rename = "a")]
+        attachment: Attachment,
+    },
+    Chunk {
+        id: String,
+        #[serde(rename = "i")]
+        index: u32,
+        #[serde(rename = "x")]
+        data: String, // Hex encoded bytes
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileRequestStatus {
+    Pending,   // Waiting for the response
+    Responded, // Attachment announced; inline chunks may still be missing
+    Complete,  // Attachment fully available (all chunks received, or an IPFS reference)
+}
+
+// A request and its response, tracked in the message index by request id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileRequestRecord {
+    pub request_id: String,
+    pub peer: String,    // The other party's VerusID
+    pub outgoing: bool,  // We asked for the file
+    pub description: String,
+    pub request_txid: Option<String>,
+    pub requested_at: u64,
+    pub response_txid: Option<String>,
+    pub attachment: Option<Attachment>,
+    #[serde(default)]
+    pub chunks: BTreeMap<u32, String>, // Received inline chunks (hex)
+    pub status: FileRequestStatus,
+}
+
+impl FileRequestRecord {
+    fn new(request_id: &str, peer: &str, outgoing: bool, requested_at: u64) -> Self {
+        FileRequestRecord {
+            request_id: request_id.to_string(),
+            peer: peer.to_string(),
+            outgoing,
+            description: String::new(),
+            request_txid: None,
+            requested_at,
+            response_txid: None,
+            attachment: None,
+            chunks: BTreeMap::new(),
+            status: FileRequestStatus::Pending,
+        }
+    }
+
+    fn refresh_status(&mut self) {
+        self.status = match &self.attachment {
+            None => FileRequestStatus::Pending,
+            Some(Attachment { source: AttachmentSource::Inline { chunks }, .. }) if self.chunks.len() < *chunks as usize => {
+                FileRequestStatus::Responded
+            }
+            Some(_) => FileRequestStatus::Complete,
+        };
+    }
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum FileRequestError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Unknown file request: {0}")]
+    UnknownRequest(String),
+    #[error("File request {0} was not sent to us")]
+    NotIncoming(String),
+    #[error("File is {0} bytes; files over {MAX_INLINE_ATTACHMENT_BYTES} bytes must be shared via IPFS")]
+    TooLargeForInline(u64),
+    #[error("Attachment not available locally: {0}")]
+    AttachmentUnavailable(String),
+    #[error("Attachment failed its integrity check")]
+    ChecksumMismatch,
+    #[error("File error: {0}")]
+    Io(String),
+}
+
+recorded_command_error!(FileRequestError, "file_request");
+
+impl From<VerusRpcError> for FileRequestError {
+    fn from(error: VerusRpcError) -> Self {
+        FileRequestError::Rpc(error)
+    }
+}
+
+// Why received chunks can't be turned back into the announced data
+#[derive(Debug, PartialEq)]
+pub enum ReassemblyError {
+    Incomplete { received: usize, expected: u32 },
+    TooLarge(u64), // Announced size above the caller's limit
+    ChecksumMismatch,
+}
+
+// Hex chunks of CHUNK_BYTES, one per chunk memo
+pub fn split_into_chunks(data: &[u8]) -> Vec<String> {
+    data.chunks(CHUNK_BYTES).map(hex::encode).collect()
+}
+
+// Number of chunks data of up to `max_bytes` is split into; chunks with higher indices are ignored
+pub fn max_chunks(max_bytes: usize) -> u32 {
+    max_bytes.div_ceil(CHUNK_BYTES) as u32
+}
+
+// Reassemble received chunks and check them against the announced size and checksum. Size and chunk count come
+// from the peer, so nothing above `max_bytes` is allocated.
+pub fn reassemble_chunks(
+    chunks: &BTreeMap<u32, String>,
+    expected_chunks: u32,
+    size: u64,
+    sha256: &str,
+    max_bytes: usize,
+) -> Result<Vec<u8>, ReassemblyError> {
+    if size > max_bytes as u64 || expected_chunks > max_chunks(max_bytes) {
+        return Err(ReassemblyError::TooLarge(size));
+    }
+    if chunks.len() < expected_chunks as usize {
+        return Err(ReassemblyError::Incomplete { received: chunks.len(), expected: expected_chunks });
+    }
+    let mut data = Vec::with_capacity(size as usize);
+    for chunk in chunks.values().take(expected_chunks as usize) {
+        data.extend(hex::decode(chunk).map_err(|_| ReassemblyError::ChecksumMismatch)?);
+        if data.len() > max_bytes {
+            return Err(ReassemblyError::ChecksumMismatch);
+        }
+    }
+    if data.len() as u64 != size || hex::encode(Sha256::digest(&data)) != sha256 {
+        return Err(ReassemblyError::ChecksumMismatch);
+    }
+    Ok(data)
+}
+
+pub fn encode_file_memo(memo: &FileMemo) -> String {
+    // Serializing these plain structs cannot fail
+    format!("{}{}", FILE_MEMO_PREFIX, serde_json::to_string(memo).unwrap_or_default())
+}
+
+// Parse a verified message text as a file protocol payload (None for regular messages)
+pub fn parse_file_memo(text: &str) -> Option<FileMemo> {
+    let payload = text.strip_prefix(FILE_MEMO_PREFIX)?;
+    match serde_json::from_str(payload) {
+        Ok(memo) => Some(memo),
+        Err(e) => {
+            log::debug!("Ignoring malformed file protocol memo: {}", e);
+            None
+        }
+    }
+}
+
+fn new_request_id(sender_identity: &str, recipient_identity: &str) -> String {
+    new_id(&[sender_identity.as_bytes(), recipient_identity.as_bytes()])
+}
+
+// Record file protocol memos from verified received messages in the index.
+// Chunk memos carry attachment data only and are removed from the returned list.
+pub fn ingest_file_memos(index: &MessageIndex, messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    messages
+        .into_iter()
+        .filter(|message| {
+            if message.direction != "received" {
+                return true;
+            }
+            let Some(memo) = parse_file_memo(&message.text) else { return true };
+            let is_chunk = matches!(memo, FileMemo::Chunk { .. });
+            record_received_memo(index, message, memo);
+            !is_chunk
+        })
+        .collect()
+}
+
+fn record_received_memo(index: &MessageIndex, message: &ChatMessage, memo: FileMemo) {
+    match memo {
+        FileMemo::Request { id, description } => {
+            index.update_file_request(&id, || FileRequestRecord::new(&id, &message.sender, false, message.timestamp), |record| {
+                if record.outgoing || record.peer != message.sender {
+                    log::warn!("Ignoring file request {} from {}: id already in use", id, message.sender);
+                    return;
+                }
+                record.description = description;
+                record.request_txid = Some(message.id.clone());
+            });
+        }
+        FileMemo::Response { id, attachment } => {
+            // Only responses to requests we sent (and still have a record of) are accepted
+            let updated = index.update_existing_file_request(&id, |record| {
+                if !record.outgoing || record.peer != message.sender {
+                    log::warn!("Ignoring file response {} from {}: not a request we sent to them", id, message.sender);
+                    return;
+                }
+                record.response_txid = Some(message.id.clone());
+                record.attachment = Some(attachment);
+                record.refresh_status();
+            });
+            if updated.is_none() {
+                log::warn!("Ignoring file response {} from {}: no such request", id, message.sender);
+            }
+        }
+        FileMemo::Chunk { id, index: chunk_index, data } => {
+            if chunk_index >= max_chunks(MAX_INLINE_ATTACHMENT_BYTES) {
+                log::warn!("Ignoring attachment chunk {} for {} from {}: beyond the inline limit", chunk_index, id, message.sender);
+                return;
+            }
+            let updated = index.update_existing_file_request(&id, |record| {
+                if !record.outgoing || record.peer != message.sender {
+                    log::warn!("Ignoring attachment chunk for {} from {}", id, message.sender);
+                    return;
+                }
+                record.chunks.insert(chunk_index, data);
+                record.refresh_status();
+            });
+            if updated.is_none() {
+                log::warn!("Ignoring attachment chunk for {} from {}: no such request", id, message.sender);
+            }
+        }
+    }
+}
+
+// Ask a contact for a document. Returns the tracked request.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_file_request(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    recipient_identity: String, // The contact being asked
+    description: String,        // What is requested, e.g. "Invoice for order 1042"
+    index: &MessageIndex,
+) -> Result<FileRequestRecord, FileRequestError> {
+    let request_id = new_request_id(&sender_identity, &recipient_identity);
+    log::info!("Sending file request {} to {}", request_id, recipient_identity);
+
+    let memo_text = encode_file_memo(&FileMemo::Request { id: request_id.clone(), description: description.clone() });
+    let txid = send_private_message(rpc_user, rpc_pass, rpc_port, sender_z_address, recipient_z_address, memo_text, sender_identity, 0.0, None).await?;
+
+    let mut record = FileRequestRecord::new(&request_id, &recipient_identity, true, now_secs());
+    record.description = description;
+    record.request_txid = Some(txid);
+    Ok(index.update_file_request(&request_id, || record, |_| {}))
+}
+
+// Answer a received request with a file: inline chunks for small files, or an IPFS reference
+// (the file itself is pinned by the user; it is read here for its name, size and checksum).
+#[allow(clippy::too_many_arguments)]
+pub async fn send_file_response(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    request_id: String,
+    file_path: String,
+    ipfs_cid: Option<String>,
+    index: &MessageIndex,
+) -> Result<FileRequestRecord, FileRequestError> {
+    let request = index.file_request(&request_id).ok_or_else(|| FileRequestError::UnknownRequest(request_id.clone()))?;
+    if request.outgoing {
+        return Err(FileRequestError::NotIncoming(request_id));
+    }
+
+    let contents = tokio::fs::read(&file_path).await.map_err(|e| FileRequestError::Io(e.to_string()))?;
+    let name = Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    let sha256 = hex::encode(Sha256::digest(&contents));
+
+    let chunks: Vec<String> = match ipfs_cid {
+        Some(_) => Vec::new(),
+        None if contents.len() > MAX_INLINE_ATTACHMENT_BYTES => {
+            return Err(FileRequestError::TooLargeForInline(contents.len() as u64));
+        }
+        None => split_into_chunks(&contents),
+    };
+    let source = match ipfs_cid {
+        Some(cid) => AttachmentSource::Ipfs { cid },
+        None => AttachmentSource::Inline { chunks: chunks.len() as u32 },
+    };
+    let attachment = Attachment { name, size: contents.len() as u64, sha256, source };
+    log::info!("Sending file response {} to {} ({} bytes, {} chunks)", request_id, request.peer, attachment.size, chunks.len());
+
+    let mut memo_texts = vec![encode_file_memo(&FileMemo::Response { id: request_id.clone(), attachment: attachment.clone() })];
+    memo_texts.extend(chunks.into_iter().enumerate().map(|(i, data)| {
+        encode_file_memo(&FileMemo::Chunk { id: request_id.clone(), index: i as u32, data })
+    }));
+    let txid = send_signed_memos(rpc_user, rpc_pass, rpc_port, sender_z_address, recipient_z_address, sender_identity, memo_texts).await?;
+
+    Ok(index.update_file_request(&request_id, || request, |record| {
+        record.response_txid = Some(txid);
+        record.attachment = Some(attachment);
+        record.status = FileRequestStatus::Complete; // We hold the file ourselves
+    }))
+}
+
+// Reassemble an inline attachment and check it against the announced size and checksum
+fn assemble_attachment(record: &FileRequestRecord) -> Result<(Attachment, Vec<u8>), FileRequestError> {
+    let attachment = record
+        .attachment
+        .clone()
+        .ok_or_else(|| FileRequestError::AttachmentUnavailable("no response received yet".to_string()))?;
+    let AttachmentSource::Inline { chunks } = attachment.source else {
+        return Err(FileRequestError::AttachmentUnavailable("attachment is shared via IPFS".to_string()));
+    };
+    let contents = reassemble_chunks(&record.chunks, chunks, attachment.size, &attachment.sha256, MAX_INLINE_ATTACHMENT_BYTES)
+        .map_err(|e| match e {
+            ReassemblyError::Incomplete { received, expected } => {
+                FileRequestError::AttachmentUnavailable(format!("{} of {} chunks received", received, expected))
+            }
+            ReassemblyError::TooLarge(size) => FileRequestError::TooLargeForInline(size),
+            ReassemblyError::ChecksumMismatch => FileRequestError::ChecksumMismatch,
+        })?;
+    Ok((attachment, contents))
+}
+
+// --- Tauri Commands ---
+
+// All tracked file requests, newest first
+#[tauri::command]
+pub fn get_file_requests(index: State<'_, MessageIndex>) -> Vec<FileRequestRecord> {
+    log::debug!("get_file_requests command received");
+    let mut requests = index.file_requests();
+    requests.sort_by_key(|r| std::cmp::Reverse(r.requested_at));
+    requests
+}
+
+// Save a received inline attachment to a user-chosen file. Returns the path, or None if cancelled.
+#[tauri::command]
+pub async fn save_file_attachment<R: Runtime>(
+    app: AppHandle<R>,
+    index: State<'_, MessageIndex>,
+    request_id: String,
+) -> Result<Option<String>, FileRequestError> {
+    log::info!("save_file_attachment command received for request {}", request_id);
+    let record = index.file_request(&request_id).ok_or_else(|| FileRequestError::UnknownRequest(request_id.clone()))?;
+    let (attachment, contents) = assemble_attachment(&record)?;
+
+    // The name comes from the peer; only its final component is used
+    let default_name = Path::new(&attachment.name)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    let Some(path) = super::export::pick_save_path(&app, "Save attachment", default_name, None)
+        .await
+        .map_err(|e| FileRequestError::Io(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+
+    tokio::fs::write(&path, contents).await.map_err(|e| FileRequestError::Io(e.to_string()))?;
+    log::info!("Saved attachment of request {} to {}", request_id, path.display());
+    Ok(Some(path.display().to_string()))
+}
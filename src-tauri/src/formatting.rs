@@ -0,0 +1,287 @@
+// File: src-tauri/src/formatting.rs
+// Description: Centralized, locale-aware formatting of timestamps and amounts for the frontend.
+// Changes:
+// - Created file with format_timestamp and format_amount commands.
+// - Each result carries a screen-reader friendly accessible_label next to the visual strings.
+// - Currency tickers and decimals are taken from the blockchain configs.
+// - normalize_timestamp_secs is shared with the message store for ordering mixed second/millisecond timestamps.
+// - Uses clock::now_secs instead of a local copy.
+// - Out-of-range UTC offsets are rejected with FormattingError instead of overflowing (utc_offset validates them).
+// - Added build_formatted_currency_amount (amounts in a non-native currency carry that currency's name).
+
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use super::credentials::get_blockchain_configs;
+use super::clock::now_secs;
+use super::error_log::recorded_command_error;
+
+// Timestamps above this are treated as milliseconds (frontend Date.now() values)
+const MILLISECOND_THRESHOLD: u64 = 1_000_000_000_000;
+
+// Default precision for Verus-family currencies
+const DEFAULT_DECIMALS: u8 = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FormattedTimestamp {
+    pub iso: String,              // RFC 3339 in the requested offset
+    pub absolute: String,         // Locale date + time (e.g., 01/05/2025 2:03 PM)
+    pub relative: String,         // Short relative string (e.g., 5 min ago)
+    pub accessible_label: String, // Full sentence for screen readers
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FormattedAmount {
+    pub value: String,            // Fixed precision, no grouping (e.g., 1234.50000000)
+    pub display: String,          // Locale grouped, trimmed, with ticker (e.g., 1,234.5 VRSC)
+    pub accessible_label: String, // Spoken form (e.g., 1234.5 Verus)
+    pub ticker: String,
+    pub decimals: u8,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum FormattingError {
+    #[error("Invalid UTC offset: {0} minutes")]
+    InvalidUtcOffset(i32),
+}
+
+recorded_command_error!(FormattingError, "formatting");
+
+// Per-locale formatting rules
+struct LocaleRules {
+    decimal_separator: char,
+    group_separator: char,
+    date_order: DateOrder,
+    date_separator: char,
+    hour12: bool,
+}
+
+enum DateOrder {
+    MonthDayYear,
+    DayMonthYear,
+    YearMonthDay,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+// --- Helper Functions ---
+
+fn locale_rules(locale: Option<&str>) -> LocaleRules {
+    let locale = locale.unwrap_or("en-US").to_lowercase().replace('_', "-");
+    let language = locale.split('-').next().unwrap_or("en");
+
+    match (language, locale.as_str()) {
+        (_, "en-us") | ("en", "en") => LocaleRules { decimal_separator: '.', group_separator: ',', date_order: DateOrder::MonthDayYear, date_separator: '/', hour12: true },
+        ("en", _) => LocaleRules { decimal_separator: '.', group_separator: ',', date_order: DateOrder::DayMonthYear, date_separator: '/', hour12: false },
+        ("de", _) | ("ru", _) | ("pl", _) | ("tr", _) => LocaleRules { decimal_separator: ',', group_separator: '.', date_order: DateOrder::DayMonthYear, date_separator: '.', hour12: false },
+        ("fr", _) | ("es", _) | ("it", _) | ("pt", _) | ("nl", _) => LocaleRules { decimal_separator: ',', group_separator: '.', date_order: DateOrder::DayMonthYear, date_separator: '/', hour12: false },
+        ("ja", _) | ("zh", _) | ("ko", _) | ("sv", _) => LocaleRules { decimal_separator: '.', group_separator: ',', date_order: DateOrder::YearMonthDay, date_separator: '-', hour12: false },
+        _ => LocaleRules { decimal_separator: '.', group_separator: ',', date_order: DateOrder::YearMonthDay, date_separator: '-', hour12: false },
+    }
+}
+
+pub fn normalize_timestamp_secs(timestamp: u64) -> u64 {
+    if timestamp > MILLISECOND_THRESHOLD {
+        timestamp / 1000
+    } else {
+        timestamp
+    }
+}
+
+fn plural(count: u64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+fn format_time_of_day(dt: &DateTime<FixedOffset>, rules: &LocaleRules) -> String {
+    if rules.hour12 {
+        let (is_pm, hour) = dt.hour12();
+        format!("{}:{:02} {}", hour, dt.minute(), if is_pm { "PM" } else { "AM" })
+    } else {
+        format!("{:02}:{:02}", dt.hour(), dt.minute())
+    }
+}
+
+fn format_date(dt: &DateTime<FixedOffset>, rules: &LocaleRules) -> String {
+    let sep = rules.date_separator;
+    match rules.date_order {
+        DateOrder::MonthDayYear => format!("{:02}{}{:02}{}{}", dt.month(), sep, dt.day(), sep, dt.year()),
+        DateOrder::DayMonthYear => format!("{:02}{}{:02}{}{}", dt.day(), sep, dt.month(), sep, dt.year()),
+        DateOrder::YearMonthDay => format!("{}{}{:02}{}{:02}", dt.year(), sep, dt.month(), sep, dt.day()),
+    }
+}
+
+// Relative description of an age in seconds (short form, long form)
+fn relative_strings(age_secs: u64) -> Option<(String, String)> {
+    match age_secs {
+        0..=59 => Some(("just now".to_string(), "just now".to_string())),
+        60..=3_599 => {
+            let minutes = age_secs / 60;
+            Some((format!("{} min ago", minutes), format!("{} ago", plural(minutes, "minute"))))
+        }
+        3_600..=86_399 => {
+            let hours = age_secs / 3_600;
+            Some((format!("{} h ago", hours), format!("{} ago", plural(hours, "hour"))))
+        }
+        86_400..=172_799 => Some(("yesterday".to_string(), "yesterday".to_string())),
+        172_800..=604_799 => {
+            let days = age_secs / 86_400;
+            Some((format!("{} days ago", days), format!("{} days ago", days)))
+        }
+        _ => None, // Older than a week: use the absolute date
+    }
+}
+
+// Insert group separators into the integer part and swap the decimal separator
+fn group_number(plain: &str, rules: &LocaleRules) -> String {
+    let (negative, digits) = match plain.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, plain),
+    };
+    let (int_part, frac_part) = match digits.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (digits, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, ch) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(rules.group_separator);
+        }
+        grouped.push(ch);
+    }
+
+    let mut result = if negative { format!("-{}", grouped) } else { grouped };
+    if let Some(frac) = frac_part {
+        result.push(rules.decimal_separator);
+        result.push_str(frac);
+    }
+    result
+}
+
+// Drop insignificant trailing zeros from a fixed precision string
+fn trim_fraction(fixed: &str) -> String {
+    if fixed.contains('.') {
+        fixed.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        fixed.to_string()
+    }
+}
+
+// Resolve ticker, spoken currency name and decimals for a chain id
+fn chain_currency(chain_id: Option<&str>) -> (String, String, u8) {
+    let configs = get_blockchain_configs();
+    let config = chain_id
+        .and_then(|id| configs.iter().find(|c| c.id == id))
+        .or_else(|| configs.first());
+
+    match config {
+        Some(c) => (c.ticker.clone(), c.name.clone(), c.decimals),
+        None => ("VRSC".to_string(), "Verus".to_string(), DEFAULT_DECIMALS),
+    }
+}
+
+// --- Public formatting functions ---
+
+// Offset from the frontend (minutes east of UTC, default UTC); anything beyond a day either way is refused
+pub fn utc_offset(utc_offset_minutes: Option<i32>) -> Result<FixedOffset, FormattingError> {
+    let minutes = utc_offset_minutes.unwrap_or(0);
+    minutes
+        .checked_mul(60)
+        .and_then(FixedOffset::east_opt)
+        .ok_or(FormattingError::InvalidUtcOffset(minutes))
+}
+
+pub fn build_formatted_timestamp(
+    timestamp: u64,
+    locale: Option<&str>,
+    offset: FixedOffset,
+    now: u64,
+) -> FormattedTimestamp {
+    let rules = locale_rules(locale);
+    let secs = normalize_timestamp_secs(timestamp);
+    let dt = DateTime::<Utc>::from_timestamp(secs as i64, 0)
+        .unwrap_or_default()
+        .with_timezone(&offset);
+
+    let date = format_date(&dt, &rules);
+    let time = format_time_of_day(&dt, &rules);
+    let absolute = format!("{} {}", date, time);
+    let spoken_date = format!("{} {}, {}", MONTH_NAMES[dt.month0() as usize], dt.day(), dt.year());
+
+    // Future timestamps (sender clock skew) are shown as "just now"
+    let age = now.saturating_sub(secs);
+    let (relative, accessible_label) = match relative_strings(age) {
+        Some((short, long)) => (short, format!("{}, {} at {}", long, spoken_date, time)),
+        None => (date.clone(), format!("{} at {}", spoken_date, time)),
+    };
+
+    FormattedTimestamp {
+        iso: dt.to_rfc3339(),
+        absolute,
+        relative,
+        accessible_label,
+    }
+}
+
+pub fn build_formatted_amount(amount: f64, chain_id: Option<&str>, locale: Option<&str>) -> FormattedAmount {
+    let rules = locale_rules(locale);
+    let (ticker, currency_name, decimals) = chain_currency(chain_id);
+
+    let value = format!("{:.*}", decimals as usize, amount);
+    let trimmed = trim_fraction(&value);
+    let display = format!("{} {}", group_number(&trimmed, &rules), ticker);
+    let accessible_label = format!("{} {}", trimmed, currency_name);
+
+    FormattedAmount {
+        value,
+        display,
+        accessible_label,
+        ticker,
+        decimals,
+    }
+}
+
+// Amount of a message or gift: `currency` is the non-native currency it was sent in (None: the chain's coin)
+pub fn build_formatted_currency_amount(amount: f64, currency: Option<&str>, locale: Option<&str>) -> FormattedAmount {
+    let Some(currency) = currency else { return build_formatted_amount(amount, None, locale) };
+    let rules = locale_rules(locale);
+    let value = format!("{:.*}", DEFAULT_DECIMALS as usize, amount);
+    let trimmed = trim_fraction(&value);
+    FormattedAmount {
+        display: format!("{} {}", group_number(&trimmed, &rules), currency),
+        accessible_label: format!("{} {}", trimmed, currency),
+        value,
+        ticker: currency.to_string(),
+        decimals: DEFAULT_DECIMALS,
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn format_timestamp(
+    timestamp: u64,
+    locale: Option<String>,
+    utc_offset_minutes: Option<i32>,
+) -> Result<FormattedTimestamp, FormattingError> {
+    log::debug!("format_timestamp command received: {} (locale: {:?})", timestamp, locale);
+    let offset = utc_offset(utc_offset_minutes)?;
+    Ok(build_formatted_timestamp(timestamp, locale.as_deref(), offset, now_secs()))
+}
+
+#[tauri::command]
+pub fn format_amount(
+    amount: f64,
+    chain_id: Option<String>,
+    locale: Option<String>,
+) -> FormattedAmount {
+    log::debug!("format_amount command received: {} (chain: {:?}, locale: {:?})", amount, chain_id, locale);
+    build_formatted_amount(amount, chain_id.as_deref(), locale.as_deref())
+}
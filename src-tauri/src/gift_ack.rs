@@ -0,0 +1,169 @@
+// File: src-tauri/src/gift_ack.rs
+// Description: Gift acknowledgment receipts ("thanks" memos referencing the gift transaction).
+// Changes:
+// - Created file. Acks are signed zero-value memos whose text is a prefixed JSON payload; received acks are
+//   attached to the original sent gift in the message index and removed from the message list.
+// - Received acks are only recorded for gifts this wallet sent to the acknowledging identity (checked with
+//   z_viewtransaction against the sender's private address).
+// - Reads the current time through clock::now_secs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use super::message_index::MessageIndex;
+use super::message_rpc::{send_private_message, ChatMessage};
+use super::response_cache::make_cached_rpc_call;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::clock::now_secs;
+
+// Memo text prefix marking a gift acknowledgment
+const ACK_MEMO_PREFIX: &str = "nymia-ack:";
+
+// Gifts must exceed this amount to be acknowledged (keeps dust from triggering ack transactions)
+pub const GIFT_ACK_THRESHOLD: f64 = 0.001;
+
+// Leaves room for the payload and signature in the 512-byte memo
+const MAX_ACK_NOTE_CHARS: usize = 160;
+
+// Payload carried in the memo text
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct AckMemo {
+    #[serde(rename = "g")]
+    gift_txid: String,
+    #[serde(rename = "m", default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+// An acknowledgment of a gift, keyed by the gift txid in the message index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GiftAck {
+    pub gift_txid: String,
+    pub ack_txid: String,
+    pub acknowledged_by: String, // VerusID that sent the ack
+    pub note: Option<String>,
+    pub acknowledged_at: u64,
+    pub outgoing: bool, // We acknowledged a gift we received
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum GiftAckError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Received gift not found: {0}")]
+    UnknownGift(String),
+    #[error("Gift of {0} is below the acknowledgment threshold of {GIFT_ACK_THRESHOLD}")]
+    BelowThreshold(f64),
+    #[error("Gift {0} was already acknowledged")]
+    AlreadyAcknowledged(String),
+    #[error("Note is longer than {MAX_ACK_NOTE_CHARS} characters")]
+    NoteTooLong,
+}
+
+impl From<VerusRpcError> for GiftAckError {
+    fn from(error: VerusRpcError) -> Self {
+        GiftAckError::Rpc(error)
+    }
+}
+
+fn encode_ack_memo(memo: &AckMemo) -> String {
+    // Serializing this plain struct cannot fail
+    format!("{}{}", ACK_MEMO_PREFIX, serde_json::to_string(memo).unwrap_or_default())
+}
+
+fn parse_ack_memo(text: &str) -> Option<AckMemo> {
+    let payload = text.strip_prefix(ACK_MEMO_PREFIX)?;
+    match serde_json::from_str(payload) {
+        Ok(memo) => Some(memo),
+        Err(e) => {
+            log::debug!("Ignoring malformed gift acknowledgment memo: {}", e);
+            None
+        }
+    }
+}
+
+// Whether `gift_txid` is a transaction of ours paying a non-zero amount to the private address of `recipient`
+async fn is_gift_sent_to(rpc_user: &str, rpc_pass: &str, rpc_port: u16, gift_txid: &str, recipient: &str) -> Result<bool, VerusRpcError> {
+    let identity: Value = make_cached_rpc_call(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(recipient)]).await?;
+    let Some(private_address) = identity.pointer("/identity/privateaddress").and_then(|v| v.as_str()) else { return Ok(false) };
+    let view: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "z_viewtransaction", vec![json!(gift_txid)]).await?;
+    let outputs = view.get("outputs").and_then(|o| o.as_array()).cloned().unwrap_or_default();
+    Ok(outputs.iter().any(|output| {
+        output.get("outgoing").and_then(|v| v.as_bool()).unwrap_or(false)
+            && output.get("address").and_then(|v| v.as_str()) == Some(private_address)
+            && output.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.0
+    }))
+}
+
+// Record acks found in verified received messages and remove them from the returned list. Acks are only
+// recorded for gifts we sent to the acknowledging identity.
+pub async fn ingest_gift_acks(rpc_user: &str, rpc_pass: &str, rpc_port: u16, index: &MessageIndex, messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    let mut remaining = Vec::with_capacity(messages.len());
+    for message in messages {
+        let memo = match parse_ack_memo(&message.text) {
+            Some(memo) if message.direction == "received" => memo,
+            _ => {
+                remaining.push(message);
+                continue;
+            }
+        };
+        if index.gift_ack(&memo.gift_txid).is_some() {
+            continue;
+        }
+        match is_gift_sent_to(rpc_user, rpc_pass, rpc_port, &memo.gift_txid, &message.sender).await {
+            Ok(true) => {
+                log::info!("Gift {} acknowledged by {}", memo.gift_txid, message.sender);
+                index.record_gift_ack(GiftAck {
+                    gift_txid: memo.gift_txid,
+                    ack_txid: message.id.clone(),
+                    acknowledged_by: message.sender.clone(),
+                    note: memo.note,
+                    acknowledged_at: message.timestamp,
+                    outgoing: false,
+                });
+            }
+            Ok(false) => log::warn!("Ignoring ack from {} for {}: not a gift we sent them", message.sender, memo.gift_txid),
+            Err(e) => log::warn!("Ignoring ack from {} for {}: gift lookup failed: {:?}", message.sender, memo.gift_txid, e),
+        }
+    }
+    remaining
+}
+
+// Send a signed zero-value ack for a received gift. The caller looks the gift up and passes its amount.
+#[allow(clippy::too_many_arguments)]
+pub async fn acknowledge_gift(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    gift_txid: String,
+    gift_amount: f64,
+    note: Option<String>,
+    index: &MessageIndex,
+) -> Result<GiftAck, GiftAckError> {
+    if gift_amount <= GIFT_ACK_THRESHOLD {
+        return Err(GiftAckError::BelowThreshold(gift_amount));
+    }
+    if index.gift_ack(&gift_txid).is_some_and(|ack| ack.outgoing) {
+        return Err(GiftAckError::AlreadyAcknowledged(gift_txid));
+    }
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note.as_ref().is_some_and(|n| n.chars().count() > MAX_ACK_NOTE_CHARS) {
+        return Err(GiftAckError::NoteTooLong);
+    }
+
+    log::info!("Acknowledging gift {} ({})", gift_txid, gift_amount);
+    let memo_text = encode_ack_memo(&AckMemo { gift_txid: gift_txid.clone(), note: note.clone() });
+    let ack_txid = send_private_message(rpc_user, rpc_pass, rpc_port, sender_z_address, recipient_z_address, memo_text, sender_identity.clone(), 0.0, None).await?;
+
+    let ack = GiftAck {
+        gift_txid,
+        ack_txid,
+        acknowledged_by: sender_identity,
+        note,
+        acknowledged_at: now_secs(),
+        outgoing: true,
+    };
+    index.record_gift_ack(ack.clone());
+    Ok(ack)
+}
@@ -0,0 +1,270 @@
+// File: src-tauri/src/gift_ledger.rs
+// Description: Per-conversation gift ledger (totals sent / received and a monthly breakdown).
+// Changes:
+// - Created file. Computed from the local message store only, like the activity timeline. Amounts are kept per
+//   currency; messages without a currency are in the chain's native coin.
+// - Added network fee tracking: sent messages whose fee wasn't known at send time get it from the wallet
+//   transaction (background, after polls), and get_fee_summary totals fees per conversation and month.
+// - Currencies recorded by i-address are totalled under their name when the currency cache knows it.
+// - The fee backfill first moves sent messages recorded under a z_sendmany opid to their txid (while the daemon
+//   still knows the operation), so they are no longer skipped.
+// - Dropped the private now_secs in favour of clock::now_secs.
+// - Uses the shared message_rpc::is_txid.
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Manager, Runtime, State};
+use super::currency::cached_display_name;
+use super::formatting::normalize_timestamp_secs;
+use super::message_rpc::{get_transaction_cost, is_txid};
+use super::message_store::MessageStore;
+use super::settings::{read_conversations, SettingsError};
+use super::wallet_rpc::get_operation_status;
+use super::clock::now_secs;
+
+// Key for amounts in the chain's native coin
+const NATIVE_CURRENCY_KEY: &str = "native";
+
+// Background fee lookups after polls run at most this often per identity
+const FEE_BACKFILL_INTERVAL_SECS: u64 = 10 * 60;
+
+// Identity -> last fee backfill (unix seconds)
+static LAST_FEE_BACKFILL: LazyLock<Mutex<HashMap<String, u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GiftTotals {
+    pub sent_total: f64,
+    pub sent_count: u32,
+    pub received_total: f64,
+    pub received_count: u32,
+}
+
+impl GiftTotals {
+    fn add(&mut self, sent: bool, amount: f64) {
+        if sent {
+            self.sent_total += amount;
+            self.sent_count += 1;
+        } else {
+            self.received_total += amount;
+            self.received_count += 1;
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MonthlyGifts {
+    pub month: String, // "YYYY-MM" (UTC)
+    pub by_currency: BTreeMap<String, GiftTotals>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GiftSummary {
+    pub conversation_id: String,
+    pub by_currency: BTreeMap<String, GiftTotals>, // "native" for the chain's own coin
+    pub monthly: Vec<MonthlyGifts>,                // Oldest first; months without gifts are left out
+    pub first_gift_at: Option<u64>,
+    pub last_gift_at: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct FeeTotals {
+    pub total_fee: f64,
+    pub transactions: u32,
+    pub unknown_fee_count: u32, // Sent messages whose fee isn't known (yet)
+}
+
+impl FeeTotals {
+    fn add(&mut self, fee: Option<f64>) {
+        match fee {
+            Some(fee) => {
+                self.total_fee += fee;
+                self.transactions += 1;
+            }
+            None => self.unknown_fee_count += 1,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct FeeSummary {
+    pub total: FeeTotals,
+    pub by_conversation: BTreeMap<String, FeeTotals>,
+    pub by_month: BTreeMap<String, FeeTotals>, // "YYYY-MM" (UTC)
+}
+
+// Sent messages stored under a z_sendmany opid (recorded before sends waited for the txid) get their txid. The
+// daemon only knows operations since its last start; older ones stay under the opid.
+async fn resolve_opid_ids<R: Runtime>(
+    app: &AppHandle<R>,
+    store: &MessageStore,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity_i_address: &str,
+    conversation_id: &str,
+) -> Result<(), SettingsError> {
+    let opids: Vec<String> = store
+        .load_conversation(app, identity_i_address, conversation_id)
+        .into_iter()
+        .filter(|m| m.direction == "sent" && m.id.starts_with("opid-"))
+        .map(|m| m.id)
+        .collect();
+    if opids.is_empty() {
+        return Ok(());
+    }
+    let statuses = match get_operation_status(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, opids).await {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            log::debug!("Could not look up opids of sent messages in {}: {:?}", conversation_id, e);
+            return Ok(());
+        }
+    };
+    for status in statuses {
+        if let Some(txid) = status.txid.filter(|txid| is_txid(txid)) {
+            if store.replace_id(app, identity_i_address, conversation_id, &status.opid, &txid)? {
+                log::info!("Sent message {} recorded under its txid {}", status.opid, txid);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Look up the network fee of sent messages that were stored without one (the wallet knows it from submission on). Returns how many were filled.
+pub async fn backfill_sent_fees<R: Runtime>(
+    app: &AppHandle<R>,
+    store: &MessageStore,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity_i_address: &str,
+    conversation_ids: Vec<String>,
+) -> Result<usize, SettingsError> {
+    let mut filled = 0;
+    for conversation_id in conversation_ids {
+        resolve_opid_ids(app, store, rpc_user, rpc_pass, rpc_port, identity_i_address, &conversation_id).await?;
+        let missing: Vec<_> = store
+            .load_conversation(app, identity_i_address, &conversation_id)
+            .into_iter()
+            .filter(|m| m.direction == "sent" && m.fee.is_none() && is_txid(&m.id))
+            .collect();
+        let mut updated = Vec::new();
+        for mut message in missing {
+            match get_transaction_cost(rpc_user, rpc_pass, rpc_port, &message.id).await {
+                Ok(cost) => {
+                    message.fee = Some(cost.fee);
+                    message.size_bytes = Some(cost.size_bytes);
+                    updated.push(message);
+                }
+                Err(e) => log::debug!("Fee of sent message {} not available: {:?}", message.id, e),
+            }
+        }
+        if !updated.is_empty() {
+            filled += updated.len();
+            // merge only fills a fee that is still missing
+            store.merge(app, identity_i_address, &conversation_id, updated)?;
+        }
+    }
+    if filled > 0 {
+        log::info!("Filled in the network fee of {} sent messages", filled);
+    }
+    Ok(filled)
+}
+
+// Background fee backfill after a poll, throttled per identity
+pub fn spawn_fee_backfill<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, identity_i_address: String) {
+    {
+        let mut last = LAST_FEE_BACKFILL.lock().unwrap_or_else(|e| e.into_inner());
+        let now = now_secs();
+        if last.get(&identity_i_address).is_some_and(|at| now.saturating_sub(*at) < FEE_BACKFILL_INTERVAL_SECS) {
+            return;
+        }
+        last.insert(identity_i_address.clone(), now);
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let conversation_ids = match read_conversations(&app, &identity_i_address) {
+            Ok(conversations) => conversations.into_iter().map(|c| c.id).collect(),
+            Err(e) => {
+                log::warn!("Fee backfill skipped, conversations unavailable: {}", e);
+                return;
+            }
+        };
+        let store = app.state::<MessageStore>();
+        if let Err(e) = backfill_sent_fees(&app, &store, &rpc_user, &rpc_pass, rpc_port, &identity_i_address, conversation_ids).await {
+            log::warn!("Fee backfill for {} failed: {}", identity_i_address, e);
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn get_gift_summary<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<GiftSummary, SettingsError> {
+    log::debug!("get_gift_summary command received for {} (user {})", conversation_id, identity_i_address);
+
+    let mut summary = GiftSummary { conversation_id: conversation_id.clone(), ..Default::default() };
+    let mut monthly: BTreeMap<String, BTreeMap<String, GiftTotals>> = BTreeMap::new();
+    for message in store.load_conversation(&app, &identity_i_address, &conversation_id) {
+        if message.amount <= 0.0 {
+            continue; // Plain message
+        }
+        let sent = message.direction == "sent";
+        let currency = match &message.currency {
+            Some(currency) => cached_display_name(currency).unwrap_or_else(|| currency.clone()),
+            None => NATIVE_CURRENCY_KEY.to_string(),
+        };
+        summary.by_currency.entry(currency.clone()).or_default().add(sent, message.amount);
+
+        let secs = normalize_timestamp_secs(message.timestamp);
+        if secs == 0 {
+            continue; // No timestamp yet; counted in the totals only
+        }
+        summary.first_gift_at = Some(summary.first_gift_at.map_or(secs, |first| first.min(secs)));
+        summary.last_gift_at = Some(summary.last_gift_at.map_or(secs, |last| last.max(secs)));
+        if let Some(date) = DateTime::<Utc>::from_timestamp(secs as i64, 0) {
+            let month = format!("{:04}-{:02}", date.year(), date.month());
+            monthly.entry(month).or_default().entry(currency).or_default().add(sent, message.amount);
+        }
+    }
+    summary.monthly = monthly.into_iter().map(|(month, by_currency)| MonthlyGifts { month, by_currency }).collect();
+    Ok(summary)
+}
+
+// Network fees paid for sent messages, per conversation and per month (all conversations if none are given)
+#[tauri::command]
+pub async fn get_fee_summary<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    conversation_ids: Option<Vec<String>>,
+) -> Result<FeeSummary, SettingsError> {
+    log::debug!("get_fee_summary command received (user {})", identity_i_address);
+    let conversation_ids = match conversation_ids {
+        Some(ids) => ids,
+        None => read_conversations(&app, &identity_i_address)?.into_iter().map(|c| c.id).collect(),
+    };
+
+    let mut summary = FeeSummary::default();
+    for conversation_id in conversation_ids {
+        for message in store.load_conversation(&app, &identity_i_address, &conversation_id) {
+            if message.direction != "sent" {
+                continue; // The sender pays the fee
+            }
+            summary.total.add(message.fee);
+            summary.by_conversation.entry(conversation_id.clone()).or_default().add(message.fee);
+            let secs = normalize_timestamp_secs(message.timestamp);
+            if let Some(date) = DateTime::<Utc>::from_timestamp(secs as i64, 0).filter(|_| secs > 0) {
+                let month = format!("{:04}-{:02}", date.year(), date.month());
+                summary.by_month.entry(month).or_default().add(message.fee);
+            }
+        }
+    }
+    Ok(summary)
+}
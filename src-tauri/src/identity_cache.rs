@@ -0,0 +1,340 @@
+// File: src-tauri/src/identity_cache.rs
+// Description: Persisted cache of resolved contact identities (name, i-address, current private address).
+// Changes:
+// - Created file with IdentityCache (managed state) backed by identity_cache.json, and the post-login warmup
+//   that batch-resolves all conversation partners so opening a conversation doesn't start with a cold getidentity.
+// - Added reverse lookup by private address (identify_recipients) for conversations recovered from outgoing memos.
+// - Entries track revocation: a cached identity that was revoked and comes back active is marked recovered (kept on
+//   later refreshes). ensure_recipient_active refuses sends to private addresses of revoked identities.
+// - The warmup reports contact identity changes (contact-identity-changed events, like the contact watcher).
+// - Added clear (emergency wipe drops the in-memory state so nothing is written back).
+// - The warmup writes changed private addresses with update_conversations (only those fields, on the current list).
+// - resolve no longer serves entries younger than 30 minutes; lookups rely on the block-aware response cache.
+// - Added ensure_transparent_recipient_active (transparent gifts: owner found by contact or primary address).
+// - Timestamps come from clock::now_secs (the local helper is gone).
+// - A failed save leaves the cache dirty (retried by the next persist) instead of dropping the changes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use super::contact_watcher::detect_change;
+use super::events::{emit_event, CONTACT_IDENTITY_CHANGED_EVENT};
+use super::identity_rpc::{check_identity_eligibility, FormattedIdentity};
+use super::rpc_client::VerusRpcError;
+use super::settings::{read_conversations, update_conversations};
+use super::storage::{load_value, save_value, StorageError};
+use super::tasks::{run_cancellable, TaskError};
+use super::clock::now_secs;
+
+const CACHE_STORE_PATH: &str = "identity_cache.json";
+const CACHE_KEY: &str = "identities";
+
+// getidentity calls in flight during the warmup
+const MAX_CONCURRENT_LOOKUPS: usize = 4;
+
+// Single task id: a new login cancels the previous warmup
+pub const WARMUP_TASK_ID: &str = "identity-warmup";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedIdentity {
+    pub identity: FormattedIdentity,
+    pub resolved_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct CacheState {
+    entries: HashMap<String, CachedIdentity>, // VerusID name -> identity
+    #[serde(skip)]
+    dirty: bool,
+}
+
+// Cheap to clone; all clones share the same entries
+#[derive(Clone, Default)]
+pub struct IdentityCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WarmupSummary {
+    pub resolved: usize,
+    pub failed: usize,
+    pub addresses_updated: usize, // Conversations whose partner changed their private address
+    pub revoked: Vec<String>,     // Conversation partners whose identity is revoked
+    pub recovered: Vec<String>,   // Partners whose identity was recovered after a revocation
+}
+
+// Conversations created by recover_sent_messages for recipients that had none
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversationsRecovered {
+    pub identity_i_address: String,
+    pub conversation_ids: Vec<String>,
+    pub unidentified_addresses: Vec<String>, // Recipients no known identity uses (no conversation created)
+}
+
+impl IdentityCache {
+    // Load the cache from disk (empty if missing or unreadable)
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let state = match load_value::<R, CacheState>(app, CACHE_STORE_PATH, CACHE_KEY) {
+            Ok(Some(state)) => {
+                log::info!("Loaded {} cached identities", state.entries.len());
+                state
+            }
+            Ok(None) => CacheState::default(),
+            Err(e) => {
+                log::warn!("Failed to load identity cache, starting empty: {}", e);
+                CacheState::default()
+            }
+        };
+        IdentityCache { inner: Arc::new(Mutex::new(state)) }
+    }
+
+    pub fn get(&self, name: &str) -> Option<CachedIdentity> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.get(name).cloned()
+    }
+
+    pub fn insert(&self, name: &str, mut identity: FormattedIdentity) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = state.entries.get(name) {
+            // Recovery only shows as a revoked identity becoming active again
+            let recovered_now = previous.identity.revoked && !identity.revoked;
+            if recovered_now {
+                log::warn!("Identity {} was recovered after being revoked", name);
+            }
+            identity.recovered = !identity.revoked && (recovered_now || previous.identity.recovered);
+            if identity.revoked && !previous.identity.revoked {
+                log::warn!("Identity {} has been revoked", name);
+            }
+        }
+        state.entries.insert(name.to_string(), CachedIdentity { identity, resolved_at: now_secs() });
+        state.dirty = true;
+    }
+
+    // Any cached identity with this private address (the daemon has no reverse lookup for z-addresses)
+    pub fn find_by_private_address(&self, private_address: &str) -> Option<FormattedIdentity> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .entries
+            .values()
+            .map(|cached| &cached.identity)
+            .find(|identity| identity.private_address == private_address)
+            .cloned()
+    }
+
+    // Resolve an identity and record the result. getidentity answers are only reused within a block (response
+    // cache); the entries here are the last known state, for change detection and while the daemon can't answer.
+    pub async fn resolve(&self, rpc_user: &str, rpc_pass: &str, rpc_port: u16, name: &str) -> Result<FormattedIdentity, VerusRpcError> {
+        let identity = check_identity_eligibility(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, name.to_string()).await?;
+        self.insert(name, identity.clone());
+        Ok(identity)
+    }
+
+    // Refuse sends to a private address whose identity is revoked (checked against the current block).
+    // Addresses of unknown identities pass; there is nothing to check them against.
+    pub async fn ensure_recipient_active(&self, rpc_user: &str, rpc_pass: &str, rpc_port: u16, private_address: &str) -> Result<(), VerusRpcError> {
+        let name = {
+            let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            state.entries.iter().find(|(_, cached)| cached.identity.private_address == private_address).map(|(name, _)| name.clone())
+        };
+        let Some(name) = name else { return Ok(()) };
+        self.ensure_active(rpc_user, rpc_pass, rpc_port, &name).await
+    }
+
+    // The same for transparent gifts: the R-address belongs to the given contact, or else to the cached identity
+    // whose primary addresses contain it. Addresses no known identity controls pass.
+    pub async fn ensure_transparent_recipient_active(
+        &self,
+        rpc_user: &str,
+        rpc_pass: &str,
+        rpc_port: u16,
+        transparent_address: &str,
+        contact: Option<&str>,
+    ) -> Result<(), VerusRpcError> {
+        let name = contact.map(str::to_string).or_else(|| {
+            let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            state
+                .entries
+                .iter()
+                .find(|(_, cached)| cached.identity.primary_addresses.iter().any(|address| address == transparent_address))
+                .map(|(name, _)| name.clone())
+        });
+        let Some(name) = name else { return Ok(()) };
+        self.ensure_active(rpc_user, rpc_pass, rpc_port, &name).await
+    }
+
+    async fn ensure_active(&self, rpc_user: &str, rpc_pass: &str, rpc_port: u16, name: &str) -> Result<(), VerusRpcError> {
+        let identity = match self.resolve(rpc_user, rpc_pass, rpc_port, name).await {
+            Ok(identity) => identity,
+            // Keep the last known state if the daemon can't tell us
+            Err(e) => {
+                log::debug!("Could not refresh {} before sending: {:?}", name, e);
+                self.get(name).map(|cached| cached.identity).ok_or(e)?
+            }
+        };
+        if identity.revoked {
+            return Err(VerusRpcError::RecipientRevoked(name.to_string()));
+        }
+        Ok(())
+    }
+
+    // Write changes to disk (no-op if nothing changed)
+    pub fn persist<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), StorageError> {
+        let snapshot = {
+            let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.dirty {
+                return Ok(());
+            }
+            // Cleared up front so changes made while saving mark it again
+            state.dirty = false;
+            state.clone()
+        };
+        if let Err(e) = save_value(app, CACHE_STORE_PATH, CACHE_KEY, &snapshot) {
+            // Not on disk yet; the next persist tries again
+            self.inner.lock().unwrap_or_else(|e| e.into_inner()).dirty = true;
+            return Err(e);
+        }
+        log::debug!("Persisted identity cache ({} entries)", snapshot.entries.len());
+        Ok(())
+    }
+
+    // Drop everything in memory without persisting (emergency wipe)
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap_or_else(|e| e.into_inner()) = Default::default();
+    }
+}
+
+// Map private addresses we sent to onto identities. Cached identities are checked first; otherwise the given
+// candidate names (e.g. senders of held message requests) are resolved until every address is matched.
+// Addresses no known identity uses are left out.
+pub async fn identify_recipients(
+    cache: &IdentityCache,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    private_addresses: &[String],
+    candidate_names: &[String],
+) -> HashMap<String, FormattedIdentity> {
+    let mut found: HashMap<String, FormattedIdentity> = private_addresses
+        .iter()
+        .filter_map(|address| cache.find_by_private_address(address).map(|identity| (address.clone(), identity)))
+        .collect();
+    for name in candidate_names {
+        if found.len() == private_addresses.len() {
+            break;
+        }
+        match cache.resolve(rpc_user, rpc_pass, rpc_port, name).await {
+            Ok(identity) if private_addresses.contains(&identity.private_address) => {
+                found.entry(identity.private_address.clone()).or_insert(identity);
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Could not resolve candidate recipient {}: {:?}", name, e),
+        }
+    }
+    found
+}
+
+// Resolve all conversation partners of an identity and refresh stale private addresses in the conversation list
+async fn warm_up<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity_i_address: &str,
+) -> Result<WarmupSummary, TaskError> {
+    let cache = app.state::<IdentityCache>().inner().clone();
+    let conversations = read_conversations(app, identity_i_address).unwrap_or_else(|e| {
+        log::warn!("Identity warmup: failed to read conversations: {}", e);
+        Vec::new()
+    });
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_LOOKUPS));
+    let mut join_set = JoinSet::new();
+    for name in conversations.iter().map(|c| c.id.clone()) {
+        let (cache, semaphore) = (cache.clone(), semaphore.clone());
+        let (rpc_user, rpc_pass) = (rpc_user.to_string(), rpc_pass.to_string());
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.ok();
+            // The warmup always asks the daemon so private address changes are picked up
+            let result = check_identity_eligibility(rpc_user, rpc_pass, rpc_port, name.clone()).await;
+            let previous = cache.get(&name).map(|cached| cached.identity);
+            if let Ok(identity) = &result {
+                cache.insert(&name, identity.clone());
+            }
+            (name, previous, result)
+        });
+    }
+
+    let mut summary = WarmupSummary { resolved: 0, failed: 0, addresses_updated: 0, revoked: Vec::new(), recovered: Vec::new() };
+    let mut address_updates: Vec<(String, String)> = Vec::new(); // Conversation id, new private address
+    while let Some(task_result) = join_set.join_next().await {
+        match task_result {
+            Ok((name, previous, Ok(identity))) => {
+                summary.resolved += 1;
+                if identity.revoked {
+                    summary.revoked.push(name.clone());
+                } else if cache.get(&name).is_some_and(|cached| cached.identity.recovered) {
+                    summary.recovered.push(name.clone());
+                }
+                if let Some(conversation) = conversations.iter().find(|c| c.id == name) {
+                    let change = detect_change(identity_i_address, &name, &conversation.recipient_private_address, previous.as_ref(), &identity);
+                    if conversation.recipient_private_address != identity.private_address {
+                        log::info!("Private address of {} changed; updating conversation", name);
+                        address_updates.push((name.clone(), identity.private_address));
+                        summary.addresses_updated += 1;
+                    }
+                    if let Some(change) = change {
+                        emit_event(app, CONTACT_IDENTITY_CHANGED_EVENT, change);
+                    }
+                }
+            }
+            Ok((name, _, Err(e))) => {
+                summary.failed += 1;
+                log::debug!("Identity warmup: failed to resolve {}: {:?}", name, e);
+            }
+            Err(e) => {
+                summary.failed += 1;
+                log::error!("Identity warmup task failed: {}", e);
+            }
+        }
+    }
+
+    if !address_updates.is_empty() {
+        let result = update_conversations(app, identity_i_address, |conversations| {
+            for (id, private_address) in address_updates {
+                if let Some(conversation) = conversations.iter_mut().find(|c| c.id == id) {
+                    conversation.recipient_private_address = private_address;
+                }
+            }
+        });
+        if let Err(e) = result {
+            log::warn!("Identity warmup: failed to save updated conversations: {}", e);
+        }
+    }
+    if let Err(e) = cache.persist(app) {
+        log::warn!("Failed to persist identity cache: {}", e);
+    }
+    log::info!(
+        "Identity warmup for {}: {} resolved, {} failed, {} addresses updated, {} revoked, {} recovered",
+        identity_i_address, summary.resolved, summary.failed, summary.addresses_updated, summary.revoked.len(), summary.recovered.len()
+    );
+    Ok(summary)
+}
+
+// Start the warmup in the background (cancellable via WARMUP_TASK_ID)
+pub fn spawn_identity_warmup<R: Runtime>(app: AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, identity_i_address: String) {
+    tauri::async_runtime::spawn(async move {
+        let result = run_cancellable(
+            &app,
+            Some(WARMUP_TASK_ID.to_string()),
+            "identity-warmup",
+            warm_up(&app, &rpc_user, &rpc_pass, rpc_port, &identity_i_address),
+        )
+        .await;
+        if let Err(e) = result {
+            log::debug!("Identity warmup for {} stopped: {}", identity_i_address, e);
+        }
+    });
+}
@@ -0,0 +1,474 @@
+// File: src-tauri/src/identity_registration.rs
+// Description: Registering a new VerusID from inside the app (name commitment, then identity registration).
+// Changes:
+// - Created file. preview_identity_registration checks the name and shows the cost (registration fee of the
+//   chain currency, referral discount, network fee) against the transparent balance. start_identity_registration
+//   sends registernamecommitment, waits for it to confirm and then calls registeridentity with a fresh primary
+//   address and a fresh sapling address as the identity's private address. Progress arrives as
+//   identity-registration events. The commitment (including its salt) is kept in the store until the identity
+//   exists, so an interrupted registration can be resumed instead of paying again.
+// - Sub-identities (name.parent@): the parent must be an identity the wallet controls with a currency namespace;
+//   fees are in the parent currency and checked against that currency's transparent balance.
+// - wait_for_confirmation is public (profile updates track their transaction the same way).
+// - Registration fees are read through the block-aware response cache.
+// - Uses clock::now_secs instead of a local copy.
+// - A name commitment answer without a txid fails the registration instead of storing an empty commitment txid.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use super::address::validate_transparent_address;
+use super::capabilities::active_capabilities;
+use super::events::{emit_event, IDENTITY_REGISTRATION_EVENT};
+use super::message_rpc::DEFAULT_TX_FEE;
+use super::response_cache::make_cached_rpc_call;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::storage::{load_value, save_value, StorageError};
+use super::wallet_rpc::{create_private_address, get_balance_summary, get_currency_balances};
+use super::clock::now_secs;
+
+const REGISTRATION_STORE_PATH: &str = "identity_registrations.json";
+const PENDING_KEY: &str = "pending";
+
+// Characters VerusID names may not contain
+const INVALID_NAME_CHARS: &[char] = &['\\', '/', ':', '*', '?', '"', '<', '>', '|', '@', '.'];
+const MAX_NAME_BYTES: usize = 64;
+
+// Both transactions usually confirm within a few blocks; give up waiting (not the registration) after this
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+// "Identity not found" from getidentity
+const RPC_INVALID_ADDRESS_OR_KEY: i32 = -5;
+
+// Names with a registration in progress
+static RUNNING: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum RegistrationError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+    #[error("Cannot register under {0}")]
+    InvalidParent(String),
+    #[error("Identity {0} already exists")]
+    NameTaken(String),
+    #[error("Insufficient funds: {needed} needed, {available} available")]
+    InsufficientFunds { needed: f64, available: f64 },
+    #[error("A registration for {0} is already in progress")]
+    AlreadyRunning(String),
+    #[error("Storage error: {0}")]
+    Storage(String),
+}
+
+impl From<VerusRpcError> for RegistrationError {
+    fn from(error: VerusRpcError) -> Self {
+        RegistrationError::Rpc(error)
+    }
+}
+
+impl From<StorageError> for RegistrationError {
+    fn from(error: StorageError) -> Self {
+        RegistrationError::Storage(error.to_string())
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RegistrationPreview {
+    pub fully_qualified_name: String,
+    pub available: bool,
+    pub fee_currency: String,
+    pub registration_fee: f64,
+    pub referral_discount: f64, // Estimate; the daemon applies the actual discount
+    pub network_fee: f64,       // Two transactions: commitment and registration
+    pub total_cost: f64,
+    pub transparent_balance: f64, // Registration is paid from transparent funds
+    pub fee_currency_balance: f64, // Transparent balance in fee_currency (same as above for top-level names)
+    pub sufficient_funds: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct RegistrationRequest {
+    pub name: String,
+    pub parent: Option<String>,   // Registers name.parent@ under that currency's namespace
+    pub referral: Option<String>, // Referring VerusID (lowers the fee)
+    pub primary_address: Option<String>, // Controls the identity; a new wallet address if omitted
+    pub attach_private_address: bool,    // Create a sapling address as the identity's private address
+}
+
+// Kept in the store from commitment until the identity exists
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingRegistration {
+    pub fully_qualified_name: String,
+    pub name: String,
+    pub parent: Option<String>,
+    pub commitment_txid: String,
+    pub name_reservation: Value, // From registernamecommitment, salt included; needed by registeridentity
+    pub primary_address: String,
+    pub private_address: Option<String>,
+    pub registration_txid: Option<String>,
+    pub started_at: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStage {
+    Committed,             // Name commitment sent
+    WaitingForCommitment,  // Waiting for the commitment to confirm
+    Registering,           // registeridentity sent
+    WaitingForRegistration,
+    Completed,
+    Failed,
+}
+
+// Payload of the identity-registration event
+#[derive(Serialize, Debug, Clone)]
+pub struct RegistrationProgress {
+    pub fully_qualified_name: String,
+    pub stage: RegistrationStage,
+    pub commitment_txid: String,
+    pub registration_txid: Option<String>,
+    pub private_address: Option<String>,
+    pub confirmations: u64,
+    pub error: Option<String>,
+}
+
+fn validate_name(name: &str) -> Result<(), RegistrationError> {
+    if name.is_empty() || name.trim() != name {
+        return Err(RegistrationError::InvalidName("name must not be empty or start/end with spaces".to_string()));
+    }
+    if name.len() > MAX_NAME_BYTES {
+        return Err(RegistrationError::InvalidName(format!("name must be at most {} bytes", MAX_NAME_BYTES)));
+    }
+    if let Some(c) = name.chars().find(|c| INVALID_NAME_CHARS.contains(c) || c.is_control()) {
+        return Err(RegistrationError::InvalidName(format!("'{}' is not allowed in names", c)));
+    }
+    Ok(())
+}
+
+fn fully_qualified_name(name: &str, parent: Option<&str>) -> String {
+    match parent {
+        Some(parent) => format!("{}.{}@", name, parent.trim_end_matches('@')),
+        None => format!("{}@", name),
+    }
+}
+
+async fn identity_exists(rpc_user: &str, rpc_pass: &str, rpc_port: u16, fully_qualified_name: &str) -> Result<bool, VerusRpcError> {
+    match make_rpc_call::<Value>(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(fully_qualified_name)]).await {
+        Ok(_) => Ok(true),
+        Err(VerusRpcError::Rpc { code, .. }) if code == RPC_INVALID_ADDRESS_OR_KEY => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// Registration fee of the namespace: the chain currency for top-level names, the parent currency otherwise.
+// Returns (currency, fee, referral discount).
+async fn registration_fee(rpc_user: &str, rpc_pass: &str, rpc_port: u16, parent: Option<&str>, with_referral: bool) -> Result<(String, f64, f64), VerusRpcError> {
+    let currency = match parent {
+        Some(parent) => parent.trim_end_matches('@').to_string(),
+        None => active_capabilities(rpc_port).map(|c| c.chain_name).unwrap_or_else(|| "VRSC".to_string()),
+    };
+    let definition: Value = make_cached_rpc_call(rpc_user, rpc_pass, rpc_port, "getcurrency", vec![json!(currency)]).await?;
+    let fee = definition.get("idregistrationfees").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let levels = definition.get("idreferrallevels").and_then(|v| v.as_u64()).unwrap_or(0);
+    // The referral share goes to the referrer chain; the registrant saves one share
+    let discount = if with_referral && levels > 0 { fee / (levels + 2) as f64 } else { 0.0 };
+    let name = definition.get("fullyqualifiedname").and_then(|v| v.as_str()).unwrap_or(&currency).to_string();
+    Ok((name, fee, discount))
+}
+
+// Sub-identities need a parent the wallet controls that defines a namespace (a currency)
+async fn check_parent(rpc_user: &str, rpc_pass: &str, rpc_port: u16, parent: &str) -> Result<(), RegistrationError> {
+    let identity: Value = match make_rpc_call(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(parent)]).await {
+        Ok(identity) => identity,
+        Err(VerusRpcError::Rpc { code, .. }) if code == RPC_INVALID_ADDRESS_OR_KEY => {
+            return Err(RegistrationError::InvalidParent(format!("{}: identity not found", parent)));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let controlled = identity.get("cansignfor").and_then(|v| v.as_bool()).unwrap_or(false)
+        && identity.get("canspendfor").and_then(|v| v.as_bool()).unwrap_or(false);
+    if !controlled {
+        return Err(RegistrationError::InvalidParent(format!("{}: not controlled by this wallet", parent)));
+    }
+    match make_rpc_call::<Value>(rpc_user, rpc_pass, rpc_port, "getcurrency", vec![json!(parent.trim_end_matches('@'))]).await {
+        Ok(_) => Ok(()),
+        Err(VerusRpcError::Rpc { .. }) => Err(RegistrationError::InvalidParent(format!("{}: no currency defined, so it has no namespace for sub-identities", parent))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn preview_identity_registration(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    name: &str,
+    parent: Option<&str>,
+    referral: Option<&str>,
+) -> Result<RegistrationPreview, RegistrationError> {
+    validate_name(name)?;
+    if let Some(parent) = parent {
+        check_parent(rpc_user, rpc_pass, rpc_port, parent).await?;
+    }
+    let fully_qualified_name = fully_qualified_name(name, parent);
+    let available = !identity_exists(rpc_user, rpc_pass, rpc_port, &fully_qualified_name).await?;
+    let (fee_currency, registration_fee, referral_discount) = registration_fee(rpc_user, rpc_pass, rpc_port, parent, referral.is_some()).await?;
+    let network_fee = 2.0 * DEFAULT_TX_FEE;
+    let total_cost = registration_fee - referral_discount + network_fee;
+    let transparent_balance = get_balance_summary(rpc_user.to_string(), rpc_pass.to_string(), rpc_port).await?.transparent;
+    let native = active_capabilities(rpc_port).is_none_or(|c| c.chain_name.eq_ignore_ascii_case(&fee_currency));
+    let (fee_currency_balance, sufficient_funds) = if native {
+        (transparent_balance, transparent_balance >= total_cost)
+    } else {
+        let balances = get_currency_balances(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, "R*".to_string(), 1).await?;
+        let balance = balances
+            .iter()
+            .find(|(currency, _)| currency.eq_ignore_ascii_case(&fee_currency))
+            .map(|(_, amount)| *amount)
+            .unwrap_or(0.0);
+        // The network fee is still paid in the chain currency
+        (balance, balance >= registration_fee - referral_discount && transparent_balance >= network_fee)
+    };
+    Ok(RegistrationPreview {
+        fully_qualified_name,
+        available,
+        fee_currency,
+        registration_fee,
+        referral_discount,
+        network_fee,
+        total_cost,
+        transparent_balance,
+        fee_currency_balance,
+        sufficient_funds,
+    })
+}
+
+pub fn read_pending_registrations<R: Runtime>(app: &AppHandle<R>) -> Vec<PendingRegistration> {
+    load_value(app, REGISTRATION_STORE_PATH, PENDING_KEY)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read pending identity registrations: {}", e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn write_pending<R: Runtime>(app: &AppHandle<R>, registration: &PendingRegistration) -> Result<(), StorageError> {
+    let mut pending = read_pending_registrations(app);
+    pending.retain(|p| p.fully_qualified_name != registration.fully_qualified_name);
+    pending.push(registration.clone());
+    save_value(app, REGISTRATION_STORE_PATH, PENDING_KEY, &pending)
+}
+
+fn remove_pending<R: Runtime>(app: &AppHandle<R>, fully_qualified_name: &str) {
+    let mut pending = read_pending_registrations(app);
+    pending.retain(|p| p.fully_qualified_name != fully_qualified_name);
+    if let Err(e) = save_value(app, REGISTRATION_STORE_PATH, PENDING_KEY, &pending) {
+        log::warn!("Failed to remove pending registration of {}: {}", fully_qualified_name, e);
+    }
+}
+
+async fn confirmations(rpc_user: &str, rpc_pass: &str, rpc_port: u16, txid: &str) -> Result<u64, VerusRpcError> {
+    let tx: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "gettransaction", vec![json!(txid)]).await?;
+    Ok(tx.get("confirmations").and_then(|v| v.as_i64()).unwrap_or(0).max(0) as u64)
+}
+
+// Wait for a transaction to confirm, reporting each check
+pub async fn wait_for_confirmation(rpc_user: &str, rpc_pass: &str, rpc_port: u16, txid: &str, mut on_check: impl FnMut(u64)) -> Result<(), VerusRpcError> {
+    let deadline = Instant::now() + CONFIRMATION_TIMEOUT;
+    while Instant::now() < deadline {
+        match confirmations(rpc_user, rpc_pass, rpc_port, txid).await {
+            Ok(count) if count > 0 => return Ok(()),
+            Ok(count) => on_check(count),
+            // Busy daemon; the next check may work
+            Err(VerusRpcError::Timeout) => on_check(0),
+            Err(e) => return Err(e),
+        }
+        tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+    }
+    Err(VerusRpcError::Timeout)
+}
+
+// Second half of a registration: wait for the commitment, register, wait for the identity
+async fn complete_registration<R: Runtime>(app: &AppHandle<R>, rpc_user: &str, rpc_pass: &str, rpc_port: u16, mut registration: PendingRegistration) -> Result<(), VerusRpcError> {
+    let progress = |registration: &PendingRegistration, stage: RegistrationStage, confirmations: u64| RegistrationProgress {
+        fully_qualified_name: registration.fully_qualified_name.clone(),
+        stage,
+        commitment_txid: registration.commitment_txid.clone(),
+        registration_txid: registration.registration_txid.clone(),
+        private_address: registration.private_address.clone(),
+        confirmations,
+        error: None,
+    };
+
+    if registration.registration_txid.is_none() {
+        wait_for_confirmation(rpc_user, rpc_pass, rpc_port, &registration.commitment_txid, |count| {
+            emit_event(app, IDENTITY_REGISTRATION_EVENT, progress(&registration, RegistrationStage::WaitingForCommitment, count))
+        })
+        .await?;
+
+        let mut identity = json!({
+            "name": registration.name,
+            "primaryaddresses": [registration.primary_address],
+            "minimumsignatures": 1,
+        });
+        if registration.parent.is_some() {
+            // The reservation carries the parent's i-address
+            if let Some(parent) = registration.name_reservation.get("parent") {
+                identity["parent"] = parent.clone();
+            }
+        }
+        if let Some(private_address) = &registration.private_address {
+            identity["privateaddress"] = json!(private_address);
+        }
+        let definition = json!({
+            "txid": registration.commitment_txid,
+            "namereservation": registration.name_reservation,
+            "identity": identity,
+        });
+        let txid: String = make_rpc_call(rpc_user, rpc_pass, rpc_port, "registeridentity", vec![definition]).await?;
+        log::info!("Registered identity {} in {}", registration.fully_qualified_name, txid);
+        registration.registration_txid = Some(txid);
+        if let Err(e) = write_pending(app, &registration) {
+            log::warn!("Failed to persist registration txid of {}: {}", registration.fully_qualified_name, e);
+        }
+        emit_event(app, IDENTITY_REGISTRATION_EVENT, progress(&registration, RegistrationStage::Registering, 0));
+    }
+
+    let registration_txid = registration.registration_txid.clone().unwrap_or_default();
+    wait_for_confirmation(rpc_user, rpc_pass, rpc_port, &registration_txid, |count| {
+        emit_event(app, IDENTITY_REGISTRATION_EVENT, progress(&registration, RegistrationStage::WaitingForRegistration, count))
+    })
+    .await?;
+    remove_pending(app, &registration.fully_qualified_name);
+    emit_event(app, IDENTITY_REGISTRATION_EVENT, progress(&registration, RegistrationStage::Completed, 1));
+    Ok(())
+}
+
+// Run the rest of a registration in the background, reporting the outcome
+fn spawn_completion<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, registration: PendingRegistration) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let name = registration.fully_qualified_name.clone();
+        let failed = RegistrationProgress {
+            fully_qualified_name: name.clone(),
+            stage: RegistrationStage::Failed,
+            commitment_txid: registration.commitment_txid.clone(),
+            registration_txid: registration.registration_txid.clone(),
+            private_address: registration.private_address.clone(),
+            confirmations: 0,
+            error: None,
+        };
+        if let Err(e) = complete_registration(&app, &rpc_user, &rpc_pass, rpc_port, registration).await {
+            // The commitment stays stored, so the registration can be resumed
+            log::error!("Registration of {} failed: {:?}", name, e);
+            emit_event(&app, IDENTITY_REGISTRATION_EVENT, RegistrationProgress { error: Some(e.to_string()), ..failed });
+        }
+        RUNNING.lock().unwrap_or_else(|e| e.into_inner()).remove(&name);
+    });
+}
+
+// Commit to the name and continue in the background. Returns once the commitment is sent.
+pub async fn start_identity_registration<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    request: RegistrationRequest,
+) -> Result<RegistrationProgress, RegistrationError> {
+    let preview = preview_identity_registration(&rpc_user, &rpc_pass, rpc_port, &request.name, request.parent.as_deref(), request.referral.as_deref()).await?;
+    if !preview.available {
+        return Err(RegistrationError::NameTaken(preview.fully_qualified_name));
+    }
+    if !preview.sufficient_funds {
+        return Err(RegistrationError::InsufficientFunds { needed: preview.total_cost, available: preview.fee_currency_balance });
+    }
+    let primary_address = match request.primary_address {
+        Some(address) => {
+            validate_transparent_address(&rpc_user, &rpc_pass, rpc_port, &address).await?;
+            address
+        }
+        None => make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getnewaddress", vec![]).await?,
+    };
+    let private_address = if request.attach_private_address {
+        Some(create_private_address(rpc_user.clone(), rpc_pass.clone(), rpc_port, None).await?.address)
+    } else {
+        None
+    };
+    let fully_qualified_name = preview.fully_qualified_name;
+    if !RUNNING.lock().unwrap_or_else(|e| e.into_inner()).insert(fully_qualified_name.clone()) {
+        return Err(RegistrationError::AlreadyRunning(fully_qualified_name));
+    }
+
+    let params = vec![
+        json!(request.name),
+        json!(primary_address),
+        json!(request.referral.unwrap_or_default()),
+        json!(request.parent.clone().unwrap_or_default()),
+    ];
+    let commitment: Value = match make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "registernamecommitment", params).await {
+        Ok(commitment) => commitment,
+        Err(e) => {
+            RUNNING.lock().unwrap_or_else(|e| e.into_inner()).remove(&fully_qualified_name);
+            return Err(e.into());
+        }
+    };
+    let Some(commitment_txid) = commitment.get("txid").and_then(|v| v.as_str()).filter(|txid| !txid.is_empty()) else {
+        RUNNING.lock().unwrap_or_else(|e| e.into_inner()).remove(&fully_qualified_name);
+        return Err(VerusRpcError::ParseError("registernamecommitment response missing txid".to_string()).into());
+    };
+    let registration = PendingRegistration {
+        fully_qualified_name: fully_qualified_name.clone(),
+        name: request.name,
+        parent: request.parent,
+        commitment_txid: commitment_txid.to_string(),
+        name_reservation: commitment.get("namereservation").cloned().unwrap_or(Value::Null),
+        primary_address,
+        private_address,
+        registration_txid: None,
+        started_at: now_secs(),
+    };
+    log::info!("Name commitment for {} sent in {}", fully_qualified_name, registration.commitment_txid);
+    // Losing the reservation would waste the commitment; keep going even if it can't be stored
+    if let Err(e) = write_pending(app, &registration) {
+        log::error!("Failed to persist name commitment of {}: {}", fully_qualified_name, e);
+    }
+
+    let started = RegistrationProgress {
+        fully_qualified_name,
+        stage: RegistrationStage::Committed,
+        commitment_txid: registration.commitment_txid.clone(),
+        registration_txid: None,
+        private_address: registration.private_address.clone(),
+        confirmations: 0,
+        error: None,
+    };
+    emit_event(app, IDENTITY_REGISTRATION_EVENT, started.clone());
+    spawn_completion(app, rpc_user, rpc_pass, rpc_port, registration);
+    Ok(started)
+}
+
+// Continue stored registrations (e.g. after a restart). Returns the names being resumed.
+pub fn resume_identity_registrations<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16) -> Vec<String> {
+    let mut resumed = Vec::new();
+    for registration in read_pending_registrations(app) {
+        if !RUNNING.lock().unwrap_or_else(|e| e.into_inner()).insert(registration.fully_qualified_name.clone()) {
+            continue;
+        }
+        log::info!("Resuming registration of {}", registration.fully_qualified_name);
+        resumed.push(registration.fully_qualified_name.clone());
+        spawn_completion(app, rpc_user.clone(), rpc_pass.clone(), rpc_port, registration);
+    }
+    resumed
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_pending_identity_registrations<R: Runtime>(app: AppHandle<R>) -> Vec<PendingRegistration> {
+    log::debug!("get_pending_identity_registrations command received");
+    read_pending_registrations(&app)
+}
@@ -5,11 +5,40 @@
 // - Added get_login_identities_fast for immediate name loading
 // - Updated get_login_identities to maintain compatibility
 // - Added get_identity_balance for individual balance fetching
+// - FormattedIdentity carries currency_balances (all currencies at the private address); filled by
+//   get_login_identities and get_identity_currency_balances
+// - Login filtering follows configurable IdentityFilterRules (watch-only IDs shown read-only, revoked IDs with a warning)
+// - check_identity_eligibility validates the identity's private address against the connected chain
+// - Revocation state comes from getidentity's status and the identity's revoked flag; eligibility reports it
+//   (with the revocation / recovery authorities when another identity holds them) instead of treating the
+//   identity as active. recovered is set by the identity cache when a revoked identity becomes active again.
+// - check_identity_eligibility also returns the primary addresses (the contact watcher compares them)
+// - Added a TTL cache for getidentity lookups (get_identity_cached; least recently used entries are evicted).
+//   Login names, eligibility checks, parent names and profiles use it; the window is a persisted setting.
+// - Added resolve_identity_address (i-address -> formatted name). The last resolved name is remembered and used
+//   while the daemon can't answer; unknown addresses come back unchanged.
+// - FormattedIdentity reports timelocks (locked flag and timelocked_until height) from the identity's flags and
+//   timelock, for login identities and eligibility checks.
+// - Split the listidentities filtering out of get_login_identities_fast as list_eligible_identities (no name
+//   lookups; the wallet identity watcher diffs it periodically).
+// - Added check_chat_eligibility (starting a chat): on top of check_identity_eligibility, revoked and locked /
+//   timelocked identities are refused, each with its own error. Identities without a private address now report
+//   NoPrivateAddress instead of NotFoundOrIneligible.
+// - Login names are fetched with one getidentity batch request (get_identities_cached) instead of a call per identity.
+// - invalidate_identity_lookups also drops the port's block-cached responses.
+// - getidentity lookups go through the block-aware response cache only; removed the TTL cache and its setting.
+// - Tests for the timelock math; a locked identity's unlock height saturates instead of overflowing on huge delays.
+// - getidentity lookups are short-circuited within the response cache's configurable TTL (LRU-bounded per port).
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use super::address::validate_recipient_address;
+use super::response_cache::{make_cached_rpc_batch, make_cached_rpc_call};
 use super::rpc_client::{make_rpc_call, VerusRpcError};
-use super::wallet_rpc::get_private_balance;
+use super::wallet_rpc::{get_currency_balances, get_private_balance, CurrencyBalances};
+use super::settings::IdentityFilterRules;
 
 // Updated struct to include balance for dropdown display
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -18,19 +47,123 @@ pub struct FormattedIdentity {
     pub i_address: String,            // identityaddress
     pub private_address: String,      // privateaddress (required, not optional)
     pub balance: Option<f64>,         // Private balance (None while loading)
+    #[serde(default)]
+    pub read_only: bool,              // Watch-only: cannot sign or spend (messages can be read, not sent)
+    #[serde(default)]
+    pub revoked: bool,                // Identity is revoked (shown with a warning)
+    #[serde(default)]
+    pub currency_balances: Option<CurrencyBalances>, // All currencies at the private address (None while loading)
+    #[serde(default)]
+    pub recovered: bool,                          // Was revoked and has been recovered since (keys may have changed hands)
+    #[serde(default)]
+    pub revocation_authority: Option<String>,     // Set when another identity can revoke this one
+    #[serde(default)]
+    pub recovery_authority: Option<String>,       // Set when another identity can recover this one
+    #[serde(default)]
+    pub primary_addresses: Vec<String>,           // Keys controlling the identity (empty where not fetched)
+    #[serde(default)]
+    pub locked: bool,                             // Locked until an unlock is requested (then timelocked until the delay passed)
+    #[serde(default)]
+    pub timelocked_until: Option<u64>,            // Block height before which funds and authority can't be used
 }
 
-// NEW: Fast function to get identities without balances for progressive loading
-pub async fn get_login_identities_fast(
-    rpc_user: String,
-    rpc_pass: String,
-    rpc_port: u16,
-) -> Result<Vec<FormattedIdentity>, VerusRpcError> {
-    log::info!("Fetching identities (fast mode - no balances)...");
+// getidentity through the block-aware response cache (answers are reused until the next block, at most for the
+// cache's configurable TTL)
+pub async fn get_identity_cached(rpc_user: &str, rpc_pass: &str, rpc_port: u16, identity: &str) -> Result<Value, VerusRpcError> {
+    make_cached_rpc_call(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(identity)]).await
+}
+
+// get_identity_cached for several identities: whatever isn't cached is fetched in one batch request.
+// Results are in the order of `identities`.
+pub async fn get_identities_cached(rpc_user: &str, rpc_pass: &str, rpc_port: u16, identities: &[String]) -> Vec<Result<Value, VerusRpcError>> {
+    let calls = identities.iter().map(|identity| ("getidentity", vec![json!(identity)])).collect();
+    match make_cached_rpc_batch(rpc_user, rpc_pass, rpc_port, calls).await {
+        Ok(results) => results,
+        Err(e) => {
+            log::warn!("getidentity batch failed: {:?}", e);
+            identities.iter().map(|_| Err(e.clone())).collect()
+        }
+    }
+}
+
+// (RPC port, i-address) -> last resolved formatted name
+static RESOLVED_NAMES: LazyLock<Mutex<HashMap<(u16, String), String>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Identity flag set while an identity is revoked
+const IDENTITY_FLAG_REVOKED: u64 = 0x8000;
+
+// Revoked per getidentity: the status field, or the flag for daemons that don't report a status
+pub fn is_revoked(identity_result: &Value) -> bool {
+    let status_revoked = identity_result
+        .get("status")
+        .and_then(|v| v.as_str())
+        .is_some_and(|status| status.eq_ignore_ascii_case("revoked"));
+    let flag_revoked = identity_result
+        .pointer("/identity/flags")
+        .and_then(|v| v.as_u64())
+        .is_some_and(|flags| flags & IDENTITY_FLAG_REVOKED != 0);
+    status_revoked || flag_revoked
+}
+
+// Identity flag set while an identity is locked; requesting an unlock starts a countdown of `timelock` blocks
+const IDENTITY_FLAG_LOCKED: u64 = 0x2;
 
+fn has_timelock(identity_details: &Value) -> bool {
+    let flags = identity_details.get("flags").and_then(|v| v.as_u64()).unwrap_or(0);
+    let timelock = identity_details.get("timelock").and_then(|v| v.as_u64()).unwrap_or(0);
+    flags & IDENTITY_FLAG_LOCKED != 0 || timelock > 0
+}
+
+// (locked, timelocked_until) of an identity at the given chain height. A locked identity reports the earliest height
+// it could be used at if an unlock were requested now; an unlocking one the height its timelock ends (None once passed).
+pub fn timelock_status(identity_details: &Value, tip_height: Option<u64>) -> (bool, Option<u64>) {
+    let flags = identity_details.get("flags").and_then(|v| v.as_u64()).unwrap_or(0);
+    let timelock = identity_details.get("timelock").and_then(|v| v.as_u64()).unwrap_or(0);
+    if flags & IDENTITY_FLAG_LOCKED != 0 {
+        return (true, tip_height.map(|tip| tip.saturating_add(timelock)));
+    }
+    (false, tip_height.filter(|tip| timelock > *tip).map(|_| timelock))
+}
+
+// Chain height for timelock checks; None (timelocks unknown) if the daemon doesn't answer
+async fn timelock_tip_height(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Option<u64> {
+    make_rpc_call::<u64>(rpc_user, rpc_pass, rpc_port, "getblockcount", vec![])
+        .await
+        .map_err(|e| log::warn!("Failed to get block height for timelock checks: {:?}", e))
+        .ok()
+}
+
+// An authority field of the identity, if it names a different identity
+fn delegated_authority(identity_details: &Value, key: &str) -> Option<String> {
+    let own_address = identity_details.get("identityaddress").and_then(|v| v.as_str());
+    identity_details
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|authority| Some(*authority) != own_address)
+        .map(String::from)
+}
+
+// Wallet identity that passes the login filter rules (before its name is looked up)
+#[derive(Serialize, Debug, Clone)]
+pub struct EligibleIdentity {
+    pub i_address: String,
+    pub private_address: String,
+    pub read_only: bool,
+    pub revoked: bool,
+    pub locked: bool,
+    pub timelocked_until: Option<u64>,
+}
+
+// listidentities filtered by the login rules; a single RPC call (plus the block height if timelocks are involved)
+pub async fn list_eligible_identities(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    rules: &IdentityFilterRules,
+) -> Result<Vec<EligibleIdentity>, VerusRpcError> {
     let identities_raw: Vec<Value> = make_rpc_call(
-        &rpc_user,
-        &rpc_pass,
+        rpc_user,
+        rpc_pass,
         rpc_port,
         "listidentities",
         vec![json!(true), json!(true), json!(true)],
@@ -39,9 +172,14 @@ pub async fn get_login_identities_fast(
 
     log::info!("Received {} raw identity entries from listidentities.", identities_raw.len());
 
+    let tip_height = if identities_raw.iter().filter_map(|o| o.get("identity")).any(has_timelock) {
+        timelock_tip_height(rpc_user, rpc_pass, rpc_port).await
+    } else {
+        None
+    };
+
     let mut qualifying_identities = Vec::new();
 
-    // Step 1: Filter identities based on new criteria
     for identity_obj in identities_raw {
         if let Some(identity_details) = identity_obj.get("identity") {
             // Check all required fields and conditions
@@ -57,14 +195,27 @@ pub async fn get_login_identities_fast(
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
 
+            let revoked = is_revoked(&identity_obj);
+
             let identity_address = identity_details.get("identityaddress")
                 .and_then(|v| v.as_str());
 
-            // Apply enhanced filtering criteria
+            // Apply filtering rules (a private address is always required for messaging)
             if let (Some(private_addr), Some(id_addr)) = (private_address, identity_address) {
-                if can_spend_for && can_sign_for {
-                    log::debug!("Identity {} qualifies: has private address, canspendfor=true, cansignfor=true", id_addr);
-                    qualifying_identities.push((id_addr.to_string(), private_addr.to_string()));
+                let full_access = can_spend_for && can_sign_for;
+                if revoked && !rules.include_revoked {
+                    log::debug!("Identity {} skipped: revoked", id_addr);
+                } else if full_access || rules.show_watch_only {
+                    log::debug!("Identity {} qualifies: canspendfor={}, cansignfor={}, revoked={}", id_addr, can_spend_for, can_sign_for, revoked);
+                    let (locked, timelocked_until) = timelock_status(identity_details, tip_height);
+                    qualifying_identities.push(EligibleIdentity {
+                        i_address: id_addr.to_string(),
+                        private_address: private_addr.to_string(),
+                        read_only: !full_access,
+                        revoked,
+                        locked,
+                        timelocked_until,
+                    });
                 } else {
                     log::debug!("Identity {} skipped: canspendfor={}, cansignfor={}", id_addr, can_spend_for, can_sign_for);
                 }
@@ -75,9 +226,23 @@ pub async fn get_login_identities_fast(
             log::warn!("Skipping raw identity entry because 'identity' sub-object is missing.");
         }
     }
+    Ok(qualifying_identities)
+}
+
+// NEW: Fast function to get identities without balances for progressive loading
+pub async fn get_login_identities_fast(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    rules: &IdentityFilterRules,
+) -> Result<Vec<FormattedIdentity>, VerusRpcError> {
+    log::info!("Fetching identities (fast mode - no balances) with rules {:?}...", rules);
+
+    // Step 1: Filter identities based on the configured rules
+    let qualifying_identities = list_eligible_identities(&rpc_user, &rpc_pass, rpc_port, rules).await?;
 
     if qualifying_identities.is_empty() {
-        log::error!("No qualifying VerusIDs found (must have private address, canspendfor=true, cansignfor=true).");
+        log::error!("No qualifying VerusIDs found (must have private address, canspendfor=true, cansignfor=true unless watch-only IDs are shown).");
         return Err(VerusRpcError::Rpc {
             code: -1,
             message: "No eligible VerusIDs found. Identities must have private addresses and spending/signing permissions.".to_string(),
@@ -88,11 +253,13 @@ pub async fn get_login_identities_fast(
 
     // Step 2: Get formatted names using getidentity + fullyqualifiedname (NO BALANCE FETCHING)
     let mut formatted_identities = Vec::new();
+    let addresses: Vec<String> = qualifying_identities.iter().map(|identity| identity.i_address.clone()).collect();
+    let lookups = get_identities_cached(&rpc_user, &rpc_pass, rpc_port, &addresses).await;
 
-    for (identity_address, private_address) in qualifying_identities {
+    for (EligibleIdentity { i_address: identity_address, private_address, read_only, revoked, locked, timelocked_until }, lookup) in qualifying_identities.into_iter().zip(lookups) {
         log::debug!("Fetching name for identity: {}", identity_address);
         
-        match make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "getidentity", vec![json!(identity_address)]).await {
+        match lookup {
             Ok(identity_result) => {
                 if let Some(fully_qualified_name) = identity_result.get("fullyqualifiedname").and_then(|v| v.as_str()) {
                     // Transform fullyqualifiedname by removing everything after the last dot before @
@@ -105,6 +272,15 @@ pub async fn get_login_identities_fast(
                         i_address: identity_address.clone(),
                         private_address: private_address.clone(),
                         balance: None, // No balance fetching in fast mode
+                        currency_balances: None,
+                        read_only,
+                        revoked,
+                        recovered: false,
+                        revocation_authority: None,
+                        recovery_authority: None,
+                        primary_addresses: Vec::new(),
+                        locked,
+                        timelocked_until,
                     });
                 } else {
                     log::warn!("No fullyqualifiedname found for identity {}, skipping", identity_address);
@@ -140,16 +316,28 @@ pub async fn get_identity_balance(
     get_private_balance(rpc_user, rpc_pass, rpc_port, private_address).await
 }
 
+// All currencies held at an identity's private address (PBaaS chains hold more than the native coin)
+pub async fn get_identity_currency_balances(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    private_address: String,
+) -> Result<CurrencyBalances, VerusRpcError> {
+    log::debug!("Fetching currency balances for private address: {}", private_address);
+    get_currency_balances(rpc_user, rpc_pass, rpc_port, private_address, 1).await
+}
+
 // Updated function with new filtering logic and balance integration (MAINTAINED FOR COMPATIBILITY)
 pub async fn get_login_identities(
     rpc_user: String,
     rpc_pass: String,
     rpc_port: u16,
+    rules: &IdentityFilterRules,
 ) -> Result<Vec<FormattedIdentity>, VerusRpcError> {
     log::info!("Fetching identities for login selection with enhanced filtering...");
 
     // First get identities without balances
-    let mut identities = get_login_identities_fast(rpc_user.clone(), rpc_pass.clone(), rpc_port).await?;
+    let mut identities = get_login_identities_fast(rpc_user.clone(), rpc_pass.clone(), rpc_port, rules).await?;
 
     // Then fetch balances for all identities
     for identity in &mut identities {
@@ -165,6 +353,10 @@ pub async fn get_login_identities(
                 identity.balance = None; // Will be displayed as "-" in UI
             }
         }
+        match get_currency_balances(rpc_user.clone(), rpc_pass.clone(), rpc_port, identity.private_address.clone(), 1).await {
+            Ok(balances) => identity.currency_balances = Some(balances),
+            Err(e) => log::warn!("Failed to fetch currency balances for {}: {:?}", identity.formatted_name, e),
+        }
     }
 
     // Sort by balance (highest first), treating None as 0
@@ -199,6 +391,34 @@ fn transform_fully_qualified_name(fully_qualified_name: &str) -> String {
     }
 }
 
+// Display name for an i-address (e.g. from transaction outputs or stored conversations). Never fails: falls back
+// to the last known name, then to the address itself.
+pub async fn resolve_identity_address(rpc_user: &str, rpc_pass: &str, rpc_port: u16, i_address: &str) -> String {
+    let i_address = i_address.trim();
+    // Names and R-/z-addresses are returned as they are
+    if !i_address.starts_with('i') || i_address.len() != 34 {
+        return i_address.to_string();
+    }
+    let key = (rpc_port, i_address.to_string());
+    match get_identity_cached(rpc_user, rpc_pass, rpc_port, i_address).await {
+        Ok(identity_result) => {
+            if let Some(fully_qualified_name) = identity_result.get("fullyqualifiedname").and_then(|v| v.as_str()) {
+                let formatted_name = transform_fully_qualified_name(fully_qualified_name);
+                RESOLVED_NAMES.lock().unwrap_or_else(|e| e.into_inner()).insert(key, formatted_name.clone());
+                return formatted_name;
+            }
+            log::warn!("No fullyqualifiedname found for identity {}", i_address);
+        }
+        Err(e) => log::debug!("Failed to resolve identity {}: {:?}", i_address, e),
+    }
+    RESOLVED_NAMES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&key)
+        .cloned()
+        .unwrap_or_else(|| i_address.to_string())
+}
+
 // NEW function for New Chat: Check identity eligibility
 pub async fn check_identity_eligibility(
     rpc_user: String,
@@ -214,7 +434,7 @@ pub async fn check_identity_eligibility(
         return Err(VerusRpcError::InvalidFormat);
     }
 
-    match make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "getidentity", vec![json!(target_identity_name)]).await {
+    match get_identity_cached(&rpc_user, &rpc_pass, rpc_port, &target_identity_name).await {
         Ok(identity_result) => {
             log::debug!("getidentity result for {}: {:?}", target_identity_name, identity_result);
             if let Some(identity_details) = identity_result.get("identity") {
@@ -237,7 +457,7 @@ pub async fn check_identity_eligibility(
                         if parent_id != system_id {
                             log::debug!("Identity '{}' is a sub-ID. Fetching parent '{}'...", name, parent_id);
                             // Get parent identity to format the name properly (name.parentname@)
-                            match make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "getidentity", vec![json!(parent_id)]).await {
+                            match get_identity_cached(&rpc_user, &rpc_pass, rpc_port, parent_id).await {
                                 Ok(parent_identity_result) => {
                                     // Extract parent name from the parent identity details
                                     if let Some(parent_name) = parent_identity_result
@@ -259,12 +479,39 @@ pub async fn check_identity_eligibility(
                             }
                         }
                         
+                        // A private address of another network would make every send to this contact fail (or worse)
+                        let private_address = private_address_opt.unwrap();
+                        validate_recipient_address(&rpc_user, &rpc_pass, rpc_port, &private_address).await?;
+
+                        let revoked = is_revoked(&identity_result);
+                        if revoked {
+                            log::warn!("Identity {} is revoked", target_identity_name);
+                        }
+                        let tip_height = if has_timelock(identity_details) {
+                            timelock_tip_height(&rpc_user, &rpc_pass, rpc_port).await
+                        } else {
+                            None
+                        };
+                        let (locked, timelocked_until) = timelock_status(identity_details, tip_height);
                         log::info!("Identity {} is eligible. Formatted as: {}", target_identity_name, formatted_name);
                         Ok(FormattedIdentity {
                             formatted_name,
                             i_address: i_address.to_string(),
-                            private_address: private_address_opt.unwrap(),
+                            private_address,
                             balance: None,
+                            currency_balances: None,
+                            read_only: false,
+                            revoked,
+                            recovered: false,
+                            revocation_authority: delegated_authority(identity_details, "revocationauthority"),
+                            recovery_authority: delegated_authority(identity_details, "recoveryauthority"),
+                            primary_addresses: identity_details
+                                .get("primaryaddresses")
+                                .and_then(|v| v.as_array())
+                                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                                .unwrap_or_default(),
+                            locked,
+                            timelocked_until,
                         })
                     } else {
                         log::warn!("Identity {} found but missing required fields.", target_identity_name);
@@ -272,7 +519,7 @@ pub async fn check_identity_eligibility(
                     }
                 } else {
                     log::warn!("Identity {} found but has no private address.", target_identity_name);
-                    Err(VerusRpcError::NotFoundOrIneligible)
+                    Err(VerusRpcError::NoPrivateAddress(target_identity_name.clone()))
                 }
             } else {
                  log::warn!("'identity' object not found in getidentity result for {}.", target_identity_name);
@@ -301,4 +548,66 @@ pub async fn check_identity_eligibility(
             }
         }
     }
-} 
\ No newline at end of file
+} 
+
+// Eligibility for starting a chat. Each reason an identity can't be messaged gets its own error: not found,
+// no private address, a private address that isn't valid on this chain, revoked, locked or timelocked.
+pub async fn check_chat_eligibility(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    target_identity_name: String,
+) -> Result<FormattedIdentity, VerusRpcError> {
+    let identity = check_identity_eligibility(rpc_user, rpc_pass, rpc_port, target_identity_name).await?;
+    if identity.revoked {
+        log::warn!("Refusing chat with revoked identity {}", identity.formatted_name);
+        return Err(VerusRpcError::RecipientRevoked(identity.formatted_name));
+    }
+    if identity.locked || identity.timelocked_until.is_some() {
+        log::warn!("Refusing chat with timelocked identity {} (until {:?})", identity.formatted_name, identity.timelocked_until);
+        return Err(VerusRpcError::IdentityTimelocked { identity: identity.formatted_name, until_height: identity.timelocked_until });
+    }
+    Ok(identity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_timelock() {
+        let identity = json!({ "flags": 0, "timelock": 0 });
+        assert!(!has_timelock(&identity));
+        assert_eq!(timelock_status(&identity, Some(1000)), (false, None));
+        // Older daemons leave the fields out
+        assert_eq!(timelock_status(&json!({}), Some(1000)), (false, None));
+    }
+
+    #[test]
+    fn locked_identity_counts_delay_from_tip() {
+        let identity = json!({ "flags": IDENTITY_FLAG_LOCKED, "timelock": 1440 });
+        assert!(has_timelock(&identity));
+        assert_eq!(timelock_status(&identity, Some(1000)), (true, Some(2440)));
+        // Unknown height: still locked, end unknown
+        assert_eq!(timelock_status(&identity, None), (true, None));
+        // Other flags don't hide the lock
+        let identity = json!({ "flags": IDENTITY_FLAG_LOCKED | 0x1, "timelock": 0 });
+        assert_eq!(timelock_status(&identity, Some(1000)), (true, Some(1000)));
+    }
+
+    #[test]
+    fn locked_identity_with_huge_delay_saturates() {
+        let identity = json!({ "flags": IDENTITY_FLAG_LOCKED, "timelock": u64::MAX });
+        assert_eq!(timelock_status(&identity, Some(1000)), (true, Some(u64::MAX)));
+    }
+
+    #[test]
+    fn unlocking_identity_reports_end_height_until_passed() {
+        let identity = json!({ "flags": 0, "timelock": 5000 });
+        assert!(has_timelock(&identity));
+        assert_eq!(timelock_status(&identity, Some(4999)), (false, Some(5000)));
+        assert_eq!(timelock_status(&identity, Some(5000)), (false, None));
+        assert_eq!(timelock_status(&identity, Some(6000)), (false, None));
+        assert_eq!(timelock_status(&identity, None), (false, None));
+    }
+}
@@ -0,0 +1,127 @@
+// File: src-tauri/src/identity_watcher.rs
+// Description: Notices identities appearing in (or disappearing from) the wallet while the app is open.
+// Changes:
+// - Created file. listidentities is filtered by the login rules periodically and compared with the previous pass:
+//   identities that became eligible (newly registered, imported, or a private address was just attached) and ones
+//   that no longer are produce a wallet-identities-changed event, so the identity picker updates by itself. Only
+//   new identities get a name lookup.
+// - Passes run on new blocks (ZMQ) while the listener is connected, every WATCH_INTERVAL otherwise
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use super::events::{emit_event, WALLET_IDENTITIES_CHANGED_EVENT};
+use super::identity_rpc::{list_eligible_identities, resolve_identity_address, EligibleIdentity};
+use super::settings::{read_identity_filter_rules, IdentityFilterRules};
+use super::zmq_listener::{wait_for_next_poll, PushTrigger};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2 * 60);
+
+// RPC port -> generation of the running watcher; bumping it stops the old one
+static WATCHERS: LazyLock<Mutex<HashMap<u16, u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AddedIdentity {
+    pub formatted_name: String,
+    #[serde(flatten)]
+    pub identity: EligibleIdentity,
+}
+
+// Payload of the wallet-identities-changed event
+#[derive(Serialize, Debug, Clone)]
+pub struct WalletIdentitiesChanged {
+    pub added: Vec<AddedIdentity>,
+    pub removed: Vec<String>, // i-addresses no longer eligible
+    pub changed: Vec<EligibleIdentity>, // Still eligible, but access, revocation, lock or private address changed
+}
+
+// Locked identities report a timelocked_until that moves with every block; that alone isn't a change
+fn same_state(previous: &EligibleIdentity, current: &EligibleIdentity) -> bool {
+    previous.private_address == current.private_address
+        && previous.read_only == current.read_only
+        && previous.revoked == current.revoked
+        && previous.locked == current.locked
+        && (current.locked || previous.timelocked_until == current.timelocked_until)
+}
+
+// Compare two passes (by i-address)
+fn diff(previous: &[EligibleIdentity], current: &[EligibleIdentity]) -> (Vec<EligibleIdentity>, Vec<String>, Vec<EligibleIdentity>) {
+    let previous_by_address: HashMap<&str, &EligibleIdentity> = previous.iter().map(|i| (i.i_address.as_str(), i)).collect();
+    let current_by_address: HashMap<&str, &EligibleIdentity> = current.iter().map(|i| (i.i_address.as_str(), i)).collect();
+    let added = current.iter().filter(|i| !previous_by_address.contains_key(i.i_address.as_str())).cloned().collect();
+    let removed = previous
+        .iter()
+        .filter(|i| !current_by_address.contains_key(i.i_address.as_str()))
+        .map(|i| i.i_address.clone())
+        .collect();
+    let changed = current
+        .iter()
+        .filter(|i| previous_by_address.get(i.i_address.as_str()).is_some_and(|p| !same_state(p, i)))
+        .cloned()
+        .collect();
+    (added, removed, changed)
+}
+
+// Watch the wallet's identities on a port. The first pass only records the baseline. Starting again replaces a
+// running watcher.
+pub fn start_identity_watcher<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16) {
+    let generation = {
+        let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = watchers.get(&rpc_port).copied().unwrap_or(0) + 1;
+        watchers.insert(rpc_port, generation);
+        generation
+    };
+    let is_current = move || WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).get(&rpc_port) == Some(&generation);
+    log::info!("Starting wallet identity watcher for port {}", rpc_port);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Baseline and the rules it was filtered with
+        let mut known: Option<(IdentityFilterRules, Vec<EligibleIdentity>)> = None;
+        while is_current() {
+            let rules = read_identity_filter_rules(&app).unwrap_or_default();
+            match list_eligible_identities(&rpc_user, &rpc_pass, rpc_port, &rules).await {
+                Ok(current) => {
+                    // Changed rules start a new baseline (the picker reloads with them anyway)
+                    if let Some((_, previous)) = known.as_ref().filter(|(known_rules, _)| *known_rules == rules) {
+                        let (added, removed, changed) = diff(previous, &current);
+                        if !added.is_empty() || !removed.is_empty() || !changed.is_empty() {
+                            let mut added_identities = Vec::new();
+                            for identity in added {
+                                let formatted_name = resolve_identity_address(&rpc_user, &rpc_pass, rpc_port, &identity.i_address).await;
+                                added_identities.push(AddedIdentity { formatted_name, identity });
+                            }
+                            log::info!(
+                                "Wallet identities changed: {} added, {} removed, {} changed",
+                                added_identities.len(), removed.len(), changed.len()
+                            );
+                            emit_event(&app, WALLET_IDENTITIES_CHANGED_EVENT, WalletIdentitiesChanged { added: added_identities, removed, changed });
+                        }
+                    }
+                    known = Some((rules, current));
+                }
+                Err(e) => log::debug!("Wallet identity watcher: listidentities failed: {:?}", e),
+            }
+            wait_for_next_poll(rpc_port, WATCH_INTERVAL, PushTrigger::Block).await;
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+// Stop watching the wallet's identities on one port, or on all ports
+#[tauri::command]
+pub fn stop_identity_watcher(rpc_port: Option<u16>) {
+    log::info!("stop_identity_watcher command received");
+    let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+    match rpc_port {
+        Some(port) => {
+            if let Some(generation) = watchers.get_mut(&port) {
+                *generation += 1;
+            }
+        }
+        None => watchers.values_mut().for_each(|generation| *generation += 1),
+    }
+}
@@ -0,0 +1,303 @@
+// File: src-tauri/src/integrity.rs
+// Description: Audit of locally persisted messages against the chain.
+// Changes:
+// - Created file with run_integrity_audit: samples persisted messages, re-verifies signatures and
+//   on-chain contents, and reports tampering or drift.
+// - The audit also runs once per identity after startup (armed in the setup hook, started when the identity's
+//   session opens, report emitted as integrity-report); get/set_startup_integrity_audit turn it off and on.
+// - The sample seed comes from clock::now_nanos.
+// - Uses the shared message_rpc::is_txid.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::capabilities::{supports, Feature};
+use super::credentials::load_credentials;
+use super::events::{emit_event, INTEGRITY_REPORT_EVENT};
+use super::memo_codec::decode_memo;
+use super::message_rpc::{is_txid, parse_and_verify_message};
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::settings::{read_conversation_messages, read_conversations, ChatMessage, SettingsError};
+use super::storage::{load_value, save_value, StorageError};
+use super::verification_cache::VerificationCache;
+use super::clock::now_nanos;
+
+const AUDIT_STORE_PATH: &str = "store.json";
+const STARTUP_AUDIT_KEY: &str = "startup_integrity_audit";
+
+// Default number of messages checked per audit
+pub const DEFAULT_AUDIT_SAMPLE_SIZE: usize = 25;
+
+// Amount differences below this are rounding noise
+const AMOUNT_EPSILON: f64 = 0.000_000_01;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    MissingOnChain,    // The wallet doesn't know the transaction (expired, reorged or fabricated)
+    SignatureInvalid,  // The on-chain memo no longer verifies
+    ContentMismatch,   // Persisted text / sender / timestamp differ from the chain
+    AmountMismatch,    // Persisted amount differs from the chain
+    Unverifiable,      // The check itself failed (daemon error)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntegrityIssue {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub kind: IntegrityIssueKind,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IntegrityReport {
+    pub identity_i_address: String,
+    pub total_messages: usize,
+    pub sampled: usize,
+    pub passed: usize,
+    pub skipped: usize, // Unsent / failed messages without a txid
+    pub issues: Vec<IntegrityIssue>,
+    pub duration_ms: u64,
+}
+
+// A persisted message together with the conversation it belongs to
+pub struct AuditCandidate {
+    pub conversation_id: String,
+    pub message: ChatMessage,
+}
+
+// Startup audit: armed by the setup hook when enabled, then run once per identity when its session opens (the
+// receiving address needed to verify recipient-bound memos is only known then)
+#[derive(Default)]
+struct StartupAudit {
+    armed: bool,
+    audited: HashSet<String>,
+}
+
+static STARTUP_AUDIT: LazyLock<Mutex<StartupAudit>> = LazyLock::new(|| Mutex::new(StartupAudit::default()));
+
+// Every persisted message of an identity
+pub fn persisted_candidates<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Result<Vec<AuditCandidate>, SettingsError> {
+    let mut candidates = Vec::new();
+    for conversation in read_conversations(app, identity_i_address)? {
+        for message in read_conversation_messages(app, identity_i_address, &conversation.id)? {
+            candidates.push(AuditCandidate { conversation_id: conversation.id.clone(), message });
+        }
+    }
+    Ok(candidates)
+}
+
+// Pick a pseudo-random sample (ordering by a seeded hash of the message id)
+fn sample(mut candidates: Vec<AuditCandidate>, sample_size: usize) -> Vec<AuditCandidate> {
+    let seed = now_nanos().to_le_bytes();
+    candidates.sort_by_cached_key(|c| {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(c.message.id.as_bytes());
+        hasher.finalize().to_vec()
+    });
+    candidates.truncate(sample_size);
+    candidates
+}
+
+// Check one persisted message against the chain. Returns None if it passed.
+async fn audit_message(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    own_private_address: &str,
+    candidate: &AuditCandidate,
+) -> Option<IntegrityIssue> {
+    let message = &candidate.message;
+    let issue = |kind: IntegrityIssueKind, detail: String| {
+        Some(IntegrityIssue {
+            message_id: message.id.clone(),
+            conversation_id: candidate.conversation_id.clone(),
+            kind,
+            detail,
+        })
+    };
+
+    let view: Value = match make_rpc_call(rpc_user, rpc_pass, rpc_port, "z_viewtransaction", vec![json!(message.id)]).await {
+        Ok(view) => view,
+        Err(VerusRpcError::Rpc { code: -5, message: error }) => {
+            return issue(IntegrityIssueKind::MissingOnChain, error);
+        }
+        Err(e) => return issue(IntegrityIssueKind::Unverifiable, e.to_string()),
+    };
+
+    // Received messages are outputs to our address; sent ones are our outgoing outputs to someone else
+    let sent = message.direction == "sent";
+    let outputs = view.get("outputs").and_then(|o| o.as_array()).cloned().unwrap_or_default();
+    let matching_outputs = outputs.iter().filter(|o| {
+        let outgoing = o.get("outgoing").and_then(|v| v.as_bool()).unwrap_or(false);
+        let to_us = o.get("address").and_then(|v| v.as_str()) == Some(own_private_address);
+        if sent { outgoing && !to_us } else { !outgoing && to_us }
+    });
+
+    // A fresh cache forces every signature to be re-verified
+    let fresh_cache = VerificationCache::default();
+    let mut last_problem = None;
+    for output in matching_outputs {
+        let Some(address) = output.get("address").and_then(|v| v.as_str()) else { continue };
+        let Some(memo) = decode_memo(
+            output.get("memoStr").and_then(|v| v.as_str()),
+            output.get("memo").and_then(|v| v.as_str()),
+        ) else {
+            continue;
+        };
+
+        let Some(parsed) = parse_and_verify_message(rpc_user, rpc_pass, rpc_port, &memo, &message.id, address, &fresh_cache).await else {
+            last_problem = Some((IntegrityIssueKind::SignatureInvalid, "On-chain memo failed signature verification".to_string()));
+            continue;
+        };
+
+        // Sent messages store the sender as our identity and a local timestamp, so only the text is compared
+        if parsed.text != message.text.trim() {
+            last_problem = Some((IntegrityIssueKind::ContentMismatch, "Persisted text differs from the on-chain memo".to_string()));
+            continue;
+        }
+        if !sent && (parsed.sender_id != message.sender || parsed.timestamp != message.timestamp) {
+            last_problem = Some((IntegrityIssueKind::ContentMismatch, format!(
+                "Persisted sender/timestamp ({}, {}) differ from the chain ({}, {})",
+                message.sender, message.timestamp, parsed.sender_id, parsed.timestamp
+            )));
+            continue;
+        }
+        let chain_amount = output.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0);
+        if (chain_amount - message.amount).abs() > AMOUNT_EPSILON {
+            last_problem = Some((IntegrityIssueKind::AmountMismatch, format!(
+                "Persisted amount {} differs from the chain amount {}", message.amount, chain_amount
+            )));
+            continue;
+        }
+        return None;
+    }
+
+    let (kind, detail) = last_problem.unwrap_or((
+        IntegrityIssueKind::ContentMismatch,
+        "No matching memo output found in the transaction".to_string(),
+    ));
+    issue(kind, detail)
+}
+
+pub async fn run_integrity_audit(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    identity_i_address: String,
+    own_private_address: String,
+    candidates: Vec<AuditCandidate>,
+    sample_size: usize,
+) -> Result<IntegrityReport, VerusRpcError> {
+    let start_time = std::time::Instant::now();
+    let total_messages = candidates.len();
+    log::info!("Starting integrity audit for {} ({} persisted messages, sample size {})", identity_i_address, total_messages, sample_size);
+
+    let (auditable, unsent): (Vec<AuditCandidate>, Vec<AuditCandidate>) =
+        candidates.into_iter().partition(|c| is_txid(&c.message.id));
+    let sampled = sample(auditable, sample_size);
+
+    let mut issues = Vec::new();
+    for candidate in &sampled {
+        if let Some(issue) = audit_message(&rpc_user, &rpc_pass, rpc_port, &own_private_address, candidate).await {
+            log::warn!("Integrity issue in message {} ({}): {:?} - {}", issue.message_id, issue.conversation_id, issue.kind, issue.detail);
+            issues.push(issue);
+        }
+    }
+
+    let report = IntegrityReport {
+        identity_i_address,
+        total_messages,
+        sampled: sampled.len(),
+        passed: sampled.len() - issues.len(),
+        skipped: unsent.len(),
+        issues,
+        duration_ms: start_time.elapsed().as_millis() as u64,
+    };
+    log::info!("Integrity audit finished: {}/{} passed, {} issues", report.passed, report.sampled, report.issues.len());
+    Ok(report)
+}
+
+// Enabled unless turned off
+fn startup_audit_enabled<R: Runtime>(app: &AppHandle<R>) -> bool {
+    match load_value::<R, bool>(app, AUDIT_STORE_PATH, STARTUP_AUDIT_KEY) {
+        Ok(enabled) => enabled.unwrap_or(true),
+        Err(e) => {
+            log::warn!("Failed to load the startup audit setting: {}", e);
+            true
+        }
+    }
+}
+
+// Called from the setup hook
+pub fn arm_startup_audit<R: Runtime>(app: &AppHandle<R>) {
+    let enabled = startup_audit_enabled(app);
+    log::debug!("Startup integrity audit {}", if enabled { "armed" } else { "disabled" });
+    STARTUP_AUDIT.lock().unwrap_or_else(|e| e.into_inner()).armed = enabled;
+}
+
+// Audit an identity's persisted messages in the background, the first time its session opens after startup.
+// The report is emitted as an integrity-report event.
+pub fn run_startup_audit_once<R: Runtime>(app: &AppHandle<R>, identity_i_address: String, own_private_address: String) {
+    {
+        let mut audit = STARTUP_AUDIT.lock().unwrap_or_else(|e| e.into_inner());
+        if !audit.armed || !audit.audited.insert(identity_i_address.clone()) {
+            return;
+        }
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let creds = match load_credentials(app.clone()).await {
+            Ok(creds) => creds,
+            Err(e) => {
+                log::warn!("Startup integrity audit skipped, no credentials: {}", e);
+                return;
+            }
+        };
+        if !supports(creds.rpc_port, Feature::ViewTransaction) {
+            log::debug!("Startup integrity audit skipped: the daemon can't view shielded transactions");
+            return;
+        }
+        let candidates = match persisted_candidates(&app, &identity_i_address) {
+            Ok(candidates) if !candidates.is_empty() => candidates,
+            Ok(_) => return,
+            Err(e) => {
+                log::warn!("Startup integrity audit skipped, persisted messages unreadable: {}", e);
+                return;
+            }
+        };
+        match run_integrity_audit(
+            creds.rpc_user,
+            creds.rpc_pass,
+            creds.rpc_port,
+            identity_i_address,
+            own_private_address,
+            candidates,
+            DEFAULT_AUDIT_SAMPLE_SIZE,
+        )
+        .await
+        {
+            Ok(report) => emit_event(&app, INTEGRITY_REPORT_EVENT, report),
+            Err(e) => log::warn!("Startup integrity audit failed: {:?}", e),
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_startup_integrity_audit<R: Runtime>(app: AppHandle<R>) -> bool {
+    log::debug!("get_startup_integrity_audit command received");
+    startup_audit_enabled(&app)
+}
+
+// Takes effect at the next start
+#[tauri::command]
+pub fn set_startup_integrity_audit<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<(), StorageError> {
+    log::info!("set_startup_integrity_audit command received: {}", enabled);
+    save_value(&app, AUDIT_STORE_PATH, STARTUP_AUDIT_KEY, &enabled)
+}
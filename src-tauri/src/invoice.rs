@@ -0,0 +1,290 @@
+// File: src-tauri/src/invoice.rs
+// Description: VerusPay invoices (payment requests) that can be pasted or scanned into a gift send.
+// Changes:
+// - Created file. Invoices use the VerusPay QR payload of Verus Mobile (JSON with verusQR version, coin ticker,
+//   address, amount in satoshis and note). Created invoices name the requesting identity as recipient; parsed ones
+//   are resolved against the connected chain (identity -> private address, z-/R-address validated) so the gift
+//   send can be pre-filled.
+// - Tests for decode_invoice (amount forms, versions, malformed and invalid fields, round trip with create_invoice).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use super::address::{validate_recipient_address, validate_transparent_address};
+use super::identity_rpc::{check_identity_eligibility, FormattedIdentity};
+use super::memo_codec::MEMO_MAX_BYTES;
+use super::rpc_client::VerusRpcError;
+
+pub const VERUSPAY_VERSION: &str = "0.1.0";
+
+const SATOSHIS_PER_COIN: f64 = 100_000_000.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InvoiceRequest {
+    pub recipient: String,        // VerusID (name@ or i-address), z-address or R-address
+    pub amount: Option<f64>,      // None: the payer chooses
+    pub currency: Option<String>, // Defaults to the connected chain's currency
+    pub memo: Option<String>,
+}
+
+// Wire format (field names as Verus Mobile writes them)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct VerusPayPayload {
+    #[serde(rename = "verusQR")]
+    verus_qr: String,
+    #[serde(rename = "coinTicker")]
+    coin_ticker: String,
+    address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    amount: Option<Value>, // Satoshis; Verus Mobile writes a string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Invoice {
+    pub payload: String, // JSON to show as QR code or copy
+    pub recipient: String,
+    pub amount: Option<f64>,
+    pub currency: String,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipientKind {
+    Identity,
+    Private,     // z-address: a regular (memo) gift
+    Transparent, // R-address: an amount-only gift
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ParsedInvoice {
+    pub recipient: String,
+    pub recipient_kind: RecipientKind,
+    pub recipient_identity: Option<FormattedIdentity>, // Resolved identity (recipient_kind Identity)
+    pub send_address: String,                          // Address the gift goes to
+    pub amount: Option<f64>,
+    pub currency: String,
+    pub memo: Option<String>,
+    pub currency_matches_chain: bool, // False: the invoice asks for another chain's currency (the UI should warn)
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum InvoiceError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Not a VerusPay invoice: {0}")]
+    Malformed(String),
+    #[error("Unsupported VerusPay invoice version {0}")]
+    UnsupportedVersion(String),
+    #[error("Invalid invoice field: {0}")]
+    InvalidField(String),
+}
+
+impl From<VerusRpcError> for InvoiceError {
+    fn from(error: VerusRpcError) -> Self {
+        InvoiceError::Rpc(error)
+    }
+}
+
+fn recipient_kind(recipient: &str) -> RecipientKind {
+    if recipient.starts_with("zs1") || recipient.starts_with("ztestsapling1") || recipient.starts_with("zregtestsapling1") {
+        RecipientKind::Private
+    } else if recipient.starts_with('R') && !recipient.ends_with('@') {
+        RecipientKind::Transparent
+    } else {
+        RecipientKind::Identity
+    }
+}
+
+fn validate_amount(amount: Option<f64>) -> Result<(), InvoiceError> {
+    match amount {
+        Some(amount) if !amount.is_finite() || amount <= 0.0 => Err(InvoiceError::InvalidField(format!("amount {} must be above 0", amount))),
+        _ => Ok(()),
+    }
+}
+
+fn validate_memo(memo: Option<&str>) -> Result<(), InvoiceError> {
+    match memo {
+        Some(memo) if memo.len() > MEMO_MAX_BYTES => Err(InvoiceError::InvalidField(format!("memo is longer than {} bytes", MEMO_MAX_BYTES))),
+        _ => Ok(()),
+    }
+}
+
+// Build an invoice payload. default_currency is the connected chain's currency.
+pub fn create_invoice(request: InvoiceRequest, default_currency: &str) -> Result<Invoice, InvoiceError> {
+    let recipient = request.recipient.trim().to_string();
+    if recipient.is_empty() {
+        return Err(InvoiceError::InvalidField("recipient is missing".to_string()));
+    }
+    validate_amount(request.amount)?;
+    let memo = request.memo.map(|memo| memo.trim().to_string()).filter(|memo| !memo.is_empty());
+    validate_memo(memo.as_deref())?;
+    let currency = request
+        .currency
+        .map(|currency| currency.trim().to_string())
+        .filter(|currency| !currency.is_empty())
+        .unwrap_or_else(|| default_currency.to_string());
+
+    let payload = VerusPayPayload {
+        verus_qr: VERUSPAY_VERSION.to_string(),
+        coin_ticker: currency.clone(),
+        address: recipient.clone(),
+        amount: request.amount.map(|amount| Value::String(((amount * SATOSHIS_PER_COIN).round() as u64).to_string())),
+        note: memo.clone(),
+    };
+    let payload = serde_json::to_string(&payload).map_err(|e| InvoiceError::Malformed(e.to_string()))?;
+    Ok(Invoice { payload, recipient, amount: request.amount, currency, memo })
+}
+
+// Read an invoice payload without checking it against the chain: (recipient, amount, currency, memo)
+pub fn decode_invoice(payload: &str) -> Result<(String, Option<f64>, String, Option<String>), InvoiceError> {
+    let payload: VerusPayPayload = serde_json::from_str(payload.trim()).map_err(|e| InvoiceError::Malformed(e.to_string()))?;
+    // Same major version only; newer minor versions only add fields
+    if payload.verus_qr.split('.').next() != VERUSPAY_VERSION.split('.').next() {
+        return Err(InvoiceError::UnsupportedVersion(payload.verus_qr));
+    }
+    let recipient = payload.address.trim().to_string();
+    if recipient.is_empty() {
+        return Err(InvoiceError::InvalidField("recipient is missing".to_string()));
+    }
+    let satoshis = match &payload.amount {
+        None => None,
+        Some(Value::String(s)) if s.trim().is_empty() => None,
+        Some(Value::String(s)) => Some(s.trim().parse::<u64>().map_err(|_| InvoiceError::InvalidField(format!("amount '{}'", s)))?),
+        Some(Value::Number(n)) => Some(n.as_u64().ok_or_else(|| InvoiceError::InvalidField(format!("amount {}", n)))?),
+        Some(other) => return Err(InvoiceError::InvalidField(format!("amount {}", other))),
+    };
+    let amount = satoshis.filter(|s| *s > 0).map(|s| s as f64 / SATOSHIS_PER_COIN);
+    let memo = payload.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+    validate_memo(memo.as_deref())?;
+    Ok((recipient, amount, payload.coin_ticker.trim().to_string(), memo))
+}
+
+// Decode an invoice and resolve its recipient on the connected chain
+pub async fn parse_invoice(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    payload: &str,
+    chain_currency: &str,
+) -> Result<ParsedInvoice, InvoiceError> {
+    let (recipient, amount, currency, memo) = decode_invoice(payload)?;
+    let kind = recipient_kind(&recipient);
+    let (recipient_identity, send_address) = match kind {
+        RecipientKind::Identity => {
+            let identity = check_identity_eligibility(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, recipient.clone()).await?;
+            let send_address = identity.private_address.clone();
+            (Some(identity), send_address)
+        }
+        RecipientKind::Private => {
+            validate_recipient_address(rpc_user, rpc_pass, rpc_port, &recipient).await?;
+            (None, recipient.clone())
+        }
+        RecipientKind::Transparent => {
+            validate_transparent_address(rpc_user, rpc_pass, rpc_port, &recipient).await?;
+            (None, recipient.clone())
+        }
+    };
+    log::info!("Parsed invoice for {} ({:?}): {:?} {}", recipient, kind, amount, currency);
+    Ok(ParsedInvoice {
+        recipient,
+        recipient_kind: kind,
+        recipient_identity,
+        send_address,
+        amount,
+        currency_matches_chain: currency.eq_ignore_ascii_case(chain_currency),
+        currency,
+        memo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_verus_mobile_payload() {
+        let payload = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":"150000000","note":"lunch"}"#;
+        let (recipient, amount, currency, memo) = decode_invoice(payload).unwrap();
+        assert_eq!(recipient, "alice@");
+        assert_eq!(amount, Some(1.5));
+        assert_eq!(currency, "VRSC");
+        assert_eq!(memo.as_deref(), Some("lunch"));
+    }
+
+    #[test]
+    fn accepts_numeric_and_missing_amounts() {
+        let numeric = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":25000000}"#;
+        assert_eq!(decode_invoice(numeric).unwrap().1, Some(0.25));
+        let missing = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@"}"#;
+        assert_eq!(decode_invoice(missing).unwrap().1, None);
+        // Zero and empty amounts leave the amount to the payer
+        let zero = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":"0"}"#;
+        assert_eq!(decode_invoice(zero).unwrap().1, None);
+        let empty = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":" "}"#;
+        assert_eq!(decode_invoice(empty).unwrap().1, None);
+    }
+
+    #[test]
+    fn trims_fields_and_drops_blank_note() {
+        let payload = r#"  {"verusQR":"0.1.0","coinTicker":" VRSC ","address":" alice@ ","note":"   "}  "#;
+        let (recipient, _, currency, memo) = decode_invoice(payload).unwrap();
+        assert_eq!(recipient, "alice@");
+        assert_eq!(currency, "VRSC");
+        assert_eq!(memo, None);
+    }
+
+    #[test]
+    fn accepts_newer_minor_version() {
+        let payload = r#"{"verusQR":"0.2.5","coinTicker":"VRSC","address":"alice@","extra":true}"#;
+        assert!(decode_invoice(payload).is_ok());
+    }
+
+    #[test]
+    fn rejects_other_major_version() {
+        let payload = r#"{"verusQR":"1.0.0","coinTicker":"VRSC","address":"alice@"}"#;
+        assert!(matches!(decode_invoice(payload), Err(InvoiceError::UnsupportedVersion(v)) if v == "1.0.0"));
+    }
+
+    #[test]
+    fn rejects_malformed_payloads() {
+        assert!(matches!(decode_invoice("not json"), Err(InvoiceError::Malformed(_))));
+        assert!(matches!(decode_invoice(r#"{"verusQR":"0.1.0","address":"alice@"}"#), Err(InvoiceError::Malformed(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_fields() {
+        let blank_address = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"  "}"#;
+        assert!(matches!(decode_invoice(blank_address), Err(InvoiceError::InvalidField(_))));
+        let text_amount = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":"1.5"}"#;
+        assert!(matches!(decode_invoice(text_amount), Err(InvoiceError::InvalidField(_))));
+        let negative_amount = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":-5}"#;
+        assert!(matches!(decode_invoice(negative_amount), Err(InvoiceError::InvalidField(_))));
+        let bool_amount = r#"{"verusQR":"0.1.0","coinTicker":"VRSC","address":"alice@","amount":true}"#;
+        assert!(matches!(decode_invoice(bool_amount), Err(InvoiceError::InvalidField(_))));
+        let long_note = serde_json::json!({
+            "verusQR": "0.1.0",
+            "coinTicker": "VRSC",
+            "address": "alice@",
+            "note": "x".repeat(MEMO_MAX_BYTES + 1),
+        });
+        assert!(matches!(decode_invoice(&long_note.to_string()), Err(InvoiceError::InvalidField(_))));
+    }
+
+    #[test]
+    fn created_invoice_decodes_back() {
+        let request = InvoiceRequest {
+            recipient: " alice@ ".to_string(),
+            amount: Some(0.1),
+            currency: None,
+            memo: Some(" thanks ".to_string()),
+        };
+        let invoice = create_invoice(request, "VRSCTEST").unwrap();
+        let (recipient, amount, currency, memo) = decode_invoice(&invoice.payload).unwrap();
+        assert_eq!(recipient, "alice@");
+        assert_eq!(amount, Some(0.1));
+        assert_eq!(currency, "VRSCTEST");
+        assert_eq!(memo.as_deref(), Some("thanks"));
+    }
+}
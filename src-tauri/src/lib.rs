@@ -20,23 +20,228 @@
 // - Added macOS window customization with almost black background for native titlebar appearance
 // - Added get_utxo_info command for Fast Messages feature
 // - Added progressive loading commands: get_login_identities_fast, get_identity_balance
+// - Added `pub mod memo_codec;` for memo compression/decompression
+// - Added formatting module with format_timestamp and format_amount commands
+// - Added protocol module with get_protocol_info and get_peer_protocol_version commands
+// - Added events and tasks modules; identity loading, balance refresh and history load accept an optional task_id
+// - Added cancel_task command and TaskRegistry managed state
+// - Added `pub mod proof_rpc;` with create_payment_proof and verify_payment_proof commands
+// - Added get_daemon_load_status command exposing work-queue backoff state
+// - Added storage and verification_cache modules; history and polling reuse persisted verification results
+// - Added notifications module; polling runs new messages through the mute-aware notification pipeline
+// - Added save_conversation_mute / load_conversation_mute settings commands
+// - get_new_received_messages polls incrementally using the per-address SyncCursor; added reset_sync_cursor command
+// - Added onboarding module with get_onboarding_state / advance_onboarding commands
+// - Added price and message_index modules; polled gifts are annotated with the conversion rate at ingestion
+// - Added get_conversion_rate and backfill_gift_rates commands
+// - Added message_store module; history, polling and sent messages are merged into canonical per-conversation lists
+// - Login identity commands apply the configured IdentityFilterRules; added save/load_identity_filter_rules
+// - Added recover_sent_messages command to rebuild the sent side of conversations from the wallet
+// - Added wallet encryption commands: get_wallet_encryption_status, encrypt_wallet, unlock_wallet, lock_wallet
+// - Added integrity module and run_integrity_audit command (persisted messages vs. chain)
+// - Added save_draft / load_draft settings commands
+// - Added network_rpc module and preview_send command (fee + mempool-aware confirmation estimate)
+// - Added prefetch module and open_conversation command (background, cancellable conversation prefetching)
+// - Added export module with export_conversation command (JSON / CSV)
+// - Added file_request module: send_file_request / send_file_response, get_file_requests and save_file_attachment;
+//   history and polling record file protocol memos in the message index
+// - Added import_conversation command (JSON archive import with optional signature re-verification)
+// - Capabilities of the connected chain are probed in connect_verus_daemon (capabilities module); commands that need
+//   z_viewtransaction or conversions fail fast on chains without them. Added get_chain_capabilities command
+// - Added spam module: polling applies the identity's SpamRules and holds filtered messages as message requests;
+//   added save/load_spam_rules and get/accept/dismiss_message_request commands
+// - Added chain_profiles module: signed community chain profile bundles are loaded at startup and extend blockchain detection.
+// - Added activity module with get_conversation_activity (per-day / per-week timeline from the local message store).
+// - Added blocklist module: block/unblock commands; blocked senders are dropped before signature verification.
+// - Registered get_unknown_sender_requests (contacts-only mode).
+// - Added list_notes command and notes module (note reservations for fast messages vs. savings).
+// - Added gift_ack module: acknowledge_gift command; received acks are attached to the original gift (get_gift_acks).
+// - Polling creates conversations for unknown senders whose gifts exceed the auto-accept threshold.
+// - Added error_log module: panic hook and CommandError conversions record redacted entries in crash.log (get_recent_errors).
+// - Registered archive_conversation / unarchive_conversation.
+// - Added api_version module: api_handshake command and legacy_* shims serving older frontend bundles.
+// - Added clock module: clock skew check against chain time on connect and before sends, optional timestamp correction.
+// - Registered set_last_read and the legacy_load_messages_for_conversation shim (command API v3).
+// - Added identity_cache module: warm_identity_cache command batch-resolves conversation partners after login.
+// - Registered mark_all_read.
+// - Added wipe module: guarded emergency_wipe command (overwrite and delete all local data, optional wallet lock, exit).
+// - send_private_message and recover_sent_messages record the fee and size of sent transactions (message index and ChatMessage).
+// - Added voice_memo module: send_voice_memo / get_voice_memo_audio (opus clips over the chunked memo transport).
+// - Added verify_queue module: polling verifies recent conversations first and queues a first-sync backlog for background verification.
+// - Added polls module: create_poll / vote_in_poll commands; tallies are returned by load_messages_for_conversation.
+// - Registered get_notification_preview / set_notification_preview.
+// - Added presence module: get_contact_presence (last activity per contact from the message store).
+// - recover_sent_messages creates conversations for recipients of outgoing memos sent from other clients (identified via the identity cache and held message request senders).
+// - Added estimate_send_fee command (expected fee, change and notes of a planned message/gift).
+// - send_private_message accepts an optional fee or fee preset (validated, passed to z_sendmany).
+// - Added external_signer module: prepare_external_send / complete_external_send / cancel_external_send (signature imported from an offline signer).
+// - Added address module (chain-aware recipient address validation) and validate_recipient_address command.
+// - Added utxo_maintenance module: Fast Messages capacity is restored by splitting a large note (after polls and via run_utxo_maintenance).
+// - Registered merge_conversations (message store).
+// - Added shielding module: shield_funds (transparent and coinbase funds into the private address) and get_wallet_operation_status.
+// - Added send_transparent_gift command (amount-only gift to an R-address, recorded as a transparent_gift message).
+// - Added get_currency_balances and get_identity_currency_balances commands (all currencies at an address as a map).
+// - Added send_currency_gift command (signed message with an amount of a PBaaS currency, recorded with its currency).
+// - Added get_wallet_security_status command (encryption, unlock time and key availability for the login flow).
+// - Added get_balance_summary command (transparent, private and unconfirmed totals in one call).
+// - Added create_private_address command (z_getnewaddress during onboarding, with guidance for attaching it to a VerusID).
+// - Added get_transaction_details command (transaction_rpc module).
+// - Added get_sync_status and start_sync_watcher commands (sync_status module, sync-progress events).
+// - Added get_network_status command (peer count, protocol version, relay fee; warns when there are no peers).
+// - Added rescan_wallet command (rescan module, wallet-rescan events).
+// - Added suggest_utxo_cleanup command (dust consolidation plan).
+// - Added estimate_conversion command (preview of a currency conversion before gifting).
+// - Added send_conversion_gift command (conversion module, conversion-gift events) and CommandError::Conversion.
+// - Registered get_gift_summary (gift_ledger module).
+// - Polling also backfills missing network fees of sent messages; added refresh_sent_fees and registered get_fee_summary.
+// - Registered balance alert settings (balance_alerts module); polling also checks the private balance against them.
+// - Registered automatic top-up settings and history (auto_topup module); polling also applies the top-up rule.
+// - Added VerusID registration commands (identity_registration module, identity-registration events) and CommandError::IdentityRegistration.
+// - Added sub-identity commands (preview_sub_identity_registration, register_sub_identity) on the registration flow.
+// - Added get_identity_profile command (profile module: display name, avatar, bio and website from the identity's content maps).
+// - Added preview_profile_publish / publish_profile commands (own profile via updateidentity) and CommandError::Profile.
+// - Sends (messages, gifts, voice memos, file requests, polls) are refused to revoked identities unless allow_revoked is set.
+// - warm_identity_cache also starts the contact watcher (contact_watcher module); added start_contact_watcher and registered stop_contact_watcher.
+// - getidentity lookups are cached (identity_rpc TTL cache, loaded at startup); registered get/set_identity_lookup_ttl.
+// - Added resolve_identity_address and resolve_identity_addresses commands (i-address -> formatted name).
+// - Added sessions module (several identities logged in at once): open_identity_session command; the polling pipeline moved into poll_received_messages, shared with the background pollers of inactive sessions.
+// - Added invoice module: create_invoice and parse_invoice commands (VerusPay invoice payloads).
+// - Added currency module (currency metadata from getcurrency): get_currency_info and resolve_currency_names commands.
+// - Added identity_watcher module: get_login_identities_fast starts the wallet identity watcher (wallet-identities-changed events).
+// - check_identity_eligibility command uses identity_rpc::check_chat_eligibility (refuses revoked and timelocked identities with distinct errors).
+// - Added rpc_timeouts module (per-method RPC timeouts, overrides loaded at startup): get_rpc_timeouts and set_rpc_timeout commands.
+// - Added ssh_tunnel module (optional SSH tunnel to a remote daemon, config loaded at startup): get_ssh_tunnel, set_ssh_tunnel and restart_ssh_tunnel commands; the app is built and run with an exit handler that closes the tunnel.
+// - Added rpc_diagnostics module (per-method call counts, errors and latency recorded by rpc_client): get_rpc_diagnostics and reset_rpc_diagnostics commands.
+// - RPC concurrency limit (rpc_client semaphore) loaded at startup; registered get/set_rpc_concurrency_limit.
+// - Added response_cache module (idempotent reads cached until the next block).
+// - Added zmq_listener module (push notifications from the daemon's ZMQ publisher): start_zmq_listener command, get_zmq_status and stop_zmq_listener.
+// - Registered get_last_read and get_poll_tallies (load_messages_for_conversation returns a message list again).
+// - Gift acks are ingested after the other memo payloads with an RPC check that the gift was ours.
+// - Polled messages are stored before the sync cursor is written; a store error keeps the cursor (and skips notifications).
+// - get_chat_history leaves out messages held as message requests.
+// - Unregistered the legacy_* shims (older frontends are served by the original commands).
+// - run_utxo_maintenance goes through utxo_maintenance::run_maintenance_now (shared running guard).
+// - Conversations created from recovered outgoing memos are added with settings::update_conversations.
+// - CommandError is recorded in the error log when a command returns it, not in the From conversions.
+// - get_wallet_encryption_status takes the optional identity and reports the login-flow fields; removed the
+//   duplicate get_wallet_security_status command.
+// - Unregistered get/set_identity_lookup_ttl (getidentity is only cached per block by the response cache).
+// - send_transparent_gift refuses revoked recipients too (owner of the R-address: the conversation's contact or a
+//   cached identity with it among its primary addresses) unless allow_revoked is set.
+// - Sent gift / message records take their timestamp from clock::now_secs.
+// - Peer protocol versions are loaded at startup and saved with the verification cache.
+// - The setup hook arms the startup integrity audit; opening an identity session runs it once for that identity.
+// - Polling hands newer-protocol placeholders back to the caller without storing, spam-filtering or notifying them.
+// - send_file_response, acknowledge_gift, vote_in_poll and prepare_external_send take allow_revoked and refuse
+//   revoked recipients like the other sends.
+// - The response cache's TTL (getidentity and the other cached reads) is loaded at startup; registered
+//   get/set_response_cache_ttl.
+// - Sent messages, voice memos and transparent / currency gifts are all recorded through record_sent_message
+//   (which takes the fee and size, kind and currency).
 
 mod credentials; // Added credentials module
 mod settings; // Added settings module
+mod formatting; // Added formatting module
+mod events; // Added events module
+mod tasks; // Added tasks module
 pub mod rpc_client;
 pub mod identity_rpc;
 pub mod message_rpc;
 pub mod wallet_rpc;
+pub mod memo_codec;
+pub mod protocol;
+pub mod proof_rpc;
+mod storage; // Added storage module
+mod verification_cache; // Added verification cache module
+mod notifications; // Added notifications module
+mod onboarding; // Added onboarding module
+pub mod price; // Added price module
+mod message_index; // Added message index module
+mod message_store; // Added message store module
+mod integrity; // Added integrity audit module
+pub mod network_rpc; // Added network_rpc module
+mod prefetch; // Added conversation prefetch module
+mod export; // Added conversation export module
+mod file_request; // Added file request protocol module
+mod capabilities; // Added chain capability matrix module
+mod spam; // Added anti-spam / message requests module
+mod chain_profiles; // Added chain profile bundle module
+mod activity; // Added conversation activity timeline module
+mod blocklist; // Added sender blocklist module
+mod notes; // Added note reservation module
+mod gift_ack; // Added gift acknowledgment module
+mod error_log; // Added panic / command failure capture module
+mod api_version; // Added command API versioning module
+mod clock; // Added clock skew guard module
+mod identity_cache; // Persisted contact identity cache and post-login warmup
+mod wipe; // Emergency wipe of local data
+mod voice_memo; // Short audio clips over chunked memos
+mod verify_queue; // Background verification of a first-sync backlog
+mod polls; // Conversation polls and vote tallies
+mod presence; // Contact last-seen heuristic
+mod external_signer; // Sends signed outside the connected daemon
+mod address; // Chain-aware recipient address validation
+mod utxo_maintenance; // Automatic UTXO splitting for Fast Messages
+mod shielding; // Transparent -> private address shielding
+mod transaction_rpc; // Transaction detail viewer
+mod sync_status; // Chain sync progress and watcher
+mod rescan; // Wallet rescans started from the app
+mod conversion; // Convert-and-send gifts
+mod gift_ledger; // Per-conversation gift ledger
+mod balance_alerts; // Low-balance threshold alerts
+mod auto_topup; // Automatic top-ups from transparent funds
+mod identity_registration; // VerusID registration flow
+mod profile; // Identity profiles (contentmultimap)
+mod contact_watcher; // Contact identity change watcher
+mod sessions; // Simultaneous identity sessions and the active identity
+mod invoice; // VerusPay invoice creation and parsing
+mod currency; // Currency metadata (names, decimals) resolved from getcurrency
+mod identity_watcher; // Periodic listidentities diff (identities appearing in the wallet)
+mod rpc_timeouts; // Per-method RPC timeouts (built-in table plus persisted overrides)
+mod ssh_tunnel; // Optional SSH tunnel to a daemon on another machine
+mod rpc_diagnostics; // Per-method RPC call statistics
+mod response_cache; // Block-aware cache for idempotent RPC reads
+mod zmq_listener; // ZMQ hashblock / hashtx subscriber (push-driven refresh)
 
 use crate::rpc_client::VerusRpcError; // Corrected
 use crate::credentials::CredentialError; // Import credential error
 use crate::settings::SettingsError; // Import settings error
 use crate::identity_rpc::FormattedIdentity; // Corrected
 use crate::message_rpc::ChatMessage; // Corrected
-use crate::wallet_rpc::UtxoInfo; // Import UtxoInfo struct
+use crate::wallet_rpc::{SaplingNote, UtxoInfo}; // Import UtxoInfo struct
+use crate::tasks::{run_cancellable, TaskError, TaskRegistry};
+use crate::proof_rpc::{PaymentProof, PaymentProofVerification};
+use crate::rpc_client::DaemonLoadStatus;
+use crate::verification_cache::VerificationCache;
+use crate::notifications::NotificationState;
+use crate::settings::SyncCursor;
+use crate::message_index::MessageIndex;
+use crate::price::PriceQuote;
+use crate::message_store::MessageStore;
+use crate::message_rpc::SentChatMessage;
+use crate::wallet_rpc::{EncryptWalletResult, WalletEncryptionStatus};
+use crate::integrity::IntegrityReport;
+use crate::message_rpc::SendPreview;
+use crate::file_request::{FileRequestError, FileRequestRecord};
+use crate::gift_ack::{GiftAck, GiftAckError};
+use crate::capabilities::{require_feature, ChainCapabilities, Feature};
+use crate::identity_cache::IdentityCache;
+use crate::voice_memo::{VoiceMemoError, VoiceMemoRecord};
+use crate::polls::PollError;
+use crate::wallet_rpc::SendFeeEstimate;
+use crate::message_rpc::FeePreset;
+use crate::external_signer::{ExternalSignerError, SigningRequest};
+use crate::utxo_maintenance::{UtxoMaintenanceError, UtxoSplitOutcome};
+use crate::shielding::{ShieldError, ShieldResult};
+use crate::wallet_rpc::WalletOperationStatus;
+use crate::conversion::ConversionError;
+use crate::identity_registration::RegistrationError;
+use crate::profile::{ProfileError, ProfileUpdate};
+use crate::invoice::{Invoice, InvoiceError, InvoiceRequest, ParsedInvoice};
 
 // Custom error type serializable for Tauri
 #[derive(Debug, serde::Serialize, thiserror::Error)]
+#[serde(remote = "Self")]
 enum CommandError {
     #[error("Verus RPC Error: {0}")]
     Rpc(String),
@@ -46,6 +251,42 @@ enum CommandError {
     Settings(String),
     #[error("Verus RPC Error: {0}")] // Use the same variant, but handle specific RPC errors
     RpcSpecific(crate::rpc_client::VerusRpcError), // Corrected
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+    #[error("File Request Error: {0}")]
+    FileRequest(String),
+    #[error("Gift Acknowledgment Error: {0}")]
+    GiftAck(String),
+    #[error("Voice Memo Error: {0}")]
+    VoiceMemo(String),
+    #[error("Poll Error: {0}")]
+    Poll(String),
+    #[error("External Signing Error: {0}")]
+    ExternalSigning(String),
+    #[error("UTXO Maintenance Error: {0}")]
+    UtxoMaintenance(String),
+    #[error("Shielding Error: {0}")]
+    Shield(String),
+    #[error("Conversion Error: {0}")]
+    Conversion(String),
+    #[error("Identity Registration Error: {0}")]
+    IdentityRegistration(String),
+    #[error("Profile Error: {0}")]
+    Profile(String),
+    #[error("Invoice Error: {0}")]
+    Invoice(String),
+}
+
+crate::error_log::recorded_command_error!(CommandError, "command");
+
+// Convert TaskError to CommandError
+impl From<TaskError> for CommandError {
+    fn from(error: TaskError) -> Self {
+        log::info!("Task stopped: {}", error);
+        match error {
+            TaskError::Cancelled(task_id) => CommandError::Cancelled(task_id),
+        }
+    }
 }
 
 // Convert VerusRpcError to CommandError
@@ -81,6 +322,126 @@ impl From<SettingsError> for CommandError {
     }
 }
 
+// Convert FileRequestError to CommandError
+impl From<FileRequestError> for CommandError {
+    fn from(error: FileRequestError) -> Self {
+        log::error!("File request failed: {:?}", error);
+        match error {
+            FileRequestError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::FileRequest(error.to_string()),
+        }
+    }
+}
+
+// Convert GiftAckError to CommandError
+impl From<GiftAckError> for CommandError {
+    fn from(error: GiftAckError) -> Self {
+        log::error!("Gift acknowledgment failed: {:?}", error);
+        match error {
+            GiftAckError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::GiftAck(error.to_string()),
+        }
+    }
+}
+
+// Convert VoiceMemoError to CommandError
+impl From<VoiceMemoError> for CommandError {
+    fn from(error: VoiceMemoError) -> Self {
+        log::error!("Voice memo failed: {:?}", error);
+        match error {
+            VoiceMemoError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::VoiceMemo(error.to_string()),
+        }
+    }
+}
+
+// Convert PollError to CommandError
+impl From<PollError> for CommandError {
+    fn from(error: PollError) -> Self {
+        log::error!("Poll operation failed: {:?}", error);
+        match error {
+            PollError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::Poll(error.to_string()),
+        }
+    }
+}
+
+// Convert ExternalSignerError to CommandError
+impl From<ExternalSignerError> for CommandError {
+    fn from(error: ExternalSignerError) -> Self {
+        log::error!("External signing failed: {:?}", error);
+        match error {
+            ExternalSignerError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::ExternalSigning(error.to_string()),
+        }
+    }
+}
+
+// Convert UtxoMaintenanceError to CommandError
+impl From<UtxoMaintenanceError> for CommandError {
+    fn from(error: UtxoMaintenanceError) -> Self {
+        log::error!("UTXO maintenance failed: {:?}", error);
+        match error {
+            UtxoMaintenanceError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::UtxoMaintenance(error.to_string()),
+        }
+    }
+}
+
+// Convert ShieldError to CommandError
+impl From<ShieldError> for CommandError {
+    fn from(error: ShieldError) -> Self {
+        log::error!("Shielding failed: {:?}", error);
+        match error {
+            ShieldError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::Shield(error.to_string()),
+        }
+    }
+}
+
+// Convert ConversionError to CommandError
+impl From<ConversionError> for CommandError {
+    fn from(error: ConversionError) -> Self {
+        log::error!("Conversion gift failed: {:?}", error);
+        match error {
+            ConversionError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::Conversion(error.to_string()),
+        }
+    }
+}
+
+// Convert RegistrationError to CommandError
+impl From<RegistrationError> for CommandError {
+    fn from(error: RegistrationError) -> Self {
+        log::error!("Identity registration failed: {:?}", error);
+        match error {
+            RegistrationError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::IdentityRegistration(error.to_string()),
+        }
+    }
+}
+
+// Convert ProfileError to CommandError
+impl From<ProfileError> for CommandError {
+    fn from(error: ProfileError) -> Self {
+        log::error!("Profile update failed: {:?}", error);
+        match error {
+            ProfileError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::Profile(error.to_string()),
+        }
+    }
+}
+
+impl From<InvoiceError> for CommandError {
+    fn from(error: InvoiceError) -> Self {
+        log::error!("Invoice handling failed: {:?}", error);
+        match error {
+            InvoiceError::Rpc(e) => CommandError::RpcSpecific(e),
+            _ => CommandError::Invoice(error.to_string()),
+        }
+    }
+}
+
 // macOS window customization function
 #[cfg(target_os = "macos")]
 fn set_macos_window_background(window: &tauri::WebviewWindow) {
@@ -103,45 +464,137 @@ fn set_macos_window_background(window: &tauri::WebviewWindow) {
     }
 }
 
+// Persist newly cached signature verifications and peer protocol versions (failures are logged, not surfaced)
+fn persist_verification_cache(app: &tauri::AppHandle, cache: &VerificationCache) {
+    if let Err(e) = cache.persist(app) {
+        log::warn!("Failed to persist verification cache: {}", e);
+    }
+    if let Err(e) = crate::protocol::persist_peer_versions(app) {
+        log::warn!("Failed to persist peer protocol versions: {}", e);
+    }
+}
+
+// Refuse sends to revoked identities unless the user confirmed sending anyway
+async fn ensure_recipient_active(
+    app: &tauri::AppHandle,
+    creds: &crate::credentials::Credentials,
+    recipient_z_address: &str,
+    allow_revoked: Option<bool>,
+) -> Result<(), CommandError> {
+    use tauri::Manager;
+    if allow_revoked.unwrap_or(false) {
+        return Ok(());
+    }
+    app.state::<IdentityCache>()
+        .ensure_recipient_active(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, recipient_z_address)
+        .await
+        .map_err(CommandError::from)
+}
+
+fn persist_message_index(app: &tauri::AppHandle, index: &MessageIndex) {
+    if let Err(e) = index.persist(app) {
+        log::warn!("Failed to persist message index: {}", e);
+    }
+}
+
+// Add a message we just sent to the message store (failures are logged, not surfaced). `cost` is the fee and size
+// if known; transparent gifts carry no memo, so no protocol version or recipient binding.
+#[allow(clippy::too_many_arguments)]
+fn record_sent_message(
+    app: &tauri::AppHandle,
+    message_store: &MessageStore,
+    identity_i_address: &str,
+    conversation_id: &str,
+    txid: &str,
+    sender: String,
+    text: String,
+    amount: f64,
+    cost: Option<crate::message_rpc::TxCost>,
+    kind: Option<crate::settings::MessageKind>,
+    currency: Option<String>,
+) {
+    let has_memo = kind != Some(crate::settings::MessageKind::TransparentGift);
+    let sent = crate::settings::ChatMessage {
+        id: txid.to_string(),
+        sender,
+        text,
+        timestamp: crate::clock::now_secs(),
+        amount,
+        confirmations: 0,
+        direction: "sent".to_string(),
+        status: Some("sent".to_string()),
+        protocol_version: has_memo.then_some(crate::protocol::PROTOCOL_VERSION),
+        recipient_bound: has_memo.then_some(true),
+        signature: None, // Not returned by the send; filled in by history recovery
+        fee: cost.map(|c| c.fee),
+        size_bytes: cost.map(|c| c.size_bytes),
+        kind,
+        currency,
+    };
+    if let Err(e) = message_store.merge(app, identity_i_address, conversation_id, vec![sent]) {
+        log::warn!("Failed to record sent message {} in message store: {}", txid, e);
+    }
+}
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
-async fn connect_verus_daemon(rpc_user: String, rpc_pass: String, rpc_port: u16) -> Result<u64, CommandError> {
+async fn connect_verus_daemon(app: tauri::AppHandle, rpc_user: String, rpc_pass: String, rpc_port: u16) -> Result<u64, CommandError> {
     // Ensure logging is initialized (can be done once at startup too)
     // TODO: Initialize logger properly in main/run function
     let _ = env_logger::try_init();
 
     log::info!("connect_verus_daemon command received");
-    crate::wallet_rpc::connect_and_get_block_height(rpc_user, rpc_pass, rpc_port) // Corrected path
+    let block_height = crate::wallet_rpc::connect_and_get_block_height(rpc_user.clone(), rpc_pass.clone(), rpc_port) // Corrected path
         .await
-        .map_err(CommandError::from)
+        .map_err(CommandError::from)?;
+
+    // Feature detection never blocks connecting; unprobed chains are treated as fully capable
+    if let Err(e) = crate::capabilities::probe_chain_capabilities(&app, &rpc_user, &rpc_pass, rpc_port).await {
+        log::warn!("Capability probe failed: {:?}", e);
+    }
+    // Warn about a skewed local clock before anything gets signed
+    crate::clock::check_on_connect(&app, &rpc_user, &rpc_pass, rpc_port).await;
+    Ok(block_height)
 }
 
 // New command to get formatted identities (fast mode - no balances)
 #[tauri::command]
 async fn get_login_identities_fast(
     app: tauri::AppHandle, // Need AppHandle to get stored credentials
+    task_id: Option<String>, // Optional id so the load can be cancelled
 ) -> Result<Vec<FormattedIdentity>, CommandError> {
     log::info!("get_login_identities_fast command received");
     // Load credentials first
-    let creds = crate::credentials::load_credentials(app).await?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let rules = crate::settings::read_identity_filter_rules(&app)?;
+    // Keep the picker current when identities appear later (e.g. a registration completes)
+    crate::identity_watcher::start_identity_watcher(&app, creds.rpc_user.clone(), creds.rpc_pass.clone(), creds.rpc_port);
     // Then call the RPC function
-    crate::identity_rpc::get_login_identities_fast(creds.rpc_user, creds.rpc_pass, creds.rpc_port)
-        .await
-        .map_err(CommandError::from)
+    run_cancellable(&app, task_id, "identities", async {
+        crate::identity_rpc::get_login_identities_fast(creds.rpc_user, creds.rpc_pass, creds.rpc_port, &rules)
+            .await
+            .map_err(CommandError::from)
+    })
+    .await
 }
 
 // New command to get formatted identities (with balances - full mode)
 #[tauri::command]
 async fn get_login_identities(
     app: tauri::AppHandle, // Need AppHandle to get stored credentials
+    task_id: Option<String>, // Optional id so the load can be cancelled
 ) -> Result<Vec<FormattedIdentity>, CommandError> {
     log::info!("get_login_identities command received");
     // Load credentials first
-    let creds = crate::credentials::load_credentials(app).await?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let rules = crate::settings::read_identity_filter_rules(&app)?;
     // Then call the RPC function
-    crate::identity_rpc::get_login_identities(creds.rpc_user, creds.rpc_pass, creds.rpc_port) // Corrected path
-        .await
-        .map_err(CommandError::from)
+    run_cancellable(&app, task_id, "identities", async {
+        crate::identity_rpc::get_login_identities(creds.rpc_user, creds.rpc_pass, creds.rpc_port, &rules) // Corrected path
+            .await
+            .map_err(CommandError::from)
+    })
+    .await
 }
 
 // NEW command to get balance for a specific identity
@@ -149,12 +602,16 @@ async fn get_login_identities(
 async fn get_identity_balance(
     app: tauri::AppHandle, // Need AppHandle for credentials
     private_address: String,
+    task_id: Option<String>, // Optional id so the balance refresh can be cancelled
 ) -> Result<f64, CommandError> {
     log::info!("get_identity_balance command received for address: {}", private_address);
-    let creds = crate::credentials::load_credentials(app).await?;
-    crate::identity_rpc::get_identity_balance(creds.rpc_user, creds.rpc_pass, creds.rpc_port, private_address)
-        .await
-        .map_err(CommandError::from)
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    run_cancellable(&app, task_id, "balance", async {
+        crate::identity_rpc::get_identity_balance(creds.rpc_user, creds.rpc_pass, creds.rpc_port, private_address)
+            .await
+            .map_err(CommandError::from)
+    })
+    .await
 }
 
 // NEW command to get private balance
@@ -191,40 +648,151 @@ async fn check_identity_eligibility(
 ) -> Result<FormattedIdentity, CommandError> {
     log::info!("check_identity_eligibility command received for: {}", target_identity_name);
     let creds = crate::credentials::load_credentials(app).await?;
-    crate::identity_rpc::check_identity_eligibility(creds.rpc_user, creds.rpc_pass, creds.rpc_port, target_identity_name) // Corrected path
+    crate::identity_rpc::check_chat_eligibility(creds.rpc_user, creds.rpc_pass, creds.rpc_port, target_identity_name)
         .await
         .map_err(CommandError::from) // Uses the updated From implementation
 }
 
 // NEW Command: Get Chat History (with automatic signature verification)
 #[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
 async fn get_chat_history(
     app: tauri::AppHandle,
     target_identity_name: String,
     own_private_address: String,
+    task_id: Option<String>, // Optional id so the history load can be cancelled
+    identity_i_address: Option<String>, // When provided, history is merged into the message store
+    cache: tauri::State<'_, VerificationCache>,
+    index: tauri::State<'_, MessageIndex>,
+    message_store: tauri::State<'_, MessageStore>,
 ) -> Result<Vec<ChatMessage>, CommandError> {
     log::info!("get_chat_history command received from: {} for owner: {}", target_identity_name, own_private_address);
-    let creds = crate::credentials::load_credentials(app).await?;
-    crate::message_rpc::get_chat_history(creds.rpc_user, creds.rpc_pass, creds.rpc_port, target_identity_name, own_private_address) // Corrected path
-        .await
-        .map_err(CommandError::from)
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let (rpc_user, rpc_pass, rpc_port) = (creds.rpc_user.clone(), creds.rpc_pass.clone(), creds.rpc_port);
+    let conversation_id = target_identity_name.clone();
+    let result = run_cancellable(&app, task_id, "history", async {
+        crate::message_rpc::get_chat_history(creds.rpc_user, creds.rpc_pass, creds.rpc_port, target_identity_name, own_private_address, &cache) // Corrected path
+            .await
+            .map_err(CommandError::from)
+    })
+    .await
+    .map(|messages| crate::file_request::ingest_file_memos(&index, messages))
+    .map(|messages| crate::voice_memo::ingest_voice_memos(&index, messages));
+    let result = match result {
+        Ok(messages) => Ok(crate::gift_ack::ingest_gift_acks(&rpc_user, &rpc_pass, rpc_port, &index, messages).await),
+        Err(e) => Err(e),
+    };
+    // Messages held as message requests stay out of the history, like they stay out of polls
+    let result = result.map(|messages| match &identity_i_address {
+        Some(identity) => crate::spam::without_held(&app, identity, messages),
+        None => messages,
+    });
+    persist_verification_cache(&app, &cache);
+    persist_message_index(&app, &index);
+    if let (Ok(messages), Some(identity)) = (&result, &identity_i_address) {
+        let incoming = messages.iter().cloned().map(Into::into).collect();
+        message_store.merge(&app, identity, &conversation_id, incoming)?;
+    }
+    result
 }
 
 // NEW Command: Get New Received Messages (Polling) (with automatic signature verification)
+// Only returns messages newer than the persisted sync cursor for the address
 #[tauri::command]
 async fn get_new_received_messages(
     app: tauri::AppHandle,
     own_private_address: String,
+    identity_i_address: Option<String>, // When provided, new messages go through the notification pipeline
 ) -> Result<Vec<ChatMessage>, CommandError> {
     log::info!("get_new_received_messages command received for owner: {}", own_private_address);
-    let creds = crate::credentials::load_credentials(app).await?;
-    crate::message_rpc::get_new_received_messages(creds.rpc_user, creds.rpc_pass, creds.rpc_port, own_private_address) // Corrected path
+    poll_received_messages(app, own_private_address, identity_i_address).await
+}
+
+// One poll of an address: the command above, and the background pollers of inactive identity sessions
+async fn poll_received_messages(
+    app: tauri::AppHandle,
+    own_private_address: String,
+    identity_i_address: Option<String>,
+) -> Result<Vec<ChatMessage>, CommandError> {
+    use tauri::Manager;
+    let (cache, index, message_store) = (app.state::<VerificationCache>(), app.state::<MessageIndex>(), app.state::<MessageStore>());
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let (rpc_user, rpc_pass, rpc_port) = (creds.rpc_user.clone(), creds.rpc_pass.clone(), creds.rpc_port);
+    let mut cursor = crate::settings::read_sync_cursor(&app, &own_private_address).unwrap_or_else(|e| {
+        log::warn!("Failed to load sync cursor, rescanning full history: {}", e);
+        SyncCursor::default()
+    });
+    // Backlog messages delivered by later polls are history too (no notifications)
+    let initial_sync = cursor.is_catching_up();
+    // Placeholders of memos from newer clients are unverified: they skip the pipeline (store, spam rules,
+    // notifications) and are only handed to the caller
+    let mut placeholders = Vec::new();
+    let result = crate::message_rpc::get_new_received_messages(creds.rpc_user, creds.rpc_pass, creds.rpc_port, own_private_address.clone(), &cache, &mut cursor) // Corrected path
         .await
         .map_err(CommandError::from)
+        .map(|messages| {
+            let (unreadable, messages): (Vec<ChatMessage>, Vec<ChatMessage>) =
+                messages.into_iter().partition(|m| m.is_newer_protocol_placeholder());
+            placeholders = unreadable;
+            messages
+        })
+        .map(|messages| crate::file_request::ingest_file_memos(&index, messages))
+        .map(|messages| crate::voice_memo::ingest_voice_memos(&index, messages));
+    let result = match result {
+        Ok(messages) => Ok(crate::gift_ack::ingest_gift_acks(&rpc_user, &rpc_pass, rpc_port, &index, messages).await),
+        Err(e) => Err(e),
+    };
+    let result = result.map(|messages| match &identity_i_address {
+        // Messages caught by the spam rules are held as message requests
+        Some(identity) => crate::spam::filter_incoming(&app, identity, messages, initial_sync),
+        None => messages,
+    });
+    persist_verification_cache(&app, &cache);
+    // The cursor only moves past messages that made it into the store; otherwise the next poll delivers them again
+    let stored = match (&result, &identity_i_address) {
+        (Ok(messages), Some(identity)) => match message_store.ingest_received(&app, identity, messages) {
+            Ok(()) => true,
+            Err(e) => {
+                log::error!("Failed to store polled messages, keeping the sync cursor: {}", e);
+                false
+            }
+        },
+        _ => true,
+    };
+    if result.is_ok() {
+        if stored {
+            if let Err(e) = crate::settings::write_sync_cursor(&app, &own_private_address, &cursor) {
+                log::warn!("Failed to persist sync cursor: {}", e);
+            }
+        }
+        crate::verify_queue::spawn_backlog_worker(&app, rpc_user.clone(), rpc_pass.clone(), rpc_port, own_private_address.clone());
+        crate::utxo_maintenance::spawn_maintenance(&app, rpc_user.clone(), rpc_pass.clone(), rpc_port, own_private_address.clone());
+    }
+    if let Ok(messages) = &result {
+        // Record the conversion rate at ingestion time for new gifts
+        crate::message_index::annotate_new_gifts(&rpc_user, &rpc_pass, rpc_port, &index, messages).await;
+        persist_message_index(&app, &index);
+    }
+    if let (Ok(messages), Some(identity)) = (&result, &identity_i_address) {
+        crate::spam::accept_gifting_senders(&app, &rpc_user, &rpc_pass, rpc_port, identity, messages).await;
+        crate::gift_ledger::spawn_fee_backfill(&app, rpc_user.clone(), rpc_pass.clone(), rpc_port, identity.clone());
+        crate::balance_alerts::spawn_balance_check(&app, rpc_user.clone(), rpc_pass.clone(), rpc_port, identity.clone(), own_private_address.clone());
+        crate::auto_topup::spawn_top_up_check(&app, rpc_user.clone(), rpc_pass.clone(), rpc_port, identity.clone(), own_private_address.clone());
+        if stored {
+            // Not stored: the messages come again with the next poll, notify then
+            crate::notifications::dispatch_message_notifications(&app, identity, messages, initial_sync);
+        }
+        crate::sessions::record_poll(&app, identity, messages.len());
+    }
+    result.map(|mut messages| {
+        messages.extend(placeholders);
+        messages
+    })
 }
 
 // NEW Command: Send Private Message/Gift (with mandatory signature)
 #[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
 async fn send_private_message(
     app: tauri::AppHandle,
     sender_z_address: String,
@@ -232,6 +800,13 @@ async fn send_private_message(
     memo_text: String,
     sender_identity: String,
     amount: f64,
+    identity_i_address: Option<String>, // When provided with conversation_id, the sent message is recorded in the message store
+    conversation_id: Option<String>,
+    fee: Option<f64>, // Explicit fee; takes precedence over fee_preset
+    fee_preset: Option<FeePreset>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    message_store: tauri::State<'_, MessageStore>,
+    index: tauri::State<'_, MessageIndex>,
 ) -> Result<String, CommandError> { // Returns txid
     log::info!(
         "send_private_message command received: to={}, amount={}, sender_id={}",
@@ -239,19 +814,42 @@ async fn send_private_message(
         amount,
         sender_identity
     );
-    let creds = crate::credentials::load_credentials(app).await?;
-    crate::message_rpc::send_private_message( // Corrected path
-        creds.rpc_user,
-        creds.rpc_pass,
+    let fee = crate::message_rpc::resolve_fee(fee, fee_preset)?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let text = memo_text.clone();
+    let sender = sender_identity.clone();
+    let txid = crate::message_rpc::send_private_message( // Corrected path
+        creds.rpc_user.clone(),
+        creds.rpc_pass.clone(),
         creds.rpc_port,
         sender_z_address,
         recipient_z_address,
         memo_text,
         sender_identity,
         amount,
+        fee,
     )
     .await
-    .map_err(CommandError::from)
+    .map_err(CommandError::from)?;
+
+    // Fee and size are informational; the send already succeeded
+    let cost = match crate::message_rpc::get_transaction_cost(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &txid).await {
+        Ok(cost) => {
+            index.record_cost(&txid, amount, cost);
+            persist_message_index(&app, &index);
+            Some(cost)
+        }
+        Err(e) => {
+            log::warn!("Could not read fee/size of sent transaction {}: {:?}", txid, e);
+            None
+        }
+    };
+
+    if let (Some(identity), Some(conversation_id)) = (&identity_i_address, &conversation_id) {
+        record_sent_message(&app, &message_store, identity, conversation_id, &txid, sender, text, amount, cost, None, None);
+    }
+    Ok(txid)
 }
 
 // NEW command to get UTXO info for Fast Messages
@@ -267,87 +865,1536 @@ async fn get_utxo_info(
         .map_err(CommandError::from)
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    // TODO: Initialize logger here instead of in command
-    env_logger::init(); // Basic logger initialization
+// NEW Command: Sapling notes of an address with their spendable status and reservation marks
+#[tauri::command]
+async fn list_notes(
+    app: tauri::AppHandle,
+    address: String,
+) -> Result<Vec<SaplingNote>, CommandError> {
+    log::info!("list_notes command received for address: {}", address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let mut notes = crate::wallet_rpc::list_notes(creds.rpc_user, creds.rpc_pass, creds.rpc_port, address.clone())
+        .await
+        .map_err(CommandError::from)?;
+    crate::notes::apply_reservations(&app, &address, &mut notes).map_err(|e| CommandError::Settings(e.to_string()))?;
+    Ok(notes)
+}
 
-    let store_plugin = tauri_plugin_store::Builder::default().build(); // Build the store plugin instance
+// NEW Command: Create a selective disclosure proof for a received payment
+#[tauri::command]
+async fn create_payment_proof(
+    app: tauri::AppHandle,
+    txid: String,
+    signing_identity: String,
+    output_index: Option<u32>,
+) -> Result<PaymentProof, CommandError> {
+    log::info!("create_payment_proof command received for tx: {}", txid);
+    let creds = crate::credentials::load_credentials(app).await?;
+    require_feature(creds.rpc_port, Feature::ViewTransaction)?;
+    crate::proof_rpc::create_payment_proof(creds.rpc_user, creds.rpc_pass, creds.rpc_port, txid, signing_identity, output_index)
+        .await
+        .map_err(CommandError::from)
+}
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(store_plugin) // Register the store plugin instance
-        .setup(|app| {
-            log::info!("Setting up Tauri application");
-            
-            // Create the main window programmatically for all platforms
-            use tauri::{WebviewUrl, WebviewWindowBuilder};
-            
-            #[cfg(target_os = "macos")]
-            {
-                use tauri::TitleBarStyle;
-                
-                let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
-                    .title("Nymia")
-                    .inner_size(900.0, 600.0)
-                    .visible(true)
-                    .resizable(true)
-                    .title_bar_style(TitleBarStyle::Transparent)
-                    .hidden_title(true)
-                    .accept_first_mouse(true);
-                
-                let window = win_builder.build()?;
-                
-                // Set custom almost black background
-                set_macos_window_background(&window);
-                
-                log::info!("macOS window created with transparent titlebar and custom background");
+// NEW Command: Verify a payment proof shared by someone else
+#[tauri::command]
+async fn verify_payment_proof(
+    app: tauri::AppHandle,
+    proof: PaymentProof,
+) -> Result<PaymentProofVerification, CommandError> {
+    log::info!("verify_payment_proof command received for tx: {}", proof.txid);
+    let creds = crate::credentials::load_credentials(app).await?;
+    require_feature(creds.rpc_port, Feature::ViewTransaction)?;
+    crate::proof_rpc::verify_payment_proof(creds.rpc_user, creds.rpc_pass, creds.rpc_port, proof)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Report daemon work-queue saturation / backoff state
+#[tauri::command]
+fn get_daemon_load_status() -> DaemonLoadStatus {
+    crate::rpc_client::daemon_load_status()
+}
+
+// NEW Command: Conversion rate of the native currency (defaults to VRSC in DAI via the Bridge.vETH basket)
+#[tauri::command]
+async fn get_conversion_rate(
+    app: tauri::AppHandle,
+    basket: Option<String>,
+    base_currency: Option<String>,
+    quote_currency: Option<String>,
+    block_height: Option<u64>,
+) -> Result<PriceQuote, CommandError> {
+    log::info!("get_conversion_rate command received (height: {:?})", block_height);
+    let creds = crate::credentials::load_credentials(app).await?;
+    require_feature(creds.rpc_port, Feature::Conversions)?;
+    crate::price::get_conversion_rate(
+        &creds.rpc_user,
+        &creds.rpc_pass,
+        creds.rpc_port,
+        basket.as_deref().unwrap_or(crate::price::DEFAULT_PRICE_BASKET),
+        base_currency.as_deref().unwrap_or(crate::price::DEFAULT_BASE_CURRENCY),
+        quote_currency.as_deref().unwrap_or(crate::price::DEFAULT_QUOTE_CURRENCY),
+        block_height,
+    )
+    .await
+    .map_err(CommandError::from)
+}
+
+// NEW Command: Annotate existing gifts with the conversion rate at the time they were received
+#[tauri::command]
+async fn backfill_gift_rates(
+    app: tauri::AppHandle,
+    own_private_address: String,
+    task_id: Option<String>, // Optional id so the backfill can be cancelled
+    index: tauri::State<'_, MessageIndex>,
+) -> Result<usize, CommandError> {
+    log::info!("backfill_gift_rates command received for owner: {}", own_private_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    require_feature(creds.rpc_port, Feature::Conversions)?;
+    let result = run_cancellable(&app, task_id, "backfill", async {
+        crate::message_index::backfill_gift_rates(creds.rpc_user, creds.rpc_pass, creds.rpc_port, own_private_address, &index)
+            .await
+            .map_err(CommandError::from)
+    })
+    .await;
+    // Keep partial progress even if the backfill stopped early
+    persist_message_index(&app, &index);
+    result
+}
+
+// NEW Command: Rebuild sent messages from the wallet (e.g., after a reinstall)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn recover_sent_messages(
+    app: tauri::AppHandle,
+    own_private_address: String,
+    sender_identity: String,
+    identity_i_address: Option<String>, // When provided, recovered messages are merged into (new) conversations
+    task_id: Option<String>, // Optional id so the recovery can be cancelled
+    message_store: tauri::State<'_, MessageStore>,
+    index: tauri::State<'_, MessageIndex>,
+    identity_cache: tauri::State<'_, IdentityCache>,
+) -> Result<Vec<SentChatMessage>, CommandError> {
+    log::info!("recover_sent_messages command received for {} ({})", sender_identity, own_private_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    require_feature(creds.rpc_port, Feature::ViewTransaction)?;
+    let recovered = run_cancellable(&app, task_id, "recovery", async {
+        crate::message_rpc::get_sent_message_history(creds.rpc_user.clone(), creds.rpc_pass.clone(), creds.rpc_port, own_private_address, sender_identity)
+            .await
+            .map_err(CommandError::from)
+    })
+    .await?;
+
+    for sent in &recovered {
+        if let (Some(fee), Some(size_bytes)) = (sent.message.fee, sent.message.size_bytes) {
+            index.record_cost(&sent.message.id, sent.message.amount, crate::message_rpc::TxCost { fee, size_bytes });
+        }
+    }
+    persist_message_index(&app, &index);
+
+    if let Some(identity) = &identity_i_address {
+        // Conversations are matched by the recipient's private address
+        let mut conversations = crate::settings::read_conversations(&app, identity)?;
+
+        // Recipients without a conversation were messaged from another client; adopt the ones we can identify
+        let mut unknown: Vec<String> = recovered
+            .iter()
+            .map(|sent| sent.recipient_private_address.clone())
+            .filter(|address| !conversations.iter().any(|c| &c.recipient_private_address == address))
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        if !unknown.is_empty() {
+            let candidates = crate::spam::held_senders(&app, identity);
+            let found = crate::identity_cache::identify_recipients(
+                &identity_cache, &creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &unknown, &candidates,
+            )
+            .await;
+            if let Err(e) = identity_cache.persist(&app) {
+                log::warn!("Failed to persist identity cache: {}", e);
             }
-            
-            // For other platforms, create a standard window
-            #[cfg(not(target_os = "macos"))]
-            {
-                let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
-                    .title("Nymia")
-                    .inner_size(900.0, 600.0)
-                    .visible(true)
-                    .resizable(true);
-                
-                let _window = win_builder.build()?;
-                
-                log::info!("Standard window created for non-macOS platform");
+
+            let mut created = Vec::new();
+            for address in &unknown {
+                let Some(recipient) = found.get(address) else { continue };
+                if conversations.iter().any(|c| c.id == recipient.formatted_name) {
+                    continue; // Known contact whose private address changed; the identity warmup updates it
+                }
+                log::info!("Creating conversation with {} from recovered outgoing memos", recipient.formatted_name);
+                conversations.push(crate::settings::Conversation {
+                    id: recipient.formatted_name.clone(),
+                    name: recipient.formatted_name.clone(),
+                    recipient_private_address: address.clone(),
+                    unread: Some(false),
+                    archived: false,
+                });
+                created.push(recipient.formatted_name.clone());
             }
-            
-            Ok(())
-        })
-        .invoke_handler(tauri::generate_handler![
-            connect_verus_daemon,
-            crate::credentials::save_credentials, // Add credential commands
-            crate::credentials::load_credentials,
-            crate::credentials::clear_credentials,
-            crate::credentials::detect_all_blockchains, // NEW: Parallel detection
-            crate::credentials::select_folder_dialog, // NEW: Folder selection
-            crate::credentials::detect_blockchain_from_path, // NEW: Custom path detection
-            get_login_identities_fast, // NEW: Fast loading without balances
-            get_login_identities, // Correct name used here
-            get_identity_balance, // NEW: Individual balance fetching
-            get_private_balance, // Add the new balance command
-            get_pending_balance, // Add the new pending balance command
-            check_identity_eligibility,
-            get_chat_history,
-            get_new_received_messages,
-            send_private_message, // Added send message command
-            // New Settings Commands
-            crate::settings::save_persistence_setting,
-            crate::settings::load_persistence_setting,
-            crate::settings::save_conversations,
-            crate::settings::load_conversations,
-            crate::settings::save_messages_for_conversation,
-            crate::settings::load_messages_for_conversation,
-            crate::settings::delete_chat_data,
-            get_utxo_info
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            if !created.is_empty() {
+                let added: Vec<crate::settings::Conversation> =
+                    conversations.iter().filter(|c| created.contains(&c.id)).cloned().collect();
+                crate::settings::update_conversations(&app, identity, |conversations| {
+                    for conversation in added {
+                        if !conversations.iter().any(|c| c.id == conversation.id) {
+                            conversations.push(conversation);
+                        }
+                    }
+                })?;
+            }
+            let unidentified: Vec<String> = unknown.into_iter().filter(|address| !found.contains_key(address)).collect();
+            if !unidentified.is_empty() {
+                log::info!("{} recipients of recovered messages match no known identity", unidentified.len());
+            }
+            if !created.is_empty() || !unidentified.is_empty() {
+                crate::events::emit_event(&app, crate::events::CONVERSATIONS_RECOVERED_EVENT, crate::identity_cache::ConversationsRecovered {
+                    identity_i_address: identity.clone(),
+                    conversation_ids: created,
+                    unidentified_addresses: unidentified,
+                });
+            }
+        }
+
+        for conversation in conversations {
+            let incoming: Vec<crate::settings::ChatMessage> = recovered
+                .iter()
+                .filter(|sent| sent.recipient_private_address == conversation.recipient_private_address)
+                .map(|sent| {
+                    let mut message: crate::settings::ChatMessage = sent.message.clone().into();
+                    message.status = Some(if sent.message.confirmations > 0 { "delivered" } else { "sent" }.to_string());
+                    message
+                })
+                .collect();
+            if !incoming.is_empty() {
+                message_store.merge(&app, identity, &conversation.id, incoming)?;
+            }
+        }
+    }
+
+    Ok(recovered)
+}
+
+// NEW Command: Wallet encryption status (used to nudge users with unencrypted wallets)
+#[tauri::command]
+async fn get_wallet_encryption_status(
+    app: tauri::AppHandle,
+    identity: Option<String>, // Also report whether this identity can sign and spend
+) -> Result<WalletEncryptionStatus, CommandError> {
+    log::info!("get_wallet_encryption_status command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::get_wallet_encryption_status(creds.rpc_user, creds.rpc_pass, creds.rpc_port, identity)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Encrypt the wallet (the daemon stops afterwards and must be restarted)
+#[tauri::command]
+async fn encrypt_wallet(app: tauri::AppHandle, passphrase: String) -> Result<EncryptWalletResult, CommandError> {
+    log::info!("encrypt_wallet command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::encrypt_wallet(creds.rpc_user, creds.rpc_pass, creds.rpc_port, passphrase)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Unlock an encrypted wallet (e.g., after a WalletLocked error when sending)
+#[tauri::command]
+async fn unlock_wallet(app: tauri::AppHandle, passphrase: String, timeout_secs: u64) -> Result<(), CommandError> {
+    log::info!("unlock_wallet command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::unlock_wallet(creds.rpc_user, creds.rpc_pass, creds.rpc_port, passphrase, timeout_secs)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Lock the wallet again
+#[tauri::command]
+async fn lock_wallet(app: tauri::AppHandle) -> Result<(), CommandError> {
+    log::info!("lock_wallet command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::lock_wallet(creds.rpc_user, creds.rpc_pass, creds.rpc_port)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Audit a sample of persisted messages against the chain (signatures, contents, existence)
+#[tauri::command]
+async fn run_integrity_audit(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    own_private_address: String,
+    sample_size: Option<usize>,
+    task_id: Option<String>, // Optional id so the audit can run in the background and be cancelled
+) -> Result<IntegrityReport, CommandError> {
+    log::info!("run_integrity_audit command received for {}", identity_i_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    require_feature(creds.rpc_port, Feature::ViewTransaction)?;
+    let candidates = crate::integrity::persisted_candidates(&app, &identity_i_address)?;
+
+    run_cancellable(&app, task_id, "audit", async {
+        crate::integrity::run_integrity_audit(
+            creds.rpc_user,
+            creds.rpc_pass,
+            creds.rpc_port,
+            identity_i_address,
+            own_private_address,
+            candidates,
+            sample_size.unwrap_or(crate::integrity::DEFAULT_AUDIT_SAMPLE_SIZE),
+        )
+        .await
+        .map_err(CommandError::from)
+    })
+    .await
+}
+
+// NEW Command: Send preview with fee and estimated confirmation time
+#[tauri::command]
+async fn preview_send(app: tauri::AppHandle, amount: f64) -> Result<SendPreview, CommandError> {
+    log::info!("preview_send command received: amount={}", amount);
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::message_rpc::preview_send(creds.rpc_user, creds.rpc_pass, creds.rpc_port, amount)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: A conversation was opened - prefetch contact, recent tx details and fee estimate in the background.
+// Results arrive as conversation-prefetch events. Returns the task id (cancel with cancel_task when navigating away).
+#[tauri::command]
+async fn open_conversation(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<String, CommandError> {
+    log::info!("open_conversation command received for {} (user {})", conversation_id, identity_i_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::prefetch::spawn_conversation_prefetch(app, crate::prefetch::PrefetchRequest {
+        rpc_user: creds.rpc_user,
+        rpc_pass: creds.rpc_pass,
+        rpc_port: creds.rpc_port,
+        identity_i_address,
+        conversation_id,
+    });
+    Ok(crate::prefetch::PREFETCH_TASK_ID.to_string())
+}
+
+// NEW Command: Called after login - resolve all conversation partners' identities and private addresses in the
+// background so the first open of each conversation doesn't wait for getidentity. Returns the task id.
+#[tauri::command]
+async fn warm_identity_cache(app: tauri::AppHandle, identity_i_address: String) -> Result<String, CommandError> {
+    log::info!("warm_identity_cache command received for {}", identity_i_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::contact_watcher::start_contact_watcher(&app, creds.rpc_user.clone(), creds.rpc_pass.clone(), creds.rpc_port, identity_i_address.clone(), None);
+    crate::identity_cache::spawn_identity_warmup(app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, identity_i_address);
+    Ok(crate::identity_cache::WARMUP_TASK_ID.to_string())
+}
+
+// NEW Command: Ask a contact for a document via the file request protocol
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn send_file_request(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    recipient_identity: String,
+    description: String,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    index: tauri::State<'_, MessageIndex>,
+) -> Result<FileRequestRecord, CommandError> {
+    log::info!("send_file_request command received: to={}, sender_id={}", recipient_identity, sender_identity);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let record = crate::file_request::send_file_request(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        sender_identity,
+        recipient_identity,
+        description,
+        &index,
+    )
+    .await?;
+    persist_message_index(&app, &index);
+    Ok(record)
+}
+
+// NEW Command: Answer a received file request with a file (inline) or an IPFS reference
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn send_file_response(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    request_id: String,
+    file_path: String,
+    ipfs_cid: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    index: tauri::State<'_, MessageIndex>,
+) -> Result<FileRequestRecord, CommandError> {
+    log::info!("send_file_response command received for request {}", request_id);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let record = crate::file_request::send_file_response(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        sender_identity,
+        request_id,
+        file_path,
+        ipfs_cid,
+        &index,
+    )
+    .await?;
+    persist_message_index(&app, &index);
+    Ok(record)
+}
+
+// NEW Command: Feature capabilities of the connected chain (probed on demand if not yet known)
+#[tauri::command]
+async fn get_chain_capabilities(app: tauri::AppHandle) -> Result<ChainCapabilities, CommandError> {
+    log::info!("get_chain_capabilities command received");
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    if let Some(capabilities) = crate::capabilities::active_capabilities(creds.rpc_port) {
+        return Ok(capabilities);
+    }
+    crate::capabilities::probe_chain_capabilities(&app, &creds.rpc_user, &creds.rpc_pass, creds.rpc_port)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Send a signed "thanks" receipt for a gift received in a conversation
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn acknowledge_gift(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    conversation_id: String,
+    gift_txid: String,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    note: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    index: tauri::State<'_, MessageIndex>,
+    message_store: tauri::State<'_, MessageStore>,
+) -> Result<GiftAck, CommandError> {
+    log::info!("acknowledge_gift command received for gift {} in {}", gift_txid, conversation_id);
+    let gift = message_store
+        .load_conversation(&app, &identity_i_address, &conversation_id)
+        .into_iter()
+        .find(|m| m.id == gift_txid && m.direction == "received")
+        .ok_or_else(|| GiftAckError::UnknownGift(gift_txid.clone()))?;
+
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let ack = crate::gift_ack::acknowledge_gift(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        sender_identity,
+        gift_txid,
+        gift.amount,
+        note,
+        &index,
+    )
+    .await?;
+    persist_message_index(&app, &index);
+    Ok(ack)
+}
+
+// NEW Command: Send a short recorded opus clip (manifest + audio chunks in one transaction)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn send_voice_memo(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    audio: Vec<u8>,
+    duration_ms: u32,
+    identity_i_address: Option<String>, // When provided with conversation_id, the sent memo is recorded in the message store
+    conversation_id: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    index: tauri::State<'_, MessageIndex>,
+    message_store: tauri::State<'_, MessageStore>,
+) -> Result<VoiceMemoRecord, CommandError> {
+    log::info!("send_voice_memo command received: to={}, {} bytes, {} ms", recipient_z_address, audio.len(), duration_ms);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let sender = sender_identity.clone();
+    let (record, manifest_text) = crate::voice_memo::send_voice_memo(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        sender_identity,
+        audio,
+        duration_ms,
+        &index,
+    )
+    .await?;
+    persist_message_index(&app, &index);
+
+    if let (Some(identity), Some(conversation_id)) = (&identity_i_address, &conversation_id) {
+        record_sent_message(&app, &message_store, identity, conversation_id, &record.txid, sender, manifest_text, 0.0, None, None, None);
+    }
+    Ok(record)
+}
+
+// NEW Command: Post a poll (question + options) in a conversation. Returns the poll txid.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn create_poll(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    conversation_id: String,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    question: String,
+    options: Vec<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    message_store: tauri::State<'_, MessageStore>,
+) -> Result<String, CommandError> {
+    log::info!("create_poll command received for {} ({} options)", conversation_id, options.len());
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let sender = sender_identity.clone();
+    let (txid, text) = crate::polls::create_poll(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        sender_identity,
+        question,
+        options,
+    )
+    .await?;
+    record_sent_message(&app, &message_store, &identity_i_address, &conversation_id, &txid, sender, text, 0.0, None, None, None);
+    Ok(txid)
+}
+
+// NEW Command: Vote for an option of a poll in the conversation. Returns the vote txid.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn vote_in_poll(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    conversation_id: String,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    poll_txid: String,
+    choice: usize,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    message_store: tauri::State<'_, MessageStore>,
+) -> Result<String, CommandError> {
+    log::info!("vote_in_poll command received for poll {} (option {})", poll_txid, choice);
+    let messages = message_store.load_conversation(&app, &identity_i_address, &conversation_id);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    let sender = sender_identity.clone();
+    let (txid, text) = crate::polls::vote_in_poll(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        sender_identity,
+        poll_txid,
+        choice,
+        &messages,
+    )
+    .await?;
+    record_sent_message(&app, &message_store, &identity_i_address, &conversation_id, &txid, sender, text, 0.0, None, None, None);
+    Ok(txid)
+}
+
+// NEW Command: Expected fee and change of a planned message/gift, before confirming
+#[tauri::command]
+async fn estimate_send_fee(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    sender_identity: String,
+    amount: f64,
+    memo_text: String,
+    memo_count: Option<usize>, // Number of memos (chunked payloads); defaults to 1
+) -> Result<SendFeeEstimate, CommandError> {
+    log::info!("estimate_send_fee command received: amount={}", amount);
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::estimate_send_fee(
+        creds.rpc_user, creds.rpc_pass, creds.rpc_port, sender_z_address, sender_identity, amount, memo_text, memo_count.unwrap_or(1),
+    )
+    .await
+    .map_err(CommandError::from)
+}
+
+// NEW Command: Prepare a message/gift for signing on another machine. Returns the signing request to export.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn prepare_external_send(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    recipient_z_address: String,
+    memo_text: String,
+    sender_identity: String,
+    amount: f64,
+    fee: Option<f64>,
+    fee_preset: Option<FeePreset>,
+    identity_i_address: Option<String>, // When provided with conversation_id, the message is recorded once sent
+    conversation_id: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+) -> Result<SigningRequest, CommandError> {
+    log::info!("prepare_external_send command received: to={}, amount={}, sender_id={}", recipient_z_address, amount, sender_identity);
+    let fee = crate::message_rpc::resolve_fee(fee, fee_preset)?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    crate::external_signer::prepare_external_send(
+        creds.rpc_user,
+        creds.rpc_pass,
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        memo_text,
+        sender_identity,
+        amount,
+        fee,
+        identity_i_address,
+        conversation_id,
+    )
+    .await
+    .map_err(CommandError::from)
+}
+
+// NEW Command: Import the external signature of a prepared send and submit it. Returns the txid.
+#[tauri::command]
+async fn complete_external_send(
+    app: tauri::AppHandle,
+    request_id: String,
+    signature: String,
+    message_store: tauri::State<'_, MessageStore>,
+) -> Result<String, CommandError> {
+    log::info!("complete_external_send command received for request {}", request_id);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let (txid, send) = crate::external_signer::complete_external_send(creds.rpc_user, creds.rpc_pass, creds.rpc_port, request_id, signature).await?;
+    if let (Some(identity), Some(conversation_id)) = (&send.identity_i_address, &send.conversation_id) {
+        record_sent_message(
+            &app,
+            &message_store,
+            identity,
+            conversation_id,
+            &txid,
+            send.unsigned.sender_identity,
+            send.unsigned.memo_text,
+            send.amount,
+            None,
+            None,
+            None,
+        );
+    }
+    Ok(txid)
+}
+
+// NEW Command: Check a recipient private address against the connected chain (wrong-network addresses are rejected)
+#[tauri::command]
+async fn validate_recipient_address(app: tauri::AppHandle, address: String) -> Result<(), CommandError> {
+    log::info!("validate_recipient_address command received for {}", address);
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::address::validate_recipient_address(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &address)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Check Fast Messages capacity now and split a large note if below the target
+#[tauri::command]
+async fn run_utxo_maintenance(app: tauri::AppHandle, address: String) -> Result<UtxoSplitOutcome, CommandError> {
+    log::info!("run_utxo_maintenance command received for {}", address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    require_feature(creds.rpc_port, Feature::SendCurrency)?;
+    crate::utxo_maintenance::run_maintenance_now(&app, &creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &address)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Move transparent (and optionally coinbase) funds into the private address. Completion of each
+// operation arrives as a shield-operation event.
+#[tauri::command]
+async fn shield_funds(app: tauri::AppHandle, private_address: String, include_coinbase: Option<bool>) -> Result<ShieldResult, CommandError> {
+    log::info!("shield_funds command received for {}", private_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::address::validate_recipient_address(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &private_address).await?;
+    crate::shielding::shield_funds(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, private_address, include_coinbase.unwrap_or(true))
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: State of async wallet operations (shielding, splits) by opid
+#[tauri::command]
+async fn get_wallet_operation_status(app: tauri::AppHandle, opids: Vec<String>) -> Result<Vec<WalletOperationStatus>, CommandError> {
+    log::debug!("get_wallet_operation_status command received for {} operations", opids.len());
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::get_operation_status(creds.rpc_user, creds.rpc_pass, creds.rpc_port, opids)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Send an amount-only gift to a contact's transparent R-address. Returns the txid.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn send_transparent_gift(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    recipient_t_address: String,
+    sender_identity: String,
+    amount: f64,
+    fee: Option<f64>,
+    fee_preset: Option<FeePreset>,
+    identity_i_address: Option<String>, // When provided with conversation_id, the gift is recorded in the message store
+    conversation_id: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the identity owning the R-address is revoked
+    message_store: tauri::State<'_, MessageStore>,
+    index: tauri::State<'_, MessageIndex>,
+    identity_cache: tauri::State<'_, IdentityCache>,
+) -> Result<String, CommandError> {
+    log::info!("send_transparent_gift command received: to={}, amount={}", recipient_t_address, amount);
+    let fee = crate::message_rpc::resolve_fee(fee, fee_preset)?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    if !allow_revoked.unwrap_or(false) {
+        identity_cache
+            .ensure_transparent_recipient_active(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &recipient_t_address, conversation_id.as_deref())
+            .await?;
+    }
+    let txid = crate::message_rpc::send_transparent_gift(
+        creds.rpc_user.clone(),
+        creds.rpc_pass.clone(),
+        creds.rpc_port,
+        sender_z_address,
+        recipient_t_address,
+        amount,
+        fee,
+    )
+    .await
+    .map_err(CommandError::from)?;
+
+    let cost = match crate::message_rpc::get_transaction_cost(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &txid).await {
+        Ok(cost) => {
+            index.record_cost(&txid, amount, cost);
+            persist_message_index(&app, &index);
+            Some(cost)
+        }
+        Err(e) => {
+            log::warn!("Could not read fee/size of transparent gift {}: {:?}", txid, e);
+            None
+        }
+    };
+
+    if let (Some(identity), Some(conversation_id)) = (&identity_i_address, &conversation_id) {
+        let kind = Some(crate::settings::MessageKind::TransparentGift);
+        record_sent_message(&app, &message_store, identity, conversation_id, &txid, sender_identity, String::new(), amount, cost, kind, None);
+    }
+    Ok(txid)
+}
+
+// NEW command: all currencies held at an address (currency name -> amount)
+#[tauri::command]
+async fn get_currency_balances(
+    app: tauri::AppHandle,
+    address: String,
+    minconf: Option<u32>, // 0 includes pending funds; defaults to 1
+) -> Result<crate::wallet_rpc::CurrencyBalances, CommandError> {
+    log::info!("get_currency_balances command received for address: {}", address);
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::get_currency_balances(creds.rpc_user, creds.rpc_pass, creds.rpc_port, address, minconf.unwrap_or(1))
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW command: all currencies at an identity's private address (progressive loading, like get_identity_balance)
+#[tauri::command]
+async fn get_identity_currency_balances(
+    app: tauri::AppHandle,
+    private_address: String,
+    task_id: Option<String>,
+) -> Result<crate::wallet_rpc::CurrencyBalances, CommandError> {
+    log::info!("get_identity_currency_balances command received for address: {}", private_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    run_cancellable(&app, task_id, "balance", async {
+        crate::identity_rpc::get_identity_currency_balances(creds.rpc_user, creds.rpc_pass, creds.rpc_port, private_address)
+            .await
+            .map_err(CommandError::from)
+    })
+    .await
+}
+
+// NEW Command: Send a signed message with a gift in any currency the chain knows (sendcurrency). Returns the txid.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn send_currency_gift(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    recipient_z_address: String,
+    memo_text: String,
+    sender_identity: String,
+    currency: String,
+    amount: f64,
+    fee: Option<f64>,
+    fee_preset: Option<FeePreset>,
+    identity_i_address: Option<String>, // When provided with conversation_id, the gift is recorded in the message store
+    conversation_id: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+    message_store: tauri::State<'_, MessageStore>,
+    index: tauri::State<'_, MessageIndex>,
+) -> Result<String, CommandError> {
+    log::info!("send_currency_gift command received: to={}, amount={} {}", recipient_z_address, amount, currency);
+    let fee = crate::message_rpc::resolve_fee(fee, fee_preset)?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    require_feature(creds.rpc_port, Feature::SendCurrency)?;
+    let (opid, currency) = crate::message_rpc::send_currency_gift(
+        creds.rpc_user.clone(),
+        creds.rpc_pass.clone(),
+        creds.rpc_port,
+        sender_z_address,
+        recipient_z_address,
+        memo_text.clone(),
+        sender_identity.clone(),
+        currency,
+        amount,
+        fee,
+    )
+    .await
+    .map_err(CommandError::from)?;
+    // sendcurrency is asynchronous; the txid is only known once the proof is built
+    let txid = crate::wallet_rpc::wait_for_operation(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &opid, std::time::Duration::from_secs(120))
+        .await
+        .map_err(CommandError::from)?;
+
+    let cost = match crate::message_rpc::get_transaction_cost(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &txid).await {
+        Ok(cost) => {
+            index.record_cost(&txid, amount, cost);
+            persist_message_index(&app, &index);
+            Some(cost)
+        }
+        Err(e) => {
+            log::warn!("Could not read fee/size of currency gift {}: {:?}", txid, e);
+            None
+        }
+    };
+
+    if let (Some(identity), Some(conversation_id)) = (&identity_i_address, &conversation_id) {
+        record_sent_message(&app, &message_store, identity, conversation_id, &txid, sender_identity, memo_text, amount, cost, None, Some(currency));
+    }
+    Ok(txid)
+}
+
+// NEW Command: Wallet-wide balance totals (transparent, private, unconfirmed) in one call
+#[tauri::command]
+async fn get_balance_summary(app: tauri::AppHandle) -> Result<crate::wallet_rpc::BalanceSummary, CommandError> {
+    log::info!("get_balance_summary command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::get_balance_summary(creds.rpc_user, creds.rpc_pass, creds.rpc_port)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Create a new private (sapling) address in the wallet
+#[tauri::command]
+async fn create_private_address(
+    app: tauri::AppHandle,
+    identity: Option<String>, // Identity the address is meant for (returns the updateidentity call to attach it)
+) -> Result<crate::wallet_rpc::NewPrivateAddress, CommandError> {
+    log::info!("create_private_address command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::wallet_rpc::create_private_address(creds.rpc_user, creds.rpc_pass, creds.rpc_port, identity)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Inputs, outputs, decryptable memos, fee and block of a wallet transaction
+#[tauri::command]
+async fn get_transaction_details(app: tauri::AppHandle, txid: String) -> Result<crate::transaction_rpc::TransactionDetails, CommandError> {
+    log::info!("get_transaction_details command received for tx: {}", txid);
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::transaction_rpc::get_transaction_details(creds.rpc_user, creds.rpc_pass, creds.rpc_port, txid)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Daemon sync progress (blocks vs headers, verification progress, time remaining)
+#[tauri::command]
+async fn get_sync_status(app: tauri::AppHandle) -> Result<crate::sync_status::SyncStatus, CommandError> {
+    log::debug!("get_sync_status command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::sync_status::get_sync_status(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Emit sync-progress events until the daemon has caught up
+#[tauri::command]
+async fn start_sync_watcher(app: tauri::AppHandle) -> Result<(), CommandError> {
+    log::info!("start_sync_watcher command received");
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::sync_status::start_sync_watcher(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port);
+    Ok(())
+}
+
+// NEW Command: Network / peer overview (warns when the daemon has no peers)
+#[tauri::command]
+async fn get_network_status(app: tauri::AppHandle) -> Result<crate::network_rpc::NetworkStatus, CommandError> {
+    log::info!("get_network_status command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::network_rpc::get_network_status(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Rescan the wallet from a block height (progress via wallet-rescan events)
+#[tauri::command]
+async fn rescan_wallet(
+    app: tauri::AppHandle,
+    from_height: u64,
+    private_addresses: Option<Vec<String>>, // Addresses whose messages should be re-polled afterwards
+) -> Result<crate::rescan::RescanProgress, CommandError> {
+    log::info!("rescan_wallet command received (from height {})", from_height);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::rescan::rescan_wallet(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, from_height, private_addresses.unwrap_or_default())
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Consolidation plan for dust notes of an address (nothing is sent)
+#[tauri::command]
+async fn suggest_utxo_cleanup(app: tauri::AppHandle, address: String) -> Result<crate::utxo_maintenance::UtxoCleanupPlan, CommandError> {
+    log::info!("suggest_utxo_cleanup command received for {}", address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::utxo_maintenance::suggest_utxo_cleanup(&app, &creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &address)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Preview how much of another currency an amount converts to (optionally via a basket)
+#[tauri::command]
+async fn estimate_conversion(
+    app: tauri::AppHandle,
+    from_currency: String,
+    to_currency: String,
+    via: Option<String>,
+    amount: f64,
+) -> Result<crate::price::ConversionEstimate, CommandError> {
+    log::info!("estimate_conversion command received: {} {} -> {} (via {:?})", amount, from_currency, to_currency, via);
+    let creds = crate::credentials::load_credentials(app).await?;
+    require_feature(creds.rpc_port, Feature::Conversions)?;
+    crate::price::estimate_conversion(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &from_currency, &to_currency, via.as_deref(), amount)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Gift a different currency than the one spent (sendcurrency convertto / via). Returns the opid;
+// completion arrives as a conversion-gift event.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command arguments map 1:1 to frontend invoke parameters
+async fn send_conversion_gift(
+    app: tauri::AppHandle,
+    sender_z_address: String,
+    sender_identity: String,
+    recipient_z_address: String,
+    recipient_identity: String,
+    memo_text: String,
+    from_currency: String,
+    to_currency: String,
+    via: Option<String>,
+    amount: f64,
+    fee: Option<f64>,
+    fee_preset: Option<FeePreset>,
+    identity_i_address: Option<String>, // When provided with conversation_id, the gift is recorded once sent
+    conversation_id: Option<String>,
+    allow_revoked: Option<bool>, // Send even if the recipient's identity is revoked
+) -> Result<crate::conversion::ConversionGiftStarted, CommandError> {
+    log::info!("send_conversion_gift command received: {} {} -> {} for {}", amount, from_currency, to_currency, recipient_identity);
+    let fee = crate::message_rpc::resolve_fee(fee, fee_preset)?;
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    ensure_recipient_active(&app, &creds, &recipient_z_address, allow_revoked).await?;
+    require_feature(creds.rpc_port, Feature::SendCurrency)?;
+    require_feature(creds.rpc_port, Feature::Conversions)?;
+    let request = crate::conversion::ConversionGiftRequest {
+        sender_z_address,
+        sender_identity,
+        recipient_z_address,
+        recipient_identity,
+        memo_text,
+        from_currency,
+        to_currency,
+        via,
+        amount,
+        fee,
+        identity_i_address,
+        conversation_id,
+    };
+    crate::conversion::send_conversion_gift(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, request)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Look up missing network fees of sent messages now. Returns how many were filled in.
+#[tauri::command]
+async fn refresh_sent_fees(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    conversation_id: Option<String>, // All conversations when omitted
+    message_store: tauri::State<'_, MessageStore>,
+) -> Result<usize, CommandError> {
+    log::info!("refresh_sent_fees command received (user {})", identity_i_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let conversation_ids = match conversation_id {
+        Some(id) => vec![id],
+        None => crate::settings::read_conversations(&app, &identity_i_address)?.into_iter().map(|c| c.id).collect(),
+    };
+    let filled = crate::gift_ledger::backfill_sent_fees(&app, &message_store, &creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &identity_i_address, conversation_ids).await?;
+    Ok(filled)
+}
+
+// NEW Command: Check a VerusID name and show what registering it costs
+#[tauri::command]
+async fn preview_identity_registration(
+    app: tauri::AppHandle,
+    name: String,
+    referral: Option<String>,
+) -> Result<crate::identity_registration::RegistrationPreview, CommandError> {
+    log::info!("preview_identity_registration command received for {}", name);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::identity_registration::preview_identity_registration(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &name, None, referral.as_deref())
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Register a new VerusID (name commitment now, registration once it confirmed). Progress arrives as
+// identity-registration events.
+#[tauri::command]
+async fn start_identity_registration(
+    app: tauri::AppHandle,
+    name: String,
+    referral: Option<String>,
+    primary_address: Option<String>, // A new wallet address when omitted
+) -> Result<crate::identity_registration::RegistrationProgress, CommandError> {
+    log::info!("start_identity_registration command received for {}", name);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let request = crate::identity_registration::RegistrationRequest {
+        name,
+        parent: None,
+        referral,
+        primary_address,
+        attach_private_address: true,
+    };
+    crate::identity_registration::start_identity_registration(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, request)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Continue registrations interrupted by a restart. Returns the names being resumed.
+#[tauri::command]
+async fn resume_identity_registrations(app: tauri::AppHandle) -> Result<Vec<String>, CommandError> {
+    log::info!("resume_identity_registrations command received");
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    Ok(crate::identity_registration::resume_identity_registrations(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port))
+}
+
+// NEW Command: Check a sub-identity name (name.parent@) under an identity the wallet controls and show its cost
+#[tauri::command]
+async fn preview_sub_identity_registration(
+    app: tauri::AppHandle,
+    parent: String,
+    name: String,
+) -> Result<crate::identity_registration::RegistrationPreview, CommandError> {
+    log::info!("preview_sub_identity_registration command received for {} under {}", name, parent);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::identity_registration::preview_identity_registration(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &name, Some(&parent), None)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Register a sub-identity (e.g. a purpose-specific chat identity). Progress arrives as
+// identity-registration events, like top-level registrations.
+#[tauri::command]
+async fn register_sub_identity(
+    app: tauri::AppHandle,
+    parent: String,
+    name: String,
+    primary_address: Option<String>, // A new wallet address when omitted
+    attach_private_address: bool,    // Give the sub-identity its own new private address (needed for chatting)
+) -> Result<crate::identity_registration::RegistrationProgress, CommandError> {
+    log::info!("register_sub_identity command received for {} under {}", name, parent);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let request = crate::identity_registration::RegistrationRequest {
+        name,
+        parent: Some(parent),
+        referral: None,
+        primary_address,
+        attach_private_address,
+    };
+    crate::identity_registration::start_identity_registration(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, request)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: A contact's profile as published in their identity (for the conversation header)
+#[tauri::command]
+async fn get_identity_profile(
+    app: tauri::AppHandle,
+    identity: String,
+) -> Result<crate::profile::IdentityProfile, CommandError> {
+    log::info!("get_identity_profile command received for {}", identity);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::profile::get_identity_profile(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &identity)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Size and fee of publishing a profile update (nothing is sent)
+#[tauri::command]
+async fn preview_profile_publish(
+    app: tauri::AppHandle,
+    identity: String,
+    display_name: Option<String>, // None keeps the current value, "" removes it
+    avatar_url: Option<String>,
+    avatar_hash: Option<String>,
+    status: Option<String>,
+) -> Result<crate::profile::ProfilePublishPreview, CommandError> {
+    log::info!("preview_profile_publish command received for {}", identity);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let update = ProfileUpdate { display_name, avatar_url, avatar_hash, status };
+    crate::profile::preview_profile_publish(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &identity, &update)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Publish the own profile into the identity's contentmultimap. Returns the txid; confirmation
+// arrives as profile-publish events.
+#[tauri::command]
+async fn publish_profile(
+    app: tauri::AppHandle,
+    identity: String,
+    display_name: Option<String>, // None keeps the current value, "" removes it
+    avatar_url: Option<String>,
+    avatar_hash: Option<String>,
+    status: Option<String>,
+) -> Result<String, CommandError> {
+    log::info!("publish_profile command received for {}", identity);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let update = ProfileUpdate { display_name, avatar_url, avatar_hash, status };
+    crate::profile::publish_profile(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, identity, update)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: (Re)start the contact watcher with a custom interval. warm_identity_cache starts it with the default.
+#[tauri::command]
+async fn start_contact_watcher(app: tauri::AppHandle, identity_i_address: String, interval_secs: Option<u64>) -> Result<(), CommandError> {
+    log::info!("start_contact_watcher command received for {}", identity_i_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    let interval = interval_secs.map(std::time::Duration::from_secs);
+    crate::contact_watcher::start_contact_watcher(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, identity_i_address, interval);
+    Ok(())
+}
+
+// NEW Command: Resolve i-address to formatted VerusID name (falls back to the address)
+#[tauri::command]
+async fn resolve_identity_address(
+    app: tauri::AppHandle,
+    i_address: String,
+) -> Result<String, CommandError> {
+    log::debug!("resolve_identity_address command received for: {}", i_address);
+    let creds = crate::credentials::load_credentials(app).await?;
+    Ok(crate::identity_rpc::resolve_identity_address(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &i_address).await)
+}
+
+// NEW Command: Resolve several i-addresses at once (i-address -> formatted name)
+#[tauri::command]
+async fn resolve_identity_addresses(
+    app: tauri::AppHandle,
+    i_addresses: Vec<String>,
+) -> Result<std::collections::HashMap<String, String>, CommandError> {
+    log::debug!("resolve_identity_addresses command received for {} addresses", i_addresses.len());
+    let creds = crate::credentials::load_credentials(app).await?;
+    let mut names = std::collections::HashMap::new();
+    for i_address in i_addresses {
+        if names.contains_key(&i_address) {
+            continue;
+        }
+        let name = crate::identity_rpc::resolve_identity_address(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &i_address).await;
+        names.insert(i_address, name);
+    }
+    Ok(names)
+}
+
+// NEW Command: Open a session for a logged-in identity (several can be open; see switch_active_identity)
+#[tauri::command]
+async fn open_identity_session(
+    app: tauri::AppHandle,
+    identity_i_address: String,
+    formatted_name: String,
+    private_address: String,
+    activate: Option<bool>, // Make it the active identity (the first session always is)
+) -> Result<crate::sessions::IdentitySession, CommandError> {
+    log::info!("open_identity_session command received for {}", identity_i_address);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::contact_watcher::start_contact_watcher(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, identity_i_address.clone(), None);
+    crate::integrity::run_startup_audit_once(&app, identity_i_address.clone(), private_address.clone());
+    Ok(crate::sessions::open_session(&app, identity_i_address, formatted_name, private_address, activate.unwrap_or(true)))
+}
+
+// NEW Command: Create a VerusPay invoice (payment request) to send to a chat partner or show as QR code
+#[tauri::command]
+async fn create_invoice(app: tauri::AppHandle, request: InvoiceRequest) -> Result<Invoice, CommandError> {
+    log::info!("create_invoice command received for {}", request.recipient);
+    let creds = crate::credentials::load_credentials(app).await?;
+    let chain_currency = crate::capabilities::active_capabilities(creds.rpc_port).map(|c| c.chain_name).unwrap_or_else(|| "VRSC".to_string());
+    crate::invoice::create_invoice(request, &chain_currency).map_err(CommandError::from)
+}
+
+// NEW Command: Parse a pasted or scanned VerusPay invoice into gift send fields
+#[tauri::command]
+async fn parse_invoice(app: tauri::AppHandle, payload: String) -> Result<ParsedInvoice, CommandError> {
+    log::info!("parse_invoice command received");
+    let creds = crate::credentials::load_credentials(app).await?;
+    let chain_currency = crate::capabilities::active_capabilities(creds.rpc_port).map(|c| c.chain_name).unwrap_or_else(|| "VRSC".to_string());
+    crate::invoice::parse_invoice(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &payload, &chain_currency)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Metadata of a currency (by i-address or name)
+#[tauri::command]
+async fn get_currency_info(app: tauri::AppHandle, currency: String) -> Result<crate::currency::CurrencyInfo, CommandError> {
+    log::debug!("get_currency_info command received for {}", currency);
+    let creds = crate::credentials::load_credentials(app).await?;
+    crate::currency::get_currency_info(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &currency)
+        .await
+        .map_err(CommandError::from)
+}
+
+// NEW Command: Display names of several currencies (i-address -> name; unknown ones map to themselves)
+#[tauri::command]
+async fn resolve_currency_names(
+    app: tauri::AppHandle,
+    currencies: Vec<String>,
+) -> Result<std::collections::HashMap<String, String>, CommandError> {
+    log::debug!("resolve_currency_names command received for {} currencies", currencies.len());
+    let creds = crate::credentials::load_credentials(app).await?;
+    let mut names = std::collections::HashMap::new();
+    for currency in currencies {
+        if names.contains_key(&currency) {
+            continue;
+        }
+        let name = crate::currency::currency_display_name(&creds.rpc_user, &creds.rpc_pass, creds.rpc_port, &currency).await;
+        names.insert(currency, name);
+    }
+    Ok(names)
+}
+
+// NEW Command: Subscribe to the daemon's ZMQ notifications (new blocks / transactions) so refreshes are push-driven.
+// endpoint (e.g. "tcp://127.0.0.1:28332") defaults to what the daemon reports; without ZMQ, polling stays timer-based.
+#[tauri::command]
+async fn start_zmq_listener(app: tauri::AppHandle, endpoint: Option<String>) -> Result<(), CommandError> {
+    log::info!("start_zmq_listener command received (endpoint: {:?})", endpoint);
+    let creds = crate::credentials::load_credentials(app.clone()).await?;
+    crate::zmq_listener::start_zmq_listener(&app, creds.rpc_user, creds.rpc_pass, creds.rpc_port, endpoint);
+    Ok(())
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // TODO: Initialize logger here instead of in command
+    env_logger::init(); // Basic logger initialization
+    crate::error_log::install_panic_hook(); // Panics are captured for get_recent_errors
+
+    let store_plugin = tauri_plugin_store::Builder::default().build(); // Build the store plugin instance
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(store_plugin) // Register the store plugin instance
+        .manage(TaskRegistry::default()) // Registry of cancellable tasks
+        .manage(NotificationState::default()) // Notification dedupe state
+        .manage(MessageStore::default()) // Canonical per-conversation message lists
+        .setup(|app| {
+            log::info!("Setting up Tauri application");
+            crate::error_log::init_crash_log(app.handle());
+            
+            // Create the main window programmatically for all platforms
+            use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+
+            // Load the persisted signature verification cache
+            app.manage(VerificationCache::load(app.handle()));
+            app.manage(MessageIndex::load(app.handle()));
+            app.manage(IdentityCache::load(app.handle()));
+
+            // Chains from installed profile bundles must be known before detection runs
+            crate::chain_profiles::load_installed(app.handle());
+            // Blocked senders are filtered from the first poll onwards
+            crate::blocklist::load_blocklist(app.handle());
+            crate::clock::load_clock_settings(app.handle());
+            crate::protocol::load_peer_versions(app.handle());
+            // Persisted messages are audited against the chain once per identity after startup
+            crate::integrity::arm_startup_audit(app.handle());
+            crate::rpc_timeouts::load_rpc_timeouts(app.handle());
+            crate::response_cache::load_response_cache_ttl(app.handle());
+            crate::ssh_tunnel::load_ssh_tunnel_config(app.handle());
+            crate::rpc_client::load_rpc_concurrency_limit(app.handle());
+            
+            #[cfg(target_os = "macos")]
+            {
+                use tauri::TitleBarStyle;
+                
+                let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
+                    .title("Nymia")
+                    .inner_size(900.0, 600.0)
+                    .visible(true)
+                    .resizable(true)
+                    .title_bar_style(TitleBarStyle::Transparent)
+                    .hidden_title(true)
+                    .accept_first_mouse(true);
+                
+                let window = win_builder.build()?;
+                
+                // Set custom almost black background
+                set_macos_window_background(&window);
+                
+                log::info!("macOS window created with transparent titlebar and custom background");
+            }
+            
+            // For other platforms, create a standard window
+            #[cfg(not(target_os = "macos"))]
+            {
+                let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::default())
+                    .title("Nymia")
+                    .inner_size(900.0, 600.0)
+                    .visible(true)
+                    .resizable(true);
+                
+                let _window = win_builder.build()?;
+                
+                log::info!("Standard window created for non-macOS platform");
+            }
+            
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            connect_verus_daemon,
+            crate::credentials::save_credentials, // Add credential commands
+            crate::credentials::load_credentials,
+            crate::credentials::clear_credentials,
+            crate::credentials::detect_all_blockchains, // NEW: Parallel detection
+            crate::credentials::select_folder_dialog, // NEW: Folder selection
+            crate::credentials::detect_blockchain_from_path, // NEW: Custom path detection
+            get_login_identities_fast, // NEW: Fast loading without balances
+            get_login_identities, // Correct name used here
+            get_identity_balance, // NEW: Individual balance fetching
+            get_private_balance, // Add the new balance command
+            get_pending_balance, // Add the new pending balance command
+            check_identity_eligibility,
+            get_chat_history,
+            get_new_received_messages,
+            send_private_message, // Added send message command
+            // New Settings Commands
+            crate::settings::save_persistence_setting,
+            crate::settings::load_persistence_setting,
+            crate::settings::save_conversations,
+            crate::settings::load_conversations,
+            crate::settings::archive_conversation,
+            crate::settings::unarchive_conversation,
+            crate::settings::save_messages_for_conversation,
+            crate::settings::load_messages_for_conversation,
+            crate::settings::set_last_read,
+            crate::settings::get_last_read,
+            crate::settings::get_poll_tallies,
+            crate::settings::mark_all_read,
+            crate::settings::delete_chat_data,
+            get_utxo_info,
+            list_notes,
+            crate::notes::set_note_reservation,
+            // Formatting Commands
+            crate::formatting::format_timestamp,
+            crate::formatting::format_amount,
+            // Protocol Commands
+            crate::protocol::get_protocol_info,
+            crate::protocol::get_peer_protocol_version,
+            // Task Commands
+            crate::tasks::cancel_task,
+            // Payment Proof Commands
+            create_payment_proof,
+            verify_payment_proof,
+            get_daemon_load_status,
+            // Notification Settings Commands
+            crate::settings::save_conversation_mute,
+            crate::settings::load_conversation_mute,
+            crate::settings::reset_sync_cursor,
+            // Onboarding Commands
+            crate::onboarding::get_onboarding_state,
+            crate::onboarding::advance_onboarding,
+            // Exchange Rate Commands
+            get_conversion_rate,
+            backfill_gift_rates,
+            crate::message_index::get_message_annotations,
+            // Message Store Commands
+            crate::message_store::get_conversation_messages,
+            // Identity Filter Commands
+            crate::settings::save_identity_filter_rules,
+            crate::settings::load_identity_filter_rules,
+            recover_sent_messages,
+            // Wallet Encryption Commands
+            get_wallet_encryption_status,
+            encrypt_wallet,
+            unlock_wallet,
+            lock_wallet,
+            run_integrity_audit,
+            crate::integrity::get_startup_integrity_audit,
+            crate::integrity::set_startup_integrity_audit,
+            // Draft Commands
+            crate::settings::save_draft,
+            crate::settings::load_draft,
+            preview_send,
+            open_conversation,
+            warm_identity_cache,
+            // Export Commands
+            crate::export::export_conversation,
+            crate::export::import_conversation,
+            // File Request Commands
+            send_file_request,
+            send_file_response,
+            crate::file_request::get_file_requests,
+            crate::file_request::save_file_attachment,
+            get_chain_capabilities,
+            // Anti-spam Commands
+            crate::settings::save_spam_rules,
+            crate::settings::load_spam_rules,
+            crate::spam::get_message_requests,
+            crate::spam::accept_message_request,
+            crate::spam::get_unknown_sender_requests,
+            crate::spam::dismiss_message_request,
+            // Chain Profile Commands
+            crate::chain_profiles::load_chain_profile_bundle,
+            crate::chain_profiles::get_chain_profile_bundles,
+            crate::chain_profiles::remove_chain_profile_bundle,
+            // Activity Commands
+            crate::activity::get_conversation_activity,
+            // Blocklist Commands
+            crate::blocklist::get_blocked_senders,
+            crate::blocklist::block_sender,
+            crate::blocklist::unblock_sender,
+            // Gift Acknowledgment Commands
+            acknowledge_gift,
+            crate::message_index::get_gift_acks,
+            // Diagnostics Commands
+            crate::error_log::get_recent_errors,
+            crate::error_log::clear_recent_errors,
+            // API Compatibility Commands
+            crate::api_version::api_handshake,
+            // Clock Commands
+            crate::clock::get_clock_status,
+            crate::clock::set_timestamp_correction,
+            // Emergency Wipe
+            crate::wipe::emergency_wipe,
+            // Voice Memo Commands
+            send_voice_memo,
+            crate::voice_memo::get_voice_memo_audio,
+            // Poll Commands
+            create_poll,
+            vote_in_poll,
+            // Notification Preview Commands
+            crate::notifications::get_notification_preview,
+            crate::notifications::set_notification_preview,
+            // Presence Commands
+            crate::presence::get_contact_presence,
+            estimate_send_fee,
+            // External Signing Commands
+            prepare_external_send,
+            complete_external_send,
+            crate::external_signer::cancel_external_send,
+            validate_recipient_address,
+            // UTXO Maintenance Commands
+            run_utxo_maintenance,
+            crate::utxo_maintenance::get_utxo_split_settings,
+            crate::utxo_maintenance::set_utxo_split_settings,
+            crate::message_store::merge_conversations,
+            shield_funds,
+            get_wallet_operation_status,
+            send_transparent_gift,
+            get_currency_balances,
+            get_identity_currency_balances,
+            send_currency_gift,
+            get_balance_summary,
+            create_private_address,
+            get_transaction_details,
+            get_sync_status,
+            start_sync_watcher,
+            crate::sync_status::stop_sync_watcher,
+            get_network_status,
+            rescan_wallet,
+            suggest_utxo_cleanup,
+            estimate_conversion,
+            send_conversion_gift,
+            crate::gift_ledger::get_gift_summary,
+            refresh_sent_fees,
+            crate::gift_ledger::get_fee_summary,
+            crate::balance_alerts::get_balance_alert_settings,
+            crate::balance_alerts::set_balance_alert_settings,
+            crate::auto_topup::get_auto_topup_settings,
+            crate::auto_topup::set_auto_topup_settings,
+            crate::auto_topup::get_auto_topup_history,
+            preview_identity_registration,
+            start_identity_registration,
+            resume_identity_registrations,
+            crate::identity_registration::get_pending_identity_registrations,
+            preview_sub_identity_registration,
+            register_sub_identity,
+            get_identity_profile,
+            preview_profile_publish,
+            publish_profile,
+            start_contact_watcher,
+            crate::contact_watcher::stop_contact_watcher,
+            resolve_identity_address,
+            resolve_identity_addresses,
+            open_identity_session,
+            crate::sessions::list_identity_sessions,
+            crate::sessions::get_active_identity,
+            crate::sessions::switch_active_identity,
+            crate::sessions::close_identity_session,
+            create_invoice,
+            parse_invoice,
+            get_currency_info,
+            resolve_currency_names,
+            crate::identity_watcher::stop_identity_watcher,
+            crate::rpc_timeouts::get_rpc_timeouts,
+            crate::rpc_timeouts::set_rpc_timeout,
+            crate::response_cache::get_response_cache_ttl,
+            crate::response_cache::set_response_cache_ttl,
+            crate::ssh_tunnel::get_ssh_tunnel,
+            crate::ssh_tunnel::set_ssh_tunnel,
+            crate::ssh_tunnel::restart_ssh_tunnel,
+            crate::rpc_diagnostics::get_rpc_diagnostics,
+            crate::rpc_diagnostics::reset_rpc_diagnostics,
+            crate::rpc_client::get_rpc_concurrency_limit,
+            crate::rpc_client::set_rpc_concurrency_limit,
+            start_zmq_listener,
+            crate::zmq_listener::get_zmq_status,
+            crate::zmq_listener::stop_zmq_listener
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, event| {
+            // Don't leave the SSH tunnel's ssh process running after the app
+            if let tauri::RunEvent::Exit = event {
+                crate::ssh_tunnel::shutdown_ssh_tunnel();
+            }
+        });
 }
@@ -0,0 +1,90 @@
+// File: src-tauri/src/memo_codec.rs
+// Description: Encodes and decodes raw memo payloads for shielded transactions.
+// Changes:
+// - Created file with optional deflate compression of memo payloads.
+// - Compressed memos are prefixed with a flag byte (0xFF) and a codec byte so receivers can detect them.
+// - Plain memos that already fit are sent unchanged to stay readable by older clients.
+
+use std::io::{Read, Write};
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use super::rpc_client::VerusRpcError;
+
+// Maximum size of a sapling memo field in bytes
+pub const MEMO_MAX_BYTES: usize = 512;
+
+// Flag byte marking a compressed memo. Per ZIP-302 a first byte of 0xFF means
+// "non-text data follows", so the daemon never renders these as memostr text.
+const COMPRESSED_MEMO_FLAG: u8 = 0xFF;
+
+// Codec identifiers following the flag byte
+const CODEC_DEFLATE: u8 = 0x01;
+// 0x02 is reserved for zstd
+
+// Upper bound for decompressed output (protects against decompression bombs)
+const MAX_DECOMPRESSED_BYTES: u64 = 16 * 1024;
+
+// Encode the full memo string into the bytes that will be hex encoded for z_sendmany.
+// Memos that fit are returned as-is; longer memos are deflate compressed.
+pub fn encode_memo(memo: &str) -> Result<Vec<u8>, VerusRpcError> {
+    let raw = memo.as_bytes();
+    if raw.len() <= MEMO_MAX_BYTES {
+        return Ok(raw.to_vec());
+    }
+
+    log::debug!("Memo is {} bytes, attempting compression", raw.len());
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(raw)
+        .and_then(|_| encoder.finish())
+        .map(|compressed| {
+            let mut payload = Vec::with_capacity(compressed.len() + 2);
+            payload.push(COMPRESSED_MEMO_FLAG);
+            payload.push(CODEC_DEFLATE);
+            payload.extend_from_slice(&compressed);
+            payload
+        })
+        .map_err(|e| VerusRpcError::ParseError(format!("Failed to compress memo: {}", e)))
+        .and_then(|payload| {
+            if payload.len() > MEMO_MAX_BYTES {
+                log::warn!("Memo still too long after compression: {} bytes", payload.len());
+                Err(VerusRpcError::MemoTooLong(payload.len()))
+            } else {
+                log::info!("Compressed memo from {} to {} bytes", raw.len(), payload.len());
+                Ok(payload)
+            }
+        })
+}
+
+// Resolve the text of a received memo.
+// Uses the hex memo when it carries the compression flag, otherwise falls back to memostr.
+pub fn decode_memo(memostr: Option<&str>, memo_hex: Option<&str>) -> Option<String> {
+    if let Some(bytes) = memo_hex.and_then(|h| hex::decode(h).ok()) {
+        if bytes.len() > 2 && bytes[0] == COMPRESSED_MEMO_FLAG {
+            return decompress_payload(bytes[1], &bytes[2..]);
+        }
+    }
+    memostr.map(|s| s.to_string())
+}
+
+// Decompress a flagged payload for the given codec
+fn decompress_payload(codec: u8, data: &[u8]) -> Option<String> {
+    match codec {
+        CODEC_DEFLATE => {
+            // Trailing zero padding of the memo field is ignored by the decoder
+            let mut text = String::new();
+            match DeflateDecoder::new(data).take(MAX_DECOMPRESSED_BYTES).read_to_string(&mut text) {
+                Ok(_) => Some(text),
+                Err(e) => {
+                    log::warn!("Failed to decompress memo payload: {}", e);
+                    None
+                }
+            }
+        }
+        _ => {
+            log::debug!("Unsupported memo codec: {:#04x}", codec);
+            None
+        }
+    }
+}
@@ -0,0 +1,318 @@
+// File: src-tauri/src/message_index.rs
+// Description: Persisted per-transaction metadata for messages (e.g., exchange rate at receipt).
+// Changes:
+// - Created file with MessageIndex (managed state) backed by message_index.json.
+// - Gifts are annotated with the conversion rate at ingestion time; backfill_gift_rates fills in older history
+//   using the basket state at the block the gift was mined in.
+// - Tracks file request / response linkage by request id (see file_request.rs).
+// - Gift annotation is skipped on chains without conversion support.
+// - Stores gift acknowledgments by gift txid (see gift_ack.rs); get_gift_acks looks them up.
+// - Entries carry the fee and serialized size of our outgoing transactions.
+// - Tracks voice memos (manifest and audio chunks) by memo id (see voice_memo.rs).
+// - Added update_existing_file_request (file responses and chunks only ever update a request we sent).
+// - Added clear (emergency wipe drops the in-memory state so nothing is written back).
+// - Dropped the private now_secs in favour of clock::now_secs.
+// - The index stays dirty after a failed save.
+// - backfill_gift_rates logs gifts whose rate lookup fails and carries on with the rest.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Runtime, State};
+use super::capabilities::{supports, Feature};
+use super::file_request::FileRequestRecord;
+use super::gift_ack::GiftAck;
+use super::message_rpc::{ChatMessage, TxCost};
+use super::price::{get_conversion_rate, PriceQuote, DEFAULT_BASE_CURRENCY, DEFAULT_PRICE_BASKET, DEFAULT_QUOTE_CURRENCY};
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::storage::{load_value, save_value, StorageError};
+use super::voice_memo::VoiceMemoRecord;
+use super::clock::now_secs;
+
+const INDEX_STORE_PATH: &str = "message_index.json";
+const INDEX_KEY: &str = "messages";
+
+// Approximate value of a gift when it was received
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RateSnapshot {
+    pub quote_currency: String,
+    pub rate: f64,                 // Quote currency per unit of the gifted currency
+    pub value: f64,                // amount * rate
+    pub block_height: Option<u64>, // Basket state height the rate was read at
+    pub recorded_at: u64,          // Unix seconds when the snapshot was taken
+    pub backfilled: bool,          // Reconstructed later from historical chain state
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageIndexEntry {
+    pub txid: String,
+    pub amount: f64,
+    pub rate: Option<RateSnapshot>,
+    #[serde(default)]
+    pub cost: Option<TxCost>, // Outgoing transactions only
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct IndexState {
+    entries: HashMap<String, MessageIndexEntry>, // txid -> entry
+    #[serde(default)]
+    file_requests: HashMap<String, FileRequestRecord>, // request id -> request/response linkage
+    #[serde(default)]
+    gift_acks: HashMap<String, GiftAck>, // gift txid -> acknowledgment
+    #[serde(default)]
+    voice_memos: HashMap<String, VoiceMemoRecord>, // memo id -> manifest and chunks
+    #[serde(skip)]
+    dirty: bool,
+}
+
+// Cheap to clone; all clones share the same entries
+#[derive(Clone, Default)]
+pub struct MessageIndex {
+    inner: Arc<Mutex<IndexState>>,
+}
+
+// Lean view of z_listreceivedbyaddress entries for backfilling
+#[derive(Deserialize, Debug)]
+struct ReceivedAmount {
+    txid: String,
+    amount: f64,
+    confirmations: i64,
+}
+
+impl MessageIndex {
+    // Load the index from disk (empty if missing or unreadable)
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let state = match load_value::<R, IndexState>(app, INDEX_STORE_PATH, INDEX_KEY) {
+            Ok(Some(state)) => {
+                log::info!("Loaded message index with {} entries", state.entries.len());
+                state
+            }
+            Ok(None) => IndexState::default(),
+            Err(e) => {
+                log::warn!("Failed to load message index, starting empty: {}", e);
+                IndexState::default()
+            }
+        };
+        MessageIndex { inner: Arc::new(Mutex::new(state)) }
+    }
+
+    pub fn get(&self, txid: &str) -> Option<MessageIndexEntry> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.get(txid).cloned()
+    }
+
+    fn has_rate(&self, txid: &str) -> bool {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.get(txid).is_some_and(|e| e.rate.is_some())
+    }
+
+    fn record_rate(&self, txid: &str, amount: f64, quote: &PriceQuote, backfilled: bool) {
+        let snapshot = RateSnapshot {
+            quote_currency: quote.quote_currency.clone(),
+            rate: quote.rate,
+            value: amount * quote.rate,
+            block_height: quote.block_height,
+            recorded_at: now_secs(),
+            backfilled,
+        };
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state.entries.entry(txid.to_string()).or_insert_with(|| MessageIndexEntry {
+            txid: txid.to_string(),
+            amount,
+            rate: None,
+            cost: None,
+        });
+        entry.rate = Some(snapshot);
+        state.dirty = true;
+    }
+
+    // Record what one of our sends cost
+    pub fn record_cost(&self, txid: &str, amount: f64, cost: TxCost) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state.entries.entry(txid.to_string()).or_insert_with(|| MessageIndexEntry {
+            txid: txid.to_string(),
+            amount,
+            rate: None,
+            cost: None,
+        });
+        entry.cost = Some(cost);
+        state.dirty = true;
+    }
+
+    pub fn file_request(&self, request_id: &str) -> Option<FileRequestRecord> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.file_requests.get(request_id).cloned()
+    }
+
+    pub fn file_requests(&self) -> Vec<FileRequestRecord> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.file_requests.values().cloned().collect()
+    }
+
+    // Create (if missing) and update a file request record; returns the updated record
+    pub fn update_file_request(
+        &self,
+        request_id: &str,
+        create: impl FnOnce() -> FileRequestRecord,
+        update: impl FnOnce(&mut FileRequestRecord),
+    ) -> FileRequestRecord {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let record = state.file_requests.entry(request_id.to_string()).or_insert_with(create);
+        update(record);
+        let record = record.clone();
+        state.dirty = true;
+        record
+    }
+
+    // Update a file request record only if it exists; returns the updated record
+    pub fn update_existing_file_request(
+        &self,
+        request_id: &str,
+        update: impl FnOnce(&mut FileRequestRecord),
+    ) -> Option<FileRequestRecord> {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let record = state.file_requests.get_mut(request_id)?;
+        update(record);
+        let record = record.clone();
+        state.dirty = true;
+        Some(record)
+    }
+
+    pub fn gift_ack(&self, gift_txid: &str) -> Option<GiftAck> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.gift_acks.get(gift_txid).cloned()
+    }
+
+    pub fn record_gift_ack(&self, ack: GiftAck) {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.gift_acks.insert(ack.gift_txid.clone(), ack);
+        state.dirty = true;
+    }
+
+    pub fn voice_memo(&self, memo_id: &str) -> Option<VoiceMemoRecord> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.voice_memos.get(memo_id).cloned()
+    }
+
+    // Create (if missing) and update a voice memo record; returns the updated record
+    pub fn update_voice_memo(
+        &self,
+        memo_id: &str,
+        create: impl FnOnce() -> VoiceMemoRecord,
+        update: impl FnOnce(&mut VoiceMemoRecord),
+    ) -> VoiceMemoRecord {
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let record = state.voice_memos.entry(memo_id.to_string()).or_insert_with(create);
+        update(record);
+        let record = record.clone();
+        state.dirty = true;
+        record
+    }
+
+    // Write changes to disk (no-op if nothing changed)
+    pub fn persist<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), StorageError> {
+        let snapshot = {
+            let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.dirty {
+                return Ok(());
+            }
+            state.dirty = false;
+            state.clone()
+        };
+        if let Err(e) = save_value(app, INDEX_STORE_PATH, INDEX_KEY, &snapshot) {
+            self.inner.lock().unwrap_or_else(|e| e.into_inner()).dirty = true;
+            return Err(e);
+        }
+        log::debug!("Persisted message index ({} entries)", snapshot.entries.len());
+        Ok(())
+    }
+
+    // Drop everything in memory without persisting (emergency wipe)
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap_or_else(|e| e.into_inner()) = Default::default();
+    }
+}
+
+// Record the current conversion rate for newly ingested gifts.
+// Best effort: a missing rate never blocks message delivery.
+pub async fn annotate_new_gifts(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    index: &MessageIndex,
+    messages: &[ChatMessage],
+) {
+    let gifts: Vec<&ChatMessage> = messages.iter().filter(|m| m.amount > 0.0 && !index.has_rate(&m.id)).collect();
+    if gifts.is_empty() || !supports(rpc_port, Feature::Conversions) {
+        return;
+    }
+
+    match get_conversion_rate(rpc_user, rpc_pass, rpc_port, DEFAULT_PRICE_BASKET, DEFAULT_BASE_CURRENCY, DEFAULT_QUOTE_CURRENCY, None).await {
+        Ok(quote) => {
+            for gift in gifts {
+                index.record_rate(&gift.id, gift.amount, &quote, false);
+            }
+        }
+        Err(e) => log::debug!("Conversion rate unavailable, gifts left unannotated: {:?}", e),
+    }
+}
+
+// Annotate gifts received before rates were recorded, using the basket state at their block height.
+// Returns the number of gifts annotated.
+pub async fn backfill_gift_rates(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    own_private_address: String,
+    index: &MessageIndex,
+) -> Result<usize, VerusRpcError> {
+    log::info!("Backfilling gift conversion rates for {}", own_private_address);
+
+    let tip_height: u64 = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getblockcount", vec![]).await?;
+    let received: Vec<ReceivedAmount> = make_rpc_call(
+        &rpc_user,
+        &rpc_pass,
+        rpc_port,
+        "z_listreceivedbyaddress",
+        vec![json!(own_private_address), json!(0)],
+    )
+    .await?;
+
+    let mut annotated = 0;
+    let mut failed = 0;
+    for entry in received.iter().filter(|e| e.amount > 0.0 && !index.has_rate(&e.txid)) {
+        // Unconfirmed gifts use the latest state
+        let height = (entry.confirmations > 0).then(|| (tip_height + 1).saturating_sub(entry.confirmations as u64));
+        // One gift without a rate (e.g. the basket didn't exist at its height) doesn't stop the others
+        match get_conversion_rate(&rpc_user, &rpc_pass, rpc_port, DEFAULT_PRICE_BASKET, DEFAULT_BASE_CURRENCY, DEFAULT_QUOTE_CURRENCY, height).await {
+            Ok(quote) => {
+                index.record_rate(&entry.txid, entry.amount, &quote, true);
+                annotated += 1;
+            }
+            Err(e) => {
+                log::warn!("No conversion rate for gift {} (height {:?}): {:?}", entry.txid, height, e);
+                failed += 1;
+            }
+        }
+    }
+
+    log::info!("Backfilled conversion rates for {} gifts ({} without a rate)", annotated, failed);
+    Ok(annotated)
+}
+
+// --- Tauri Commands ---
+
+// Index entries for the given transactions (unknown txids are omitted)
+#[tauri::command]
+pub fn get_message_annotations(index: State<'_, MessageIndex>, txids: Vec<String>) -> Vec<MessageIndexEntry> {
+    log::debug!("get_message_annotations command received for {} txids", txids.len());
+    txids.iter().filter_map(|txid| index.get(txid)).collect()
+}
+
+// Acknowledgments for the given gift transactions (unacknowledged txids are omitted)
+#[tauri::command]
+pub fn get_gift_acks(index: State<'_, MessageIndex>, txids: Vec<String>) -> Vec<GiftAck> {
+    log::debug!("get_gift_acks command received for {} txids", txids.len());
+    txids.iter().filter_map(|txid| index.gift_ack(txid)).collect()
+}
@@ -11,11 +11,76 @@
 // - BREAKING: Extended message format to {message_text}//f//{sender_identity}//t//{unix_timestamp}//{signature}
 // - Zero-trust approach: Only verified messages are displayed, unverified messages are silently filtered
 // - Message sending fails if signing fails (no fallback to unsigned messages)
+// - Added memo compression via memo_codec so longer messages fit in the 512-byte memo field
+// - Receive parsers read the hex memo to transparently decompress flagged payloads
+// - Memo layout moved to protocol.rs; outgoing memos now declare a protocol version (//v//{version})
+// - parse_and_verify_message returns a ParsedMemo and accepts the supported version range
+// - Added protocol_version to ChatMessage
+// - Signatures (protocol v3) cover the recipient z-address; verification uses our own receiving address
+// - Added recipient_bound to ChatMessage (false for older protocol versions)
+// - Verification results are looked up in / written to the persistent VerificationCache
+// - Signature verification runs concurrently (bounded by MAX_CONCURRENT_VERIFICATIONS) in history and polling
+// - Polling is incremental: get_new_received_messages skips entries already covered by the address SyncCursor
+// - Added get_sent_message_history: rebuilds sent messages from outgoing outputs of our own wallet transactions
+// - parse_and_verify_message is public for the integrity audit
+// - Added preview_send: fee, total and estimated confirmation time before sending
+// - ChatMessage carries the memo signature (for export / record keeping)
+// - Memo signing/encoding moved into build_signed_memo_hex; added send_signed_memos (multiple memos in one transaction)
+// - Verification errors (daemon side) are tracked as pending in the VerificationCache; polling re-verifies due entries
+//   once the daemon is synced, so messages filtered while the identity index was catching up are recovered
+// - Memos from blocked senders (blocklist module) are dropped before verification
+// - Outgoing timestamps come from clock::outgoing_timestamp (optional chain-time correction)
+// - ChatMessage carries the fee and serialized size of outgoing transactions (get_transaction_cost)
+// - Large unverified backlogs (first sync) are prioritized: recent conversations are verified per poll, the rest
+//   is queued for background verification (verify_queue) and the sync cursor holds back until it is delivered
+// - send_private_message accepts an explicit fee (resolve_fee: custom amount or preset, checked against sane bounds)
+// - Recipient addresses are validated against the connected chain (address module) before signing
+// - Memo signing split into prepare_memo / assemble_signed_memo_hex / submit_signed_memo so an external signer can
+//   produce the signature (external_signer module)
+// - Added send_transparent_gift (amount-only z_sendmany to a transparent R-address; memos can't go to t-addresses)
+// - Added send_currency_gift (signed memo plus an amount of any currency via sendcurrency, currency checked with getcurrency)
+// - A locked wallet is reported as WalletLocked when signing, not as SigningFailed
+// - build_signed_memo_hex is public (used by convert-and-send gifts)
+// - History and polling send their verifymessage calls as JSON-RPC batches (VERIFY_BATCH_SIZE per request, falling
+//   back to single calls if a batch fails); parse_and_verify_message is split into prepare / finish steps for this
+// - Gift currency definitions are read through the block-aware response cache
+// - Invalid signatures are cached as rejected and only memos that can still verify count towards (and are deferred
+//   by) the verification backlog, so undeliverable memos no longer hold back the sync cursor. verifymessage errors
+//   are no longer folded into "invalid" here, so retryable daemon errors reach the retry tracking.
+// - submit_signed_memo waits for the z_sendmany operation and returns the txid instead of the opid
+// - send_transparent_gift waits for its operation too and returns the txid
+// - send_signed_memos waits for its operation too and returns the txid
+// - Verified senders are checked against the blocklist by i-address as well
+// - Memos left without any verification outcome (e.g. a failed batch task) hold the sync cursor below them, so
+//   a later poll verifies them instead of folding them under the finalized height
+// - Received messages carry the currency of gifts in a non-native currency (from the output's currencyvalues)
+// - Memos from clients with a newer protocol version are no longer dropped: polling returns them once per run as
+//   unreadable placeholders (kind NewerProtocol, no text or signature) instead.
+// - Newer-protocol placeholders carry no sender (the claimed one is unverified), stay out of chat history, and hold
+//   the sync cursor below them so they are verified once the app is updated.
+// - Added is_txid (shared by the gift ledger, polls and the integrity audit).
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use hex;
-use super::rpc_client::{make_rpc_call, sign_message, verify_message, VerusRpcError};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use super::response_cache::make_cached_rpc_call;
+use super::rpc_client::{make_rpc_batch, make_rpc_call, sign_message, VerusRpcError};
+use super::memo_codec::{decode_memo, encode_memo};
+use super::address::{validate_recipient_address, validate_transparent_address};
+use super::verification_cache::VerificationCache;
+use super::verify_queue::{enqueue, BacklogItem};
+use super::blocklist::{is_blocked, is_blocked_identity};
+use super::settings::{MessageKind, SyncCursor};
+use super::wallet_rpc::wait_for_operation;
+use super::capabilities::active_capabilities;
+use super::currency::currency_display_name;
+use super::network_rpc::{estimate_send_timing, is_daemon_synced, SendTimingEstimate};
+use super::protocol::{build_memo, is_recipient_bound, parse_memo, record_peer_version, signed_payload, MemoParseError, MAX_SUPPORTED_PROTOCOL_VERSION, PROTOCOL_VERSION};
 
 // Struct for imported chat messages
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -27,8 +92,51 @@ pub struct ChatMessage {
     pub amount: f64, // Amount from the transaction
     pub confirmations: i64, // Confirmations from the transaction
     pub direction: String, // "received"
+    pub protocol_version: u32, // Protocol version declared by the sender's client
+    pub recipient_bound: bool, // Signature covers our receiving address (replay protected)
+    pub signature: Option<String>, // VerusID signature from the memo
+    pub fee: Option<f64>, // Transaction fee paid (outgoing messages only)
+    pub size_bytes: Option<u64>, // Serialized transaction size (outgoing messages only)
+    #[serde(default)]
+    pub currency: Option<String>, // Currency of the amount; None for the chain's native coin
+    #[serde(default)]
+    pub kind: Option<MessageKind>, // NewerProtocol for placeholders of memos we can't read yet
 }
 
+impl ChatMessage {
+    // Placeholder for a memo from a newer client: unverified, so it belongs to no conversation
+    pub fn is_newer_protocol_placeholder(&self) -> bool {
+        self.kind == Some(MessageKind::NewerProtocol)
+    }
+}
+
+// Whether a message id is a txid (sends still in flight are recorded under a z_sendmany opid)
+pub(crate) fn is_txid(id: &str) -> bool {
+    id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// What an outgoing transaction cost, from the wallet's view of it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct TxCost {
+    pub fee: f64,
+    pub size_bytes: u64,
+}
+
+// Maximum number of signature verification requests in flight at once
+const MAX_CONCURRENT_VERIFICATIONS: usize = 8;
+
+// verifymessage calls per batch request
+const VERIFY_BATCH_SIZE: usize = 25;
+
+// Unverified memos above which a poll only verifies the most recent conversations (first sync)
+const VERIFICATION_BACKLOG_THRESHOLD: usize = 64;
+
+// Uncached verifications per poll while a backlog exists
+const PRIORITY_BATCH: usize = 48;
+
+// Blocks with at least this many confirmations are folded into the sync cursor height (reorg margin)
+const SYNC_CURSOR_FINALITY_CONFIRMATIONS: u64 = 10;
+
 // Struct for the z_listreceivedbyaddress RPC response item
 #[derive(Deserialize, Debug)]
 pub struct ReceivedByAddressEntry {
@@ -36,71 +144,408 @@ pub struct ReceivedByAddressEntry {
     amount: f64,
     confirmations: i64,
     memostr: Option<String>, // Memo might be absent
-    // memo: String, // We only need memostr
+    memo: Option<String>, // Raw hex memo, needed for compressed payloads
     // outindex: u32,
-    // change: bool,
+    #[serde(default)]
+    change: bool, // Change returned to us by one of our own sends
+    // Amounts per currency i-address; outputs of sendcurrency gifts in another currency carry it here, while
+    // amount only covers the native coin
+    #[serde(default)]
+    currencyvalues: Option<HashMap<String, f64>>,
     // blocktime: Option<u64>, // Add blocktime if available and needed for timestamp
 }
 
+impl ReceivedByAddressEntry {
+    // Memo text with compressed payloads transparently decoded
+    fn memo_text(&self) -> Option<String> {
+        decode_memo(self.memostr.as_deref(), self.memo.as_deref())
+    }
+
+    // Currency i-address and amount of a non-native currency the output carries (None for native gifts)
+    fn foreign_currency_value(&self, native_currency_id: Option<&str>) -> Option<(&str, f64)> {
+        self.currencyvalues
+            .as_ref()?
+            .iter()
+            .find(|(currency, amount)| **amount > 0.0 && Some(currency.as_str()) != native_currency_id)
+            .map(|(currency, amount)| (currency.as_str(), *amount))
+    }
+
+    // Block height derived from confirmations (None while unconfirmed)
+    fn block_height(&self, tip_height: u64) -> Option<u64> {
+        if self.confirmations > 0 {
+            Some((tip_height + 1).saturating_sub(self.confirmations as u64))
+        } else {
+            None
+        }
+    }
+}
+
+// Verified contents of a received memo
+#[derive(Debug, Clone)]
+pub struct ParsedMemo {
+    pub text: String,
+    pub sender_id: String,
+    pub timestamp: u64,
+    pub signature: String,
+    pub protocol_version: u32,
+    pub recipient_bound: bool,
+}
+
+// Daemon-side failures (identity not indexed yet, timeouts, overload) may succeed later
+fn is_retryable_verification_error(error: &VerusRpcError) -> bool {
+    !matches!(error, VerusRpcError::ParseError(_) | VerusRpcError::Format | VerusRpcError::InvalidFormat)
+}
+
+// A parsed memo waiting for its signature check
+struct PendingVerification {
+    parsed: ParsedMemo,
+    original_message: String, // Signed payload passed to verifymessage
+    cached: bool,             // Verified before (no verifymessage call needed)
+}
+
 // Helper function to parse message with signature verification
-async fn parse_and_verify_message(
+pub async fn parse_and_verify_message(
     rpc_user: &str,
     rpc_pass: &str,
     rpc_port: u16,
     memo: &str,
     txid: &str,
-) -> Option<(String, String, u64, String)> { // Returns (message_text, sender_id, timestamp, signature) if valid
-    // Parse new signature format: {message_text}//f//{sender_identity}//t//{timestamp}//{signature}
-    if let Some(sender_marker_pos) = memo.find("//f//") {
-        let message_text = memo[..sender_marker_pos].trim();
-        let after_sender_marker = &memo[sender_marker_pos + 5..]; // 5 = "//f//".len()
-        
-        if let Some(time_marker_pos) = after_sender_marker.find("//t//") {
-            let sender_id = after_sender_marker[..time_marker_pos].trim();
-            let after_time_marker = &after_sender_marker[time_marker_pos + 5..]; // 5 = "//t//".len()
-            
-            if let Some(sig_marker_pos) = after_time_marker.find("//") {
-                let timestamp_str = after_time_marker[..sig_marker_pos].trim();
-                let signature = after_time_marker[sig_marker_pos + 2..].trim(); // 2 = "//".len()
-                
-                // Parse timestamp - reject message if invalid (strict parsing)
-                if let Ok(timestamp) = timestamp_str.parse::<u64>() {
-                    // Reconstruct the original message for verification (without signature)
-                    let original_message = format!("{}//f//{}//t//{}", message_text, sender_id, timestamp);
-                    
-                    // Verify the signature
-                    match verify_message(rpc_user, rpc_pass, rpc_port, sender_id, signature, &original_message).await {
-                        Ok(true) => {
-                            log::debug!("Message verification successful for tx {}: '{}' from {} at timestamp {}", 
-                                txid, message_text, sender_id, timestamp);
-                            return Some((message_text.to_string(), sender_id.to_string(), timestamp, signature.to_string()));
-                        }
-                        Ok(false) => {
-                            log::warn!("Message verification failed for tx {} - signature invalid. Message silently filtered.", txid);
-                            return None;
-                        }
-                        Err(e) => {
-                            log::error!("Message verification error for tx {}: {:?}. Message silently filtered.", txid, e);
-                            return None;
-                        }
-                    }
-                } else {
-                    log::warn!("Skipping message in tx {} due to invalid timestamp format: '{}'", txid, timestamp_str);
-                    return None;
-                }
-            } else {
-                // Legacy format without signature - silently filter out
-                log::debug!("Skipping legacy unsigned message in tx {} (no signature marker)", txid);
-                return None;
-            }
-        } else {
+    receiving_address: &str, // Our z-address the memo was received on
+    cache: &VerificationCache,
+) -> Option<ParsedMemo> { // Returns the parsed memo only if the signature is valid
+    let pending = prepare_verification(memo, txid, receiving_address, cache)?;
+    let verification = if pending.cached {
+        Ok(true)
+    } else {
+        check_signature(rpc_user, rpc_pass, rpc_port, &pending.parsed.sender_id, &pending.parsed.signature, &pending.original_message).await
+    };
+    finish_verification(verification, pending, memo, txid, receiving_address, cache)
+}
+
+// verifymessage keeping daemon errors apart from invalid signatures (unlike rpc_client::verify_message), so
+// failures while the identity index catches up can be retried
+async fn check_signature(rpc_user: &str, rpc_pass: &str, rpc_port: u16, sender_id: &str, signature: &str, message: &str) -> Result<bool, VerusRpcError> {
+    make_rpc_call(rpc_user, rpc_pass, rpc_port, "verifymessage", vec![json!(sender_id), json!(signature), json!(message)]).await
+}
+
+// Parse a memo and decide whether its signature still needs checking. None for memos that are skipped (not a
+// signed message, unsupported version) or whose verification is deferred.
+fn prepare_verification(memo: &str, txid: &str, receiving_address: &str, cache: &VerificationCache) -> Option<PendingVerification> {
+    let parts = match parse_memo(memo) {
+        Ok(parts) => parts,
+        Err(MemoParseError::NoSenderMarker) => {
+            log::trace!("Skipping memo in tx {} (no sender marker): {}", txid, memo);
+            return None;
+        }
+        Err(MemoParseError::NoTimestampMarker) => {
             log::trace!("Skipping memo in tx {} (no timestamp marker): {}", txid, memo);
             return None;
         }
-    } else {
-        log::trace!("Skipping memo in tx {} (no sender marker): {}", txid, memo);
+        Err(MemoParseError::NoSignature) => {
+            // Legacy format without signature - silently filter out
+            log::debug!("Skipping legacy unsigned message in tx {} (no signature marker)", txid);
+            return None;
+        }
+        Err(MemoParseError::InvalidTimestamp(timestamp_str)) => {
+            log::warn!("Skipping message in tx {} due to invalid timestamp format: '{}'", txid, timestamp_str);
+            return None;
+        }
+        Err(MemoParseError::InvalidVersion(version_str)) => {
+            log::warn!("Skipping message in tx {} due to invalid protocol version: '{}'", txid, version_str);
+            return None;
+        }
+        Err(MemoParseError::UnsupportedVersion { version, sender_id, timestamp }) => {
+            log::warn!("Skipping message in tx {} from {}: protocol version {} is outside supported range", txid, sender_id, version);
+            record_peer_version(&sender_id, version, timestamp);
+            return None;
+        }
+    };
+
+    // Reconstruct the original signed payload for verification (without signature).
+    // For recipient-bound versions this includes our receiving address, so a memo signed
+    // for someone else fails verification here.
+    let original_message = signed_payload(parts.text, parts.sender_id, parts.timestamp, parts.protocol_version, receiving_address);
+    let recipient_bound = is_recipient_bound(parts.protocol_version);
+    if !recipient_bound {
+        log::debug!("Message in tx {} uses protocol v{} without recipient binding", txid, parts.protocol_version);
+    }
+
+    // Memos that recently failed with a retryable error wait for their scheduled retry
+    if cache.retry_deferred(txid, memo, receiving_address) {
+        log::trace!("Verification of tx {} deferred until its scheduled retry", txid);
         return None;
     }
+
+    // Previously verified memos skip the verifymessage round trip; rejected ones are dropped
+    let cached = match cache.get(txid, memo, receiving_address) {
+        Some(true) => {
+            log::trace!("Signature for tx {} found in verification cache", txid);
+            true
+        }
+        Some(false) => {
+            log::trace!("Skipping tx {} (signature rejected earlier)", txid);
+            return None;
+        }
+        None => false,
+    };
+    Some(PendingVerification {
+        parsed: ParsedMemo {
+            text: parts.text.to_string(),
+            sender_id: parts.sender_id.to_string(),
+            timestamp: parts.timestamp,
+            signature: parts.signature.to_string(),
+            protocol_version: parts.protocol_version,
+            recipient_bound,
+        },
+        original_message,
+        cached,
+    })
+}
+
+// Apply the outcome of a signature check (cache, peer version) and return the memo if it is valid
+fn finish_verification(
+    verification: Result<bool, VerusRpcError>,
+    pending: PendingVerification,
+    memo: &str,
+    txid: &str,
+    receiving_address: &str,
+    cache: &VerificationCache,
+) -> Option<ParsedMemo> {
+    let parsed = pending.parsed;
+    match verification {
+        Ok(true) => {
+            log::debug!("Message verification successful for tx {}: '{}' from {} at timestamp {} (protocol v{})",
+                txid, parsed.text, parsed.sender_id, parsed.timestamp, parsed.protocol_version);
+            cache.insert_verified(txid, memo, receiving_address);
+            record_peer_version(&parsed.sender_id, parsed.protocol_version, parsed.timestamp);
+            Some(parsed)
+        }
+        Ok(false) => {
+            log::warn!("Message verification failed for tx {} - signature invalid. Message silently filtered.", txid);
+            cache.insert_rejected(txid, memo, receiving_address);
+            None
+        }
+        Err(e) if is_retryable_verification_error(&e) => {
+            log::warn!("Message verification error for tx {}: {:?}. Will retry once the daemon is synced.", txid, e);
+            cache.record_retryable_failure(txid, memo, receiving_address, &e.to_string());
+            None
+        }
+        Err(e) => {
+            log::error!("Message verification error for tx {}: {:?}. Message silently filtered.", txid, e);
+            cache.insert_rejected(txid, memo, receiving_address);
+            None
+        }
+    }
+}
+
+// verifymessage for several memos in one batch request, with per-call errors kept as errors (see check_signature);
+// if the batch itself fails, the memos are verified one by one.
+async fn verify_batch(rpc_user: &str, rpc_pass: &str, rpc_port: u16, batch: &[(String, String, String)]) -> Vec<Result<bool, VerusRpcError>> {
+    let calls = batch
+        .iter()
+        .map(|(sender_id, signature, message)| ("verifymessage", vec![json!(sender_id), json!(signature), json!(message)]))
+        .collect();
+    match make_rpc_batch::<bool>(rpc_user, rpc_pass, rpc_port, calls).await {
+        Ok(results) => results,
+        Err(e) => {
+            log::warn!("verifymessage batch of {} failed, verifying one by one: {:?}", batch.len(), e);
+            let mut results = Vec::with_capacity(batch.len());
+            for (sender_id, signature, message) in batch {
+                results.push(check_signature(rpc_user, rpc_pass, rpc_port, sender_id, signature, message).await);
+            }
+            results
+        }
+    }
+}
+
+// Verify the memos of many transactions. Uncached signatures are checked in batches of VERIFY_BATCH_SIZE
+// (at most MAX_CONCURRENT_VERIFICATIONS batches in flight). Returns the verified entries in their original listing order.
+async fn verify_entries_concurrently(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    entries: Vec<ReceivedByAddressEntry>,
+    receiving_address: &str,
+    cache: &VerificationCache,
+) -> Vec<(ReceivedByAddressEntry, ParsedMemo)> {
+    let mut prepared = Vec::new();
+    for entry in entries {
+        // Ignore transactions without memos
+        let Some(memo) = entry.memo_text() else { continue };
+
+        // Blocked senders are dropped before spending a verifymessage call on them
+        if parse_memo(&memo).is_ok_and(|parts| is_blocked(parts.sender_id)) {
+            log::trace!("Dropping memo in tx {} from a blocked sender", entry.txid);
+            continue;
+        }
+        if let Some(pending) = prepare_verification(&memo, &entry.txid, receiving_address, cache) {
+            prepared.push((entry, memo, pending));
+        }
+    }
+
+    // Signatures to check, as (index into prepared, (sender, signature, signed payload))
+    let uncached: Vec<(usize, (String, String, String))> = prepared
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, _, pending))| !pending.cached)
+        .map(|(i, (_, _, pending))| (i, (pending.parsed.sender_id.clone(), pending.parsed.signature.clone(), pending.original_message.clone())))
+        .collect();
+    let mut outcomes: HashMap<usize, Result<bool, VerusRpcError>> = HashMap::new();
+    if !uncached.is_empty() {
+        log::debug!("Verifying {} signatures in batches of {}", uncached.len(), VERIFY_BATCH_SIZE);
+        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_VERIFICATIONS));
+        let mut join_set = JoinSet::new();
+        for chunk in uncached.chunks(VERIFY_BATCH_SIZE) {
+            let (indices, batch): (Vec<usize>, Vec<(String, String, String)>) = chunk.iter().cloned().unzip();
+            let semaphore = semaphore.clone();
+            let rpc_user = rpc_user.to_string();
+            let rpc_pass = rpc_pass.to_string();
+            join_set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                let results = verify_batch(&rpc_user, &rpc_pass, rpc_port, &batch).await;
+                Some(indices.into_iter().zip(results).collect::<Vec<_>>())
+            });
+        }
+        while let Some(task_result) = join_set.join_next().await {
+            match task_result {
+                Ok(Some(results)) => outcomes.extend(results),
+                Ok(None) => {}
+                Err(e) => log::error!("Verification task failed: {}", e),
+            }
+        }
+    }
+
+    let verified = prepared
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (entry, memo, pending))| {
+            let verification = if pending.cached {
+                Ok(true)
+            } else {
+                // Memos whose batch task failed are left out, like failed verifications
+                outcomes.remove(&i)?
+            };
+            // Unverified messages are silently filtered out
+            let parsed = finish_verification(verification, pending, &memo, &entry.txid, receiving_address, cache)?;
+            Some((entry, parsed))
+        })
+        .collect::<Vec<_>>();
+
+    // The sender is authentic now; check it by i-address too (it may have written its name differently)
+    let mut allowed = Vec::with_capacity(verified.len());
+    for (entry, parsed) in verified {
+        if is_blocked_identity(rpc_user, rpc_pass, rpc_port, &parsed.sender_id).await {
+            log::trace!("Dropping memo in tx {} from a blocked identity", entry.txid);
+            continue;
+        }
+        allowed.push((entry, parsed));
+    }
+    allowed
+}
+
+// A memo that still needs a verifymessage call and can pass it: a signed message of a supported version from a
+// sender that isn't blocked, with no definitive outcome in the cache and no retry scheduled for later
+fn awaits_verification(tx: &ReceivedByAddressEntry, cache: &VerificationCache, receiving_address: &str) -> bool {
+    let Some(memo) = tx.memo_text() else { return false };
+    parse_memo(&memo).is_ok_and(|parts| !is_blocked(parts.sender_id))
+        && cache.get(&tx.txid, &memo, receiving_address).is_none()
+        && !cache.retry_deferred(&tx.txid, &memo, receiving_address)
+}
+
+// Split new entries when many memos still need verification: memos of the conversations with the most recent
+// activity are verified now, the rest is returned as deferred (newest conversations first). Memos with a known
+// outcome (cached, rejected, unparseable) are cheap and always processed.
+fn prioritize_backlog(
+    entries: Vec<ReceivedByAddressEntry>,
+    cache: &VerificationCache,
+    receiving_address: &str,
+) -> (Vec<ReceivedByAddressEntry>, Vec<ReceivedByAddressEntry>) {
+    let needs_verification = |tx: &ReceivedByAddressEntry| awaits_verification(tx, cache, receiving_address);
+    if entries.iter().filter(|tx| needs_verification(tx)).count() <= VERIFICATION_BACKLOG_THRESHOLD {
+        return (entries, Vec::new());
+    }
+    let (uncached, mut now): (Vec<ReceivedByAddressEntry>, Vec<ReceivedByAddressEntry>) = entries.into_iter().partition(needs_verification);
+
+    // The claimed sender is only used for ordering; nothing is trusted before verification
+    let mut keyed: Vec<(String, ReceivedByAddressEntry)> = uncached
+        .into_iter()
+        .map(|tx| {
+            let sender = tx.memo_text().and_then(|memo| parse_memo(&memo).ok().map(|p| p.sender_id.to_string())).unwrap_or_default();
+            (sender, tx)
+        })
+        .collect();
+    let mut newest_by_sender: HashMap<String, i64> = HashMap::new();
+    for (sender, tx) in &keyed {
+        let newest = newest_by_sender.entry(sender.clone()).or_insert(i64::MAX);
+        *newest = (*newest).min(tx.confirmations);
+    }
+    keyed.sort_by(|(a_sender, a), (b_sender, b)| {
+        (newest_by_sender[a_sender], a_sender, a.confirmations).cmp(&(newest_by_sender[b_sender], b_sender, b.confirmations))
+    });
+
+    let deferred = keyed.split_off(PRIORITY_BATCH.min(keyed.len()));
+    log::info!("Verification backlog: verifying {} recent memos now, deferring {}", keyed.len(), deferred.len());
+    now.extend(keyed.into_iter().map(|(_, tx)| tx));
+    (now, deferred.into_iter().map(|(_, tx)| tx).collect())
+}
+
+// Sender (as claimed), timestamp and version of a memo written by a client with a newer protocol than ours. Its
+// layout and signed payload may have changed, so it can't be read or verified, only shown as a placeholder.
+fn newer_protocol_memo(tx: &ReceivedByAddressEntry) -> Option<(String, u64, u32)> {
+    let memo = tx.memo_text()?;
+    match parse_memo(&memo) {
+        Err(MemoParseError::UnsupportedVersion { version, sender_id, timestamp })
+            if version > MAX_SUPPORTED_PROTOCOL_VERSION && !is_blocked(&sender_id) =>
+        {
+            Some((sender_id, timestamp, version))
+        }
+        _ => None,
+    }
+}
+
+// Txids of newer-protocol memos already returned as placeholders in this run (they stay above the sync cursor, so
+// every poll sees them again)
+static ANNOUNCED_PLACEHOLDERS: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+// Placeholder for a memo from a newer client: no sender, text or signature (the claimed sender is unverified), but
+// the amount received is real. The memo is verified and delivered as a message once the app is updated.
+async fn newer_protocol_placeholder(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    tx: &ReceivedByAddressEntry,
+    claimed_sender: &str,
+    timestamp: u64,
+    version: u32,
+) -> ChatMessage {
+    log::info!("Memo in tx {} (claimed sender {}) uses protocol v{}; showing it as unreadable until Nymia is updated", tx.txid, claimed_sender, version);
+    let (amount, currency) = received_amount(rpc_user, rpc_pass, rpc_port, tx).await;
+    ChatMessage {
+        id: tx.txid.clone(),
+        sender: String::new(),
+        text: String::new(),
+        timestamp,
+        amount,
+        confirmations: tx.confirmations,
+        direction: "received".to_string(),
+        protocol_version: version,
+        recipient_bound: false,
+        signature: None,
+        fee: None,
+        size_bytes: None,
+        currency,
+        kind: Some(MessageKind::NewerProtocol),
+    }
+}
+
+// Amount and currency of a received output as recorded in messages: the non-native currency (by name) if the
+// output carries one, the native amount otherwise
+async fn received_amount(rpc_user: &str, rpc_pass: &str, rpc_port: u16, tx: &ReceivedByAddressEntry) -> (f64, Option<String>) {
+    let native_currency_id = active_capabilities(rpc_port).map(|capabilities| capabilities.chain_id);
+    match tx.foreign_currency_value(native_currency_id.as_deref()) {
+        Some((currency, amount)) => (amount, Some(currency_display_name(rpc_user, rpc_pass, rpc_port, currency).await)),
+        None => (tx.amount, None),
+    }
 }
 
 // NEW function for New Chat: Get chat history from received memos
@@ -110,6 +555,7 @@ pub async fn get_chat_history(
     rpc_port: u16,
     target_identity_name: String, // The user we want history *from*
     own_private_address: String, // The logged-in user's z-addr
+    cache: &VerificationCache,
 ) -> Result<Vec<ChatMessage>, VerusRpcError> {
     log::info!("Fetching chat history from {} for owner {}", target_identity_name, own_private_address);
 
@@ -127,45 +573,53 @@ pub async fn get_chat_history(
 
     let mut chat_messages = Vec::new();
 
-    for tx in received_txs {
-        if let Some(memostr) = tx.memostr {
-            // Parse and verify message - only verified messages are processed
-            if let Some((message_text, sender_id, timestamp, _signature)) = 
-                parse_and_verify_message(&rpc_user, &rpc_pass, rpc_port, &memostr, &tx.txid).await {
-                
-                // Only process if this message is from the target identity
-                if sender_id == target_identity_name {
-                    chat_messages.push(ChatMessage {
-                        id: tx.txid,
-                        sender: target_identity_name.clone(),
-                        text: message_text,
-                        timestamp: timestamp,
-                        amount: tx.amount,
-                        confirmations: tx.confirmations,
-                        direction: "received".to_string(),
-                    });
-                }
-            }
-            // Note: Unverified messages are silently filtered out - no logging needed per zero-trust requirement
+    // Parse and verify messages concurrently - only verified messages are processed
+    let verified = verify_entries_concurrently(&rpc_user, &rpc_pass, rpc_port, received_txs, &own_private_address, cache).await;
+
+    for (tx, parsed) in verified {
+        // Only process if this message is from the target identity
+        if parsed.sender_id == target_identity_name {
+            let (amount, currency) = received_amount(&rpc_user, &rpc_pass, rpc_port, &tx).await;
+            chat_messages.push(ChatMessage {
+                id: tx.txid,
+                sender: target_identity_name.clone(),
+                text: parsed.text,
+                timestamp: parsed.timestamp,
+                amount,
+                confirmations: tx.confirmations,
+                direction: "received".to_string(),
+                protocol_version: parsed.protocol_version,
+                recipient_bound: parsed.recipient_bound,
+                signature: Some(parsed.signature),
+                fee: None,
+                size_bytes: None,
+                currency,
+                kind: None,
+            });
         }
     }
 
-    log::info!("Found {} verified messages from {}", chat_messages.len(), target_identity_name);
+    log::info!("Found {} messages from {}", chat_messages.len(), target_identity_name);
     // Sort by timestamp ascending (oldest first)
     chat_messages.sort_by_key(|m| m.timestamp);
 
     Ok(chat_messages)
 }
 
-// NEW function for polling new received messages (for ANY sender)
+// NEW function for polling new received messages (for ANY sender). Only returns messages the sync cursor hasn't
+// covered yet (command API v2); earlier messages come from get_chat_history.
 pub async fn get_new_received_messages(
     rpc_user: String,
     rpc_pass: String,
     rpc_port: u16,
     own_private_address: String, // The logged-in user's z-addr
+    cache: &VerificationCache,
+    cursor: &mut SyncCursor, // Updated in place; the caller persists it
 ) -> Result<Vec<ChatMessage>, VerusRpcError> {
     log::info!("Polling for new received messages for owner {}", own_private_address);
 
+    let tip_height: u64 = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getblockcount", vec![]).await?;
+
     // Call with 0 confirmations to include unconfirmed messages
     let params = vec![json!(own_private_address), json!(0)]; 
     let received_txs: Vec<ReceivedByAddressEntry> = match make_rpc_call(
@@ -186,52 +640,312 @@ pub async fn get_new_received_messages(
 
     log::debug!("Received {} total transactions (including unconfirmed) for address {}", received_txs.len(), own_private_address);
 
-    let mut chat_messages = Vec::new();
+    // Skip entries the cursor already covers
+    let processed: HashSet<String> = cursor.processed_txids.iter().cloned().collect();
+    let (new_txs, known_txs): (Vec<ReceivedByAddressEntry>, Vec<ReceivedByAddressEntry>) =
+        received_txs.into_iter().partition(|tx| {
+            let below_cursor = matches!(
+                (tx.block_height(tip_height), cursor.last_block_height),
+                (Some(height), Some(cursor_height)) if height <= cursor_height
+            );
+            !below_cursor && !processed.contains(&tx.txid)
+        });
+    log::debug!("{} new transactions since sync cursor ({} already processed)", new_txs.len(), known_txs.len());
 
-    for tx in received_txs {
-        if let Some(memostr) = tx.memostr {
-            // Parse and verify message - only verified messages are processed
-            if let Some((message_text, sender_id, timestamp, _signature)) = 
-                parse_and_verify_message(&rpc_user, &rpc_pass, rpc_port, &memostr, &tx.txid).await {
-                
-                // Validate sender format
-                let is_valid_sender = sender_id.ends_with('@') && sender_id.len() > 1;
-                let has_message_content = !message_text.is_empty();
-                let has_gift_amount = tx.amount > 0.0;
-                
-                if is_valid_sender && (has_message_content || has_gift_amount) {
-                    log::debug!(
-                        "Found valid verified message/gift in tx {}: '{}' from sender '{}', amount: {}, timestamp: {}",
-                        tx.txid,
-                        message_text,
-                        sender_id,
-                        tx.amount,
-                        timestamp
-                    );
-                    chat_messages.push(ChatMessage {
-                        id: tx.txid,
-                        sender: sender_id,
-                        text: message_text,
-                        timestamp: timestamp,
-                        amount: tx.amount,
-                        confirmations: tx.confirmations,
-                        direction: "received".to_string(),
-                    });
-                } else {
-                    log::trace!("Skipping verified memo in tx {} due to invalid format or no content/gift: {}", tx.txid, memostr);
-                }
+    // Re-verify earlier retryable failures (possibly behind the cursor) once the daemon has caught up
+    let due_retries = cache.due_retries(&own_private_address);
+    let (retry_txs, known_txs): (Vec<ReceivedByAddressEntry>, Vec<ReceivedByAddressEntry>) =
+        known_txs.into_iter().partition(|tx| due_retries.contains(&tx.txid));
+    let mut new_txs = new_txs;
+    if !retry_txs.is_empty() {
+        match is_daemon_synced(&rpc_user, &rpc_pass, rpc_port).await {
+            Ok(true) => {
+                log::info!("Re-verifying {} messages whose verification previously failed", retry_txs.len());
+                new_txs.extend(retry_txs);
             }
-            // Note: Unverified messages are silently filtered out - no logging needed per zero-trust requirement
-        } // Ignore transactions without memos
+            Ok(false) => log::debug!("Daemon still syncing, postponing {} verification retries", retry_txs.len()),
+            Err(e) => log::debug!("Could not read daemon sync state, postponing verification retries: {:?}", e),
+        }
     }
 
-    log::info!("Parsed {} verified messages from polling.", chat_messages.len());
+    // On a large backlog only the most recent conversations are verified now; the rest goes to the background queue
+    let (new_txs, deferred) = prioritize_backlog(new_txs, cache, &own_private_address);
+    enqueue(
+        &own_private_address,
+        deferred
+            .iter()
+            .filter_map(|tx| tx.memo_text().map(|memo| BacklogItem { txid: tx.txid.clone(), memo }))
+            .collect(),
+    );
+    cursor.backlog_remaining = deferred.len();
+
+    // Memo-less entries never become messages, so they count as processed right away
+    let mut newly_processed: Vec<(String, Option<u64>)> = new_txs
+        .iter()
+        .filter(|tx| tx.memo_text().is_none())
+        .map(|tx| (tx.txid.clone(), tx.block_height(tip_height)))
+        .collect();
+
+    // Memos from newer clients are returned once per run as placeholders, but never count as processed: the cursor
+    // stays below them so an updated app verifies them
+    let mut chat_messages = Vec::new();
+    let mut lowest_unreadable: Option<u64> = None;
+    for tx in &new_txs {
+        let Some((claimed_sender, timestamp, version)) = newer_protocol_memo(tx) else { continue };
+        if let Some(height) = tx.block_height(tip_height) {
+            lowest_unreadable = Some(lowest_unreadable.map_or(height, |lowest| lowest.min(height)));
+        }
+        if ANNOUNCED_PLACEHOLDERS.lock().unwrap_or_else(|e| e.into_inner()).insert(tx.txid.clone()) {
+            chat_messages.push(newer_protocol_placeholder(&rpc_user, &rpc_pass, rpc_port, tx, &claimed_sender, timestamp, version).await);
+        }
+    }
+
+    // Memos this pass tries to verify, to find the ones that end up without any outcome
+    let attempted: Vec<(String, String, Option<u64>)> = new_txs
+        .iter()
+        .filter(|tx| awaits_verification(tx, cache, &own_private_address))
+        .filter_map(|tx| tx.memo_text().map(|memo| (tx.txid.clone(), memo, tx.block_height(tip_height))))
+        .collect();
+
+    // Parse and verify messages concurrently - only verified messages are processed
+    let verified = verify_entries_concurrently(&rpc_user, &rpc_pass, rpc_port, new_txs, &own_private_address, cache).await;
+    newly_processed.extend(verified.iter().map(|(tx, _)| (tx.txid.clone(), tx.block_height(tip_height))));
+    let verified_txids: HashSet<&str> = verified.iter().map(|(tx, _)| tx.txid.as_str()).collect();
+    let lowest_unresolved = attempted
+        .iter()
+        .filter(|(txid, memo, _)| {
+            !verified_txids.contains(txid.as_str())
+                && cache.get(txid, memo, &own_private_address).is_none()
+                && !cache.retry_deferred(txid, memo, &own_private_address)
+        })
+        .filter_map(|(_, _, height)| *height)
+        .min();
+
+    // Advance the cursor up to the finality margin
+    let finalized_height = tip_height.saturating_sub(SYNC_CURSOR_FINALITY_CONFIRMATIONS);
+    let mut cursor_height = cursor.last_block_height.unwrap_or(0).max(finalized_height);
+    // Deferred entries must stay above the cursor so a later poll picks them up
+    if let Some(lowest_deferred) = deferred.iter().filter_map(|tx| tx.block_height(tip_height)).min() {
+        cursor_height = cursor_height.min(lowest_deferred.saturating_sub(1));
+    }
+    // So must memos without a verification outcome (retryable failures are tracked by the cache and retried
+    // behind the cursor; rejected ones are done)
+    if let Some(lowest_unresolved) = lowest_unresolved {
+        log::debug!("Holding the sync cursor below height {} for memos without a verification outcome", lowest_unresolved);
+        cursor_height = cursor_height.min(lowest_unresolved.saturating_sub(1));
+    }
+    if let Some(lowest_unreadable) = lowest_unreadable {
+        log::debug!("Holding the sync cursor below height {} for memos from newer clients", lowest_unreadable);
+        cursor_height = cursor_height.min(lowest_unreadable.saturating_sub(1));
+    }
+    let mut processed_txids: Vec<String> = known_txs
+        .iter()
+        .filter(|tx| processed.contains(&tx.txid))
+        .map(|tx| (tx.txid.clone(), tx.block_height(tip_height)))
+        .chain(newly_processed)
+        .filter(|(_, height)| height.is_none_or(|h| h > cursor_height))
+        .map(|(txid, _)| txid)
+        .collect();
+    processed_txids.sort();
+    processed_txids.dedup();
+    cursor.last_block_height = Some(cursor_height);
+    cursor.processed_txids = processed_txids;
+
+    for (tx, parsed) in verified {
+        // Validate sender format
+        let is_valid_sender = parsed.sender_id.ends_with('@') && parsed.sender_id.len() > 1;
+        let has_message_content = !parsed.text.is_empty();
+        let (amount, currency) = received_amount(&rpc_user, &rpc_pass, rpc_port, &tx).await;
+        let has_gift_amount = amount > 0.0;
+
+        if is_valid_sender && (has_message_content || has_gift_amount) {
+            log::debug!(
+                "Found valid verified message/gift in tx {}: '{}' from sender '{}', amount: {}, timestamp: {}",
+                tx.txid,
+                parsed.text,
+                parsed.sender_id,
+                amount,
+                parsed.timestamp
+            );
+            chat_messages.push(ChatMessage {
+                id: tx.txid,
+                sender: parsed.sender_id,
+                text: parsed.text,
+                timestamp: parsed.timestamp,
+                amount,
+                confirmations: tx.confirmations,
+                direction: "received".to_string(),
+                protocol_version: parsed.protocol_version,
+                recipient_bound: parsed.recipient_bound,
+                signature: Some(parsed.signature),
+                fee: None,
+                size_bytes: None,
+                currency,
+                kind: None,
+            });
+        } else {
+            log::trace!("Skipping verified memo in tx {} due to invalid format or no content/gift", tx.txid);
+        }
+    }
+
+    log::info!("Parsed {} messages from polling.", chat_messages.len());
     // No sorting needed here, frontend will handle merging and sorting
 
     Ok(chat_messages)
 }
 
+// Default shielded transaction fee applied by the daemon (z_sendmany without an explicit fee)
+pub const DEFAULT_TX_FEE: f64 = 0.0001;
+
+// How long a send waits for its z_sendmany operation (proof building) before giving up on the txid
+const SEND_OPERATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Bounds for a caller-chosen fee. Below the default the transaction may not be relayed; the upper bound
+// catches unit mistakes (e.g. satoshis entered as coins).
+pub const MIN_CUSTOM_FEE: f64 = DEFAULT_TX_FEE;
+pub const MAX_CUSTOM_FEE: f64 = 0.01;
+
+// Named fee levels offered by the send dialog
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeePreset {
+    Standard, // Daemon default
+    Priority,
+    Urgent,
+}
+
+impl FeePreset {
+    pub fn fee(self) -> f64 {
+        match self {
+            FeePreset::Standard => DEFAULT_TX_FEE,
+            FeePreset::Priority => 0.0005,
+            FeePreset::Urgent => 0.001,
+        }
+    }
+}
+
+// Fee to pass to z_sendmany: an explicit fee wins over a preset; None leaves the daemon default
+pub fn resolve_fee(fee: Option<f64>, preset: Option<FeePreset>) -> Result<Option<f64>, VerusRpcError> {
+    let Some(fee) = fee.or(preset.map(FeePreset::fee)) else { return Ok(None) };
+    if !fee.is_finite() || !(MIN_CUSTOM_FEE..=MAX_CUSTOM_FEE).contains(&fee) {
+        return Err(VerusRpcError::InvalidFee(format!("{} is outside {}..={}", fee, MIN_CUSTOM_FEE, MAX_CUSTOM_FEE)));
+    }
+    Ok(Some(fee))
+}
+
+// Shown to the user before a message/gift is sent
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendPreview {
+    pub amount: f64,
+    pub fee: f64,
+    pub total: f64,
+    pub timing: Option<SendTimingEstimate>, // None if chain conditions couldn't be read
+}
+
+pub async fn preview_send(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    amount: f64,
+) -> Result<SendPreview, VerusRpcError> {
+    log::info!("Building send preview for amount {}", amount);
+    // The estimate is advisory; failures never block sending
+    let timing = match estimate_send_timing(&rpc_user, &rpc_pass, rpc_port, amount).await {
+        Ok(timing) => Some(timing),
+        Err(e) => {
+            log::warn!("Send timing estimate unavailable: {:?}", e);
+            None
+        }
+    };
+
+    Ok(SendPreview {
+        amount,
+        fee: DEFAULT_TX_FEE,
+        total: amount + DEFAULT_TX_FEE,
+        timing,
+    })
+}
+
+// A memo ready to be signed: the exact payload plus what is needed to assemble the memo afterwards.
+// Signing is done by the daemon (build_signed_memo_hex) or by an external signer (external_signer module).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnsignedMemo {
+    pub memo_text: String,
+    pub sender_identity: String,
+    pub timestamp: u64,
+    pub recipient_z_address: String,
+    pub payload: String, // Exact string to pass to signmessage
+}
+
+pub async fn prepare_memo(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    recipient_z_address: &str,
+    memo_text: &str,
+    sender_identity: &str,
+) -> UnsignedMemo {
+    // 1. Generate UTC timestamp when sending to blockchain (chain-derived if the user enabled correction)
+    super::clock::refresh_if_stale(rpc_user, rpc_pass, rpc_port).await;
+    let timestamp = super::clock::outgoing_timestamp();
+
+    // 2. Construct the base message for signing (without signature), including our protocol version
+    // and the recipient address so the signed memo can't be replayed to another recipient
+    let payload = signed_payload(memo_text, sender_identity, timestamp, PROTOCOL_VERSION, recipient_z_address);
+    log::debug!("Base message for signing: \"{}\" (timestamp: {})", payload, timestamp);
+    UnsignedMemo {
+        memo_text: memo_text.to_string(),
+        sender_identity: sender_identity.to_string(),
+        timestamp,
+        recipient_z_address: recipient_z_address.to_string(),
+        payload,
+    }
+}
+
+// Combine a prepared memo with its signature and encode it for z_sendmany (hex, compressed if needed)
+pub fn assemble_signed_memo_hex(unsigned: &UnsignedMemo, signature: &str) -> Result<String, VerusRpcError> {
+    // 4. Construct the full memo string with signature
+    let full_memo = build_memo(&unsigned.memo_text, &unsigned.sender_identity, unsigned.timestamp, PROTOCOL_VERSION, signature);
+    log::debug!("Constructed signed memo string: \"{}\"", full_memo);
+
+    // 5. Convert the memo string to its hexadecimal representation
+    // The z_sendmany memo limit is 512 bytes. Memos over the limit are deflate
+    // compressed (with a flag byte); if they still don't fit, sending fails here.
+    let memo_bytes = encode_memo(&full_memo)?;
+    let memo_hex = hex::encode(memo_bytes);
+    log::debug!("Hex encoded memo: {}", memo_hex);
+    Ok(memo_hex)
+}
+
+// Sign a memo for the recipient with the daemon and encode it for z_sendmany
+pub async fn build_signed_memo_hex(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    recipient_z_address: &str,
+    memo_text: &str,
+    sender_identity: &str,
+) -> Result<String, VerusRpcError> {
+    let unsigned = prepare_memo(rpc_user, rpc_pass, rpc_port, recipient_z_address, memo_text, sender_identity).await;
+
+    // 3. MANDATORY SIGNING: Sign the base message
+    let signature_response = match sign_message(rpc_user, rpc_pass, rpc_port, sender_identity, &unsigned.payload).await {
+        Ok(sig) => {
+            log::info!("Message signed successfully. Hash: {}", sig.hash);
+            sig
+        }
+        Err(VerusRpcError::WalletLocked) => return Err(VerusRpcError::WalletLocked),
+        Err(e) => {
+            log::error!("CRITICAL: Message signing failed: {:?}. Message will NOT be sent.", e);
+            return Err(VerusRpcError::SigningFailed);
+        }
+    };
+
+    assemble_signed_memo_hex(&unsigned, &signature_response.signature)
+}
+
 // NEW function for sending a message/gift with mandatory signature
+#[allow(clippy::too_many_arguments)]
 pub async fn send_private_message(
     rpc_user: String,
     rpc_pass: String,
@@ -240,7 +954,8 @@ pub async fn send_private_message(
     recipient_z_address: String, // Target user's private address
     memo_text: String,             // The actual message content (optional)
     sender_identity: String,       // Logged-in user's VerusID (e.g., user@)
-    amount: f64                    // Amount to send (0 if just a message)
+    amount: f64,                   // Amount to send (0 if just a message)
+    fee: Option<f64>,              // Explicit fee (validated with resolve_fee); None uses the daemon default
 ) -> Result<String, VerusRpcError> // Returns the txid on success
 {
     log::info!("send_private_message received memo_text: >>>{}<<<", memo_text); 
@@ -254,39 +969,26 @@ pub async fn send_private_message(
     );
     log::debug!("Original memo text: \"{}\"", memo_text);
 
-    // 1. Generate UTC timestamp when sending to blockchain
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-
-    // 2. Construct the base message for signing (without signature)
-    let base_message = format!("{}//f//{}//t//{}", memo_text, sender_identity, timestamp);
-    log::debug!("Base message for signing: \"{}\" (timestamp: {})", base_message, timestamp);
-
-    // 3. MANDATORY SIGNING: Sign the base message
-    let signature_response = match sign_message(&rpc_user, &rpc_pass, rpc_port, &sender_identity, &base_message).await {
-        Ok(sig) => {
-            log::info!("Message signed successfully. Hash: {}", sig.hash);
-            sig
-        }
-        Err(e) => {
-            log::error!("CRITICAL: Message signing failed: {:?}. Message will NOT be sent.", e);
-            return Err(VerusRpcError::SigningFailed);
-        }
-    };
-
-    // 4. Construct the full memo string with signature
-    let full_memo = format!("{}//f//{}//t//{}//{}", memo_text, sender_identity, timestamp, signature_response.signature);
-    log::debug!("Constructed signed memo string: \"{}\"", full_memo);
+    // Checked before signing so a bad fee or wrong-chain address never costs a signature
+    let fee = resolve_fee(fee, None)?;
+    validate_recipient_address(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address).await?;
+    let memo_hex = build_signed_memo_hex(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address, &memo_text, &sender_identity).await?;
 
-    // 5. Convert the memo string to its hexadecimal representation
-    // Ensure the memo is not too long - z_sendmany memo limit is typically 512 bytes.
-    // Hex encoding doubles the length, so the original memo should be < 256 bytes.
-    // The frontend already limits input to 412 characters, which is safe.
-    let memo_hex = hex::encode(full_memo.as_bytes());
-    log::debug!("Hex encoded memo: {}", memo_hex);
+    submit_signed_memo(&rpc_user, &rpc_pass, rpc_port, &sender_z_address, &recipient_z_address, amount, &memo_hex, fee).await
+}
 
+// Send an already signed and encoded memo (with the amount) via z_sendmany. Returns the txid.
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_signed_memo(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    sender_z_address: &str,
+    recipient_z_address: &str,
+    amount: f64,
+    memo_hex: &str,
+    fee: Option<f64>, // Already validated with resolve_fee
+) -> Result<String, VerusRpcError> {
     // 6. Construct the parameters for the z_sendmany RPC call
     let amounts_param = json!([
         {
@@ -296,16 +998,25 @@ pub async fn send_private_message(
         }
     ]);
 
-    let params = vec![
+    let mut params = vec![
         json!(sender_z_address),
         amounts_param,
         json!(1), // minconf (optional, default 1)
-        // fee (optional, default 0.0001) - Daemon handles this
     ];
+    // fee (optional, default 0.0001) - Daemon handles this unless the caller chose one
+    if let Some(fee) = fee {
+        log::info!("Using custom fee {}", fee);
+        params.push(json!(fee));
+    }
 
     // 7. Make the RPC call
     log::info!("Executing z_sendmany with signed message...");
-    match make_rpc_call::<String>(&rpc_user, &rpc_pass, rpc_port, "z_sendmany", params).await {
+    // z_sendmany is asynchronous: it returns an opid, the txid is only known once the proof is built
+    let result = match make_rpc_call::<String>(rpc_user, rpc_pass, rpc_port, "z_sendmany", params).await {
+        Ok(opid) => wait_for_operation(rpc_user, rpc_pass, rpc_port, &opid, SEND_OPERATION_TIMEOUT).await,
+        Err(e) => Err(e),
+    };
+    match result {
         Ok(txid) => {
             log::info!("z_sendmany successful with signed message, txid: {}", txid);
             Ok(txid)
@@ -315,4 +1026,259 @@ pub async fn send_private_message(
             Err(e)
         }
     }
-} 
\ No newline at end of file
+}
+
+// Send a gift to a transparent R-address. Transparent outputs carry no memo, so nothing is signed; the recipient
+// sees a plain payment. Returns the txid.
+pub async fn send_transparent_gift(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_t_address: String,
+    amount: f64,
+    fee: Option<f64>,
+) -> Result<String, VerusRpcError> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(VerusRpcError::InvalidAmount("a transparent gift needs an amount above zero".to_string()));
+    }
+    let fee = resolve_fee(fee, None)?;
+    validate_transparent_address(&rpc_user, &rpc_pass, rpc_port, &recipient_t_address).await?;
+    log::info!("Sending transparent gift of {} from {} to {}", amount, sender_z_address, recipient_t_address);
+
+    let mut params = vec![
+        json!(sender_z_address),
+        json!([{ "address": recipient_t_address, "amount": amount }]),
+        json!(1), // minconf
+    ];
+    if let Some(fee) = fee {
+        params.push(json!(fee));
+    }
+    let opid = make_rpc_call::<String>(&rpc_user, &rpc_pass, rpc_port, "z_sendmany", params).await?;
+    let txid = wait_for_operation(&rpc_user, &rpc_pass, rpc_port, &opid, SEND_OPERATION_TIMEOUT).await?;
+    log::info!("Transparent gift sent, txid: {}", txid);
+    Ok(txid)
+}
+
+// Fully qualified name of a gift currency, or InvalidCurrency if the chain doesn't know it
+pub async fn resolve_gift_currency(rpc_user: &str, rpc_pass: &str, rpc_port: u16, currency: &str) -> Result<String, VerusRpcError> {
+    let currency = currency.trim();
+    if currency.is_empty() {
+        return Err(VerusRpcError::InvalidCurrency("no currency given".to_string()));
+    }
+    let definition: Value = match make_cached_rpc_call(rpc_user, rpc_pass, rpc_port, "getcurrency", vec![json!(currency)]).await {
+        Ok(definition) => definition,
+        Err(VerusRpcError::Rpc { message, .. }) => {
+            return Err(VerusRpcError::InvalidCurrency(format!("{} ({})", currency, message)));
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(definition
+        .get("fullyqualifiedname")
+        .and_then(|v| v.as_str())
+        .unwrap_or(currency)
+        .to_string())
+}
+
+// Send a signed memo with an amount of the given currency (native or PBaaS). z_sendmany only moves the native
+// coin, so this goes through sendcurrency. Returns the opid of the async operation and the resolved currency name.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_currency_gift(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    memo_text: String,
+    sender_identity: String,
+    currency: String,
+    amount: f64,
+    fee: Option<f64>,
+) -> Result<(String, String), VerusRpcError> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(VerusRpcError::InvalidAmount("a currency gift needs an amount above zero".to_string()));
+    }
+    // Checked before signing, like send_private_message
+    let fee = resolve_fee(fee, None)?;
+    let currency = resolve_gift_currency(&rpc_user, &rpc_pass, rpc_port, &currency).await?;
+    validate_recipient_address(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address).await?;
+    log::info!("Sending gift of {} {} from {} to {}", amount, currency, sender_z_address, recipient_z_address);
+    let memo_hex = build_signed_memo_hex(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address, &memo_text, &sender_identity).await?;
+
+    // sendcurrency takes memos as text; a leading '#' marks hex (needed for compressed memos)
+    let output = json!({
+        "address": recipient_z_address,
+        "currency": currency,
+        "amount": amount,
+        "memo": format!("#{}", memo_hex),
+    });
+    let mut params = vec![json!(sender_z_address), json!([output]), json!(1)]; // minconf
+    if let Some(fee) = fee {
+        params.push(json!(fee));
+    }
+    let opid: String = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "sendcurrency", params).await?;
+    log::info!("Currency gift submitted as operation {}", opid);
+    Ok((opid, currency))
+}
+
+// Send several signed memos to one recipient in a single transaction (one zero-value output per memo).
+// Used for payloads that don't fit a single memo, e.g. inline file attachments.
+pub async fn send_signed_memos(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    memo_texts: Vec<String>,
+) -> Result<String, VerusRpcError> // Returns the txid on success
+{
+    log::info!(
+        "Attempting to send {} signed memos: from_addr={}, to_addr={}, sender_id={}",
+        memo_texts.len(),
+        sender_z_address,
+        recipient_z_address,
+        sender_identity
+    );
+
+    validate_recipient_address(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address).await?;
+    let mut outputs = Vec::with_capacity(memo_texts.len());
+    for memo_text in &memo_texts {
+        let memo_hex = build_signed_memo_hex(&rpc_user, &rpc_pass, rpc_port, &recipient_z_address, memo_text, &sender_identity).await?;
+        outputs.push(json!({
+            "address": recipient_z_address,
+            "amount": 0.0,
+            "memo": memo_hex
+        }));
+    }
+
+    let params = vec![json!(sender_z_address), json!(outputs), json!(1)];
+    let opid = make_rpc_call::<String>(&rpc_user, &rpc_pass, rpc_port, "z_sendmany", params).await?;
+    let txid = wait_for_operation(&rpc_user, &rpc_pass, rpc_port, &opid, SEND_OPERATION_TIMEOUT).await?;
+    log::info!("z_sendmany successful with {} signed memos, txid: {}", memo_texts.len(), txid);
+    Ok(txid)
+}
+
+// Fee and serialized size of one of our wallet transactions (gettransaction reports sends with a negative fee)
+pub async fn get_transaction_cost(rpc_user: &str, rpc_pass: &str, rpc_port: u16, txid: &str) -> Result<TxCost, VerusRpcError> {
+    let tx: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "gettransaction", vec![json!(txid)]).await?;
+    let fee = tx.get("fee").and_then(|v| v.as_f64()).unwrap_or(0.0).abs();
+    let size_bytes = tx.get("hex").and_then(|v| v.as_str()).map(|hex| hex.len() as u64 / 2).unwrap_or(0);
+    Ok(TxCost { fee, size_bytes })
+}
+
+// Sent message recovered from the wallet, with the z-address it was sent to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SentChatMessage {
+    pub recipient_private_address: String,
+    #[serde(flatten)]
+    pub message: ChatMessage,
+}
+
+// Rebuild sent messages from the wallet (e.g., after a reinstall).
+// Our sends are found via the change notes they return to our address and via listtransactions;
+// z_viewtransaction decrypts their outgoing outputs, which carry our own signed memos.
+pub async fn get_sent_message_history(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    own_private_address: String, // The logged-in user's z-addr
+    sender_identity: String,     // Only memos signed as this identity are recovered
+) -> Result<Vec<SentChatMessage>, VerusRpcError> {
+    log::info!("Recovering sent messages of {} from {}", sender_identity, own_private_address);
+
+    let mut candidate_txids: Vec<(String, i64)> = Vec::new(); // (txid, confirmations)
+
+    let received: Vec<ReceivedByAddressEntry> = make_rpc_call(
+        &rpc_user,
+        &rpc_pass,
+        rpc_port,
+        "z_listreceivedbyaddress",
+        vec![json!(own_private_address), json!(0)],
+    )
+    .await?;
+    candidate_txids.extend(received.into_iter().filter(|tx| tx.change).map(|tx| (tx.txid, tx.confirmations)));
+
+    // Sends that returned no change still show up in the wallet transaction list
+    match make_rpc_call::<Vec<Value>>(&rpc_user, &rpc_pass, rpc_port, "listtransactions", vec![json!("*"), json!(10000)]).await {
+        Ok(transactions) => candidate_txids.extend(transactions.iter().filter_map(|tx| {
+            let txid = tx.get("txid").and_then(|v| v.as_str())?;
+            let confirmations = tx.get("confirmations").and_then(|v| v.as_i64()).unwrap_or(0);
+            Some((txid.to_string(), confirmations))
+        })),
+        Err(e) => log::warn!("listtransactions failed, recovering from change notes only: {:?}", e),
+    }
+
+    let mut seen = HashSet::new();
+    candidate_txids.retain(|(txid, _)| seen.insert(txid.clone()));
+    log::debug!("Inspecting {} candidate wallet transactions for sent memos", candidate_txids.len());
+
+    let mut sent_messages = Vec::new();
+    for (txid, confirmations) in candidate_txids {
+        let view: Value = match make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_viewtransaction", vec![json!(txid)]).await {
+            Ok(view) => view,
+            Err(e) => {
+                log::debug!("z_viewtransaction failed for {}: {:?}", txid, e);
+                continue;
+            }
+        };
+
+        let outputs = view.get("outputs").and_then(|o| o.as_array()).cloned().unwrap_or_default();
+        let mut cost = None; // Looked up once a transaction turns out to carry one of our memos
+        for output in outputs {
+            let outgoing = output.get("outgoing").and_then(|v| v.as_bool()).unwrap_or(false);
+            let Some(recipient) = output.get("address").and_then(|v| v.as_str()) else { continue };
+            if !outgoing || recipient == own_private_address {
+                continue; // Change or incoming output
+            }
+
+            let memo = decode_memo(
+                output.get("memoStr").and_then(|v| v.as_str()),
+                output.get("memo").and_then(|v| v.as_str()),
+            );
+            let Some(memo) = memo else { continue };
+
+            // Outgoing outputs are only decryptable by the sender, so the memo needs no signature check,
+            // but the wallet may hold several identities.
+            let parts = match parse_memo(&memo) {
+                Ok(parts) if parts.sender_id == sender_identity => parts,
+                _ => continue,
+            };
+            let amount = output.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            if parts.text.is_empty() && amount <= 0.0 {
+                continue;
+            }
+            if cost.is_none() {
+                cost = get_transaction_cost(&rpc_user, &rpc_pass, rpc_port, &txid)
+                    .await
+                    .inspect_err(|e| log::debug!("No fee/size for {}: {:?}", txid, e))
+                    .ok();
+            }
+
+            sent_messages.push(SentChatMessage {
+                recipient_private_address: recipient.to_string(),
+                message: ChatMessage {
+                    id: txid.clone(),
+                    sender: sender_identity.clone(),
+                    text: parts.text.to_string(),
+                    timestamp: parts.timestamp,
+                    amount,
+                    confirmations,
+                    direction: "sent".to_string(),
+                    protocol_version: parts.protocol_version,
+                    recipient_bound: is_recipient_bound(parts.protocol_version),
+                    signature: Some(parts.signature.to_string()),
+                    fee: cost.map(|c| c.fee),
+                    size_bytes: cost.map(|c| c.size_bytes),
+                    currency: None,
+                    kind: None,
+                },
+            });
+        }
+    }
+
+    log::info!("Recovered {} sent messages for {}", sent_messages.len(), sender_identity);
+    sent_messages.sort_by_key(|m| m.message.timestamp);
+    Ok(sent_messages)
+}
+
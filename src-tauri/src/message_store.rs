@@ -0,0 +1,324 @@
+// File: src-tauri/src/message_store.rs
+// Description: Canonical per-conversation message lists merged from history, polling and sent messages.
+// Changes:
+// - Created file with MessageStore (managed state): messages are keyed by txid and deduplicated on merge.
+// - Lists are hydrated from and written through to settings persistence when the user opted in.
+// - load_conversation is public for conversation prefetching.
+// - Merging fills in the fee and size of sent messages.
+// - Added merge_conversations (duplicate contact folded into the primary conversation, deduplicated by txid).
+// - Added replace_id (sent messages recorded under a z_sendmany opid are moved to their txid).
+// - Corrected the sort comment: messages are recorded in seconds; only records from older versions may carry milliseconds.
+// - Added clear (emergency wipe).
+// - Received messages keep the currency of non-native gifts.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Runtime, State};
+use super::events::{emit_event, MESSAGE_STORE_UPDATED_EVENT};
+use super::formatting::normalize_timestamp_secs;
+use super::message_rpc;
+use super::settings::{
+    merge_conversation_records, read_conversation_messages, read_persistence_preference, write_conversation_messages, ChatMessage,
+    Conversation, SettingsError,
+};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MessageStoreUpdate {
+    pub identity_i_address: String,
+    pub conversation_id: String,
+    pub message_count: usize,
+}
+
+// Managed state: identity:conversation -> canonical messages (sorted oldest first)
+#[derive(Default)]
+pub struct MessageStore {
+    conversations: Mutex<HashMap<String, Vec<ChatMessage>>>,
+}
+
+impl From<message_rpc::ChatMessage> for ChatMessage {
+    fn from(message: message_rpc::ChatMessage) -> Self {
+        ChatMessage {
+            id: message.id,
+            sender: message.sender,
+            text: message.text,
+            timestamp: message.timestamp,
+            amount: message.amount,
+            confirmations: message.confirmations,
+            direction: message.direction,
+            status: None,
+            protocol_version: Some(message.protocol_version),
+            recipient_bound: Some(message.recipient_bound),
+            signature: message.signature,
+            fee: message.fee,
+            size_bytes: message.size_bytes,
+            kind: message.kind,
+            currency: message.currency,
+        }
+    }
+}
+
+fn conversation_key(identity_i_address: &str, conversation_id: &str) -> String {
+    format!("{}:{}", identity_i_address, conversation_id)
+}
+
+// Fold an incoming copy of a message into the stored one. Returns true if anything changed.
+fn merge_message(existing: &mut ChatMessage, incoming: ChatMessage) -> bool {
+    let mut changed = false;
+    if incoming.confirmations > existing.confirmations {
+        existing.confirmations = incoming.confirmations;
+        changed = true;
+    }
+    if incoming.status.is_some() && incoming.status != existing.status {
+        existing.status = incoming.status;
+        changed = true;
+    }
+    if existing.text.is_empty() && !incoming.text.is_empty() {
+        existing.text = incoming.text;
+        changed = true;
+    }
+    if existing.protocol_version.is_none() && incoming.protocol_version.is_some() {
+        existing.protocol_version = incoming.protocol_version;
+        changed = true;
+    }
+    if existing.recipient_bound.is_none() && incoming.recipient_bound.is_some() {
+        existing.recipient_bound = incoming.recipient_bound;
+        changed = true;
+    }
+    if existing.signature.is_none() && incoming.signature.is_some() {
+        existing.signature = incoming.signature;
+        changed = true;
+    }
+    if existing.fee.is_none() && incoming.fee.is_some() {
+        existing.fee = incoming.fee;
+        existing.size_bytes = incoming.size_bytes;
+        changed = true;
+    }
+    changed
+}
+
+impl MessageStore {
+    // Stored list for a conversation, hydrated from persistence on first access
+    pub fn load_conversation<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        identity_i_address: &str,
+        conversation_id: &str,
+    ) -> Vec<ChatMessage> {
+        let key = conversation_key(identity_i_address, conversation_id);
+        if let Some(messages) = self.conversations.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return messages.clone();
+        }
+
+        let persisted = match read_persistence_preference(app, identity_i_address) {
+            Ok(true) => read_conversation_messages(app, identity_i_address, conversation_id).unwrap_or_else(|e| {
+                log::warn!("Failed to hydrate message store for {}: {}", conversation_id, e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        self.conversations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key)
+            .or_insert(persisted)
+            .clone()
+    }
+
+    // Merge messages into a conversation and return the canonical list
+    pub fn merge<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        identity_i_address: &str,
+        conversation_id: &str,
+        incoming: Vec<ChatMessage>,
+    ) -> Result<Vec<ChatMessage>, SettingsError> {
+        self.load_conversation(app, identity_i_address, conversation_id);
+
+        let key = conversation_key(identity_i_address, conversation_id);
+        let (messages, changed) = {
+            let mut conversations = self.conversations.lock().unwrap_or_else(|e| e.into_inner());
+            let messages = conversations.entry(key).or_default();
+            let mut changed = false;
+            for message in incoming {
+                match messages.iter_mut().find(|m| m.id == message.id) {
+                    Some(existing) => changed |= merge_message(existing, message),
+                    None => {
+                        messages.push(message);
+                        changed = true;
+                    }
+                }
+            }
+            if changed {
+                // Timestamps are unix seconds, but records persisted by older versions may carry milliseconds
+                messages.sort_by(|a, b| {
+                    normalize_timestamp_secs(a.timestamp)
+                        .cmp(&normalize_timestamp_secs(b.timestamp))
+                        .then_with(|| a.id.cmp(&b.id))
+                });
+            }
+            (messages.clone(), changed)
+        };
+
+        if changed {
+            if read_persistence_preference(app, identity_i_address)? {
+                write_conversation_messages(app, identity_i_address, conversation_id, &messages)?;
+            }
+            emit_event(app, MESSAGE_STORE_UPDATED_EVENT, MessageStoreUpdate {
+                identity_i_address: identity_i_address.to_string(),
+                conversation_id: conversation_id.to_string(),
+                message_count: messages.len(),
+            });
+        }
+        Ok(messages)
+    }
+
+    // Move a message to a new id (a sent message recorded under its opid gets its txid). If the txid is already
+    // stored (e.g. rebuilt from the wallet), the opid copy is folded into it. Returns false if old_id isn't stored.
+    pub fn replace_id<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        identity_i_address: &str,
+        conversation_id: &str,
+        old_id: &str,
+        new_id: &str,
+    ) -> Result<bool, SettingsError> {
+        self.load_conversation(app, identity_i_address, conversation_id);
+
+        let key = conversation_key(identity_i_address, conversation_id);
+        let messages = {
+            let mut conversations = self.conversations.lock().unwrap_or_else(|e| e.into_inner());
+            let messages = conversations.entry(key).or_default();
+            let Some(position) = messages.iter().position(|m| m.id == old_id) else {
+                return Ok(false);
+            };
+            let mut message = messages.remove(position);
+            message.id = new_id.to_string();
+            match messages.iter_mut().find(|m| m.id == new_id) {
+                Some(existing) => {
+                    merge_message(existing, message);
+                }
+                None => {
+                    messages.push(message);
+                    messages.sort_by(|a, b| {
+                        normalize_timestamp_secs(a.timestamp)
+                            .cmp(&normalize_timestamp_secs(b.timestamp))
+                            .then_with(|| a.id.cmp(&b.id))
+                    });
+                }
+            }
+            messages.clone()
+        };
+
+        if read_persistence_preference(app, identity_i_address)? {
+            write_conversation_messages(app, identity_i_address, conversation_id, &messages)?;
+        }
+        emit_event(app, MESSAGE_STORE_UPDATED_EVENT, MessageStoreUpdate {
+            identity_i_address: identity_i_address.to_string(),
+            conversation_id: conversation_id.to_string(),
+            message_count: messages.len(),
+        });
+        Ok(true)
+    }
+
+    // Merge polled messages, grouped by sender (conversation id)
+    pub fn ingest_received<R: Runtime>(
+        &self,
+        app: &AppHandle<R>,
+        identity_i_address: &str,
+        messages: &[message_rpc::ChatMessage],
+    ) -> Result<(), SettingsError> {
+        let mut by_conversation: HashMap<String, Vec<ChatMessage>> = HashMap::new();
+        for message in messages {
+            by_conversation.entry(message.sender.clone()).or_default().push(message.clone().into());
+        }
+        for (conversation_id, incoming) in by_conversation {
+            self.merge(app, identity_i_address, &conversation_id, incoming)?;
+        }
+        Ok(())
+    }
+
+    // Replace the primary's list with the merged one and drop the duplicate's (records are already persisted)
+    fn apply_merge(&self, identity_i_address: &str, primary_id: &str, duplicate_id: &str, messages: Vec<ChatMessage>) {
+        let mut conversations = self.conversations.lock().unwrap_or_else(|e| e.into_inner());
+        conversations.remove(&conversation_key(identity_i_address, duplicate_id));
+        conversations.insert(conversation_key(identity_i_address, primary_id), messages);
+    }
+
+    pub fn clear_identity(&self, identity_i_address: &str) {
+        let prefix = format!("{}:", identity_i_address);
+        self.conversations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    pub fn clear(&self) {
+        self.conversations.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+// --- Tauri Commands ---
+
+// Canonical, deduplicated message list for a conversation (oldest first)
+#[tauri::command]
+pub async fn get_conversation_messages<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<Vec<ChatMessage>, SettingsError> {
+    log::debug!("get_conversation_messages command received for {} (user {})", conversation_id, identity_i_address);
+    Ok(store.load_conversation(&app, &identity_i_address, &conversation_id))
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MergedConversation {
+    pub conversation: Conversation,
+    pub merged_from: String,
+    pub message_count: usize,
+}
+
+// Fold a duplicate conversation of the same contact (e.g. added by i-address and by name, or before and after an
+// address rotation) into the primary one. Messages are united and deduplicated by txid; the primary's private
+// address is kept.
+#[tauri::command]
+pub async fn merge_conversations<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    primary_id: String,
+    duplicate_id: String,
+) -> Result<MergedConversation, SettingsError> {
+    log::info!("merge_conversations command received: {} into {} (user {})", duplicate_id, primary_id, identity_i_address);
+    if primary_id == duplicate_id {
+        return Err(SettingsError::Invalid("cannot merge a conversation into itself".to_string()));
+    }
+
+    let mut messages = store.load_conversation(&app, &identity_i_address, &primary_id);
+    for message in store.load_conversation(&app, &identity_i_address, &duplicate_id) {
+        match messages.iter_mut().find(|m| m.id == message.id) {
+            Some(existing) => {
+                merge_message(existing, message);
+            }
+            None => messages.push(message),
+        }
+    }
+    messages.sort_by(|a, b| {
+        normalize_timestamp_secs(a.timestamp)
+            .cmp(&normalize_timestamp_secs(b.timestamp))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+
+    // Persisted first: if it fails, nothing changed in memory either
+    let conversation = merge_conversation_records(&app, &identity_i_address, &primary_id, &duplicate_id, &messages)?;
+    let message_count = messages.len();
+    store.apply_merge(&identity_i_address, &primary_id, &duplicate_id, messages);
+    emit_event(&app, MESSAGE_STORE_UPDATED_EVENT, MessageStoreUpdate {
+        identity_i_address: identity_i_address.clone(),
+        conversation_id: primary_id.clone(),
+        message_count,
+    });
+    log::info!("Merged conversation {} into {} ({} messages)", duplicate_id, primary_id, message_count);
+    Ok(MergedConversation { conversation, merged_from: duplicate_id, message_count })
+}
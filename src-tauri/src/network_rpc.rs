@@ -0,0 +1,203 @@
+// File: src-tauri/src/network_rpc.rs
+// Description: Chain / mempool conditions used to advise users before sending.
+// Changes:
+// - Created file with estimate_send_timing (mempool congestion + recent block intervals -> confirmation ETA).
+// - Added is_daemon_synced (block / header heights and verification progress).
+// - Added get_network_status (connections, protocol version, relay fee and peer summary; warns with no peers).
+// - Block times for the interval estimate come from the block-aware response cache.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use super::response_cache::make_cached_background_rpc_call;
+use super::rpc_client::{make_background_rpc_call, VerusRpcError};
+
+// Verus targets one block per minute
+const TARGET_BLOCK_INTERVAL_SECS: f64 = 60.0;
+
+// Number of recent blocks used to measure the actual block interval
+const BLOCK_INTERVAL_SAMPLE: u64 = 20;
+
+// Approximate usable block size for shielded transactions
+const BLOCK_CAPACITY_BYTES: u64 = 2_000_000;
+
+// Verification progress at which the daemon counts as caught up
+pub const SYNCED_VERIFICATION_PROGRESS: f64 = 0.9999;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Congestion {
+    Low,      // Mempool fits in the next block
+    Moderate, // A few blocks of backlog
+    High,     // Long backlog; expect noticeable delays
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendTimingEstimate {
+    pub mempool_transactions: u64,
+    pub mempool_bytes: u64,
+    pub congestion: Congestion,
+    pub average_block_interval_secs: f64,  // Measured over recent blocks
+    pub blocks_until_included: u64,
+    pub recommended_confirmations: u64,    // Larger gifts warrant waiting for more confirmations
+    pub estimated_seconds: u64,            // Until the recommended confirmations are reached
+    pub summary: String,                   // e.g., "a few minutes", "about an hour"
+}
+
+// More value at stake -> more confirmations before the recipient should rely on it
+fn recommended_confirmations(amount: f64) -> u64 {
+    match amount {
+        a if a >= 100.0 => 10,
+        a if a >= 1.0 => 3,
+        _ => 1,
+    }
+}
+
+fn describe_duration(seconds: u64) -> String {
+    match seconds {
+        0..=299 => "a few minutes".to_string(),
+        300..=2_699 => format!("about {} minutes", seconds.div_ceil(60)),
+        2_700..=5_399 => "about an hour".to_string(),
+        _ => format!("about {} hours", (seconds + 1_799) / 3_600),
+    }
+}
+
+async fn block_time(rpc_user: &str, rpc_pass: &str, rpc_port: u16, height: u64) -> Result<u64, VerusRpcError> {
+    let hash: String = make_cached_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockhash", vec![json!(height)]).await?;
+    let header: Value = make_cached_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockheader", vec![json!(hash)]).await?;
+    header
+        .get("time")
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| VerusRpcError::ParseError("getblockheader response missing time".to_string()))
+}
+
+// Average interval of recent blocks, falling back to the target interval
+async fn average_block_interval(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> f64 {
+    let measured = async {
+        let tip: u64 = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockcount", vec![]).await?;
+        let start = tip.saturating_sub(BLOCK_INTERVAL_SAMPLE);
+        if start == tip {
+            return Ok(None);
+        }
+        let tip_time = block_time(rpc_user, rpc_pass, rpc_port, tip).await?;
+        let start_time = block_time(rpc_user, rpc_pass, rpc_port, start).await?;
+        Ok::<_, VerusRpcError>(Some(tip_time.saturating_sub(start_time) as f64 / (tip - start) as f64))
+    };
+
+    match measured.await {
+        Ok(Some(interval)) if interval > 0.0 => interval,
+        Ok(_) => TARGET_BLOCK_INTERVAL_SECS,
+        Err(e) => {
+            log::debug!("Could not measure block interval, using target: {:?}", e);
+            TARGET_BLOCK_INTERVAL_SECS
+        }
+    }
+}
+
+// Estimate how long a send of `amount` will take to reach the recommended confirmations
+pub async fn estimate_send_timing(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    amount: f64,
+) -> Result<SendTimingEstimate, VerusRpcError> {
+    let mempool: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getmempoolinfo", vec![]).await?;
+    let mempool_transactions = mempool.get("size").and_then(|v| v.as_u64()).unwrap_or(0);
+    let mempool_bytes = mempool.get("bytes").and_then(|v| v.as_u64()).unwrap_or(0);
+
+    let average_block_interval_secs = average_block_interval(rpc_user, rpc_pass, rpc_port).await;
+
+    // Our transaction lands after the current backlog
+    let blocks_until_included = 1 + mempool_bytes / BLOCK_CAPACITY_BYTES;
+    let congestion = match blocks_until_included {
+        1 => Congestion::Low,
+        2..=5 => Congestion::Moderate,
+        _ => Congestion::High,
+    };
+
+    let recommended_confirmations = recommended_confirmations(amount);
+    let blocks_total = blocks_until_included + recommended_confirmations - 1;
+    let estimated_seconds = (blocks_total as f64 * average_block_interval_secs).round() as u64;
+
+    log::debug!(
+        "Send timing: mempool {} txs / {} bytes, interval {:.1}s, {} blocks -> {}s",
+        mempool_transactions, mempool_bytes, average_block_interval_secs, blocks_total, estimated_seconds
+    );
+
+    Ok(SendTimingEstimate {
+        mempool_transactions,
+        mempool_bytes,
+        congestion,
+        average_block_interval_secs,
+        blocks_until_included,
+        recommended_confirmations,
+        estimated_seconds,
+        summary: describe_duration(estimated_seconds),
+    })
+}
+
+// Whether the daemon has caught up with the chain (identity lookups are only reliable then)
+pub async fn is_daemon_synced(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Result<bool, VerusRpcError> {
+    let info: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockchaininfo", vec![]).await?;
+    let blocks = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    let headers = info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0);
+    let progress = info.get("verificationprogress").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    log::trace!("Daemon sync: blocks {} / headers {}, progress {:.6}", blocks, headers, progress);
+    Ok(headers > 0 && blocks >= headers && progress >= SYNCED_VERIFICATION_PROGRESS)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkStatus {
+    pub connections: u64,
+    pub inbound_peers: u64,
+    pub outbound_peers: u64,
+    pub protocol_version: Option<u64>,
+    pub subversion: Option<String>,     // Daemon user agent, e.g. "/MagicBean:.../"
+    pub relay_fee: Option<f64>,         // Minimum fee per kB for relaying
+    pub best_peer_height: Option<u64>,  // Highest synced height among peers
+    pub warnings: Vec<String>,          // Daemon warnings plus our own (e.g. no peers)
+    pub can_propagate: bool,            // At least one peer to relay sent messages to
+}
+
+// Connection overview so the app can warn when sent messages would not leave this node
+pub async fn get_network_status(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Result<NetworkStatus, VerusRpcError> {
+    let info: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getnetworkinfo", vec![]).await?;
+    // Peer details only refine the summary; the connection count comes from getnetworkinfo
+    let peers: Vec<Value> = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getpeerinfo", vec![])
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("getpeerinfo failed: {:?}", e);
+            Vec::new()
+        });
+
+    let connections = info.get("connections").and_then(|v| v.as_u64()).unwrap_or(peers.len() as u64);
+    let inbound_peers = peers.iter().filter(|p| p.get("inbound").and_then(|v| v.as_bool()).unwrap_or(false)).count() as u64;
+    let best_peer_height = peers
+        .iter()
+        .filter_map(|p| p.get("synced_blocks").or_else(|| p.get("startingheight")).and_then(|v| v.as_i64()))
+        .filter(|h| *h >= 0)
+        .max()
+        .map(|h| h as u64);
+
+    let mut warnings: Vec<String> = info
+        .get("warnings")
+        .and_then(|v| v.as_str())
+        .filter(|w| !w.is_empty())
+        .map(|w| vec![w.to_string()])
+        .unwrap_or_default();
+    if connections == 0 {
+        log::warn!("Daemon has no peers; sent messages will not propagate");
+        warnings.push("The daemon has no peers; sent messages will not propagate until it connects.".to_string());
+    }
+
+    Ok(NetworkStatus {
+        connections,
+        inbound_peers,
+        outbound_peers: connections.saturating_sub(inbound_peers),
+        protocol_version: info.get("protocolversion").and_then(|v| v.as_u64()),
+        subversion: info.get("subversion").and_then(|v| v.as_str()).map(String::from),
+        relay_fee: info.get("relayfee").and_then(|v| v.as_f64()),
+        best_peer_height,
+        warnings,
+        can_propagate: connections > 0,
+    })
+}
@@ -0,0 +1,77 @@
+// File: src-tauri/src/notes.rs
+// Description: User-assigned reservations of sapling notes (fast messages vs. savings).
+// Changes:
+// - Created file. Reservations are stored per note (note_reservations.json, keyed by "txid:outindex") and
+//   attached to list_notes results; entries of spent notes are pruned when the address's notes are listed.
+// - read_reservations is public (UTXO maintenance leaves savings notes out of its source check).
+// - NoteError goes into the error log when a command returns it.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, Runtime};
+use super::error_log::recorded_command_error;
+use super::storage::{load_value, save_value};
+use super::wallet_rpc::{NoteReservation, SaplingNote};
+
+const RESERVATIONS_STORE_PATH: &str = "note_reservations.json";
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum NoteError {
+    #[error("Storage error: {0}")]
+    Storage(String),
+}
+
+recorded_command_error!(NoteError, "notes");
+
+impl From<super::storage::StorageError> for NoteError {
+    fn from(error: super::storage::StorageError) -> Self {
+        NoteError::Storage(error.to_string())
+    }
+}
+
+fn note_key(txid: &str, outindex: u32) -> String {
+    format!("{}:{}", txid, outindex)
+}
+
+pub fn read_reservations<R: Runtime>(app: &AppHandle<R>, address: &str) -> Result<HashMap<String, NoteReservation>, NoteError> {
+    Ok(load_value(app, RESERVATIONS_STORE_PATH, address)?.unwrap_or_default())
+}
+
+// Fill in the stored reservation of each note and forget reservations of notes that are gone
+pub fn apply_reservations<R: Runtime>(app: &AppHandle<R>, address: &str, notes: &mut [SaplingNote]) -> Result<(), NoteError> {
+    let mut reservations = read_reservations(app, address)?;
+    for note in notes.iter_mut() {
+        note.reservation = reservations.get(&note_key(&note.txid, note.outindex)).copied();
+    }
+
+    let count = reservations.len();
+    reservations.retain(|key, _| notes.iter().any(|n| note_key(&n.txid, n.outindex) == *key));
+    if reservations.len() != count {
+        log::debug!("Pruned {} reservations of spent notes for {}", count - reservations.len(), address);
+        save_value(app, RESERVATIONS_STORE_PATH, address, &reservations)?;
+    }
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+// Mark a note as reserved for fast messages or savings (None clears the mark)
+#[tauri::command]
+pub async fn set_note_reservation<R: Runtime>(
+    app: AppHandle<R>,
+    address: String,
+    txid: String,
+    outindex: u32,
+    reservation: Option<NoteReservation>,
+) -> Result<(), NoteError> {
+    log::info!("set_note_reservation command received for {}:{} ({:?})", txid, outindex, reservation);
+    let mut reservations = read_reservations(&app, &address)?;
+    let key = note_key(&txid, outindex);
+    match reservation {
+        Some(reservation) => reservations.insert(key, reservation),
+        None => reservations.remove(&key),
+    };
+    save_value(&app, RESERVATIONS_STORE_PATH, &address, &reservations)?;
+    Ok(())
+}
@@ -0,0 +1,171 @@
+// File: src-tauri/src/notifications.rs
+// Description: Backend notification pipeline for newly received messages.
+// Changes:
+// - Created file: evaluates per-conversation mute settings (with keyword / gift exceptions)
+//   and emits message-notification events for new messages.
+// - Seeding (no notifications) is driven by the caller's initial sync instead of the first poll per identity.
+// - Timed mutes (muted_until) stop suppressing notifications once they expire; messages are stored either way.
+// - Per-identity preview setting (full / sender only / generic); redaction happens here, before the event is emitted.
+// - The record's timestamp comes from clock::now_secs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use super::events::{emit_event, MESSAGE_NOTIFICATION_EVENT};
+use super::message_rpc::ChatMessage;
+use super::settings::{read_mute_settings, MuteSettings};
+use super::storage::{load_value, save_value, StorageError};
+use super::clock::now_secs;
+
+const NOTIFICATION_STORE_PATH: &str = "store.json";
+
+// How much of a message a notification may reveal
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationPreview {
+    #[default]
+    Full,       // Sender, text and gift amount
+    SenderOnly, // Who wrote, not what
+    Generic,    // Just "new message" (locked mode: nothing identifying leaves the backend)
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "detail", rename_all = "snake_case")]
+pub enum NotificationReason {
+    Normal,               // Conversation not muted
+    KeywordException(String), // Muted, but the text matched an exception keyword
+    GiftException,        // Muted, but the gift met the amount exception
+}
+
+// Fields the preview setting hides are None
+#[derive(Serialize, Debug, Clone)]
+pub struct MessageNotification {
+    pub conversation_id: Option<String>,
+    pub sender: Option<String>,
+    pub txid: String,
+    pub text: Option<String>,
+    pub amount: Option<f64>,
+    pub reason: NotificationReason,
+    pub preview: NotificationPreview,
+}
+
+// Managed state: which messages were already notified, per identity
+#[derive(Default)]
+pub struct NotificationState {
+    notified: Mutex<HashSet<String>>, // identity:txid
+}
+
+// Decide whether a message should notify. None means suppressed.
+pub fn evaluate_notification(settings: &MuteSettings, text: &str, amount: f64, now_secs: u64) -> Option<NotificationReason> {
+    if !settings.is_muted_at(now_secs) {
+        return Some(NotificationReason::Normal);
+    }
+    if let Some(min_gift) = settings.min_gift_exception {
+        if amount > 0.0 && amount >= min_gift {
+            return Some(NotificationReason::GiftException);
+        }
+    }
+    let lowercase_text = text.to_lowercase();
+    settings
+        .keyword_exceptions
+        .iter()
+        .map(|k| k.trim())
+        .find(|k| !k.is_empty() && lowercase_text.contains(&k.to_lowercase()))
+        .map(|k| NotificationReason::KeywordException(k.to_string()))
+}
+
+fn get_preview_key(identity_i_address: &str) -> String {
+    format!("notification_preview_{}", identity_i_address)
+}
+
+pub fn read_notification_preview<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> NotificationPreview {
+    load_value::<R, NotificationPreview>(app, NOTIFICATION_STORE_PATH, &get_preview_key(identity_i_address))
+        .unwrap_or_else(|e| {
+            // Fail closed: an unreadable setting must not reveal message contents
+            log::warn!("Failed to read notification preview setting, using generic previews: {}", e);
+            Some(NotificationPreview::Generic)
+        })
+        .unwrap_or_default()
+}
+
+// Build the notification payload, leaving out whatever the preview setting hides
+fn build_notification(message: &ChatMessage, reason: NotificationReason, preview: NotificationPreview) -> MessageNotification {
+    let (show_sender, show_content) = match preview {
+        NotificationPreview::Full => (true, true),
+        NotificationPreview::SenderOnly => (true, false),
+        NotificationPreview::Generic => (false, false),
+    };
+    // A matched keyword would reveal part of the text
+    let reason = match reason {
+        NotificationReason::KeywordException(_) if !show_content => NotificationReason::KeywordException(String::new()),
+        reason => reason,
+    };
+    MessageNotification {
+        // Conversations are keyed by the partner's VerusID name
+        conversation_id: show_sender.then(|| message.sender.clone()),
+        sender: show_sender.then(|| message.sender.clone()),
+        txid: message.id.clone(),
+        text: show_content.then(|| message.text.clone()),
+        amount: show_content.then_some(message.amount),
+        reason,
+        preview,
+    }
+}
+
+// Run new messages through the pipeline and emit notifications.
+// On an initial (full history) sync the messages are only recorded so history doesn't notify.
+pub fn dispatch_message_notifications<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    messages: &[ChatMessage],
+    initial_sync: bool,
+) {
+    let state = app.state::<NotificationState>();
+
+    let new_messages: Vec<&ChatMessage> = {
+        let mut notified = state.notified.lock().unwrap_or_else(|e| e.into_inner());
+        messages
+            .iter()
+            .filter(|m| notified.insert(format!("{}:{}", identity_i_address, m.id)))
+            .collect()
+    };
+
+    if initial_sync {
+        log::debug!("Seeded notification state for {} with {} messages", identity_i_address, new_messages.len());
+        return;
+    }
+
+    let now = now_secs();
+    let preview = read_notification_preview(app, identity_i_address);
+    for message in new_messages {
+        // Conversations are keyed by the partner's VerusID name
+        let conversation_id = &message.sender;
+        let settings = read_mute_settings(app, identity_i_address, conversation_id).unwrap_or_else(|e| {
+            log::warn!("Failed to read mute settings for {}: {}", conversation_id, e);
+            MuteSettings::default()
+        });
+
+        match evaluate_notification(&settings, &message.text, message.amount, now) {
+            Some(reason) => {
+                log::debug!("Notifying for message {} from {} ({:?})", message.id, message.sender, reason);
+                emit_event(app, MESSAGE_NOTIFICATION_EVENT, build_notification(message, reason, preview));
+            }
+            None => log::debug!("Notification suppressed for muted conversation {}", conversation_id),
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_notification_preview<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> NotificationPreview {
+    log::debug!("get_notification_preview command received for {}", identity_i_address);
+    read_notification_preview(&app, &identity_i_address)
+}
+
+#[tauri::command]
+pub fn set_notification_preview<R: Runtime>(app: AppHandle<R>, identity_i_address: String, preview: NotificationPreview) -> Result<(), StorageError> {
+    log::info!("set_notification_preview command received for {}: {:?}", identity_i_address, preview);
+    save_value(&app, NOTIFICATION_STORE_PATH, &get_preview_key(&identity_i_address), &preview)
+}
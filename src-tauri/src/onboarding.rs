@@ -0,0 +1,261 @@
+// File: src-tauri/src/onboarding.rs
+// Description: Resumable onboarding state machine (detect -> select chain -> verify connection -> pick identity -> finish).
+// Changes:
+// - Created file with OnboardingState persisted in the store and get_onboarding_state / advance_onboarding commands.
+// - Identity selection honours the configured identity filter rules.
+// - Selecting a detected chain keeps its cookie path (cookie-file authentication).
+// - OnboardingAction's Debug output redacts manual credentials (it ends up in logs and error messages).
+// - Detected chains are kept without their credentials; selecting a chain re-reads the selected one's, so only the
+//   selected chain's credentials are ever stored.
+// - OnboardingError goes into the error log when a command returns it.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime};
+use super::credentials::{load_credentials, run_parallel_detection, save_credentials, BlockchainDetectionResult, BlockchainStatus, CredentialError, Credentials, DiscoveryError};
+use super::error_log::recorded_command_error;
+use super::identity_rpc::{get_login_identities_fast, FormattedIdentity};
+use super::rpc_client::VerusRpcError;
+use super::settings::read_identity_filter_rules;
+use super::storage::{load_value, save_value, StorageError};
+use super::wallet_rpc::connect_and_get_block_height;
+
+const STORE_PATH: &str = "store.json";
+const ONBOARDING_KEY: &str = "onboarding_state";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    #[default]
+    Detect,
+    SelectChain,
+    VerifyConnection,
+    PickIdentity,
+    Finished,
+}
+
+impl OnboardingStep {
+    fn previous(self) -> OnboardingStep {
+        match self {
+            OnboardingStep::Detect | OnboardingStep::SelectChain => OnboardingStep::Detect,
+            OnboardingStep::VerifyConnection => OnboardingStep::SelectChain,
+            OnboardingStep::PickIdentity => OnboardingStep::VerifyConnection,
+            OnboardingStep::Finished => OnboardingStep::PickIdentity,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OnboardingState {
+    pub step: OnboardingStep,
+    #[serde(default)]
+    pub detected_chains: Vec<BlockchainDetectionResult>, // Result of the last detection run
+    pub selected_chain_id: Option<String>,
+    pub block_height: Option<u64>,               // Reported by the daemon during verification
+    pub selected_identity: Option<FormattedIdentity>,
+    pub last_error: Option<String>,              // Why the last action did not advance
+}
+
+// Actions sent by the frontend. Each is only valid on its own step (Back / Reset always are).
+#[derive(Deserialize, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OnboardingAction {
+    Detect,
+    SelectChain {
+        chain_id: String,
+        credentials: Option<Credentials>, // Manual credentials; defaults to the detected ones
+    },
+    VerifyConnection,
+    PickIdentity { i_address: String },
+    Back,
+    Reset,
+}
+
+// Logged and put into error messages, so manual credentials are left out
+impl std::fmt::Debug for OnboardingAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OnboardingAction::Detect => write!(f, "Detect"),
+            OnboardingAction::SelectChain { chain_id, credentials } => f
+                .debug_struct("SelectChain")
+                .field("chain_id", chain_id)
+                .field("credentials", &credentials.as_ref().map(|_| "<redacted>"))
+                .finish(),
+            OnboardingAction::VerifyConnection => write!(f, "VerifyConnection"),
+            OnboardingAction::PickIdentity { i_address } => f.debug_struct("PickIdentity").field("i_address", i_address).finish(),
+            OnboardingAction::Back => write!(f, "Back"),
+            OnboardingAction::Reset => write!(f, "Reset"),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum OnboardingError {
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Action {action} is not valid in step {step:?}")]
+    InvalidTransition { step: OnboardingStep, action: String },
+    #[error("Unknown or unavailable blockchain: {0}")]
+    UnknownChain(String),
+    #[error("Identity not found in wallet: {0}")]
+    UnknownIdentity(String),
+    #[error("Credential error: {0}")]
+    Credentials(String),
+    #[error("Detection error: {0}")]
+    Detection(String),
+}
+
+recorded_command_error!(OnboardingError, "onboarding");
+
+impl From<StorageError> for OnboardingError {
+    fn from(error: StorageError) -> Self {
+        OnboardingError::Storage(error.to_string())
+    }
+}
+
+impl From<CredentialError> for OnboardingError {
+    fn from(error: CredentialError) -> Self {
+        OnboardingError::Credentials(error.to_string())
+    }
+}
+
+impl From<DiscoveryError> for OnboardingError {
+    fn from(error: DiscoveryError) -> Self {
+        OnboardingError::Detection(error.to_string())
+    }
+}
+
+fn load_state<R: Runtime>(app: &AppHandle<R>) -> Result<OnboardingState, OnboardingError> {
+    let mut state = load_value::<R, OnboardingState>(app, STORE_PATH, ONBOARDING_KEY)?.unwrap_or_default();
+    // States saved by earlier versions still hold every detected chain's credentials
+    state.detected_chains = without_credentials(state.detected_chains);
+    Ok(state)
+}
+
+fn save_state<R: Runtime>(app: &AppHandle<R>, state: &OnboardingState) -> Result<(), OnboardingError> {
+    save_value(app, STORE_PATH, ONBOARDING_KEY, state)?;
+    Ok(())
+}
+
+// Detection results without their RPC credentials (only the selected chain's are stored, by save_credentials)
+fn without_credentials(chains: Vec<BlockchainDetectionResult>) -> Vec<BlockchainDetectionResult> {
+    chains.into_iter().map(|chain| BlockchainDetectionResult { credentials: None, ..chain }).collect()
+}
+
+// Credentials of a detected chain, read again from its config (the state doesn't keep them)
+async fn detected_credentials(chain_id: &str) -> Result<Credentials, OnboardingError> {
+    let detection = run_parallel_detection().await?;
+    detection
+        .blockchains
+        .into_iter()
+        .find(|c| c.blockchain_id == chain_id && matches!(c.status, BlockchainStatus::Available | BlockchainStatus::Loading))
+        .and_then(|c| c.credentials)
+        .ok_or_else(|| OnboardingError::UnknownChain(chain_id.to_string()))
+}
+
+fn invalid(step: OnboardingStep, action: &OnboardingAction) -> OnboardingError {
+    OnboardingError::InvalidTransition { step, action: format!("{:?}", action) }
+}
+
+fn describe_rpc_error(error: &VerusRpcError) -> String {
+    match error {
+        VerusRpcError::Rpc { code: -28, message } => format!("Daemon is still loading: {}", message),
+        _ => error.to_string(),
+    }
+}
+
+// Apply one action to the state. RPC failures are recorded in last_error instead of failing the command.
+async fn apply_action<R: Runtime>(
+    app: &AppHandle<R>,
+    mut state: OnboardingState,
+    action: OnboardingAction,
+) -> Result<OnboardingState, OnboardingError> {
+    state.last_error = None;
+
+    match (&action, state.step) {
+        (OnboardingAction::Reset, _) => return Ok(OnboardingState::default()),
+        (OnboardingAction::Back, step) => state.step = step.previous(),
+
+        // Re-detecting is allowed while choosing a chain
+        (OnboardingAction::Detect, OnboardingStep::Detect | OnboardingStep::SelectChain) => {
+            let detection = run_parallel_detection().await?;
+            state.detected_chains = without_credentials(detection.blockchains);
+            state.step = OnboardingStep::SelectChain;
+        }
+
+        (OnboardingAction::SelectChain { chain_id, credentials }, OnboardingStep::SelectChain) => {
+            if !state.detected_chains.iter().any(|c| &c.blockchain_id == chain_id) && credentials.is_none() {
+                return Err(OnboardingError::UnknownChain(chain_id.clone()));
+            }
+            let credentials = match credentials {
+                Some(manual) => manual.clone(),
+                None => detected_credentials(chain_id).await?,
+            };
+            save_credentials(app.clone(), credentials.rpc_user, credentials.rpc_pass, credentials.rpc_port, credentials.cookie_path).await?;
+            state.selected_chain_id = Some(chain_id.clone());
+            state.block_height = None;
+            state.selected_identity = None;
+            state.step = OnboardingStep::VerifyConnection;
+        }
+
+        (OnboardingAction::VerifyConnection, OnboardingStep::VerifyConnection) => {
+            let creds = load_credentials(app.clone()).await?;
+            match connect_and_get_block_height(creds.rpc_user, creds.rpc_pass, creds.rpc_port).await {
+                Ok(height) => {
+                    state.block_height = Some(height);
+                    state.step = OnboardingStep::PickIdentity;
+                }
+                Err(e) => {
+                    log::warn!("Onboarding connection check failed: {:?}", e);
+                    state.last_error = Some(describe_rpc_error(&e));
+                }
+            }
+        }
+
+        (OnboardingAction::PickIdentity { i_address }, OnboardingStep::PickIdentity) => {
+            let creds = load_credentials(app.clone()).await?;
+            let rules = read_identity_filter_rules(app).unwrap_or_default();
+            match get_login_identities_fast(creds.rpc_user, creds.rpc_pass, creds.rpc_port, &rules).await {
+                Ok(identities) => {
+                    let identity = identities
+                        .into_iter()
+                        .find(|i| &i.i_address == i_address)
+                        .ok_or_else(|| OnboardingError::UnknownIdentity(i_address.clone()))?;
+                    state.selected_identity = Some(identity);
+                    state.step = OnboardingStep::Finished;
+                }
+                Err(e) => {
+                    log::warn!("Onboarding identity lookup failed: {:?}", e);
+                    state.last_error = Some(describe_rpc_error(&e));
+                }
+            }
+        }
+
+        (_, step) => return Err(invalid(step, &action)),
+    }
+
+    Ok(state)
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn get_onboarding_state<R: Runtime>(app: AppHandle<R>) -> Result<OnboardingState, OnboardingError> {
+    log::debug!("get_onboarding_state command received");
+    load_state(&app)
+}
+
+#[tauri::command]
+pub async fn advance_onboarding<R: Runtime>(
+    app: AppHandle<R>,
+    action: OnboardingAction,
+) -> Result<OnboardingState, OnboardingError> {
+    let state = load_state(&app)?;
+    log::info!("advance_onboarding command received: {:?} (current step: {:?})", action, state.step);
+
+    let next = apply_action(&app, state, action).await?;
+    save_state(&app, &next)?;
+
+    log::info!("Onboarding now at step {:?}", next.step);
+    Ok(next)
+}
@@ -0,0 +1,205 @@
+// File: src-tauri/src/polls.rs
+// Description: Lightweight polls in conversations (a poll message and vote messages referencing it).
+// Changes:
+// - Created file. Polls and votes are signed memos whose text is a prefixed JSON payload. Tallies are computed
+//   from a conversation's messages when it is loaded; a voter's latest vote counts.
+// - Polls are keyed by the txid send_private_message resolves (peers only know the txid); votes for polls still
+//   recorded under a z_sendmany opid are refused, since no peer could match them
+// - Uses the shared message_rpc::is_txid.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use super::message_rpc::{is_txid, send_private_message};
+use super::rpc_client::VerusRpcError;
+use super::settings::ChatMessage;
+
+// Memo text prefix marking a poll or vote
+const POLL_MEMO_PREFIX: &str = "nymia-poll:";
+
+pub const MAX_POLL_OPTIONS: usize = 8;
+const MAX_QUESTION_CHARS: usize = 160;
+const MAX_OPTION_CHARS: usize = 40;
+
+// Payload carried in the memo text. Field names are short to leave room in the 512-byte memo.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "t", rename_all = "snake_case")]
+enum PollMemo {
+    Poll {
+        #[serde(rename = "q")]
+        question: String,
+        #[serde(rename = "o")]
+        options: Vec<String>,
+    },
+    Vote {
+        #[serde(rename = "p")]
+        poll_txid: String,
+        #[serde(rename = "c")]
+        choice: usize, // Index into the poll's options
+    },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PollOptionTally {
+    pub label: String,
+    pub votes: usize,
+    pub voters: Vec<String>,
+}
+
+// Current state of a poll, returned with the conversation's messages
+#[derive(Serialize, Debug, Clone)]
+pub struct PollTally {
+    pub poll_txid: String,
+    pub question: String,
+    pub created_by: String,
+    pub created_at: u64,
+    pub options: Vec<PollOptionTally>,
+    pub total_votes: usize,
+    pub my_choice: Option<usize>, // Our own latest vote
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum PollError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("A poll needs a question and 2 to {MAX_POLL_OPTIONS} options")]
+    InvalidPoll,
+    #[error("Question or option text is too long")]
+    TextTooLong,
+    #[error("Unknown poll: {0}")]
+    UnknownPoll(String),
+    #[error("Poll has no option {0}")]
+    InvalidChoice(usize),
+    #[error("Poll {0} is recorded without its transaction id; votes for it can't be matched")]
+    UnresolvedPoll(String),
+}
+
+impl From<VerusRpcError> for PollError {
+    fn from(error: VerusRpcError) -> Self {
+        PollError::Rpc(error)
+    }
+}
+
+fn encode_poll_memo(memo: &PollMemo) -> String {
+    // Serializing these plain structs cannot fail
+    format!("{}{}", POLL_MEMO_PREFIX, serde_json::to_string(memo).unwrap_or_default())
+}
+
+fn parse_poll_memo(text: &str) -> Option<PollMemo> {
+    let payload = text.strip_prefix(POLL_MEMO_PREFIX)?;
+    match serde_json::from_str(payload) {
+        Ok(memo) => Some(memo),
+        Err(e) => {
+            log::debug!("Ignoring malformed poll memo: {}", e);
+            None
+        }
+    }
+}
+
+pub fn is_vote(message: &ChatMessage) -> bool {
+    matches!(parse_poll_memo(&message.text), Some(PollMemo::Vote { .. }))
+}
+
+// Tally every poll in a conversation. Votes are applied in timestamp order so a voter's latest vote wins;
+// votes for unknown polls or out-of-range options are ignored.
+pub fn tally_polls(messages: &[ChatMessage]) -> Vec<PollTally> {
+    let mut polls: Vec<PollTally> = Vec::new();
+    let mut votes: Vec<(&ChatMessage, String, usize)> = Vec::new();
+    for message in messages {
+        match parse_poll_memo(&message.text) {
+            Some(PollMemo::Poll { question, options }) => polls.push(PollTally {
+                poll_txid: message.id.clone(),
+                question,
+                created_by: message.sender.clone(),
+                created_at: message.timestamp,
+                options: options.into_iter().map(|label| PollOptionTally { label, votes: 0, voters: Vec::new() }).collect(),
+                total_votes: 0,
+                my_choice: None,
+            }),
+            Some(PollMemo::Vote { poll_txid, choice }) => votes.push((message, poll_txid, choice)),
+            None => {}
+        }
+    }
+    votes.sort_by_key(|(message, _, _)| message.timestamp);
+
+    // (poll txid, voter) -> (choice, our own vote)
+    let mut latest: HashMap<(&str, &str), (usize, bool)> = HashMap::new();
+    for (message, poll_txid, choice) in &votes {
+        let Some(poll) = polls.iter().find(|p| &p.poll_txid == poll_txid) else { continue };
+        if *choice >= poll.options.len() {
+            continue;
+        }
+        latest.insert((poll_txid.as_str(), message.sender.as_str()), (*choice, message.direction == "sent"));
+    }
+    for ((poll_txid, voter), (choice, own)) in latest {
+        let Some(poll) = polls.iter_mut().find(|p| p.poll_txid == poll_txid) else { continue };
+        poll.options[choice].votes += 1;
+        poll.options[choice].voters.push(voter.to_string());
+        poll.total_votes += 1;
+        if own {
+            poll.my_choice = Some(choice);
+        }
+    }
+    for poll in &mut polls {
+        for option in &mut poll.options {
+            option.voters.sort();
+        }
+    }
+    polls
+}
+
+// Send a poll. Returns the txid and the memo text (to record the sent message).
+#[allow(clippy::too_many_arguments)]
+pub async fn create_poll(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    question: String,
+    options: Vec<String>,
+) -> Result<(String, String), PollError> {
+    let question = question.trim().to_string();
+    let options: Vec<String> = options.into_iter().map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect();
+    if question.is_empty() || options.len() < 2 || options.len() > MAX_POLL_OPTIONS {
+        return Err(PollError::InvalidPoll);
+    }
+    if question.chars().count() > MAX_QUESTION_CHARS || options.iter().any(|o| o.chars().count() > MAX_OPTION_CHARS) {
+        return Err(PollError::TextTooLong);
+    }
+
+    log::info!("Creating poll with {} options", options.len());
+    let memo_text = encode_poll_memo(&PollMemo::Poll { question, options });
+    let txid = send_private_message(rpc_user, rpc_pass, rpc_port, sender_z_address, recipient_z_address, memo_text.clone(), sender_identity, 0.0, None).await?;
+    Ok((txid, memo_text))
+}
+
+// Vote in a poll of the conversation. Returns the txid and the memo text (to record the sent message).
+#[allow(clippy::too_many_arguments)]
+pub async fn vote_in_poll(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    poll_txid: String,
+    choice: usize,
+    messages: &[ChatMessage], // The conversation the poll belongs to
+) -> Result<(String, String), PollError> {
+    let poll = tally_polls(messages)
+        .into_iter()
+        .find(|p| p.poll_txid == poll_txid)
+        .ok_or_else(|| PollError::UnknownPoll(poll_txid.clone()))?;
+    if choice >= poll.options.len() {
+        return Err(PollError::InvalidChoice(choice));
+    }
+    if !is_txid(&poll.poll_txid) {
+        return Err(PollError::UnresolvedPoll(poll_txid));
+    }
+
+    log::info!("Voting for option {} in poll {}", choice, poll_txid);
+    let memo_text = encode_poll_memo(&PollMemo::Vote { poll_txid, choice });
+    let txid = send_private_message(rpc_user, rpc_pass, rpc_port, sender_z_address, recipient_z_address, memo_text.clone(), sender_identity, 0.0, None).await?;
+    Ok((txid, memo_text))
+}
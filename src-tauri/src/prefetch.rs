@@ -0,0 +1,130 @@
+// File: src-tauri/src/prefetch.rs
+// Description: Background prefetching of data the user will likely need in an opened conversation.
+// Changes:
+// - Created file: refreshes the contact identity, recent transaction confirmations and the send timing estimate,
+//   emitting each result as a conversation-prefetch event. Runs as a cancellable task.
+// - Contact lookup goes through the identity cache (warmed after login).
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, Runtime};
+use super::events::{emit_event, CONVERSATION_PREFETCH_EVENT};
+use super::identity_cache::IdentityCache;
+use super::identity_rpc::FormattedIdentity;
+use super::message_store::MessageStore;
+use super::network_rpc::{estimate_send_timing, SendTimingEstimate};
+use super::rpc_client::make_background_rpc_call;
+use super::tasks::{run_cancellable, TaskError};
+
+// Single task id: opening another conversation cancels the previous prefetch
+pub const PREFETCH_TASK_ID: &str = "conversation-prefetch";
+
+// Recent messages whose confirmations are refreshed
+const RECENT_TX_PREFETCH: usize = 5;
+
+// Messages with this many confirmations are considered settled and not refreshed
+const SETTLED_CONFIRMATIONS: i64 = 10;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum PrefetchData {
+    Contact(FormattedIdentity),
+    Confirmations(Vec<TxConfirmations>),
+    SendTiming(SendTimingEstimate),
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TxConfirmations {
+    pub txid: String,
+    pub confirmations: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversationPrefetchEvent {
+    pub identity_i_address: String,
+    pub conversation_id: String,
+    pub data: PrefetchData,
+}
+
+pub struct PrefetchRequest {
+    pub rpc_user: String,
+    pub rpc_pass: String,
+    pub rpc_port: u16,
+    pub identity_i_address: String,
+    pub conversation_id: String, // Contact VerusID name
+}
+
+fn emit_prefetch<R: Runtime>(app: &AppHandle<R>, request: &PrefetchRequest, data: PrefetchData) {
+    emit_event(app, CONVERSATION_PREFETCH_EVENT, ConversationPrefetchEvent {
+        identity_i_address: request.identity_i_address.clone(),
+        conversation_id: request.conversation_id.clone(),
+        data,
+    });
+}
+
+// Refresh confirmations of the latest unsettled messages and fold them into the message store
+async fn prefetch_confirmations<R: Runtime>(app: &AppHandle<R>, request: &PrefetchRequest) {
+    let store = app.state::<MessageStore>();
+    let messages = store.load_conversation(app, &request.identity_i_address, &request.conversation_id);
+
+    let mut updated = Vec::new();
+    let mut confirmations = Vec::new();
+    for message in messages.iter().rev().filter(|m| m.confirmations < SETTLED_CONFIRMATIONS).take(RECENT_TX_PREFETCH) {
+        match make_background_rpc_call::<Value>(&request.rpc_user, &request.rpc_pass, request.rpc_port, "gettransaction", vec![json!(message.id)]).await {
+            Ok(tx) => {
+                let count = tx.get("confirmations").and_then(|v| v.as_i64()).unwrap_or(message.confirmations);
+                confirmations.push(TxConfirmations { txid: message.id.clone(), confirmations: count });
+                let mut refreshed = message.clone();
+                refreshed.confirmations = count;
+                if refreshed.direction == "sent" && count > 0 {
+                    refreshed.status = Some("delivered".to_string());
+                }
+                updated.push(refreshed);
+            }
+            Err(e) => log::debug!("Prefetch: gettransaction failed for {}: {:?}", message.id, e),
+        }
+    }
+
+    if !updated.is_empty() {
+        if let Err(e) = store.merge(app, &request.identity_i_address, &request.conversation_id, updated) {
+            log::warn!("Prefetch: failed to store refreshed confirmations: {}", e);
+        }
+    }
+    emit_prefetch(app, request, PrefetchData::Confirmations(confirmations));
+}
+
+// Prefetch body; individual failures are logged and skipped
+async fn prefetch_conversation<R: Runtime>(app: &AppHandle<R>, request: &PrefetchRequest) -> Result<(), TaskError> {
+    log::debug!("Prefetching data for conversation {}", request.conversation_id);
+
+    // Served from the identity cache when the login warmup already resolved this contact
+    let identities = app.state::<IdentityCache>().inner().clone();
+    match identities.resolve(&request.rpc_user, &request.rpc_pass, request.rpc_port, &request.conversation_id).await {
+        Ok(contact) => {
+            if let Err(e) = identities.persist(app) {
+                log::warn!("Failed to persist identity cache: {}", e);
+            }
+            emit_prefetch(app, request, PrefetchData::Contact(contact))
+        }
+        Err(e) => log::debug!("Prefetch: contact refresh failed for {}: {:?}", request.conversation_id, e),
+    }
+
+    prefetch_confirmations(app, request).await;
+
+    match estimate_send_timing(&request.rpc_user, &request.rpc_pass, request.rpc_port, 0.0).await {
+        Ok(timing) => emit_prefetch(app, request, PrefetchData::SendTiming(timing)),
+        Err(e) => log::debug!("Prefetch: send timing estimate failed: {:?}", e),
+    }
+
+    Ok(())
+}
+
+// Start prefetching in the background, replacing (cancelling) any running prefetch
+pub fn spawn_conversation_prefetch<R: Runtime>(app: AppHandle<R>, request: PrefetchRequest) {
+    tauri::async_runtime::spawn(async move {
+        let result = run_cancellable(&app, Some(PREFETCH_TASK_ID.to_string()), "prefetch", prefetch_conversation(&app, &request)).await;
+        if let Err(e) = result {
+            log::debug!("Prefetch for {} stopped: {}", request.conversation_id, e);
+        }
+    });
+}
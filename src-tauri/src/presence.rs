@@ -0,0 +1,55 @@
+// File: src-tauri/src/presence.rs
+// Description: "Last seen" heuristic per contact, derived from their most recent verified message.
+// Changes:
+// - Created file. Computed from the local message store only; no presence traffic is ever sent.
+// - Reads the current time through clock::now_secs.
+
+use serde::Serialize;
+use tauri::{AppHandle, Runtime, State};
+use super::formatting::normalize_timestamp_secs;
+use super::message_store::MessageStore;
+use super::settings::{read_conversations, SettingsError};
+use super::clock::now_secs;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ContactPresence {
+    pub contact_id: String,
+    pub last_active: Option<u64>, // Unix seconds of their latest signed message; None if they never wrote
+    pub last_message_txid: Option<String>,
+}
+
+// --- Tauri Commands ---
+
+// Last activity of the given contacts (all conversation partners if none are given)
+#[tauri::command]
+pub async fn get_contact_presence<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    contact_ids: Option<Vec<String>>,
+) -> Result<Vec<ContactPresence>, SettingsError> {
+    log::debug!("get_contact_presence command received (user {})", identity_i_address);
+    let contact_ids = match contact_ids {
+        Some(ids) => ids,
+        None => read_conversations(&app, &identity_i_address)?.into_iter().map(|c| c.id).collect(),
+    };
+    let now = now_secs();
+
+    Ok(contact_ids
+        .into_iter()
+        .map(|contact_id| {
+            // Only received messages are from the contact, and only verified ones are ever stored
+            let latest = store
+                .load_conversation(&app, &identity_i_address, &contact_id)
+                .into_iter()
+                .filter(|m| m.direction == "received" && m.sender == contact_id)
+                .max_by_key(|m| normalize_timestamp_secs(m.timestamp));
+            ContactPresence {
+                // The timestamp is the sender's clock; a fast clock must not make them look active in the future
+                last_active: latest.as_ref().map(|m| normalize_timestamp_secs(m.timestamp).min(now)),
+                last_message_txid: latest.map(|m| m.id),
+                contact_id,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,129 @@
+// File: src-tauri/src/price.rs
+// Description: Conversion rates for the native currency, read from on-chain basket currency state.
+// Changes:
+// - Created file with get_conversion_rate, using getcurrencystate so rates can also be looked up at past block heights.
+// - Added estimate_conversion (estimateconversion preview of a conversion, optionally via a basket currency).
+// - Currency ids come from the shared currency metadata cache (currency.rs).
+// - getcurrencystate answers are reused until the next block (response_cache).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use super::currency::get_currency_info;
+use super::response_cache::make_cached_background_rpc_call;
+use super::rpc_client::{make_background_rpc_call, VerusRpcError};
+
+// Default basket and quote currency used for fiat-like valuation (VRSC mainnet)
+pub const DEFAULT_PRICE_BASKET: &str = "Bridge.vETH";
+pub const DEFAULT_BASE_CURRENCY: &str = "VRSC";
+pub const DEFAULT_QUOTE_CURRENCY: &str = "DAI.vETH";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PriceQuote {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub basket: String,
+    pub rate: f64,                  // Units of quote currency per unit of base currency
+    pub block_height: Option<u64>,  // Height of the basket state the rate was taken from
+}
+
+async fn resolve_currency_id(rpc_user: &str, rpc_pass: &str, rpc_port: u16, name: &str) -> Result<String, VerusRpcError> {
+    Ok(get_currency_info(rpc_user, rpc_pass, rpc_port, name).await?.currency_id)
+}
+
+// Rate of base in quote currency from the basket reserves, at the given height (latest when None)
+pub async fn get_conversion_rate(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    basket: &str,
+    base_currency: &str,
+    quote_currency: &str,
+    block_height: Option<u64>,
+) -> Result<PriceQuote, VerusRpcError> {
+    let base_id = resolve_currency_id(rpc_user, rpc_pass, rpc_port, base_currency).await?;
+    let quote_id = resolve_currency_id(rpc_user, rpc_pass, rpc_port, quote_currency).await?;
+
+    let mut params = vec![json!(basket)];
+    if let Some(height) = block_height {
+        params.push(json!(height.to_string()));
+    }
+    let states: Value = make_cached_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getcurrencystate", params).await?;
+
+    // Response is an array of { height, blocktime, currencystate } entries
+    let entry = states.as_array().and_then(|a| a.first()).unwrap_or(&states);
+    let state_height = entry.get("height").and_then(|v| v.as_u64()).or(block_height);
+    let reserves = entry
+        .get("currencystate")
+        .and_then(|s| s.get("reservecurrencies"))
+        .and_then(|r| r.as_array())
+        .ok_or_else(|| VerusRpcError::ParseError(format!("No reserve currencies in state of {}", basket)))?;
+
+    let price_in_reserve = |id: &str| {
+        reserves
+            .iter()
+            .find(|r| r.get("currencyid").and_then(|v| v.as_str()) == Some(id))
+            .and_then(|r| r.get("priceinreserve").and_then(|v| v.as_f64()))
+            .filter(|p| *p > 0.0)
+    };
+
+    let (Some(base_price), Some(quote_price)) = (price_in_reserve(&base_id), price_in_reserve(&quote_id)) else {
+        log::warn!("Basket {} does not hold both {} and {}", basket, base_currency, quote_currency);
+        return Err(VerusRpcError::NotFoundOrIneligible);
+    };
+
+    Ok(PriceQuote {
+        base_currency: base_currency.to_string(),
+        quote_currency: quote_currency.to_string(),
+        basket: basket.to_string(),
+        rate: quote_price / base_price,
+        block_height: state_height,
+    })
+}
+
+// Preview of converting an amount, as estimated by the daemon from the current basket state
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConversionEstimate {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub via: Option<String>,       // Basket the conversion goes through (None for direct basket conversions)
+    pub amount: f64,
+    pub net_input_amount: f64,     // Amount converted after conversion fees
+    pub estimated_output: f64,     // Estimated amount of to_currency received
+    pub effective_rate: f64,       // estimated_output / amount
+}
+
+pub async fn estimate_conversion(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    from_currency: &str,
+    to_currency: &str,
+    via: Option<&str>,
+    amount: f64,
+) -> Result<ConversionEstimate, VerusRpcError> {
+    if !amount.is_finite() || amount <= 0.0 {
+        return Err(VerusRpcError::InvalidAmount("conversion amount must be above zero".to_string()));
+    }
+    let mut request = json!({ "currency": from_currency, "convertto": to_currency, "amount": amount });
+    if let Some(via) = via {
+        request["via"] = json!(via);
+    }
+    let estimate: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "estimateconversion", vec![request]).await?;
+
+    let estimated_output = estimate
+        .get("estimatedcurrencyout")
+        .and_then(|v| v.as_f64())
+        .ok_or_else(|| VerusRpcError::ParseError("estimateconversion response missing estimatedcurrencyout".to_string()))?;
+    let net_input_amount = estimate.get("netinputamount").and_then(|v| v.as_f64()).unwrap_or(amount);
+    log::debug!("Conversion estimate: {} {} -> {} {} (via {:?})", amount, from_currency, estimated_output, to_currency, via);
+
+    Ok(ConversionEstimate {
+        from_currency: from_currency.to_string(),
+        to_currency: to_currency.to_string(),
+        via: via.map(String::from),
+        amount,
+        net_input_amount,
+        estimated_output,
+        effective_rate: estimated_output / amount,
+    })
+}
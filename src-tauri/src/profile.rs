@@ -0,0 +1,312 @@
+// File: src-tauri/src/profile.rs
+// Description: Contact profiles (display name, avatar, bio, website) stored in VerusID content maps.
+// Changes:
+// - Created file with get_identity_profile. Profile fields live under well-known VDXF keys (nymia::profile.*,
+//   resolved to key ids with getvdxfid) in the identity's contentmultimap; the avatar hash may also be a
+//   contentmap entry. Values are either hex-encoded text or data descriptors with a text message. Everything
+//   read from the chain is length-limited and sanitized before it reaches the conversation header.
+// - Added publishing of the own profile (display name, avatar, status) with updateidentity: the changed keys are
+//   merged into the existing contentmultimap, a returntx dry run gives the size and fee preview, and the update
+//   is tracked until it confirms (profile-publish events).
+// - Profiles are read through the getidentity lookup cache; a confirmed publish invalidates it.
+// - getvdxfid results come from the block-aware response cache
+// - Profiles are built from the block-cached getidentity answer each time (no profile TTL cache of its own).
+
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::events::{emit_event, PROFILE_PUBLISH_EVENT};
+use super::identity_registration::wait_for_confirmation;
+use super::identity_rpc::get_identity_cached;
+use super::message_rpc::DEFAULT_TX_FEE;
+use super::response_cache::{invalidate_responses, make_cached_rpc_call};
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+
+// VDXF key names of the profile fields
+pub const DISPLAY_NAME_KEY: &str = "nymia::profile.displayname";
+pub const AVATAR_URL_KEY: &str = "nymia::profile.avatar";
+pub const AVATAR_HASH_KEY: &str = "nymia::profile.avatarhash";
+pub const BIO_KEY: &str = "nymia::profile.bio";
+pub const WEBSITE_KEY: &str = "nymia::profile.website";
+pub const STATUS_KEY: &str = "nymia::profile.status";
+
+const MAX_DISPLAY_NAME_CHARS: usize = 64;
+const MAX_BIO_CHARS: usize = 500;
+const MAX_STATUS_CHARS: usize = 140;
+const MAX_URL_CHARS: usize = 512;
+
+// (RPC port, key name) -> VDXF key id
+static VDXF_IDS: LazyLock<Mutex<HashMap<(u16, String), String>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct IdentityProfile {
+    pub identity: String,              // Fully qualified name
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,    // http(s) or ipfs only
+    pub avatar_hash: Option<String>,   // Hex digest of the avatar image, to check a download against
+    pub bio: Option<String>,
+    pub website: Option<String>,       // http(s) only
+    pub status: Option<String>,
+}
+
+// Fields to publish: None keeps the current value, an empty string removes it
+#[derive(Debug, Clone, Default)]
+pub struct ProfileUpdate {
+    pub display_name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub avatar_hash: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfilePublishPreview {
+    pub identity: String,
+    pub changed_keys: Vec<String>, // VDXF key names written or removed
+    pub size_bytes: u64,
+    pub fee: f64,
+}
+
+// Payload of the profile-publish event
+#[derive(Serialize, Debug, Clone)]
+pub struct ProfilePublishUpdate {
+    pub identity: String,
+    pub txid: String,
+    pub status: String, // "confirming", "confirmed" or "failed"
+    pub confirmations: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum ProfileError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Invalid profile field: {0}")]
+    InvalidField(String),
+    #[error("Identity {0} is not controlled by this wallet")]
+    NotControlled(String),
+    #[error("Nothing to publish")]
+    NothingToPublish,
+}
+
+impl From<VerusRpcError> for ProfileError {
+    fn from(error: VerusRpcError) -> Self {
+        ProfileError::Rpc(error)
+    }
+}
+
+// Key id of a VDXF key name (the daemon hashes the name; the result never changes)
+pub async fn vdxf_id(rpc_user: &str, rpc_pass: &str, rpc_port: u16, key_name: &str) -> Result<String, VerusRpcError> {
+    let cache_key = (rpc_port, key_name.to_string());
+    if let Some(id) = VDXF_IDS.lock().unwrap_or_else(|e| e.into_inner()).get(&cache_key) {
+        return Ok(id.clone());
+    }
+    let result: Value = make_cached_rpc_call(rpc_user, rpc_pass, rpc_port, "getvdxfid", vec![json!(key_name)]).await?;
+    let id = result
+        .get("vdxfid")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VerusRpcError::ParseError(format!("getvdxfid returned no id for {}", key_name)))?
+        .to_string();
+    VDXF_IDS.lock().unwrap_or_else(|e| e.into_inner()).insert(cache_key, id.clone());
+    Ok(id)
+}
+
+// Text of a content map value: hex-encoded UTF-8, or a data descriptor ({ <descriptor key>: { objectdata } })
+// whose object data is a message or hex
+fn decode_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(hex_text) => hex::decode(hex_text).ok().and_then(|bytes| String::from_utf8(bytes).ok()),
+        Value::Object(map) => {
+            let descriptor = map.values().next()?;
+            let data = descriptor.get("objectdata")?;
+            match data {
+                Value::String(_) => decode_value(data),
+                _ => data.get("message").and_then(|v| v.as_str()).map(String::from),
+            }
+        }
+        _ => None,
+    }
+}
+
+// Latest value stored under a key of the contentmultimap
+fn multimap_text(identity: &Value, key_id: &str) -> Option<String> {
+    let values = identity.pointer("/contentmultimap")?.get(key_id)?;
+    match values {
+        Value::Array(values) => values.iter().rev().find_map(decode_value),
+        value => decode_value(value),
+    }
+}
+
+fn sanitize_text(text: &str, max_chars: usize) -> Option<String> {
+    let cleaned: String = text
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n')
+        .take(max_chars)
+        .collect();
+    let cleaned = cleaned.trim().to_string();
+    (!cleaned.is_empty()).then_some(cleaned)
+}
+
+fn sanitize_url(url: &str, schemes: &[&str]) -> Option<String> {
+    let url = url.trim();
+    let allowed = schemes.iter().any(|scheme| url.to_lowercase().starts_with(scheme));
+    (allowed && url.len() <= MAX_URL_CHARS && !url.chars().any(|c| c.is_whitespace() || c.is_control())).then(|| url.to_string())
+}
+
+fn sanitize_hash(hash: &str) -> Option<String> {
+    let hash = hash.trim().to_lowercase();
+    (hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
+}
+
+// Profile of an identity (None fields when not published)
+pub async fn get_identity_profile(rpc_user: &str, rpc_pass: &str, rpc_port: u16, identity: &str) -> Result<IdentityProfile, VerusRpcError> {
+    log::debug!("Fetching profile of {}", identity);
+    let result = get_identity_cached(rpc_user, rpc_pass, rpc_port, identity).await?;
+    let details = result.get("identity").ok_or_else(|| VerusRpcError::ParseError("getidentity response missing identity".to_string()))?;
+
+    let mut fields = HashMap::new();
+    for key_name in [DISPLAY_NAME_KEY, AVATAR_URL_KEY, AVATAR_HASH_KEY, BIO_KEY, WEBSITE_KEY, STATUS_KEY] {
+        let key_id = vdxf_id(rpc_user, rpc_pass, rpc_port, key_name).await?;
+        if let Some(text) = multimap_text(details, &key_id) {
+            fields.insert(key_name, text);
+        } else if key_name == AVATAR_HASH_KEY {
+            // contentmap holds 32-byte values, only meaningful for the hash
+            if let Some(hash) = details.pointer("/contentmap").and_then(|m| m.get(&key_id)).and_then(|v| v.as_str()) {
+                fields.insert(key_name, hash.to_string());
+            }
+        }
+    }
+    let field = |key: &str| fields.get(key).map(String::as_str);
+    let profile = IdentityProfile {
+        identity: result.get("fullyqualifiedname").and_then(|v| v.as_str()).unwrap_or(identity).to_string(),
+        display_name: field(DISPLAY_NAME_KEY).and_then(|t| sanitize_text(t, MAX_DISPLAY_NAME_CHARS)).map(|t| t.replace('\n', " ")),
+        avatar_url: field(AVATAR_URL_KEY).and_then(|u| sanitize_url(u, &["https://", "http://", "ipfs://"])),
+        avatar_hash: field(AVATAR_HASH_KEY).and_then(sanitize_hash),
+        bio: field(BIO_KEY).and_then(|t| sanitize_text(t, MAX_BIO_CHARS)),
+        website: field(WEBSITE_KEY).and_then(|u| sanitize_url(u, &["https://", "http://"])),
+        status: field(STATUS_KEY).and_then(|t| sanitize_text(t, MAX_STATUS_CHARS)).map(|t| t.replace('\n', " ")),
+    };
+    Ok(profile)
+}
+
+// Check one field of an update the way readers will sanitize it. Empty means remove.
+fn validate_field(key_name: &'static str, value: &str) -> Result<(&'static str, String), ProfileError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok((key_name, String::new()));
+    }
+    let (valid, max_chars) = match key_name {
+        DISPLAY_NAME_KEY => (sanitize_text(value, MAX_DISPLAY_NAME_CHARS), MAX_DISPLAY_NAME_CHARS),
+        STATUS_KEY => (sanitize_text(value, MAX_STATUS_CHARS), MAX_STATUS_CHARS),
+        AVATAR_URL_KEY => (sanitize_url(value, &["https://", "http://", "ipfs://"]), MAX_URL_CHARS),
+        _ => (sanitize_hash(value), 64),
+    };
+    match valid {
+        Some(valid) if valid.eq_ignore_ascii_case(value) && value.chars().count() <= max_chars => Ok((key_name, valid)),
+        _ => Err(ProfileError::InvalidField(format!("{} is not a valid value for {}", value, key_name))),
+    }
+}
+
+// The updateidentity definition: the identity's content multimap with the changed keys replaced
+async fn build_profile_update(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity: &str,
+    update: &ProfileUpdate,
+) -> Result<(String, Vec<String>, Value), ProfileError> {
+    let fields = [
+        (DISPLAY_NAME_KEY, &update.display_name),
+        (AVATAR_URL_KEY, &update.avatar_url),
+        (AVATAR_HASH_KEY, &update.avatar_hash),
+        (STATUS_KEY, &update.status),
+    ];
+    let changes: Vec<(&str, String)> = fields
+        .into_iter()
+        .filter_map(|(key_name, value)| value.as_deref().map(|v| validate_field(key_name, v)))
+        .collect::<Result<_, _>>()?;
+    if changes.is_empty() {
+        return Err(ProfileError::NothingToPublish);
+    }
+
+    let result: Value = make_rpc_call(rpc_user, rpc_pass, rpc_port, "getidentity", vec![json!(identity)]).await?;
+    let fully_qualified_name = result.get("fullyqualifiedname").and_then(|v| v.as_str()).unwrap_or(identity).to_string();
+    if !result.get("cansignfor").and_then(|v| v.as_bool()).unwrap_or(false) {
+        return Err(ProfileError::NotControlled(fully_qualified_name));
+    }
+    let details = result.get("identity").ok_or_else(|| VerusRpcError::ParseError("getidentity response missing identity".to_string()))?;
+    let mut multimap: Map<String, Value> = details.get("contentmultimap").and_then(|v| v.as_object()).cloned().unwrap_or_default();
+    let mut changed_keys = Vec::new();
+    for (key_name, value) in changes {
+        let key_id = vdxf_id(rpc_user, rpc_pass, rpc_port, key_name).await?;
+        if value.is_empty() {
+            multimap.remove(&key_id);
+        } else {
+            multimap.insert(key_id, json!([hex::encode(value.as_bytes())]));
+        }
+        changed_keys.push(key_name.to_string());
+    }
+    // The daemon keeps every field not given here
+    let definition = json!({
+        "name": details.get("name").cloned().unwrap_or(Value::Null),
+        "parent": details.get("parent").cloned().unwrap_or(Value::Null),
+        "contentmultimap": multimap,
+    });
+    Ok((fully_qualified_name, changed_keys, definition))
+}
+
+// Size and fee of publishing the update (the transaction is built, not sent)
+pub async fn preview_profile_publish(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity: &str,
+    update: &ProfileUpdate,
+) -> Result<ProfilePublishPreview, ProfileError> {
+    let (identity, changed_keys, definition) = build_profile_update(rpc_user, rpc_pass, rpc_port, identity, update).await?;
+    let raw_tx: String = make_rpc_call(rpc_user, rpc_pass, rpc_port, "updateidentity", vec![definition, json!(true)]).await?; // returntx
+    Ok(ProfilePublishPreview { identity, changed_keys, size_bytes: raw_tx.len() as u64 / 2, fee: DEFAULT_TX_FEE })
+}
+
+// Publish the update and track it until it confirms. Returns the txid.
+pub async fn publish_profile<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    identity: String,
+    update: ProfileUpdate,
+) -> Result<String, ProfileError> {
+    let (identity, changed_keys, definition) = build_profile_update(&rpc_user, &rpc_pass, rpc_port, &identity, &update).await?;
+    let txid: String = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "updateidentity", vec![definition]).await?;
+    log::info!("Published profile of {} ({:?}) in {}", identity, changed_keys, txid);
+
+    let app = app.clone();
+    let tracked_txid = txid.clone();
+    tauri::async_runtime::spawn(async move {
+        let update = |status: &str, confirmations: u64, error: Option<String>| ProfilePublishUpdate {
+            identity: identity.clone(),
+            txid: tracked_txid.clone(),
+            status: status.to_string(),
+            confirmations,
+            error,
+        };
+        let result = wait_for_confirmation(&rpc_user, &rpc_pass, rpc_port, &tracked_txid, |count| {
+            emit_event(&app, PROFILE_PUBLISH_EVENT, update("confirming", count, None))
+        })
+        .await;
+        match result {
+            Ok(()) => {
+                // Show the new profile instead of the cached one
+                invalidate_responses(rpc_port);
+                emit_event(&app, PROFILE_PUBLISH_EVENT, update("confirmed", 1, None));
+            }
+            Err(e) => {
+                log::warn!("Profile update {} of {} did not confirm: {}", tracked_txid, identity, e);
+                emit_event(&app, PROFILE_PUBLISH_EVENT, update("failed", 0, Some(e.to_string())));
+            }
+        }
+    });
+    Ok(txid)
+}
@@ -0,0 +1,241 @@
+// File: src-tauri/src/proof_rpc.rs
+// Description: Selective disclosure proofs for received payments (gifts).
+// Changes:
+// - Created file with create_payment_proof and verify_payment_proof.
+// - Proofs are built from z_viewtransaction (our viewing key) and attested by the recipient's VerusID signature,
+//   so a third party can check them with verifymessage without access to the wallet.
+// - Verification checks the claims against the chain instead of trusting the attestation alone: the recipient
+//   address must be the attesting identity's private address, the transaction must have the shielded output, and
+//   where the verifier's wallet can view the output (e.g. the sender's), address and amount must match.
+//   Signature check errors count as an invalid signature instead of aborting the verification.
+// - The record's timestamp comes from clock::now_secs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use super::response_cache::make_cached_rpc_call;
+use super::rpc_client::{make_rpc_call, sign_message, verify_message, VerusRpcError};
+use super::clock::now_secs;
+
+// Amounts within this are the same (z_viewtransaction reports floats)
+const AMOUNT_TOLERANCE: f64 = 0.000_000_005;
+
+// Version of the canonical statement format
+const PAYMENT_PROOF_VERSION: u32 = 1;
+
+// Shareable proof that a specific shielded output was received
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentProof {
+    pub version: u32,
+    pub txid: String,
+    pub output_index: u32,
+    pub recipient_address: String, // Our z-address that received the output
+    pub amount: f64,
+    pub memo_hash: String,         // SHA-256 of the raw memo bytes (memo itself is not disclosed)
+    pub block_hash: Option<String>,
+    pub attested_by: String,       // VerusID that signed the statement
+    pub statement: String,         // Canonical statement that was signed
+    pub signature: String,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentProofVerification {
+    pub statement_matches: bool,    // Statement rebuilt from the proof fields matches the signed one
+    pub signature_valid: bool,      // Signature verifies against attested_by
+    pub transaction_found: bool,    // Transaction is known to the verifying daemon
+    pub block_hash_matches: bool,   // Transaction is mined in the claimed block
+    pub recipient_owned: bool,      // recipient_address is the private address of attested_by
+    pub output_found: bool,         // The transaction has a shielded output at output_index
+    pub output_matches: Option<bool>, // Address and amount of the output; None if the verifier's wallet can't view it
+    pub confirmations: Option<i64>,
+    pub valid: bool,                // All of the above (an output that can't be viewed doesn't count against it)
+}
+
+// Build the canonical statement that gets signed
+fn proof_statement(
+    txid: &str,
+    output_index: u32,
+    recipient_address: &str,
+    amount: f64,
+    memo_hash: &str,
+    block_hash: Option<&str>,
+) -> String {
+    format!(
+        "nymia-payment-proof:v{}|{}|{}|{}|{:.8}|{}|{}",
+        PAYMENT_PROOF_VERSION,
+        txid,
+        output_index,
+        recipient_address,
+        amount,
+        memo_hash,
+        block_hash.unwrap_or("")
+    )
+}
+
+fn memo_hash_hex(memo_hex: &str) -> String {
+    let memo_bytes = hex::decode(memo_hex).unwrap_or_default();
+    hex::encode(Sha256::digest(&memo_bytes))
+}
+
+// Create a proof for a payment we received
+pub async fn create_payment_proof(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    txid: String,
+    signing_identity: String,       // Our VerusID that attests the proof
+    output_index: Option<u32>,      // Specific output, defaults to the first incoming one
+) -> Result<PaymentProof, VerusRpcError> {
+    log::info!("Creating payment proof for tx {} (attested by {})", txid, signing_identity);
+
+    let view: Value = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_viewtransaction", vec![json!(txid)]).await?;
+    let outputs = view.get("outputs").and_then(|o| o.as_array()).ok_or(VerusRpcError::ParseError(
+        "z_viewtransaction response missing outputs".to_string(),
+    ))?;
+
+    // Pick the received output (outgoing == false)
+    let output = outputs
+        .iter()
+        .filter(|o| !o.get("outgoing").and_then(|v| v.as_bool()).unwrap_or(false))
+        .find(|o| match output_index {
+            Some(index) => o.get("output").and_then(|v| v.as_u64()) == Some(index as u64),
+            None => true,
+        })
+        .ok_or_else(|| {
+            log::warn!("No incoming output found in tx {} for proof", txid);
+            VerusRpcError::NotFoundOrIneligible
+        })?;
+
+    let recipient_address = output.get("address").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let amount = output.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let found_index = output.get("output").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    let memo_hash = memo_hash_hex(output.get("memo").and_then(|v| v.as_str()).unwrap_or(""));
+
+    // Block hash anchors the proof to the chain (absent while unconfirmed)
+    let tx_info: Value = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "gettransaction", vec![json!(txid)]).await?;
+    let block_hash = tx_info.get("blockhash").and_then(|v| v.as_str()).map(String::from);
+    if block_hash.is_none() {
+        log::warn!("Transaction {} is not yet mined; proof will not be anchored to a block", txid);
+    }
+
+    let statement = proof_statement(&txid, found_index, &recipient_address, amount, &memo_hash, block_hash.as_deref());
+    let signature = sign_message(&rpc_user, &rpc_pass, rpc_port, &signing_identity, &statement).await?;
+
+    let created_at = now_secs();
+
+    log::info!("Payment proof created for tx {} output {}", txid, found_index);
+
+    Ok(PaymentProof {
+        version: PAYMENT_PROOF_VERSION,
+        txid,
+        output_index: found_index,
+        recipient_address,
+        amount,
+        memo_hash,
+        block_hash,
+        attested_by: signing_identity,
+        statement,
+        signature: signature.signature,
+        created_at,
+    })
+}
+
+// Verify a proof received from someone else (or our own, before sharing)
+pub async fn verify_payment_proof(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    proof: PaymentProof,
+) -> Result<PaymentProofVerification, VerusRpcError> {
+    log::info!("Verifying payment proof for tx {} attested by {}", proof.txid, proof.attested_by);
+
+    let expected_statement = proof_statement(
+        &proof.txid,
+        proof.output_index,
+        &proof.recipient_address,
+        proof.amount,
+        &proof.memo_hash,
+        proof.block_hash.as_deref(),
+    );
+    let statement_matches = expected_statement == proof.statement;
+    if !statement_matches {
+        log::warn!("Payment proof statement does not match its fields for tx {}", proof.txid);
+    }
+
+    let signature_valid = verify_message(&rpc_user, &rpc_pass, rpc_port, &proof.attested_by, &proof.signature, &proof.statement)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Could not check the payment proof signature of {}: {:?}", proof.attested_by, e);
+            false
+        });
+
+    // The attester can only vouch for payments to its own address
+    let recipient_owned = match make_cached_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "getidentity", vec![json!(proof.attested_by)]).await {
+        Ok(identity) => identity.pointer("/identity/privateaddress").and_then(|v| v.as_str()) == Some(proof.recipient_address.as_str()),
+        Err(e) => {
+            log::warn!("Could not look up {} for proof verification: {:?}", proof.attested_by, e);
+            false
+        }
+    };
+
+    // Transaction lookup (requires the verifier's daemon to know the tx, e.g. via txindex)
+    let (transaction_found, block_hash_matches, output_found, confirmations) =
+        match make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "getrawtransaction", vec![json!(proof.txid), json!(1)]).await {
+            Ok(raw_tx) => {
+                let on_chain_block = raw_tx.get("blockhash").and_then(|v| v.as_str());
+                let confirmations = raw_tx.get("confirmations").and_then(|v| v.as_i64());
+                let shielded_outputs = raw_tx.get("vShieldedOutput").and_then(|v| v.as_array()).map(|a| a.len()).unwrap_or(0);
+                (
+                    true,
+                    on_chain_block.is_some() && on_chain_block == proof.block_hash.as_deref(),
+                    (proof.output_index as usize) < shielded_outputs,
+                    confirmations,
+                )
+            }
+            Err(e) => {
+                log::warn!("Could not look up transaction {} for proof verification: {:?}", proof.txid, e);
+                (false, false, false, None)
+            }
+        };
+
+    // Shielded amounts are only visible to wallets with a key for the transaction (recipient or sender)
+    let output_matches = match make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "z_viewtransaction", vec![json!(proof.txid)]).await {
+        Ok(view) => view
+            .get("outputs")
+            .and_then(|o| o.as_array())
+            .and_then(|outputs| outputs.iter().find(|o| o.get("output").and_then(|v| v.as_u64()) == Some(proof.output_index as u64)))
+            .map(|output| {
+                output.get("address").and_then(|v| v.as_str()) == Some(proof.recipient_address.as_str())
+                    && output.get("value").and_then(|v| v.as_f64()).is_some_and(|value| (value - proof.amount).abs() < AMOUNT_TOLERANCE)
+            }),
+        Err(e) => {
+            log::debug!("Output of {} can't be viewed by this wallet: {:?}", proof.txid, e);
+            None
+        }
+    };
+    if output_matches == Some(false) {
+        log::warn!("Payment proof for tx {} claims an output the transaction doesn't have", proof.txid);
+    }
+
+    let valid = statement_matches
+        && signature_valid
+        && transaction_found
+        && block_hash_matches
+        && recipient_owned
+        && output_found
+        && output_matches != Some(false);
+    log::info!("Payment proof for tx {} valid: {}", proof.txid, valid);
+
+    Ok(PaymentProofVerification {
+        statement_matches,
+        signature_valid,
+        transaction_found,
+        block_hash_matches,
+        recipient_owned,
+        output_found,
+        output_matches,
+        confirmations,
+        valid,
+    })
+}
@@ -0,0 +1,253 @@
+// File: src-tauri/src/protocol.rs
+// Description: Messaging protocol versioning and memo wire format.
+// Changes:
+// - Created file with protocol version constants and the supported version range.
+// - Moved memo layout parsing/building here from message_rpc.rs.
+// - Outgoing memos embed the protocol version: {text}//f//{sender}//t//{timestamp}//v//{version}//{signature}
+// - Legacy memos without a version marker are treated as version 1.
+// - Added peer protocol version registry and get_peer_protocol_version / get_protocol_info commands.
+// - Protocol version 3 binds signatures to the recipient: the signed payload ends with //r//{recipient_address}.
+//   The recipient is not transmitted; receivers rebuild it from their own receiving address.
+// - The peer version registry is persisted (loaded at startup, saved after polls and history loads when it changed).
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::storage::{load_value, save_value, StorageError};
+
+// Version written into outgoing memos
+pub const PROTOCOL_VERSION: u32 = 3;
+
+// First version whose signatures cover the recipient address
+pub const RECIPIENT_BINDING_VERSION: u32 = 3;
+
+// Range of versions this client can parse and verify
+pub const MIN_SUPPORTED_PROTOCOL_VERSION: u32 = 1;
+pub const MAX_SUPPORTED_PROTOCOL_VERSION: u32 = PROTOCOL_VERSION;
+
+const SENDER_MARKER: &str = "//f//";
+const TIME_MARKER: &str = "//t//";
+const VERSION_PREFIX: &str = "v//"; // Follows the "//" after the timestamp
+const FIELD_SEPARATOR: &str = "//";
+const RECIPIENT_MARKER: &str = "//r//"; // Only part of the signed payload, never transmitted
+
+const PEER_VERSIONS_STORE_PATH: &str = "store.json";
+const PEER_VERSIONS_KEY: &str = "peer_protocol_versions";
+
+// Highest protocol version detected per peer identity
+static PEER_VERSIONS: LazyLock<Mutex<HashMap<String, PeerProtocolInfo>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Set when PEER_VERSIONS changed since it was last saved
+static PEER_VERSIONS_DIRTY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PeerProtocolInfo {
+    pub identity: String,
+    pub version: u32,              // Highest version seen from this peer
+    pub newer_than_supported: bool, // Peer runs a newer client than we can fully read
+    pub last_seen_timestamp: u64,  // Timestamp of the message that reported the version
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProtocolInfo {
+    pub current_version: u32,
+    pub min_supported_version: u32,
+    pub max_supported_version: u32,
+}
+
+// Fields extracted from a memo before signature verification
+#[derive(Debug)]
+pub struct MemoParts<'a> {
+    pub text: &'a str,
+    pub sender_id: &'a str,
+    pub timestamp: u64,
+    pub protocol_version: u32,
+    pub signature: &'a str,
+}
+
+#[derive(Debug)]
+pub enum MemoParseError {
+    NoSenderMarker,
+    NoTimestampMarker,
+    NoSignature,             // Legacy unsigned format
+    InvalidTimestamp(String),
+    InvalidVersion(String),
+    UnsupportedVersion { version: u32, sender_id: String, timestamp: u64 },
+}
+
+// Split a memo into its fields. Does not verify the signature.
+pub fn parse_memo(memo: &str) -> Result<MemoParts<'_>, MemoParseError> {
+    let sender_marker_pos = memo.find(SENDER_MARKER).ok_or(MemoParseError::NoSenderMarker)?;
+    let text = memo[..sender_marker_pos].trim();
+    let after_sender_marker = &memo[sender_marker_pos + SENDER_MARKER.len()..];
+
+    let time_marker_pos = after_sender_marker.find(TIME_MARKER).ok_or(MemoParseError::NoTimestampMarker)?;
+    let sender_id = after_sender_marker[..time_marker_pos].trim();
+    let after_time_marker = &after_sender_marker[time_marker_pos + TIME_MARKER.len()..];
+
+    let sig_marker_pos = after_time_marker.find(FIELD_SEPARATOR).ok_or(MemoParseError::NoSignature)?;
+    let timestamp_str = after_time_marker[..sig_marker_pos].trim();
+    let after_timestamp = &after_time_marker[sig_marker_pos + FIELD_SEPARATOR.len()..];
+
+    // Strict timestamp parsing
+    let timestamp = timestamp_str
+        .parse::<u64>()
+        .map_err(|_| MemoParseError::InvalidTimestamp(timestamp_str.to_string()))?;
+
+    // Optional version field (absent in version 1 memos)
+    let (protocol_version, signature) = match after_timestamp.strip_prefix(VERSION_PREFIX) {
+        Some(versioned) => {
+            let version_end = versioned.find(FIELD_SEPARATOR).ok_or(MemoParseError::NoSignature)?;
+            let version_str = versioned[..version_end].trim();
+            let version = version_str
+                .parse::<u32>()
+                .map_err(|_| MemoParseError::InvalidVersion(version_str.to_string()))?;
+            (version, versioned[version_end + FIELD_SEPARATOR.len()..].trim())
+        }
+        None => (1, after_timestamp.trim()),
+    };
+
+    if !(MIN_SUPPORTED_PROTOCOL_VERSION..=MAX_SUPPORTED_PROTOCOL_VERSION).contains(&protocol_version) {
+        return Err(MemoParseError::UnsupportedVersion {
+            version: protocol_version,
+            sender_id: sender_id.to_string(),
+            timestamp,
+        });
+    }
+
+    Ok(MemoParts {
+        text,
+        sender_id,
+        timestamp,
+        protocol_version,
+        signature,
+    })
+}
+
+// Transmitted memo fields before the signature
+fn memo_header(text: &str, sender_id: &str, timestamp: u64, protocol_version: u32) -> String {
+    if protocol_version <= 1 {
+        format!("{}{}{}{}{}", text, SENDER_MARKER, sender_id, TIME_MARKER, timestamp)
+    } else {
+        format!(
+            "{}{}{}{}{}{}{}{}",
+            text, SENDER_MARKER, sender_id, TIME_MARKER, timestamp, FIELD_SEPARATOR, VERSION_PREFIX, protocol_version
+        )
+    }
+}
+
+// The exact string that is signed for a given protocol version.
+// From RECIPIENT_BINDING_VERSION on, the recipient address is appended so a memo
+// cannot be replayed to a different recipient.
+pub fn signed_payload(text: &str, sender_id: &str, timestamp: u64, protocol_version: u32, recipient_address: &str) -> String {
+    let header = memo_header(text, sender_id, timestamp, protocol_version);
+    if protocol_version >= RECIPIENT_BINDING_VERSION {
+        format!("{}{}{}", header, RECIPIENT_MARKER, recipient_address)
+    } else {
+        header
+    }
+}
+
+// Full memo as transmitted: header followed by the signature
+pub fn build_memo(text: &str, sender_id: &str, timestamp: u64, protocol_version: u32, signature: &str) -> String {
+    format!("{}{}{}", memo_header(text, sender_id, timestamp, protocol_version), FIELD_SEPARATOR, signature)
+}
+
+// Whether a verified memo of this version is bound to its recipient
+pub fn is_recipient_bound(protocol_version: u32) -> bool {
+    protocol_version >= RECIPIENT_BINDING_VERSION
+}
+
+// Record the protocol version a peer used
+pub fn record_peer_version(identity: &str, version: u32, timestamp: u64) {
+    let newer_than_supported = version > MAX_SUPPORTED_PROTOCOL_VERSION;
+    if newer_than_supported {
+        log::warn!(
+            "Peer {} is using protocol version {} (newer than supported {}). Their messages may not be readable until Nymia is updated.",
+            identity, version, MAX_SUPPORTED_PROTOCOL_VERSION
+        );
+    }
+
+    let mut peers = match PEER_VERSIONS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let mut changed = false;
+    let entry = peers.entry(identity.to_string()).or_insert_with(|| {
+        changed = true;
+        PeerProtocolInfo {
+            identity: identity.to_string(),
+            version,
+            newer_than_supported,
+            last_seen_timestamp: timestamp,
+        }
+    });
+    if version > entry.version || (version == entry.version && newer_than_supported != entry.newer_than_supported) {
+        entry.version = version;
+        entry.newer_than_supported = newer_than_supported;
+        changed = true;
+    }
+    if timestamp > entry.last_seen_timestamp {
+        entry.last_seen_timestamp = timestamp;
+        changed = true;
+    }
+    if changed {
+        PEER_VERSIONS_DIRTY.store(true, Ordering::Relaxed);
+    }
+}
+
+// Startup: versions seen in earlier sessions, so a peer on a newer client is known before their next message
+pub fn load_peer_versions<R: Runtime>(app: &AppHandle<R>) {
+    match load_value::<R, HashMap<String, PeerProtocolInfo>>(app, PEER_VERSIONS_STORE_PATH, PEER_VERSIONS_KEY) {
+        Ok(Some(saved)) => {
+            let mut peers = PEER_VERSIONS.lock().unwrap_or_else(|e| e.into_inner());
+            for (identity, info) in saved {
+                // Anything recorded meanwhile is more recent
+                peers.entry(identity).or_insert(info);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to load peer protocol versions: {}", e),
+    }
+}
+
+// Save the registry if it changed (after message polls and history loads)
+pub fn persist_peer_versions<R: Runtime>(app: &AppHandle<R>) -> Result<(), StorageError> {
+    if !PEER_VERSIONS_DIRTY.swap(false, Ordering::Relaxed) {
+        return Ok(());
+    }
+    let peers = PEER_VERSIONS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    save_value(app, PEER_VERSIONS_STORE_PATH, PEER_VERSIONS_KEY, &peers).inspect_err(|_| {
+        // Saved on the next attempt
+        PEER_VERSIONS_DIRTY.store(true, Ordering::Relaxed);
+    })
+}
+
+pub fn peer_version(identity: &str) -> Option<PeerProtocolInfo> {
+    let peers = match PEER_VERSIONS.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    peers.get(identity).cloned()
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_protocol_info() -> ProtocolInfo {
+    ProtocolInfo {
+        current_version: PROTOCOL_VERSION,
+        min_supported_version: MIN_SUPPORTED_PROTOCOL_VERSION,
+        max_supported_version: MAX_SUPPORTED_PROTOCOL_VERSION,
+    }
+}
+
+// Detected protocol version for a conversation partner (None until a message from them was parsed)
+#[tauri::command]
+pub fn get_peer_protocol_version(peer_identity: String) -> Option<PeerProtocolInfo> {
+    log::debug!("get_peer_protocol_version command received for {}", peer_identity);
+    peer_version(&peer_identity)
+}
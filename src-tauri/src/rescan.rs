@@ -0,0 +1,131 @@
+// File: src-tauri/src/rescan.rs
+// Description: Wallet rescans started from the app (restored wallets, imported keys missing old messages).
+// Changes:
+// - Created file. Uses rescanblockchain or z_rescan, whichever the daemon has. The daemon keeps scanning after our
+//   request times out, so completion is detected by the wallet answering again; wallet-rescan events report
+//   progress. Sync cursors of the given addresses are reset afterwards so polling picks up the recovered messages.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use super::capabilities::method_available;
+use super::events::{emit_event, WALLET_RESCAN_EVENT};
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::settings::{write_sync_cursor, SyncCursor};
+
+// Supported rescan methods, in order of preference; both take the start height
+const RESCAN_METHODS: [&str; 2] = ["rescanblockchain", "z_rescan"];
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+// Ports with a rescan in progress
+static RUNNING: LazyLock<Mutex<HashSet<u16>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RescanState {
+    Started,
+    Running,
+    Completed,
+    Failed,
+}
+
+// Payload of the wallet-rescan event
+#[derive(Serialize, Debug, Clone)]
+pub struct RescanProgress {
+    pub state: RescanState,
+    pub method: String,
+    pub from_height: u64,
+    pub chain_height: u64,
+    pub elapsed_secs: u64,
+    pub error: Option<String>,
+}
+
+fn finish(rpc_port: u16) {
+    RUNNING.lock().unwrap_or_else(|e| e.into_inner()).remove(&rpc_port);
+}
+
+// Wait until the wallet answers again (it is locked for the whole scan)
+async fn wait_for_wallet(rpc_user: &str, rpc_pass: &str, rpc_port: u16, mut on_progress: impl FnMut()) -> Result<(), VerusRpcError> {
+    loop {
+        tokio::time::sleep(PROGRESS_INTERVAL).await;
+        match make_rpc_call::<Value>(rpc_user, rpc_pass, rpc_port, "getwalletinfo", vec![]).await {
+            Ok(_) => return Ok(()),
+            Err(VerusRpcError::Timeout) => on_progress(),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Start a rescan from the given height. Returns the initial progress; the rest arrives as wallet-rescan events.
+pub async fn rescan_wallet<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    from_height: u64,
+    private_addresses: Vec<String>, // Sync cursors to reset once the rescan finished
+) -> Result<RescanProgress, VerusRpcError> {
+    let mut method = None;
+    for candidate in RESCAN_METHODS {
+        if method_available(&rpc_user, &rpc_pass, rpc_port, candidate).await? {
+            method = Some(candidate);
+            break;
+        }
+    }
+    let method = method.ok_or_else(|| VerusRpcError::NotSupported("Wallet rescan".to_string()))?;
+
+    let chain_height: u64 = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getblockcount", vec![]).await?;
+    if from_height > chain_height {
+        return Err(VerusRpcError::Rpc { code: -8, message: format!("Start height {} is above the chain height {}", from_height, chain_height) });
+    }
+    if !RUNNING.lock().unwrap_or_else(|e| e.into_inner()).insert(rpc_port) {
+        return Err(VerusRpcError::Rpc { code: -4, message: "A wallet rescan is already running".to_string() });
+    }
+    log::info!("Starting wallet rescan with {} from height {} (chain height {})", method, from_height, chain_height);
+
+    let started = Instant::now();
+    let progress = move |state: RescanState, error: Option<String>| RescanProgress {
+        state,
+        method: method.to_string(),
+        from_height,
+        chain_height,
+        elapsed_secs: started.elapsed().as_secs(),
+        error,
+    };
+    let initial = progress(RescanState::Started, None);
+    emit_event(app, WALLET_RESCAN_EVENT, initial.clone());
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, method, vec![json!(from_height)]).await {
+            Ok(_) => Ok(()),
+            // Our request gave up, the daemon didn't; follow the scan until the wallet is available again
+            Err(VerusRpcError::Timeout) => {
+                wait_for_wallet(&rpc_user, &rpc_pass, rpc_port, || emit_event(&app, WALLET_RESCAN_EVENT, progress(RescanState::Running, None))).await
+            }
+            Err(e) => Err(e),
+        };
+        finish(rpc_port);
+
+        match result {
+            Ok(()) => {
+                log::info!("Wallet rescan finished after {}s", started.elapsed().as_secs());
+                for address in &private_addresses {
+                    if let Err(e) = write_sync_cursor(&app, address, &SyncCursor::default()) {
+                        log::warn!("Failed to reset sync cursor of {} after rescan: {}", address, e);
+                    }
+                }
+                emit_event(&app, WALLET_RESCAN_EVENT, progress(RescanState::Completed, None));
+            }
+            Err(e) => {
+                log::error!("Wallet rescan failed: {:?}", e);
+                emit_event(&app, WALLET_RESCAN_EVENT, progress(RescanState::Failed, Some(e.to_string())));
+            }
+        }
+    });
+    Ok(initial)
+}
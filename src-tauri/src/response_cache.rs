@@ -0,0 +1,259 @@
+// File: src-tauri/src/response_cache.rs
+// Description: Cache for RPC reads whose answers only change with a new block.
+// Changes:
+// - Created file. Answers of idempotent reads (identities, currencies, currency states, block data, vdxf ids) are
+//   kept per port and (method, params) until the chain tip moves. The tip is checked with getblockcount at most every
+//   few seconds, and sync status polls report it too, so polling cycles within one block hit the cache.
+// - Added make_cached_rpc_batch (batch requests answered from the cache where possible).
+// - Answers also expire after a configurable window (persisted, 0 disables the cache) and a full port evicts its
+//   least recently used answer instead of dropping all of them; get/set_response_cache_ttl commands.
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use super::rpc_client::{make_background_rpc_call, make_rpc_batch, make_rpc_call, VerusRpcError};
+use super::storage::{load_value, save_value, StorageError};
+
+// Methods whose answers depend only on the chain state (getblockchaininfo is not one: headers and verification
+// progress change between blocks)
+const CACHEABLE_METHODS: [&str; 7] = ["getidentity", "getcurrency", "getcurrencystate", "getblockhash", "getblockheader", "getblock", "getvdxfid"];
+
+// Cached answers may be this much older than the last tip check
+const TIP_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+const MAX_ENTRIES_PER_PORT: usize = 1000;
+
+const CACHE_STORE_PATH: &str = "store.json";
+const CACHE_TTL_KEY: &str = "response_cache_ttl_secs";
+
+// Answers are reused for at most this long, even within one block (0 disables the cache)
+const DEFAULT_TTL_SECS: u64 = 60;
+const MAX_TTL_SECS: u64 = 60 * 60;
+
+// Mirrors the persisted setting
+static TTL_SECS: Mutex<u64> = Mutex::new(DEFAULT_TTL_SECS);
+
+struct CachedResponse {
+    fetched_at: Instant,
+    last_used: Instant,
+    value: Value,
+}
+
+#[derive(Default)]
+struct PortCache {
+    tip: Option<u64>,
+    tip_checked: Option<Instant>,
+    epoch: u64, // Bumped whenever the entries are dropped; answers fetched in an older epoch aren't stored
+    entries: HashMap<String, CachedResponse>, // "method params" -> result
+}
+
+impl PortCache {
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.epoch += 1;
+    }
+
+    // Answer if it is younger than the TTL
+    fn lookup(&mut self, key: &str, ttl: Duration) -> Option<Value> {
+        let cached = self.entries.get_mut(key).filter(|cached| cached.fetched_at.elapsed() < ttl)?;
+        cached.last_used = Instant::now();
+        Some(cached.value.clone())
+    }
+
+    // Store an answer fetched in `epoch`, evicting the least recently used one when full
+    fn store(&mut self, epoch: u64, key: String, value: Value) {
+        if self.epoch != epoch || ttl().is_zero() {
+            return;
+        }
+        if self.entries.len() >= MAX_ENTRIES_PER_PORT && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter().min_by_key(|(_, cached)| cached.last_used).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        let now = Instant::now();
+        self.entries.insert(key, CachedResponse { fetched_at: now, last_used: now, value });
+    }
+}
+
+static CACHES: LazyLock<Mutex<HashMap<u16, PortCache>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn ttl() -> Duration {
+    Duration::from_secs(*TTL_SECS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+pub fn load_response_cache_ttl<R: Runtime>(app: &AppHandle<R>) {
+    match load_value::<R, u64>(app, CACHE_STORE_PATH, CACHE_TTL_KEY) {
+        Ok(ttl_secs) => *TTL_SECS.lock().unwrap_or_else(|e| e.into_inner()) = ttl_secs.unwrap_or(DEFAULT_TTL_SECS).min(MAX_TTL_SECS),
+        Err(e) => log::warn!("Failed to load response cache TTL: {}", e),
+    }
+}
+
+fn cache_key(method: &str, params: &[Value]) -> String {
+    format!("{} {}", method, Value::Array(params.to_vec()))
+}
+
+// A chain height seen on a port (by the tip check or elsewhere, e.g. sync status). A new tip drops the port's answers.
+pub fn observe_tip_height(rpc_port: u16, height: u64) {
+    let mut caches = CACHES.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = caches.entry(rpc_port).or_default();
+    cache.tip_checked = Some(Instant::now());
+    if cache.tip != Some(height) {
+        if !cache.entries.is_empty() {
+            log::debug!("New tip {} on port {}: dropping {} cached responses", height, rpc_port, cache.entries.len());
+        }
+        cache.tip = Some(height);
+        cache.clear();
+    }
+}
+
+// Drop a port's answers (after our own transactions, e.g. an identity update)
+pub fn invalidate_responses(rpc_port: u16) {
+    if let Some(cache) = CACHES.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&rpc_port) {
+        cache.clear();
+    }
+}
+
+// Check the tip if the last check is older than TIP_CHECK_INTERVAL
+async fn refresh_tip(rpc_user: &str, rpc_pass: &str, rpc_port: u16) {
+    {
+        let mut caches = CACHES.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = caches.entry(rpc_port).or_default();
+        if cache.tip_checked.is_some_and(|checked| checked.elapsed() < TIP_CHECK_INTERVAL) {
+            return;
+        }
+        // Concurrent calls don't each check
+        cache.tip_checked = Some(Instant::now());
+    }
+    match make_rpc_call::<u64>(rpc_user, rpc_pass, rpc_port, "getblockcount", vec![]).await {
+        Ok(height) => observe_tip_height(rpc_port, height),
+        Err(e) => {
+            // The tip is unknown, so are the cached answers
+            log::debug!("Tip check on port {} failed, dropping cached responses: {:?}", rpc_port, e);
+            invalidate_responses(rpc_port);
+        }
+    }
+}
+
+async fn cached_call<T: DeserializeOwned>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    method: &str,
+    params: Vec<Value>,
+    background: bool,
+) -> Result<T, VerusRpcError> {
+    if !CACHEABLE_METHODS.contains(&method) {
+        return if background {
+            make_background_rpc_call(rpc_user, rpc_pass, rpc_port, method, params).await
+        } else {
+            make_rpc_call(rpc_user, rpc_pass, rpc_port, method, params).await
+        };
+    }
+    refresh_tip(rpc_user, rpc_pass, rpc_port).await;
+    let key = cache_key(method, &params);
+    let ttl = ttl();
+    let (cached, epoch) = {
+        let mut caches = CACHES.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = caches.entry(rpc_port).or_default();
+        (cache.lookup(&key, ttl), cache.epoch)
+    };
+    if let Some(value) = cached {
+        log::trace!("{} served from response cache", key);
+        return serde_json::from_value(value).map_err(|e| VerusRpcError::ParseError(e.to_string()));
+    }
+
+    let value: Value = if background {
+        make_background_rpc_call(rpc_user, rpc_pass, rpc_port, method, params).await?
+    } else {
+        make_rpc_call(rpc_user, rpc_pass, rpc_port, method, params).await?
+    };
+    CACHES.lock().unwrap_or_else(|e| e.into_inner()).entry(rpc_port).or_default().store(epoch, key, value.clone());
+    serde_json::from_value(value).map_err(|e| VerusRpcError::ParseError(e.to_string()))
+}
+
+// make_rpc_call, answered from the cache while the tip hasn't moved (for CACHEABLE_METHODS; other methods pass through)
+pub async fn make_cached_rpc_call<T: DeserializeOwned>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    method: &str,
+    params: Vec<Value>,
+) -> Result<T, VerusRpcError> {
+    cached_call(rpc_user, rpc_pass, rpc_port, method, params, false).await
+}
+
+// make_background_rpc_call with the same cache
+pub async fn make_cached_background_rpc_call<T: DeserializeOwned>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    method: &str,
+    params: Vec<Value>,
+) -> Result<T, VerusRpcError> {
+    cached_call(rpc_user, rpc_pass, rpc_port, method, params, true).await
+}
+
+// make_rpc_batch with the same cache: cached answers are served, the rest is fetched in one batch request.
+// Results are in the order of `calls`.
+pub async fn make_cached_rpc_batch(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    calls: Vec<(&str, Vec<Value>)>,
+) -> Result<Vec<Result<Value, VerusRpcError>>, VerusRpcError> {
+    refresh_tip(rpc_user, rpc_pass, rpc_port).await;
+    let keys: Vec<Option<String>> = calls
+        .iter()
+        .map(|(method, params)| CACHEABLE_METHODS.contains(method).then(|| cache_key(method, params)))
+        .collect();
+    let ttl = ttl();
+    let (mut results, epoch) = {
+        let mut caches = CACHES.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = caches.entry(rpc_port).or_default();
+        let results: Vec<Option<Result<Value, VerusRpcError>>> =
+            keys.iter().map(|key| key.as_ref().and_then(|key| cache.lookup(key, ttl)).map(Ok)).collect();
+        (results, cache.epoch)
+    };
+    let missing: Vec<usize> = (0..calls.len()).filter(|i| results[*i].is_none()).collect();
+    if !missing.is_empty() {
+        log::trace!("Batch of {}: {} served from response cache", calls.len(), calls.len() - missing.len());
+        let missing_calls = missing.iter().map(|i| calls[*i].clone()).collect();
+        let fetched = make_rpc_batch::<Value>(rpc_user, rpc_pass, rpc_port, missing_calls).await?;
+        let mut caches = CACHES.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = caches.entry(rpc_port).or_default();
+        for (i, result) in missing.into_iter().zip(fetched) {
+            if let (Ok(value), Some(key)) = (&result, &keys[i]) {
+                cache.store(epoch, key.clone(), value.clone());
+            }
+            results[i] = Some(result);
+        }
+    }
+    Ok(results.into_iter().map(|result| result.unwrap_or(Err(VerusRpcError::Format))).collect())
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_response_cache_ttl() -> u64 {
+    log::debug!("get_response_cache_ttl command received");
+    *TTL_SECS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// How long answers are reused at most (0 disables the cache; capped at an hour). A new block drops them earlier.
+#[tauri::command]
+pub fn set_response_cache_ttl<R: Runtime>(app: AppHandle<R>, ttl_secs: u64) -> Result<u64, StorageError> {
+    log::info!("set_response_cache_ttl command received: {}s", ttl_secs);
+    let ttl_secs = ttl_secs.min(MAX_TTL_SECS);
+    save_value(&app, CACHE_STORE_PATH, CACHE_TTL_KEY, &ttl_secs)?;
+    *TTL_SECS.lock().unwrap_or_else(|e| e.into_inner()) = ttl_secs;
+    if ttl_secs == 0 {
+        for cache in CACHES.lock().unwrap_or_else(|e| e.into_inner()).values_mut() {
+            cache.clear();
+        }
+    }
+    Ok(ttl_secs)
+}
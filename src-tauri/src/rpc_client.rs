@@ -4,10 +4,41 @@
 // - Moved RpcResponse, RpcError, VerusRpcError, and make_rpc_call from verus_rpc.rs.
 // - Added SignatureResponse struct for signmessage API response
 // - Added signature verification specific error handling
+// - Added MemoTooLong error for memos exceeding the memo field even after compression
+// - Added work-queue saturation detection ("Work queue depth exceeded") with a global backoff state
+// - Added make_background_rpc_call for non-essential calls, paused and gradually resumed while the daemon is saturated
+// - JSON-RPC errors sent with an HTTP error status are parsed from the body instead of reported as network errors
+// - Added wallet lock / passphrase / encryption state errors (codes -13, -14, -15)
+// - Params of passphrase methods are redacted from debug logs
+// - Added NotSupported error for features the connected chain lacks
+// - Added InvalidFee error for custom send fees outside the accepted bounds
+// - Added InvalidAddress error for recipient addresses of another network or type
+// - Added InvalidAmount error (e.g. a zero-value transparent gift)
+// - Added InvalidCurrency error for gift currencies the connected chain doesn't know
+// - sign_message keeps WalletLocked instead of reporting SigningFailed; code mapping exposed as error_from_code
+// - Added RecipientRevoked error (sends to revoked identities are refused unless explicitly allowed)
+// - Added NoPrivateAddress and IdentityTimelocked errors (reasons a chat can't be started with an identity)
+// - One pooled HTTP client per daemon port (rpc_http_client), shared by all calls instead of a new client per call,
+//   so keep-alive connections are reused (history verification makes hundreds of calls in a row)
+// - Added make_rpc_batch (several calls in one JSON-RPC batch request, results in call order)
+// - Timeouts are per method (rpc_timeouts table with overrides) instead of a flat 10 seconds
+// - Calls and batches to the local port of a configured SSH tunnel (re)open the tunnel first
+// - A 401 on a port with cookie auth is retried once with the re-read cookie (it rotates when the daemon restarts)
+// - Calls and batches are timed and recorded in rpc_diagnostics
+// - Requests in flight are capped by a global semaphore (limit configurable and persisted; reported in DaemonLoadStatus)
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::time::Duration;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use super::rpc_timeouts::rpc_timeout;
+use super::credentials::refreshed_cookie_auth;
+use super::rpc_diagnostics::{batch_label, record_call};
+use super::ssh_tunnel::ensure_tunnel;
+use super::storage::{load_value, save_value, StorageError};
 
 // Define structs for the JSON-RPC request and response
 #[derive(Deserialize, Debug)]
@@ -51,6 +82,220 @@ pub enum VerusRpcError {
     SigningFailed,
     #[error("Message verification failed")]
     VerificationFailed,
+    #[error("Message is too long to fit in a memo ({0} bytes)")]
+    MemoTooLong(usize),
+    #[error("Daemon is overloaded (work queue depth exceeded)")]
+    WorkQueueExceeded,
+    #[error("Background request deferred while the daemon is overloaded")]
+    Throttled,
+    #[error("Wallet is locked; unlock it with the wallet passphrase first")]
+    WalletLocked,
+    #[error("The wallet passphrase entered was incorrect")]
+    WalletPassphraseIncorrect,
+    #[error("Wallet encryption state does not allow this: {0}")]
+    WalletEncryptionState(String),
+    #[error("Passphrase is too weak (at least {0} characters required)")]
+    WeakPassphrase(usize),
+    #[error("{0} not supported on this chain")]
+    NotSupported(String),
+    #[error("Invalid fee: {0}")]
+    InvalidFee(String),
+    #[error("Invalid recipient address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid currency: {0}")]
+    InvalidCurrency(String),
+    #[error("Recipient identity {0} has been revoked")]
+    RecipientRevoked(String),
+    #[error("Identity {0} has no private address and cannot receive messages")]
+    NoPrivateAddress(String),
+    #[error("Identity {identity} is locked or timelocked")]
+    IdentityTimelocked { identity: String, until_height: Option<u64> },
+}
+
+// Map daemon JSON-RPC errors to specific variants where the frontend reacts to them
+fn map_rpc_error(error: RpcError) -> VerusRpcError {
+    error_from_code(error.code, error.message)
+}
+
+// Same mapping for error codes reported outside a JSON-RPC response (e.g. failed async wallet operations)
+pub fn error_from_code(code: i32, message: String) -> VerusRpcError {
+    match code {
+        -13 => VerusRpcError::WalletLocked,              // RPC_WALLET_UNLOCK_NEEDED
+        -14 => VerusRpcError::WalletPassphraseIncorrect, // RPC_WALLET_PASSPHRASE_INCORRECT
+        -15 => VerusRpcError::WalletEncryptionState(message), // RPC_WALLET_WRONG_ENC_STATE
+        code => VerusRpcError::Rpc { code, message },
+    }
+}
+
+// Methods whose params contain secrets and must never be logged
+const SENSITIVE_METHODS: [&str; 3] = ["encryptwallet", "walletpassphrase", "walletpassphrasechange"];
+
+// Text returned by the daemon's HTTP server when its RPC work queue is full
+const WORK_QUEUE_EXCEEDED_TEXT: &str = "Work queue depth exceeded";
+
+// Backoff tuning for work-queue saturation
+const BASE_BACKOFF_MS: u64 = 2_000;
+const MAX_BACKOFF_MS: u64 = 60_000;
+const MAX_BACKOFF_LEVEL: u32 = 6;
+const SUCCESSES_PER_RECOVERY_STEP: u32 = 5;
+const BACKGROUND_SPACING_PER_LEVEL_MS: u64 = 500;
+
+// Daemon load state shared by all RPC calls
+#[derive(Default)]
+struct DaemonLoadState {
+    backoff_level: u32,                  // 0 = healthy
+    paused_until: Option<Instant>,       // Background calls are rejected until then
+    successes_since_step: u32,           // Successful calls since the last level change
+    last_background_call: Option<Instant>,
+}
+
+static DAEMON_LOAD: LazyLock<Mutex<DaemonLoadState>> = LazyLock::new(|| Mutex::new(DaemonLoadState::default()));
+
+// Keep-alive connections kept open per daemon, and how long an unused one stays open
+const POOL_MAX_IDLE_PER_HOST: usize = 16;
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// HTTP client (and with it the connection pool) per daemon RPC port
+static CLIENTS: LazyLock<Mutex<HashMap<u16, reqwest::Client>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Shared client for a daemon endpoint. Clones share the connection pool.
+pub fn rpc_http_client(rpc_port: u16) -> reqwest::Client {
+    CLIENTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(rpc_port)
+        .or_insert_with(|| {
+            log::debug!("Creating HTTP client for RPC port {}", rpc_port);
+            reqwest::Client::builder()
+                .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+                .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+                .tcp_nodelay(true)
+                .build()
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to configure HTTP client, using defaults: {}", e);
+                    reqwest::Client::new()
+                })
+        })
+        .clone()
+}
+
+// Requests (calls or batches) in flight at once, over all daemons
+const CONCURRENCY_STORE_PATH: &str = "store.json";
+const CONCURRENCY_KEY: &str = "rpc_max_in_flight";
+const DEFAULT_MAX_IN_FLIGHT: usize = 16;
+const MAX_IN_FLIGHT_LIMIT: usize = 64;
+
+// Waits longer than this for a free slot are logged
+const SLOW_SLOT_WAIT: Duration = Duration::from_secs(1);
+
+// Current limit and its semaphore. Changing the limit swaps in a new semaphore; calls holding a permit of the old
+// one finish normally.
+static LIMITER: LazyLock<Mutex<(usize, Arc<Semaphore>)>> =
+    LazyLock::new(|| Mutex::new((DEFAULT_MAX_IN_FLIGHT, Arc::new(Semaphore::new(DEFAULT_MAX_IN_FLIGHT)))));
+
+fn set_max_in_flight(limit: usize) {
+    *LIMITER.lock().unwrap_or_else(|e| e.into_inner()) = (limit, Arc::new(Semaphore::new(limit)));
+}
+
+// Wait for a free request slot (detection, history verification and balance refreshes all share them)
+async fn acquire_request_slot(label: &str) -> Option<OwnedSemaphorePermit> {
+    let semaphore = LIMITER.lock().unwrap_or_else(|e| e.into_inner()).1.clone();
+    let waiting_since = Instant::now();
+    let permit = semaphore.acquire_owned().await.ok();
+    let waited = waiting_since.elapsed();
+    if waited > SLOW_SLOT_WAIT {
+        log::debug!("RPC request {} waited {:?} for a free slot", label, waited);
+    }
+    permit
+}
+
+pub fn load_rpc_concurrency_limit<R: Runtime>(app: &AppHandle<R>) {
+    match load_value::<R, usize>(app, CONCURRENCY_STORE_PATH, CONCURRENCY_KEY) {
+        Ok(Some(limit)) => set_max_in_flight(limit.clamp(1, MAX_IN_FLIGHT_LIMIT)),
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to load RPC concurrency limit: {}", e),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DaemonLoadStatus {
+    pub saturated: bool,        // Background calls are currently paused
+    pub backoff_level: u32,
+    pub resume_in_ms: u64,      // Time until background calls resume (0 if not paused)
+    pub in_flight_requests: usize,
+    pub max_in_flight_requests: usize,
+}
+
+fn load_state() -> std::sync::MutexGuard<'static, DaemonLoadState> {
+    DAEMON_LOAD.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Record a saturation error and extend the pause for background calls
+fn record_saturation() {
+    let mut state = load_state();
+    state.backoff_level = (state.backoff_level + 1).min(MAX_BACKOFF_LEVEL);
+    state.successes_since_step = 0;
+    let backoff_ms = (BASE_BACKOFF_MS << (state.backoff_level - 1)).min(MAX_BACKOFF_MS);
+    state.paused_until = Some(Instant::now() + Duration::from_millis(backoff_ms));
+    log::warn!(
+        "Daemon work queue saturated (level {}). Pausing background RPC calls for {} ms",
+        state.backoff_level, backoff_ms
+    );
+}
+
+// Record a successful call; the backoff level decreases step by step
+fn record_success() {
+    let mut state = load_state();
+    if state.backoff_level == 0 {
+        return;
+    }
+    state.successes_since_step += 1;
+    if state.successes_since_step >= SUCCESSES_PER_RECOVERY_STEP {
+        state.backoff_level -= 1;
+        state.successes_since_step = 0;
+        log::info!("Daemon load recovering, backoff level now {}", state.backoff_level);
+    }
+}
+
+// Decide whether a background call may go out now.
+// While recovering, background calls are spaced out proportionally to the backoff level.
+fn admit_background_call() -> bool {
+    let mut state = load_state();
+    let now = Instant::now();
+    if let Some(until) = state.paused_until {
+        if now < until {
+            return false;
+        }
+    }
+    if state.backoff_level > 0 {
+        let spacing = Duration::from_millis(BACKGROUND_SPACING_PER_LEVEL_MS * state.backoff_level as u64);
+        if let Some(last) = state.last_background_call {
+            if now.duration_since(last) < spacing {
+                return false;
+            }
+        }
+    }
+    state.last_background_call = Some(now);
+    true
+}
+
+// Current daemon load status (for the UI)
+pub fn daemon_load_status() -> DaemonLoadStatus {
+    let state = load_state();
+    let resume_in_ms = state
+        .paused_until
+        .map(|until| until.saturating_duration_since(Instant::now()).as_millis() as u64)
+        .unwrap_or(0);
+    let (max_in_flight_requests, semaphore) = LIMITER.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    DaemonLoadStatus {
+        saturated: resume_in_ms > 0,
+        backoff_level: state.backoff_level,
+        resume_in_ms,
+        in_flight_requests: max_in_flight_requests.saturating_sub(semaphore.available_permits()),
+        max_in_flight_requests,
+    }
 }
 
 // Convert reqwest::Error to String for serialization
@@ -66,7 +311,11 @@ impl From<reqwest::Error> for VerusRpcError {
     }
 }
 
-// Helper function for generic RPC calls
+fn is_auth_failure<T>(result: &Result<T, VerusRpcError>) -> bool {
+    matches!(result, Err(VerusRpcError::Rpc { code: 401, .. }))
+}
+
+// Helper function for generic RPC calls (essential: never throttled, e.g. sends and user actions)
 pub async fn make_rpc_call<T: for<'de> Deserialize<'de>>(
     rpc_user: &str,
     rpc_pass: &str,
@@ -74,7 +323,149 @@ pub async fn make_rpc_call<T: for<'de> Deserialize<'de>>(
     method: &str,
     params: Vec<Value>,
 ) -> Result<T, VerusRpcError> {
-    let client = reqwest::Client::new();
+    ensure_tunnel(rpc_port).await.map_err(|e| VerusRpcError::NetworkError(e.to_string()))?;
+    let _slot = acquire_request_slot(method).await;
+    let started = Instant::now();
+    let mut result = execute_rpc_call(rpc_user, rpc_pass, rpc_port, method, params.clone()).await;
+    if is_auth_failure(&result) {
+        if let Some((cookie_user, cookie_pass)) = refreshed_cookie_auth(rpc_port, rpc_pass) {
+            result = execute_rpc_call(&cookie_user, &cookie_pass, rpc_port, method, params).await;
+        }
+    }
+    record_call(method, started.elapsed(), &result);
+    match &result {
+        Ok(_) => record_success(),
+        Err(VerusRpcError::WorkQueueExceeded) => record_saturation(),
+        Err(_) => {}
+    }
+    result
+}
+
+// Helper for non-essential background calls (balances, avatars, refreshes).
+// Deferred with VerusRpcError::Throttled while the daemon's work queue is saturated.
+pub async fn make_background_rpc_call<T: for<'de> Deserialize<'de>>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    method: &str,
+    params: Vec<Value>,
+) -> Result<T, VerusRpcError> {
+    if !admit_background_call() {
+        log::debug!("Deferring background RPC call {} while daemon is overloaded", method);
+        return Err(VerusRpcError::Throttled);
+    }
+    make_rpc_call(rpc_user, rpc_pass, rpc_port, method, params).await
+}
+
+// Batches take longer than single calls; the timeout of the slowest method grows with the batch
+const BATCH_TIMEOUT_PER_CALL: Duration = Duration::from_millis(200);
+
+// Send several calls as one JSON-RPC batch request (essential, like make_rpc_call). The outer error is for the
+// request as a whole; each call has its own result, in the order of `calls`.
+pub async fn make_rpc_batch<T: for<'de> Deserialize<'de>>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    calls: Vec<(&str, Vec<Value>)>,
+) -> Result<Vec<Result<T, VerusRpcError>>, VerusRpcError> {
+    if calls.is_empty() {
+        return Ok(Vec::new());
+    }
+    let label = batch_label(&calls.iter().map(|(method, _)| *method).collect::<Vec<_>>());
+    ensure_tunnel(rpc_port).await.map_err(|e| VerusRpcError::NetworkError(e.to_string()))?;
+    let _slot = acquire_request_slot(&label).await;
+    let started = Instant::now();
+    let mut result = execute_rpc_batch(rpc_user, rpc_pass, rpc_port, calls.clone()).await;
+    if is_auth_failure(&result) {
+        if let Some((cookie_user, cookie_pass)) = refreshed_cookie_auth(rpc_port, rpc_pass) {
+            result = execute_rpc_batch(&cookie_user, &cookie_pass, rpc_port, calls).await;
+        }
+    }
+    record_call(&label, started.elapsed(), &result);
+    match &result {
+        Ok(_) => record_success(),
+        Err(VerusRpcError::WorkQueueExceeded) => record_saturation(),
+        Err(_) => {}
+    }
+    result
+}
+
+async fn execute_rpc_batch<T: for<'de> Deserialize<'de>>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    calls: Vec<(&str, Vec<Value>)>,
+) -> Result<Vec<Result<T, VerusRpcError>>, VerusRpcError> {
+    let count = calls.len();
+    let timeout = calls.iter().map(|(method, _)| rpc_timeout(method)).max().unwrap_or_default();
+    let request_body: Vec<Value> = calls
+        .iter()
+        .enumerate()
+        .map(|(id, (method, params))| json!({ "jsonrpc": "1.0", "id": id, "method": method, "params": params }))
+        .collect();
+    if calls.iter().any(|(method, _)| SENSITIVE_METHODS.contains(method)) {
+        log::debug!("Making RPC batch of {} calls, params=<redacted>", count);
+    } else {
+        log::debug!("Making RPC batch of {} calls: {:?}", count, calls.iter().map(|(method, _)| *method).collect::<Vec<_>>());
+    }
+
+    let response = rpc_http_client(rpc_port)
+        .post(format!("http://localhost:{}", rpc_port))
+        .basic_auth(rpc_user, Some(rpc_pass))
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .timeout(timeout + BATCH_TIMEOUT_PER_CALL * count as u32)
+        .send()
+        .await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(VerusRpcError::Rpc { code: 401, message: "Authentication failed.".to_string() });
+    }
+    let body = response.text().await?;
+    if status == reqwest::StatusCode::SERVICE_UNAVAILABLE && body.contains(WORK_QUEUE_EXCEEDED_TEXT) {
+        log::warn!("RPC batch rejected: {}", WORK_QUEUE_EXCEEDED_TEXT);
+        return Err(VerusRpcError::WorkQueueExceeded);
+    }
+    let responses: Vec<Value> = match serde_json::from_str(&body) {
+        Ok(responses) => responses,
+        // A batch-level failure comes back as a single error response
+        Err(_) => {
+            return match serde_json::from_str::<RpcResponse<Value>>(&body) {
+                Ok(RpcResponse { error: Some(err), .. }) => Err(map_rpc_error(err)),
+                _ => Err(VerusRpcError::NetworkError(format!("HTTP {}: {}", status, body))),
+            };
+        }
+    };
+
+    // Responses may arrive in any order; match them to the calls by id
+    let mut results: Vec<Option<Result<T, VerusRpcError>>> = (0..count).map(|_| None).collect();
+    for mut response in responses {
+        let Some(id) = response.get("id").and_then(|v| v.as_u64()).map(|id| id as usize).filter(|id| *id < count) else {
+            log::warn!("RPC batch response with unknown id: {:?}", response.get("id"));
+            continue;
+        };
+        let error = response.get("error").filter(|e| !e.is_null()).cloned();
+        results[id] = Some(match (error, response.get_mut("result").map(Value::take)) {
+            (Some(error), _) => match serde_json::from_value::<RpcError>(error) {
+                Ok(error) => Err(map_rpc_error(error)),
+                Err(e) => Err(VerusRpcError::ParseError(e.to_string())),
+            },
+            (None, Some(result)) => serde_json::from_value(result).map_err(|e| VerusRpcError::ParseError(e.to_string())),
+            (None, None) => Err(VerusRpcError::Format),
+        });
+    }
+    Ok(results.into_iter().map(|result| result.unwrap_or(Err(VerusRpcError::Format))).collect())
+}
+
+// Performs the HTTP request for a single RPC call
+async fn execute_rpc_call<T: for<'de> Deserialize<'de>>(
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    method: &str,
+    params: Vec<Value>,
+) -> Result<T, VerusRpcError> {
+    let client = rpc_http_client(rpc_port);
     let rpc_url = format!("http://localhost:{}", rpc_port);
 
     let request_body = json!({
@@ -84,20 +475,42 @@ pub async fn make_rpc_call<T: for<'de> Deserialize<'de>>(
         "params": params
     });
 
-    log::debug!("Making RPC call: method={}, params={:?}", method, params);
+    if SENSITIVE_METHODS.contains(&method) {
+        log::debug!("Making RPC call: method={}, params=<redacted>", method);
+    } else {
+        log::debug!("Making RPC call: method={}, params={:?}", method, params);
+    }
 
     let request = client
         .post(rpc_url)
         .basic_auth(rpc_user, Some(rpc_pass))
         .header("Content-Type", "application/json")
         .json(&request_body)
-        .timeout(Duration::from_secs(10));
+        .timeout(rpc_timeout(method));
 
     match request.send().await {
         Ok(response) => {
             if response.status() == reqwest::StatusCode::UNAUTHORIZED {
                 return Err(VerusRpcError::Rpc { code: 401, message: "Authentication failed.".to_string() });
             }
+            if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+                // The daemon answers 503 with a plain text body when its work queue is full
+                let body = response.text().await.unwrap_or_default();
+                if body.contains(WORK_QUEUE_EXCEEDED_TEXT) {
+                    log::warn!("RPC call {} rejected: {}", method, WORK_QUEUE_EXCEEDED_TEXT);
+                    return Err(VerusRpcError::WorkQueueExceeded);
+                }
+                return Err(VerusRpcError::NetworkError(format!("Service unavailable: {}", body)));
+            }
+            if matches!(response.status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR | reqwest::StatusCode::NOT_FOUND) {
+                // The daemon reports JSON-RPC errors with an error status and a JSON body
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                return match serde_json::from_str::<RpcResponse<Value>>(&body) {
+                    Ok(RpcResponse { error: Some(err), .. }) => Err(map_rpc_error(err)),
+                    _ => Err(VerusRpcError::NetworkError(format!("HTTP {}: {}", status, body))),
+                };
+            }
             match response.error_for_status() {
                 Ok(successful_response) => {
                     match successful_response.json::<RpcResponse<T>>().await {
@@ -105,7 +518,7 @@ pub async fn make_rpc_call<T: for<'de> Deserialize<'de>>(
                             if let Some(result) = rpc_response.result {
                                 Ok(result)
                             } else if let Some(err) = rpc_response.error {
-                                Err(VerusRpcError::Rpc { code: err.code, message: err.message })
+                                Err(map_rpc_error(err))
                             } else {
                                 Err(VerusRpcError::Format)
                             }
@@ -144,6 +557,11 @@ pub async fn sign_message(
             log::info!("Message signed successfully. Hash: {}", signature_response.hash);
             Ok(signature_response)
         }
+        // The user can fix a locked wallet; keep that distinct from other signing failures
+        Err(VerusRpcError::WalletLocked) => {
+            log::warn!("Cannot sign with {}: wallet is locked", verusid);
+            Err(VerusRpcError::WalletLocked)
+        }
         Err(e) => {
             log::error!("Failed to sign message: {:?}", e);
             Err(VerusRpcError::SigningFailed)
@@ -182,4 +600,22 @@ pub async fn verify_message(
             Ok(false)
         }
     }
-} 
\ No newline at end of file
+} 
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_rpc_concurrency_limit() -> usize {
+    log::debug!("get_rpc_concurrency_limit command received");
+    LIMITER.lock().unwrap_or_else(|e| e.into_inner()).0
+}
+
+// Maximum RPC requests in flight at once (1 to 64). Returns the limit in effect.
+#[tauri::command]
+pub fn set_rpc_concurrency_limit<R: Runtime>(app: AppHandle<R>, limit: usize) -> Result<usize, StorageError> {
+    log::info!("set_rpc_concurrency_limit command received: {}", limit);
+    let limit = limit.clamp(1, MAX_IN_FLIGHT_LIMIT);
+    save_value(&app, CONCURRENCY_STORE_PATH, CONCURRENCY_KEY, &limit)?;
+    set_max_in_flight(limit);
+    Ok(limit)
+}
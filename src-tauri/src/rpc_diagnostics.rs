@@ -0,0 +1,138 @@
+// File: src-tauri/src/rpc_diagnostics.rs
+// Description: Per-method RPC statistics for bug reports about slowness.
+// Changes:
+// - Created file. make_rpc_call and make_rpc_batch record every call (latency and outcome); get_rpc_diagnostics
+//   reports counts, error counts, average / 95th percentile latency and the last error per method.
+// - Timestamps come from clock::now_secs (the local helper is gone).
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use super::rpc_client::VerusRpcError;
+use super::clock::now_secs;
+
+// Latency samples kept per method for the percentile (the average covers all calls)
+const MAX_SAMPLES: usize = 200;
+
+#[derive(Default)]
+struct MethodStats {
+    calls: u64,
+    errors: u64,
+    total_ms: u128,
+    recent_ms: VecDeque<u64>,
+    last_error: Option<(String, u64)>, // Error text and unix time
+}
+
+struct Diagnostics {
+    since: u64,
+    started: Instant,
+    methods: HashMap<String, MethodStats>,
+}
+
+impl Diagnostics {
+    fn new() -> Self {
+        Diagnostics { since: now_secs(), started: Instant::now(), methods: HashMap::new() }
+    }
+}
+
+static DIAGNOSTICS: LazyLock<Mutex<Diagnostics>> = LazyLock::new(|| Mutex::new(Diagnostics::new()));
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MethodDiagnostics {
+    pub method: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub average_ms: u64,
+    pub p95_ms: u64, // Over the last MAX_SAMPLES calls
+    pub max_ms: u64, // Over the last MAX_SAMPLES calls
+    pub last_error: Option<String>,
+    pub last_error_at: Option<u64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RpcDiagnostics {
+    pub since: u64, // App start or last reset (unix time)
+    pub uptime_secs: u64,
+    pub total_calls: u64,
+    pub total_errors: u64,
+    pub methods: Vec<MethodDiagnostics>, // Slowest (by total time spent) first
+}
+
+// Record one call (batches are recorded once, under "batch:" and their methods)
+pub fn record_call<T>(method: &str, elapsed: Duration, result: &Result<T, VerusRpcError>) {
+    let elapsed_ms = elapsed.as_millis() as u64;
+    let mut diagnostics = DIAGNOSTICS.lock().unwrap_or_else(|e| e.into_inner());
+    let stats = diagnostics.methods.entry(method.to_string()).or_default();
+    stats.calls += 1;
+    stats.total_ms += elapsed_ms as u128;
+    if stats.recent_ms.len() == MAX_SAMPLES {
+        stats.recent_ms.pop_front();
+    }
+    stats.recent_ms.push_back(elapsed_ms);
+    if let Err(e) = result {
+        stats.errors += 1;
+        stats.last_error = Some((e.to_string(), now_secs()));
+    }
+}
+
+// Name a batch is recorded under, e.g. "batch:getidentity"
+pub fn batch_label(methods: &[&str]) -> String {
+    let mut methods: Vec<&str> = methods.to_vec();
+    methods.sort_unstable();
+    methods.dedup();
+    format!("batch:{}", methods.join("+"))
+}
+
+fn percentile(samples: &VecDeque<u64>, percent: usize) -> u64 {
+    let mut sorted: Vec<u64> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    match sorted.len() {
+        0 => 0,
+        len => sorted[((len * percent).div_ceil(100)).clamp(1, len) - 1],
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_rpc_diagnostics() -> RpcDiagnostics {
+    log::debug!("get_rpc_diagnostics command received");
+    let diagnostics = DIAGNOSTICS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut methods: Vec<(u128, MethodDiagnostics)> = diagnostics
+        .methods
+        .iter()
+        .map(|(method, stats)| {
+            let (last_error, last_error_at) = match &stats.last_error {
+                Some((error, at)) => (Some(error.clone()), Some(*at)),
+                None => (None, None),
+            };
+            (stats.total_ms, MethodDiagnostics {
+                method: method.clone(),
+                calls: stats.calls,
+                errors: stats.errors,
+                average_ms: (stats.total_ms / stats.calls.max(1) as u128) as u64,
+                p95_ms: percentile(&stats.recent_ms, 95),
+                max_ms: stats.recent_ms.iter().copied().max().unwrap_or(0),
+                last_error,
+                last_error_at,
+            })
+        })
+        .collect();
+    methods.sort_by(|(a, _), (b, _)| b.cmp(a));
+    let methods: Vec<MethodDiagnostics> = methods.into_iter().map(|(_, method)| method).collect();
+    RpcDiagnostics {
+        since: diagnostics.since,
+        uptime_secs: diagnostics.started.elapsed().as_secs(),
+        total_calls: methods.iter().map(|m| m.calls).sum(),
+        total_errors: methods.iter().map(|m| m.errors).sum(),
+        methods,
+    }
+}
+
+// Start collecting from scratch (e.g. right before reproducing a problem)
+#[tauri::command]
+pub fn reset_rpc_diagnostics() {
+    log::info!("reset_rpc_diagnostics command received");
+    *DIAGNOSTICS.lock().unwrap_or_else(|e| e.into_inner()) = Diagnostics::new();
+}
@@ -0,0 +1,116 @@
+// File: src-tauri/src/rpc_timeouts.rs
+// Description: How long make_rpc_call waits for each RPC method.
+// Changes:
+// - Created file. Built-in timeouts per method (sends and wallet scans get longer, health checks shorter than the
+//   10 second default), with per-method overrides persisted in the store and mirrored here so calls don't need it.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use super::storage::{load_value, save_value, StorageError};
+
+const TIMEOUTS_STORE_PATH: &str = "store.json";
+const TIMEOUTS_KEY: &str = "rpc_timeout_overrides";
+
+// Methods not in the table below
+pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+// Bounds for overrides
+const MIN_TIMEOUT_SECS: u64 = 1;
+const MAX_TIMEOUT_SECS: u64 = 10 * 60;
+
+// Built-in timeouts (seconds) for methods that differ from the default
+const METHOD_TIMEOUTS: [(&str, u64); 18] = [
+    // Health checks: a daemon this slow to answer is as good as down
+    ("getblockcount", 3),
+    ("getconnectioncount", 3),
+    ("getmempoolinfo", 5),
+    ("getnetworkinfo", 5),
+    ("getpeerinfo", 5),
+    // Sends select notes and build proofs; busy wallets take a while
+    ("z_sendmany", 60),
+    ("sendcurrency", 60),
+    ("z_mergetoaddress", 60),
+    ("z_shieldcoinbase", 60),
+    ("registernamecommitment", 30),
+    ("registeridentity", 30),
+    ("updateidentity", 30),
+    // Wallet-wide scans grow with the wallet
+    ("z_listreceivedbyaddress", 30),
+    ("z_listunspent", 30),
+    ("z_gettotalbalance", 30),
+    ("z_getbalance", 20),
+    ("listtransactions", 20),
+    ("listidentities", 20),
+];
+
+// Method -> override (seconds); mirrors the persisted setting
+static OVERRIDES: LazyLock<Mutex<HashMap<String, u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RpcTimeout {
+    pub timeout_secs: u64,
+    pub default_secs: u64,
+    pub overridden: bool,
+}
+
+fn builtin_timeout_secs(method: &str) -> u64 {
+    METHOD_TIMEOUTS
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map(|(_, secs)| *secs)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS)
+}
+
+// Timeout for a call of `method` (override, else built-in, else the default)
+pub fn rpc_timeout(method: &str) -> Duration {
+    let overridden = OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()).get(method).copied();
+    Duration::from_secs(overridden.unwrap_or_else(|| builtin_timeout_secs(method)))
+}
+
+pub fn load_rpc_timeouts<R: Runtime>(app: &AppHandle<R>) {
+    match load_value::<R, HashMap<String, u64>>(app, TIMEOUTS_STORE_PATH, TIMEOUTS_KEY) {
+        Ok(overrides) => *OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()) = overrides.unwrap_or_default(),
+        Err(e) => log::warn!("Failed to load RPC timeout overrides: {}", e),
+    }
+}
+
+// --- Tauri Commands ---
+
+// Effective timeout of every method with a built-in or overridden timeout
+#[tauri::command]
+pub fn get_rpc_timeouts() -> BTreeMap<String, RpcTimeout> {
+    log::debug!("get_rpc_timeouts command received");
+    let overrides = OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let mut timeouts: BTreeMap<String, RpcTimeout> = METHOD_TIMEOUTS
+        .iter()
+        .map(|(method, secs)| (method.to_string(), RpcTimeout { timeout_secs: *secs, default_secs: *secs, overridden: false }))
+        .collect();
+    for (method, secs) in overrides {
+        let default_secs = builtin_timeout_secs(&method);
+        timeouts.insert(method, RpcTimeout { timeout_secs: secs, default_secs, overridden: true });
+    }
+    timeouts
+}
+
+// Override one method's timeout (clamped to 1 second .. 10 minutes); None restores the built-in one.
+// Returns the effective timeout.
+#[tauri::command]
+pub fn set_rpc_timeout<R: Runtime>(app: AppHandle<R>, method: String, timeout_secs: Option<u64>) -> Result<u64, StorageError> {
+    log::info!("set_rpc_timeout command received: {} = {:?}", method, timeout_secs);
+    let method = method.trim().to_string();
+    let mut overrides = OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    match timeout_secs {
+        Some(secs) => {
+            overrides.insert(method.clone(), secs.clamp(MIN_TIMEOUT_SECS, MAX_TIMEOUT_SECS));
+        }
+        None => {
+            overrides.remove(&method);
+        }
+    }
+    save_value(&app, TIMEOUTS_STORE_PATH, TIMEOUTS_KEY, &overrides)?;
+    *OVERRIDES.lock().unwrap_or_else(|e| e.into_inner()) = overrides;
+    Ok(rpc_timeout(&method).as_secs())
+}
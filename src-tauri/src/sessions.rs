@@ -0,0 +1,306 @@
+// File: src-tauri/src/sessions.rs
+// Description: Several identities logged in at once, with one of them active in the UI.
+// Changes:
+// - Created file. Each open session keeps its identity's state (message store, contact watcher, caches) alive and
+//   gets a background poller while it is not the active identity (the frontend keeps polling the active one), so
+//   switching is instant. Messages arriving for inactive identities are counted per session and reported as
+//   identity-session-updated events; switching to a session resets its count.
+// - Background pollers run on ZMQ transaction notifications while the listener is connected (timer otherwise)
+// - Background poll results mark their conversations unread in the backend (the frontend only does this for the
+//   active identity), so inactive sessions show them after switching
+// - Added close_all_sessions (emergency wipe).
+// - Background pollers follow the block notifications of the connected daemon's port (transactions polled them
+//   several times a minute).
+// - mark_unread goes through update_conversations.
+// - SessionError goes into the error log when a command returns it.
+// - Uses clock::now_secs instead of a local copy.
+// - mark_unread skips placeholders of memos from newer clients (their sender is unverified).
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use super::credentials::load_credentials;
+use super::error_log::recorded_command_error;
+use super::events::{emit_event, IDENTITY_SESSION_EVENT};
+use super::message_store::MessageStore;
+use super::message_rpc::ChatMessage;
+use super::settings::{read_conversations, update_conversations};
+use super::zmq_listener::{wait_for_next_poll, PushTrigger};
+use super::clock::now_secs;
+
+// Inactive sessions are polled this often
+const BACKGROUND_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Debug, Clone)]
+pub struct IdentitySession {
+    pub identity_i_address: String,
+    pub formatted_name: String,
+    pub private_address: String,
+    pub opened_at: u64,
+    pub active: bool,
+    pub new_messages: u32, // Received by the background poller since the session was last active
+    pub unread_conversations: u32,
+    pub last_poll: Option<u64>,
+    pub last_poll_error: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum SessionError {
+    #[error("No open session for identity {0}")]
+    UnknownSession(String),
+}
+
+recorded_command_error!(SessionError, "sessions");
+
+struct SessionEntry {
+    session: IdentitySession,
+    generation: u64, // Of the background poller; bumping it stops the poller
+}
+
+#[derive(Default)]
+struct SessionsState {
+    sessions: HashMap<String, SessionEntry>,
+    active: Option<String>,
+    generation: u64,
+}
+
+static SESSIONS: LazyLock<Mutex<SessionsState>> = LazyLock::new(|| Mutex::new(SessionsState::default()));
+
+// Copy of a session with the current active flag and unread conversation count
+fn snapshot<R: Runtime>(app: &AppHandle<R>, session: &IdentitySession, active: bool) -> IdentitySession {
+    let mut session = session.clone();
+    session.active = active;
+    session.unread_conversations = read_conversations(app, &session.identity_i_address)
+        .map(|conversations| conversations.iter().filter(|c| c.unread == Some(true)).count() as u32)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to count unread conversations of {}: {}", session.identity_i_address, e);
+            0
+        });
+    session
+}
+
+fn find_session<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Option<IdentitySession> {
+    let state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = state.sessions.get(identity_i_address)?;
+    let active = state.active.as_deref() == Some(identity_i_address);
+    let session = entry.session.clone();
+    drop(state);
+    Some(snapshot(app, &session, active))
+}
+
+pub fn is_active_identity(identity_i_address: &str) -> bool {
+    SESSIONS.lock().unwrap_or_else(|e| e.into_inner()).active.as_deref() == Some(identity_i_address)
+}
+
+// Open (or reopen) a session and start its background poller. The first session becomes active by itself.
+pub fn open_session(app: &AppHandle, identity_i_address: String, formatted_name: String, private_address: String, activate: bool) -> IdentitySession {
+    let (session, active, generation, open) = {
+        let mut state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        state.generation += 1;
+        let generation = state.generation;
+        // Reopening keeps the count of messages not seen yet
+        let new_messages = state
+            .sessions
+            .get(&identity_i_address)
+            .filter(|entry| entry.session.private_address == private_address)
+            .map(|entry| entry.session.new_messages)
+            .unwrap_or(0);
+        let session = IdentitySession {
+            identity_i_address: identity_i_address.clone(),
+            formatted_name,
+            private_address: private_address.clone(),
+            opened_at: now_secs(),
+            active: false,
+            new_messages,
+            unread_conversations: 0,
+            last_poll: None,
+            last_poll_error: None,
+        };
+        state.sessions.insert(identity_i_address.clone(), SessionEntry { session: session.clone(), generation });
+        if activate || state.active.is_none() {
+            state.active = Some(identity_i_address.clone());
+        }
+        let active = state.active.as_deref() == Some(identity_i_address.as_str());
+        (session, active, generation, state.sessions.len())
+    };
+    log::info!("Opened identity session for {} ({} open)", identity_i_address, open);
+    spawn_background_poller(app, identity_i_address, private_address, generation);
+
+    let session = snapshot(app, &session, active);
+    emit_event(app, IDENTITY_SESSION_EVENT, session.clone());
+    session
+}
+
+fn spawn_background_poller(app: &AppHandle, identity_i_address: String, private_address: String, generation: u64) {
+    let is_current = {
+        let identity = identity_i_address.clone();
+        move || {
+            SESSIONS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .sessions
+                .get(&identity)
+                .is_some_and(|entry| entry.generation == generation)
+        }
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // Once per block at most: inactive sessions don't need mempool latency
+            match load_credentials(app.clone()).await {
+                Ok(creds) => wait_for_next_poll(creds.rpc_port, BACKGROUND_POLL_INTERVAL, PushTrigger::Block).await,
+                Err(_) => tokio::time::sleep(BACKGROUND_POLL_INTERVAL).await,
+            }
+            if !is_current() {
+                break;
+            }
+            if is_active_identity(&identity_i_address) {
+                continue;
+            }
+            log::debug!("Background poll for {}", identity_i_address);
+            let error = match crate::poll_received_messages(app.clone(), private_address.clone(), Some(identity_i_address.clone())).await {
+                Ok(messages) => {
+                    mark_unread(&app, &identity_i_address, &messages);
+                    None
+                }
+                Err(e) => {
+                    log::warn!("Background poll for {} failed: {:?}", identity_i_address, e);
+                    Some(format!("{:?}", e))
+                }
+            };
+            if let Some(entry) = SESSIONS.lock().unwrap_or_else(|e| e.into_inner()).sessions.get_mut(&identity_i_address) {
+                entry.session.last_poll_error = error;
+            }
+        }
+        log::debug!("Background poller for {} stopped", identity_i_address);
+    });
+}
+
+// Mark the existing conversations of background-polled messages unread (like the frontend does for the active
+// identity; messages from senders without a conversation don't create one)
+fn mark_unread(app: &AppHandle, identity_i_address: &str, messages: &[ChatMessage]) {
+    if messages.is_empty() {
+        return;
+    }
+    let result = update_conversations(app, identity_i_address, |conversations| {
+        let mut marked = 0;
+        for conversation in conversations.iter_mut() {
+            if conversation.unread != Some(true)
+                && messages.iter().any(|m| !m.is_newer_protocol_placeholder() && m.sender == conversation.id)
+            {
+                conversation.unread = Some(true);
+                marked += 1;
+            }
+        }
+        marked
+    });
+    let marked = match result {
+        Ok(0) => return,
+        Ok(marked) => marked,
+        Err(e) => {
+            log::warn!("Failed to mark conversations of {} unread: {}", identity_i_address, e);
+            return;
+        }
+    };
+    log::debug!("Marked {} conversations of {} unread", marked, identity_i_address);
+    if let Some(session) = find_session(app, identity_i_address) {
+        emit_event(app, IDENTITY_SESSION_EVENT, session);
+    }
+}
+
+// Called after every poll of an identity (frontend or background). New messages for inactive sessions are counted.
+pub fn record_poll<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str, new_messages: usize) {
+    let counted = {
+        let mut state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let active = state.active.as_deref() == Some(identity_i_address);
+        let Some(entry) = state.sessions.get_mut(identity_i_address) else { return };
+        entry.session.last_poll = Some(now_secs());
+        if active || new_messages == 0 {
+            return;
+        }
+        entry.session.new_messages = entry.session.new_messages.saturating_add(new_messages as u32);
+        entry.session.clone()
+    };
+    log::info!("{} new messages for inactive identity {}", new_messages, identity_i_address);
+    emit_event(app, IDENTITY_SESSION_EVENT, snapshot(app, &counted, false));
+}
+
+// Forget every session, which stops their background pollers (emergency wipe)
+pub fn close_all_sessions() {
+    let mut state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    state.sessions.clear();
+    state.active = None;
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn list_identity_sessions<R: Runtime>(app: AppHandle<R>) -> Vec<IdentitySession> {
+    log::debug!("list_identity_sessions command received");
+    let (sessions, active) = {
+        let state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let sessions: Vec<IdentitySession> = state.sessions.values().map(|entry| entry.session.clone()).collect();
+        (sessions, state.active.clone())
+    };
+    let mut sessions: Vec<IdentitySession> = sessions
+        .iter()
+        .map(|session| snapshot(&app, session, active.as_deref() == Some(session.identity_i_address.as_str())))
+        .collect();
+    sessions.sort_by_key(|session| session.opened_at);
+    sessions
+}
+
+#[tauri::command]
+pub fn get_active_identity() -> Option<String> {
+    log::debug!("get_active_identity command received");
+    SESSIONS.lock().unwrap_or_else(|e| e.into_inner()).active.clone()
+}
+
+// Make an open session the active identity. Nothing is reloaded; its new message count is reset.
+#[tauri::command]
+pub fn switch_active_identity<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> Result<IdentitySession, SessionError> {
+    log::info!("switch_active_identity command received: {}", identity_i_address);
+    {
+        let mut state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = state
+            .sessions
+            .get_mut(&identity_i_address)
+            .ok_or_else(|| SessionError::UnknownSession(identity_i_address.clone()))?;
+        entry.session.new_messages = 0;
+        state.active = Some(identity_i_address.clone());
+    }
+    let session = find_session(&app, &identity_i_address).ok_or_else(|| SessionError::UnknownSession(identity_i_address.clone()))?;
+    emit_event(&app, IDENTITY_SESSION_EVENT, session.clone());
+    Ok(session)
+}
+
+// Log out one identity: stops its poller and contact watcher and drops its in-memory messages. If it was active,
+// the earliest other session becomes active. Returns the new active identity.
+#[tauri::command]
+pub fn close_identity_session<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> Result<Option<String>, SessionError> {
+    log::info!("close_identity_session command received: {}", identity_i_address);
+    let active = {
+        let mut state = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .sessions
+            .remove(&identity_i_address)
+            .ok_or_else(|| SessionError::UnknownSession(identity_i_address.clone()))?;
+        if state.active.as_deref() == Some(identity_i_address.as_str()) {
+            state.active = state
+                .sessions
+                .values()
+                .min_by_key(|entry| entry.session.opened_at)
+                .map(|entry| entry.session.identity_i_address.clone());
+        }
+        state.active.clone()
+    };
+    super::contact_watcher::stop_contact_watcher(Some(identity_i_address.clone()));
+    app.state::<MessageStore>().clear_identity(&identity_i_address);
+    if let Some(session) = active.as_deref().and_then(|identity| find_session(&app, identity)) {
+        emit_event(&app, IDENTITY_SESSION_EVENT, session);
+    }
+    Ok(active)
+}
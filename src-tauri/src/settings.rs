@@ -7,26 +7,77 @@
 // - Added Tauri commands for saving/loading conversations.
 // - Added Tauri commands for saving/loading messages per conversation.
 // - Added Tauri command for deleting chat data.
+// - Added optional protocol_version to persisted ChatMessage.
+// - Added optional recipient_bound to persisted ChatMessage.
+// - Added per-conversation MuteSettings (keyword and gift amount exceptions) with save/load commands.
+// - Added per-address SyncCursor for incremental message polling, plus reset_sync_cursor command.
+// - Added read/write helpers for the persistence preference and conversation messages (used by the message store).
+// - delete_chat_data also clears the in-memory message store for the identity.
+// - Added IdentityFilterRules (login identity eligibility) with save/load commands.
+// - Added read_conversations helper.
+// - Added save_draft / load_draft commands (unsent message text per identity + conversation).
+// - Added optional signature to persisted ChatMessage.
+// - Added write_conversations helper (used by save_conversations and conversation import).
+// - Added per-identity SpamRules (unknown sender minimum gift, per-sender rate limit, keyword filters) with save/load commands.
+// - delete_chat_data also deletes held message requests.
+// - SpamRules gained contacts_only (allowlist mode).
+// - SpamRules gained auto_accept_gift_above (gifts from unknown senders bypass message requests).
+// - Conversations can be archived; load_conversations takes a filter (active by default, archived, all).
+// - MuteSettings gained an optional muted_until timestamp.
+// - Added per-conversation last-read markers (set_last_read); load_messages_for_conversation returns the marker.
+// - Added mark_all_read (all conversations of an identity in one store save, single conversations-read event).
+// - ChatMessage has a kind (None for memo messages; transparent_gift for amount-only sends to an R-address).
+// - Added merge_conversation_records (folds a duplicate conversation's records into the primary in one store save).
+// - Added optional fee and size_bytes to persisted ChatMessage.
+// - SyncCursor tracks the remaining verification backlog (is_catching_up).
+// - load_messages_for_conversation returns poll tallies; vote messages are folded into them.
+// - Added optional currency to persisted ChatMessage (gifts in a currency other than the native coin).
+// - Added MessageKind::ConversionGift (convert-and-send gifts; amount and currency are what the sender spent).
+// - IdentityFilterRules can be compared (the wallet identity watcher re-baselines when they change).
+// - load_messages_for_conversation returns a message list again (the shipped frontend expects one); the last-read
+//   marker and poll tallies have their own commands (get_last_read, get_poll_tallies).
+// - save_conversations keeps stored archived conversations missing from the saved list (the default list doesn't
+//   contain them, so saving it back must not delete them).
+// - load_conversations without a filter returns all conversations to v1 frontends (command API v2).
+// - Added update_conversations: conversation list changes (frontend saves and backend tasks) are made under one lock
+//   on the freshly stored list, so concurrent writers only change their own fields.
+// - SettingsError goes into the error log when a command returns it.
+// - Read markers are timestamped with clock::now_secs.
+// - Added MessageKind::NewerProtocol (placeholder for a memo this version can't read).
+// - delete_chat_data removes the conversations' mute settings too.
 
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Runtime};
+use tauri::{AppHandle, Manager, Runtime};
 use tauri_plugin_store::{StoreExt, Error as StoreError};
+use super::clock::now_secs;
 use std::collections::HashMap; // Needed if using HashMap approach later
+use std::sync::{Mutex, MutexGuard};
 use serde_json::json; // Import serde_json macro for json!() usage
+use super::api_version::frontend_api_version;
+use super::error_log::recorded_command_error;
+use super::events::{emit_event, CONVERSATIONS_READ_EVENT};
+use super::message_store::MessageStore;
+use super::polls::{is_vote, tally_polls, PollTally};
 
 // Use the same store path as credentials for simplicity, just different keys
 const STORE_PATH: &str = "store.json";
 
+// Held for every read-modify-write of a conversation list, so the frontend's saves and backend tasks (identity
+// warmup, contact watcher, auto-accept, background polls) don't overwrite each other's changes
+static CONVERSATIONS_LOCK: Mutex<()> = Mutex::new(());
+
 // --- Structs mirroring frontend types ---
 
 // Mirror src/lib/types.ts Conversation
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Conversation {
     pub id: String,         // Unique ID, typically the recipient's VerusID name (e.g., user@)
     pub name: String,       // Display name (VerusID name)
     pub recipient_private_address: String, // The recipient's z-address needed for sending
     #[serde(default)] // Handle optional field during deserialization
     pub unread: Option<bool>,   // Optional flag for unread messages
+    #[serde(default)]
+    pub archived: bool,         // Hidden from the default conversation list
 }
 
 // Mirror src/lib/types.ts ChatMessage
@@ -41,10 +92,128 @@ pub struct ChatMessage {
     pub direction: String, // "received" | "sent"
     #[serde(default)] // Handle optional field during deserialization
     pub status: Option<String>, // Optional delivery status for sent messages "sent" | "delivered" | "failed"
+    #[serde(default)]
+    pub protocol_version: Option<u32>, // Protocol version declared by the sender's client
+    #[serde(default)]
+    pub recipient_bound: Option<bool>, // Signature covers the receiving address
+    #[serde(default)]
+    pub signature: Option<String>, // VerusID signature from the memo
+    #[serde(default)]
+    pub fee: Option<f64>, // Transaction fee paid (sent messages)
+    #[serde(default)]
+    pub size_bytes: Option<u64>, // Serialized transaction size (sent messages)
+    #[serde(default)]
+    pub kind: Option<MessageKind>, // None for regular memo messages
+    #[serde(default)]
+    pub currency: Option<String>, // Currency of the amount; None for the chain's native coin
+}
+
+// Messages that aren't regular signed memos
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageKind {
+    TransparentGift, // Amount only, sent to a transparent address (no memo, no signature)
+    ConversionGift,  // Signed memo plus a conversion paid out to the recipient's VerusID
+    NewerProtocol,   // Memo from a newer client; unreadable (and unverified) until the app is updated
+}
+
+// Position of the "new messages" divider in a conversation
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LastReadMarker {
+    pub txid: Option<String>, // Last read message, when marked by message
+    pub timestamp: u64,       // Unix seconds; messages after this are unread
+    pub updated_at: u64,
+}
+
+// Which conversations load_conversations returns
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversationFilter {
+    Active,   // Default list
+    Archived,
+    All,      // For search
+}
+
+impl ConversationFilter {
+    fn includes(self, conversation: &Conversation) -> bool {
+        match self {
+            ConversationFilter::Active => !conversation.archived,
+            ConversationFilter::Archived => conversation.archived,
+            ConversationFilter::All => true,
+        }
+    }
+}
+
+// Per-conversation notification mute settings
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MuteSettings {
+    pub muted: bool,
+    #[serde(default)]
+    pub keyword_exceptions: Vec<String>, // Always notify if the text contains one of these (case-insensitive)
+    #[serde(default)]
+    pub min_gift_exception: Option<f64>, // Always notify for gifts of at least this amount
+    #[serde(default)]
+    pub muted_until: Option<u64>, // Unix seconds; the mute lapses afterwards (None = until unmuted)
+}
+
+impl MuteSettings {
+    // Muted and, for a timed mute, not yet expired
+    pub fn is_muted_at(&self, now_secs: u64) -> bool {
+        self.muted && self.muted_until.is_none_or(|until| now_secs < until)
+    }
+}
+
+// Anti-spam rules for incoming messages (per identity). Filtered messages go to the message requests bucket.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SpamRules {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub min_gift_from_unknown: Option<f64>, // Senders without a conversation must attach at least this amount
+    #[serde(default)]
+    pub max_messages_per_hour: Option<u32>, // Per sender
+    #[serde(default)]
+    pub blocked_keywords: Vec<String>, // Case-insensitive
+    #[serde(default)]
+    pub contacts_only: bool, // Strict mode: only senders with a conversation reach the inbox (independent of `enabled`)
+    #[serde(default)]
+    pub auto_accept_gift_above: Option<f64>, // Unknown senders gifting more than this get a conversation immediately
+}
+
+// Which wallet identities are offered at login (global, applies before an identity is chosen)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct IdentityFilterRules {
+    #[serde(default)]
+    pub show_watch_only: bool, // Include IDs we cannot sign/spend for (read-only)
+    #[serde(default)]
+    pub include_revoked: bool, // Include revoked IDs (flagged with a warning)
+}
+
+// Incremental polling position for a receiving z-address
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SyncCursor {
+    pub last_block_height: Option<u64>, // Entries mined at or below this height are already processed
+    #[serde(default)]
+    pub processed_txids: Vec<String>,   // Processed entries above the height (or still unconfirmed)
+    #[serde(default)]
+    pub backlog_remaining: usize,       // Memos still queued for background verification
+}
+
+impl SyncCursor {
+    // A fresh cursor means the next poll scans the full history
+    pub fn is_initial(&self) -> bool {
+        self.last_block_height.is_none() && self.processed_txids.is_empty()
+    }
+
+    // Still delivering history: the first scan, or a verification backlog that later polls work through
+    pub fn is_catching_up(&self) -> bool {
+        self.is_initial() || self.backlog_remaining > 0
+    }
 }
 
 // Custom error type (can be expanded)
 #[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
 pub enum SettingsError {
     #[error("Store plugin error: {0}")]
     Store(String),
@@ -54,8 +223,12 @@ pub enum SettingsError {
     Serialization(String),
     #[error("Deserialization error: {0}")]
     Deserialization(String),
+    #[error("Invalid request: {0}")]
+    Invalid(String),
 }
 
+recorded_command_error!(SettingsError, "settings");
+
 impl From<StoreError> for SettingsError {
     fn from(error: StoreError) -> Self {
         SettingsError::Store(error.to_string())
@@ -76,6 +249,236 @@ fn get_messages_key(identity_i_address: &str, conversation_id: &str) -> String {
     format!("messages_{}_{}", identity_i_address, conversation_id)
 }
 
+fn get_draft_key(identity_i_address: &str, conversation_id: &str) -> String {
+    format!("draft_{}_{}", identity_i_address, conversation_id)
+}
+
+fn get_mute_key(identity_i_address: &str, conversation_id: &str) -> String {
+    format!("mute_{}_{}", identity_i_address, conversation_id)
+}
+
+fn get_last_read_key(identity_i_address: &str, conversation_id: &str) -> String {
+    format!("last_read_{}_{}", identity_i_address, conversation_id)
+}
+
+pub fn read_last_read<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    conversation_id: &str,
+) -> Result<Option<LastReadMarker>, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_last_read_key(identity_i_address, conversation_id)) {
+        Some(value) => serde_json::from_value::<LastReadMarker>(value)
+            .map(Some)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse last-read marker: {}", e))),
+        None => Ok(None),
+    }
+}
+
+const IDENTITY_FILTER_RULES_KEY: &str = "identity_filter_rules";
+
+pub fn read_identity_filter_rules<R: Runtime>(app: &AppHandle<R>) -> Result<IdentityFilterRules, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(IDENTITY_FILTER_RULES_KEY) {
+        Some(value) => serde_json::from_value::<IdentityFilterRules>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse identity filter rules: {}", e))),
+        None => Ok(IdentityFilterRules::default()),
+    }
+}
+
+fn get_spam_rules_key(identity_i_address: &str) -> String {
+    format!("spam_rules_{}", identity_i_address)
+}
+
+// Load the anti-spam rules for an identity (defaults to disabled)
+pub fn read_spam_rules<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Result<SpamRules, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_spam_rules_key(identity_i_address)) {
+        Some(value) => serde_json::from_value::<SpamRules>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse spam rules: {}", e))),
+        None => Ok(SpamRules::default()),
+    }
+}
+
+fn get_sync_cursor_key(private_address: &str) -> String {
+    format!("sync_cursor_{}", private_address)
+}
+
+// Load the polling cursor for an address (defaults to a fresh cursor)
+pub fn read_sync_cursor<R: Runtime>(app: &AppHandle<R>, private_address: &str) -> Result<SyncCursor, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_sync_cursor_key(private_address)) {
+        Some(value) => serde_json::from_value::<SyncCursor>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse sync cursor: {}", e))),
+        None => Ok(SyncCursor::default()),
+    }
+}
+
+pub fn write_sync_cursor<R: Runtime>(app: &AppHandle<R>, private_address: &str, cursor: &SyncCursor) -> Result<(), SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    let cursor_json = serde_json::to_value(cursor)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(get_sync_cursor_key(private_address), cursor_json);
+    store.save()?;
+    Ok(())
+}
+
+pub fn read_conversations<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Result<Vec<Conversation>, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_conversations_key(identity_i_address)) {
+        Some(value) => serde_json::from_value::<Vec<Conversation>>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse conversations Vec: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn lock_conversations() -> MutexGuard<'static, ()> {
+    CONVERSATIONS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_conversations<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    conversations: &[Conversation],
+) -> Result<(), SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    let conversations_json = serde_json::to_value(conversations)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(get_conversations_key(identity_i_address), conversations_json);
+    store.save()?;
+    Ok(())
+}
+
+// Change the stored conversation list of an identity under the conversations lock. `change` gets the list as
+// stored right now and should only touch the conversations and fields it means to change; the list is saved if it
+// changed.
+pub fn update_conversations<R: Runtime, T>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    change: impl FnOnce(&mut Vec<Conversation>) -> T,
+) -> Result<T, SettingsError> {
+    let _guard = lock_conversations();
+    let mut conversations = read_conversations(app, identity_i_address)?;
+    let stored = conversations.clone();
+    let result = change(&mut conversations);
+    if conversations != stored {
+        write_conversations(app, identity_i_address, &conversations)?;
+    }
+    Ok(result)
+}
+
+// Whether the user opted in to local chat persistence (defaults to false)
+pub fn read_persistence_preference<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Result<bool, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_preference_key(identity_i_address)) {
+        Some(value) => serde_json::from_value::<bool>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse preference bool: {}", e))),
+        None => Ok(false),
+    }
+}
+
+pub fn read_conversation_messages<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    conversation_id: &str,
+) -> Result<Vec<ChatMessage>, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_messages_key(identity_i_address, conversation_id)) {
+        Some(value) => serde_json::from_value::<Vec<ChatMessage>>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse messages Vec for {}: {}", conversation_id, e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub fn write_conversation_messages<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    conversation_id: &str,
+    messages: &[ChatMessage],
+) -> Result<(), SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    let messages_json = serde_json::to_value(messages)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(get_messages_key(identity_i_address, conversation_id), messages_json);
+    store.save()?;
+    Ok(())
+}
+
+// Fold a duplicate conversation into the primary one in a single store save: the merged message list replaces the
+// primary's, the duplicate's draft, mute settings and last-read marker fill in what the primary lacks (the later
+// last-read marker wins), and the duplicate's entry and records are removed. Returns the updated primary.
+pub fn merge_conversation_records<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    primary_id: &str,
+    duplicate_id: &str,
+    messages: &[ChatMessage],
+) -> Result<Conversation, SettingsError> {
+    let _guard = lock_conversations();
+    let mut conversations = read_conversations(app, identity_i_address)?;
+    let duplicate_index = conversations
+        .iter()
+        .position(|c| c.id == duplicate_id)
+        .ok_or_else(|| SettingsError::NotFound(format!("Conversation {}", duplicate_id)))?;
+    let duplicate = conversations.remove(duplicate_index);
+    let primary = conversations
+        .iter_mut()
+        .find(|c| c.id == primary_id)
+        .ok_or_else(|| SettingsError::NotFound(format!("Conversation {}", primary_id)))?;
+    primary.unread = Some(primary.unread == Some(true) || duplicate.unread == Some(true));
+    primary.archived = primary.archived && duplicate.archived;
+    let merged = primary.clone();
+
+    let store = app.store(STORE_PATH)?;
+    if read_persistence_preference(app, identity_i_address)? {
+        let messages_json = serde_json::to_value(messages).map_err(|e| SettingsError::Serialization(e.to_string()))?;
+        store.set(get_messages_key(identity_i_address, primary_id), messages_json);
+    }
+    for key_fn in [get_draft_key, get_mute_key] {
+        let (primary_key, duplicate_key) = (key_fn(identity_i_address, primary_id), key_fn(identity_i_address, duplicate_id));
+        if let (None, Some(value)) = (store.get(&primary_key), store.get(&duplicate_key)) {
+            store.set(primary_key, value);
+        }
+    }
+    let later_marker = [
+        read_last_read(app, identity_i_address, primary_id)?,
+        read_last_read(app, identity_i_address, duplicate_id)?,
+    ]
+    .into_iter()
+    .flatten()
+    .max_by_key(|m| m.timestamp);
+    if let Some(marker) = later_marker {
+        let marker_json = serde_json::to_value(&marker).map_err(|e| SettingsError::Serialization(e.to_string()))?;
+        store.set(get_last_read_key(identity_i_address, primary_id), marker_json);
+    }
+    for key in [
+        get_messages_key(identity_i_address, duplicate_id),
+        get_draft_key(identity_i_address, duplicate_id),
+        get_mute_key(identity_i_address, duplicate_id),
+        get_last_read_key(identity_i_address, duplicate_id),
+    ] {
+        store.delete(key);
+    }
+    let conversations_json = serde_json::to_value(&conversations).map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(get_conversations_key(identity_i_address), conversations_json);
+    store.save()?;
+    Ok(merged)
+}
+
+// Load mute settings for a conversation (defaults to not muted)
+pub fn read_mute_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    conversation_id: &str,
+) -> Result<MuteSettings, SettingsError> {
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_mute_key(identity_i_address, conversation_id)) {
+        Some(value) => serde_json::from_value::<MuteSettings>(value)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse mute settings: {}", e))),
+        None => Ok(MuteSettings::default()),
+    }
+}
+
 // --- Tauri Commands ---
 
 #[tauri::command]
@@ -123,12 +526,15 @@ pub async fn save_conversations<R: Runtime>(
     conversations: Vec<Conversation>,
 ) -> Result<(), SettingsError> {
     log::info!("Saving {} conversations for {}", conversations.len(), identity_i_address);
-    let store = app.store(STORE_PATH)?;
-    let key = get_conversations_key(&identity_i_address);
-    let conversations_json = serde_json::to_value(conversations)
-        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
-    store.set(key, conversations_json);
-    store.save()?;
+    // Lists loaded with the default filter don't contain archived conversations
+    update_conversations(&app, &identity_i_address, |stored| {
+        let archived: Vec<Conversation> = stored
+            .drain(..)
+            .filter(|stored| stored.archived && !conversations.iter().any(|c| c.id == stored.id))
+            .collect();
+        *stored = conversations;
+        stored.extend(archived);
+    })?;
     log::info!("Conversations saved successfully.");
     Ok(())
 }
@@ -137,21 +543,62 @@ pub async fn save_conversations<R: Runtime>(
 pub async fn load_conversations<R: Runtime>(
     app: AppHandle<R>,
     identity_i_address: String,
+    filter: Option<ConversationFilter>, // Defaults to active (non-archived) conversations; all for v1 frontends
 ) -> Result<Vec<Conversation>, SettingsError> {
     log::info!("Loading conversations for {}", identity_i_address);
     let store = app.store(STORE_PATH)?;
     let key = get_conversations_key(&identity_i_address);
-    match store.get(&key) {
+    let conversations = match store.get(&key) {
         Some(value) => {
             log::debug!("Found conversations value for {}", identity_i_address);
              serde_json::from_value::<Vec<Conversation>>(value.clone())
-                 .map_err(|e| SettingsError::Deserialization(format!("Failed to parse conversations Vec: {}", e)))
+                 .map_err(|e| SettingsError::Deserialization(format!("Failed to parse conversations Vec: {}", e)))?
         }
         None => {
             log::info!("No conversations found in store for {}", identity_i_address);
-            Ok(Vec::new()) // Return empty Vec if not found
+            Vec::new() // Return empty Vec if not found
         }
-    }
+    };
+    // v1 frontends don't know about archiving and expect every conversation
+    let filter = filter.unwrap_or(if frontend_api_version() < 2 { ConversationFilter::All } else { ConversationFilter::Active });
+    Ok(conversations.into_iter().filter(|c| filter.includes(c)).collect())
+}
+
+// Set or clear the archived flag of a conversation
+fn set_conversation_archived<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    conversation_id: &str,
+    archived: bool,
+) -> Result<(), SettingsError> {
+    update_conversations(app, identity_i_address, |conversations| {
+        let conversation = conversations
+            .iter_mut()
+            .find(|c| c.id == conversation_id)
+            .ok_or_else(|| SettingsError::NotFound(format!("Conversation {}", conversation_id)))?;
+        conversation.archived = archived;
+        Ok(())
+    })?
+}
+
+#[tauri::command]
+pub async fn archive_conversation<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<(), SettingsError> {
+    log::info!("Archiving conversation {} for {}", conversation_id, identity_i_address);
+    set_conversation_archived(&app, &identity_i_address, &conversation_id, true)
+}
+
+#[tauri::command]
+pub async fn unarchive_conversation<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<(), SettingsError> {
+    log::info!("Unarchiving conversation {} for {}", conversation_id, identity_i_address);
+    set_conversation_archived(&app, &identity_i_address, &conversation_id, false)
 }
 
 #[tauri::command]
@@ -181,17 +628,120 @@ pub async fn load_messages_for_conversation<R: Runtime>(
     log::info!("Loading messages for conversation {} (user {})", conversation_id, identity_i_address);
      let store = app.store(STORE_PATH)?;
     let key = get_messages_key(&identity_i_address, &conversation_id);
-    match store.get(&key) {
+    let messages = match store.get(&key) {
         Some(value) => {
             log::debug!("Found messages value for conversation {}", conversation_id);
              serde_json::from_value::<Vec<ChatMessage>>(value.clone())
-                 .map_err(|e| SettingsError::Deserialization(format!("Failed to parse messages Vec for {}: {}", conversation_id, e)))
+                 .map_err(|e| SettingsError::Deserialization(format!("Failed to parse messages Vec for {}: {}", conversation_id, e)))?
         }
         None => {
             log::info!("No messages found in store for conversation {}", conversation_id);
-            Ok(Vec::new()) // Return empty Vec if not found
+            Vec::new() // Return empty Vec if not found
         }
+    };
+    // Poll votes are only shown through the tallies (get_poll_tallies)
+    Ok(messages.into_iter().filter(|m| !is_vote(m)).collect())
+}
+
+#[tauri::command]
+pub async fn get_last_read<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<Option<LastReadMarker>, SettingsError> {
+    log::debug!("get_last_read command received for {} (user {})", conversation_id, identity_i_address);
+    read_last_read(&app, &identity_i_address, &conversation_id)
+}
+
+// Polls of a conversation with the votes counted so far
+#[tauri::command]
+pub async fn get_poll_tallies<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<Vec<PollTally>, SettingsError> {
+    log::debug!("get_poll_tallies command received for {} (user {})", conversation_id, identity_i_address);
+    let messages = read_conversation_messages(&app, &identity_i_address, &conversation_id)?;
+    Ok(tally_polls(&messages))
+}
+
+// Persist the "new messages" divider position. Pass the last read message's txid, or a timestamp.
+#[tauri::command]
+pub async fn set_last_read<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+    txid: Option<String>,
+    timestamp: Option<u64>,
+) -> Result<LastReadMarker, SettingsError> {
+    log::debug!("set_last_read command received for {} (user {}): txid={:?}, timestamp={:?}", conversation_id, identity_i_address, txid, timestamp);
+    // A marked message's own timestamp wins over the one passed along
+    let message_timestamp = match &txid {
+        Some(txid) => read_conversation_messages(&app, &identity_i_address, &conversation_id)?
+            .into_iter()
+            .find(|m| &m.id == txid)
+            .map(|m| m.timestamp),
+        None => None,
+    };
+    let timestamp = message_timestamp
+        .or(timestamp)
+        .map(super::formatting::normalize_timestamp_secs)
+        .ok_or_else(|| SettingsError::NotFound("last-read txid or timestamp".to_string()))?;
+
+    let marker = LastReadMarker {
+        txid,
+        timestamp,
+        updated_at: now_secs(),
+    };
+    let store = app.store(STORE_PATH)?;
+    let marker_json = serde_json::to_value(&marker).map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(get_last_read_key(&identity_i_address, &conversation_id), marker_json);
+    store.save()?;
+    Ok(marker)
+}
+
+// Payload of the conversations-read event
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversationsRead {
+    pub identity_i_address: String,
+    pub conversation_ids: Vec<String>, // Conversations that had unread messages
+    pub read_at: u64,
+}
+
+// Clear the unread flag of every conversation and move each last-read marker to now, saving the store once
+#[tauri::command]
+pub async fn mark_all_read<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+) -> Result<ConversationsRead, SettingsError> {
+    log::info!("mark_all_read command received for {}", identity_i_address);
+    let read_at = now_secs();
+    let _guard = lock_conversations();
+    let mut conversations = read_conversations(&app, &identity_i_address)?;
+    let conversation_ids: Vec<String> = conversations
+        .iter_mut()
+        .filter(|c| c.unread == Some(true))
+        .map(|c| {
+            c.unread = Some(false);
+            c.id.clone()
+        })
+        .collect();
+
+    let marker_json = serde_json::to_value(LastReadMarker { txid: None, timestamp: read_at, updated_at: read_at })
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    let conversations_json = serde_json::to_value(&conversations)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    let store = app.store(STORE_PATH)?;
+    for conversation in &conversations {
+        store.set(get_last_read_key(&identity_i_address, &conversation.id), marker_json.clone());
     }
+    store.set(get_conversations_key(&identity_i_address), conversations_json);
+    store.save()?;
+
+    let update = ConversationsRead { identity_i_address, conversation_ids, read_at };
+    log::debug!("Marked {} conversations as read", update.conversation_ids.len());
+    emit_event(&app, CONVERSATIONS_READ_EVENT, update.clone());
+    Ok(update)
 }
 
 #[tauri::command]
@@ -201,6 +751,7 @@ pub async fn delete_chat_data<R: Runtime>(
 ) -> Result<(), SettingsError> {
      log::warn!("Attempting to delete ALL chat data (preference, conversations, messages) for identity: {}", identity_i_address);
     let store = app.store(STORE_PATH)?;
+    let _guard = lock_conversations();
 
     let pref_key = get_preference_key(&identity_i_address);
     let convos_key = get_conversations_key(&identity_i_address);
@@ -229,9 +780,12 @@ pub async fn delete_chat_data<R: Runtime>(
          }
     }
 
-    // 4. Delete messages for each conversation
+    // 4. Delete messages (and drafts, read markers, mutes) for each conversation
     let mut messages_deleted = 0;
     for convo in conversations_to_delete {
+         store.delete(get_draft_key(&identity_i_address, &convo.id));
+         store.delete(get_last_read_key(&identity_i_address, &convo.id));
+         store.delete(get_mute_key(&identity_i_address, &convo.id));
          let msg_key = get_messages_key(&identity_i_address, &convo.id);
          if store.has(&msg_key) {
             if store.delete(&msg_key) {
@@ -244,9 +798,137 @@ pub async fn delete_chat_data<R: Runtime>(
     }
     log::info!("Deleted message data for {} conversations.", messages_deleted);
 
-    // 5. Save changes to the store file
+    // 5. Delete held message requests
+    store.delete(super::spam::message_requests_key(&identity_i_address));
+
+    // 6. Drop the in-memory canonical lists
+    if let Some(message_store) = app.try_state::<MessageStore>() {
+        message_store.clear_identity(&identity_i_address);
+    }
+
+    // 7. Save changes to the store file
     store.save()?;
     log::warn!("Completed deletion of chat data for identity: {}. Store saved.", identity_i_address);
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[tauri::command]
+pub async fn save_conversation_mute<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+    mute_settings: MuteSettings,
+) -> Result<(), SettingsError> {
+    log::info!("Saving mute settings for conversation {} (user {}): muted={}", conversation_id, identity_i_address, mute_settings.muted);
+    let mut mute_settings = mute_settings;
+    // The frontend may pass Date.now() milliseconds
+    mute_settings.muted_until = mute_settings.muted_until.map(super::formatting::normalize_timestamp_secs);
+    let store = app.store(STORE_PATH)?;
+    let key = get_mute_key(&identity_i_address, &conversation_id);
+    let settings_json = serde_json::to_value(mute_settings)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(key, settings_json);
+    store.save()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn load_conversation_mute<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<MuteSettings, SettingsError> {
+    log::debug!("Loading mute settings for conversation {} (user {})", conversation_id, identity_i_address);
+    read_mute_settings(&app, &identity_i_address, &conversation_id)
+}
+
+// Forget the polling cursor so the next poll rescans the full history (e.g., after deleting chat data)
+#[tauri::command]
+pub async fn reset_sync_cursor<R: Runtime>(
+    app: AppHandle<R>,
+    own_private_address: String,
+) -> Result<(), SettingsError> {
+    log::info!("Resetting sync cursor for {}", own_private_address);
+    let store = app.store(STORE_PATH)?;
+    store.delete(get_sync_cursor_key(&own_private_address));
+    store.save()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn save_identity_filter_rules<R: Runtime>(
+    app: AppHandle<R>,
+    rules: IdentityFilterRules,
+) -> Result<(), SettingsError> {
+    log::info!("Saving identity filter rules: {:?}", rules);
+    let store = app.store(STORE_PATH)?;
+    let rules_json = serde_json::to_value(rules)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(IDENTITY_FILTER_RULES_KEY, rules_json);
+    store.save()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn load_identity_filter_rules<R: Runtime>(app: AppHandle<R>) -> Result<IdentityFilterRules, SettingsError> {
+    log::debug!("Loading identity filter rules");
+    read_identity_filter_rules(&app)
+}
+
+#[tauri::command]
+pub async fn save_spam_rules<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    rules: SpamRules,
+) -> Result<(), SettingsError> {
+    log::info!("Saving spam rules for {}: {:?}", identity_i_address, rules);
+    let store = app.store(STORE_PATH)?;
+    let rules_json = serde_json::to_value(rules)
+        .map_err(|e| SettingsError::Serialization(e.to_string()))?;
+    store.set(get_spam_rules_key(&identity_i_address), rules_json);
+    store.save()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn load_spam_rules<R: Runtime>(app: AppHandle<R>, identity_i_address: String) -> Result<SpamRules, SettingsError> {
+    log::debug!("Loading spam rules for {}", identity_i_address);
+    read_spam_rules(&app, &identity_i_address)
+}
+
+// Save unsent message text. An empty draft removes the stored one.
+#[tauri::command]
+pub async fn save_draft<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+    text: String,
+) -> Result<(), SettingsError> {
+    log::debug!("Saving draft for conversation {} (user {}): {} chars", conversation_id, identity_i_address, text.len());
+    let store = app.store(STORE_PATH)?;
+    let key = get_draft_key(&identity_i_address, &conversation_id);
+    if text.trim().is_empty() {
+        store.delete(&key);
+    } else {
+        store.set(key, json!(text));
+    }
+    store.save()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn load_draft<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    conversation_id: String,
+) -> Result<Option<String>, SettingsError> {
+    log::debug!("Loading draft for conversation {} (user {})", conversation_id, identity_i_address);
+    let store = app.store(STORE_PATH)?;
+    match store.get(get_draft_key(&identity_i_address, &conversation_id)) {
+        Some(value) => serde_json::from_value::<String>(value)
+            .map(Some)
+            .map_err(|e| SettingsError::Deserialization(format!("Failed to parse draft: {}", e))),
+        None => Ok(None),
+    }
+}
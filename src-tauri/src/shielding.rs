@@ -0,0 +1,262 @@
+// File: src-tauri/src/shielding.rs
+// Description: Moves transparent funds into the identity's private (messaging) address.
+// Changes:
+// - Created file. Coinbase UTXOs are shielded with z_shieldcoinbase, other transparent UTXOs with z_mergetoaddress
+//   (falling back to a sendcurrency from "R*" where merging isn't enabled). The daemon runs these as async
+//   operations; each one is awaited in the background and reported with a shield-operation event.
+// - Added shield_amount: a fixed amount from the transparent balance (automatic top-ups); the caller tracks it.
+// - Shielding only spends the identity's own primary R-addresses (found with listidentities by the private
+//   address) instead of every wallet t-address; the sendcurrency fallback sends each address's non-coinbase
+//   UTXOs (listunspent) from that address. Answers without an opid are errors instead of being tracked.
+// - shield_amount sends from a given source address of the identity instead of "R*"; source_addresses and the
+//   per-address spendable funds (spendable_by_source) are shared with automatic top-ups.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use super::events::{emit_event, SHIELD_OPERATION_EVENT};
+use super::message_rpc::DEFAULT_TX_FEE;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::wallet_rpc::{wait_for_operation, WalletOperationStatus};
+
+// Shielding many UTXOs means many proofs; give the operations plenty of time
+const SHIELD_OPERATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// JSON-RPC "method not found"
+const RPC_METHOD_NOT_FOUND: i32 = -32601;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShieldSource {
+    Coinbase,
+    Transparent,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ShieldOperation {
+    pub source: ShieldSource,
+    pub opid: String,
+    pub value: f64,           // Amount being shielded (before the fee)
+    pub utxos: u64,           // UTXOs included in this operation
+    pub remaining_utxos: u64, // Left for another run (the daemon limits UTXOs per operation)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ShieldResult {
+    pub private_address: String,
+    pub operations: Vec<ShieldOperation>,
+}
+
+// Payload of the shield-operation event
+#[derive(Serialize, Debug, Clone)]
+pub struct ShieldOperationUpdate {
+    pub private_address: String,
+    pub source: ShieldSource,
+    pub status: WalletOperationStatus,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum ShieldError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("No transparent funds to shield")]
+    NothingToShield,
+    #[error("No wallet identity with transparent addresses owns {0}")]
+    NoSourceAddresses(String),
+}
+
+impl From<VerusRpcError> for ShieldError {
+    fn from(error: VerusRpcError) -> Self {
+        ShieldError::Rpc(error)
+    }
+}
+
+// "Nothing to do" answers of the shielding RPCs are errors; they just mean there are no such funds
+fn is_nothing_to_shield(error: &VerusRpcError) -> bool {
+    matches!(error, VerusRpcError::Rpc { message, .. } if message.contains("Could not find any"))
+}
+
+fn as_u64(value: &Value, key: &str) -> u64 {
+    value.get(key).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+fn as_f64(value: &Value, key: &str) -> f64 {
+    value.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0)
+}
+
+fn operation_id(result: &Value) -> Result<String, VerusRpcError> {
+    result
+        .get("opid")
+        .and_then(|v| v.as_str())
+        .filter(|opid| !opid.is_empty())
+        .map(String::from)
+        .ok_or_else(|| VerusRpcError::ParseError("shielding answer has no opid".to_string()))
+}
+
+// Primary R-addresses of the wallet identity whose private address this is; shielding spends only from these
+pub async fn source_addresses(rpc_user: &str, rpc_pass: &str, rpc_port: u16, private_address: &str) -> Result<Vec<String>, ShieldError> {
+    let identities: Vec<Value> = make_rpc_call(rpc_user, rpc_pass, rpc_port, "listidentities", vec![json!(true), json!(true), json!(false)]).await?;
+    let addresses: Vec<String> = identities
+        .iter()
+        .filter_map(|entry| entry.get("identity"))
+        .find(|identity| identity.get("privateaddress").and_then(|v| v.as_str()) == Some(private_address))
+        .and_then(|identity| identity.get("primaryaddresses"))
+        .and_then(|v| v.as_array())
+        .map(|addresses| {
+            addresses
+                .iter()
+                .filter_map(|v| v.as_str())
+                .filter(|address| address.starts_with('R'))
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    if addresses.is_empty() {
+        return Err(ShieldError::NoSourceAddresses(private_address.to_string()));
+    }
+    Ok(addresses)
+}
+
+// z_shieldcoinbase takes a single source address, so each address gets its own operation
+async fn shield_coinbase(rpc_user: &str, rpc_pass: &str, rpc_port: u16, sources: &[String], private_address: &str) -> Result<Vec<ShieldOperation>, VerusRpcError> {
+    let mut operations = Vec::new();
+    for source in sources {
+        match make_rpc_call::<Value>(rpc_user, rpc_pass, rpc_port, "z_shieldcoinbase", vec![json!(source), json!(private_address)]).await {
+            Ok(result) => operations.push(ShieldOperation {
+                source: ShieldSource::Coinbase,
+                opid: operation_id(&result)?,
+                value: as_f64(&result, "shieldingValue"),
+                utxos: as_u64(&result, "shieldingUTXOs"),
+                remaining_utxos: as_u64(&result, "remainingUTXOs"),
+            }),
+            Err(e) if is_nothing_to_shield(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(operations)
+}
+
+// Non-coinbase transparent funds. z_mergetoaddress can be disabled on a daemon; then each address's non-coinbase
+// UTXOs are sent with sendcurrency instead (which doesn't report UTXO counts).
+async fn shield_transparent(rpc_user: &str, rpc_pass: &str, rpc_port: u16, sources: &[String], private_address: &str) -> Result<Vec<ShieldOperation>, VerusRpcError> {
+    let merge = make_rpc_call::<Value>(rpc_user, rpc_pass, rpc_port, "z_mergetoaddress", vec![json!(sources), json!(private_address)]).await;
+    match merge {
+        Ok(result) => {
+            return Ok(vec![ShieldOperation {
+                source: ShieldSource::Transparent,
+                opid: operation_id(&result)?,
+                value: as_f64(&result, "mergingTransparentValue"),
+                utxos: as_u64(&result, "mergingUTXOs"),
+                remaining_utxos: as_u64(&result, "remainingUTXOs"),
+            }]);
+        }
+        Err(e) if is_nothing_to_shield(&e) => return Ok(Vec::new()),
+        Err(VerusRpcError::Rpc { code, message }) if code == RPC_METHOD_NOT_FOUND || message.contains("experimental") => {
+            log::info!("z_mergetoaddress unavailable ({}), shielding with sendcurrency", message);
+        }
+        Err(e) => return Err(e),
+    }
+
+    let mut operations = Vec::new();
+    for (source, value, count) in spendable_by_source(rpc_user, rpc_pass, rpc_port, sources).await? {
+        let amount = value - DEFAULT_TX_FEE;
+        if amount <= 0.0 {
+            continue;
+        }
+        let opid: String = make_rpc_call(
+            rpc_user,
+            rpc_pass,
+            rpc_port,
+            "sendcurrency",
+            vec![json!(source), json!([{ "address": private_address, "amount": amount }])],
+        )
+        .await?;
+        if opid.is_empty() {
+            return Err(VerusRpcError::ParseError("sendcurrency returned no opid".to_string()));
+        }
+        operations.push(ShieldOperation { source: ShieldSource::Transparent, opid, value: amount, utxos: count, remaining_utxos: 0 });
+    }
+    Ok(operations)
+}
+
+// Confirmed non-coinbase funds per source address, as (address, value, UTXO count). Coinbase UTXOs can't be spent
+// to a z-address with sendcurrency; they are left to z_shieldcoinbase.
+pub async fn spendable_by_source(rpc_user: &str, rpc_pass: &str, rpc_port: u16, sources: &[String]) -> Result<Vec<(String, f64, u64)>, VerusRpcError> {
+    let utxos: Vec<Value> = make_rpc_call(rpc_user, rpc_pass, rpc_port, "listunspent", vec![json!(1), json!(9999999), json!(sources)]).await?;
+    Ok(sources
+        .iter()
+        .map(|source| {
+            let (value, count) = utxos
+                .iter()
+                .filter(|utxo| utxo.get("address").and_then(|v| v.as_str()) == Some(source.as_str()))
+                .filter(|utxo| !utxo.get("generated").and_then(|v| v.as_bool()).unwrap_or(false))
+                .filter(|utxo| utxo.get("spendable").and_then(|v| v.as_bool()).unwrap_or(true))
+                .fold((0.0, 0u64), |(value, count), utxo| (value + as_f64(utxo, "amount"), count + 1));
+            (source.clone(), value, count)
+        })
+        .collect())
+}
+
+// Shield a fixed amount with sendcurrency from one of the identity's own addresses (see source_addresses). Unlike
+// shield_funds, the operation is not tracked here.
+pub async fn shield_amount(rpc_user: &str, rpc_pass: &str, rpc_port: u16, source: &str, private_address: &str, amount: f64) -> Result<ShieldOperation, VerusRpcError> {
+    log::info!("Shielding {} from {} into {}", amount, source, private_address);
+    let opid: String = make_rpc_call(
+        rpc_user,
+        rpc_pass,
+        rpc_port,
+        "sendcurrency",
+        vec![json!(source), json!([{ "address": private_address, "amount": amount }])],
+    )
+    .await?;
+    Ok(ShieldOperation { source: ShieldSource::Transparent, opid, value: amount, utxos: 0, remaining_utxos: 0 })
+}
+
+// Await an operation in the background and report how it ended
+fn track_operation<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, private_address: String, operation: &ShieldOperation) {
+    let app = app.clone();
+    let (opid, source) = (operation.opid.clone(), operation.source);
+    tauri::async_runtime::spawn(async move {
+        let result = wait_for_operation(&rpc_user, &rpc_pass, rpc_port, &opid, SHIELD_OPERATION_TIMEOUT).await;
+        let status = match result {
+            Ok(txid) => {
+                log::info!("Shielding operation {} succeeded: {}", opid, txid);
+                WalletOperationStatus { opid, status: "success".to_string(), txid: Some(txid), error: None }
+            }
+            Err(e) => {
+                log::warn!("Shielding operation {} failed: {}", opid, e);
+                WalletOperationStatus { opid, status: "failed".to_string(), txid: None, error: Some(e.to_string()) }
+            }
+        };
+        emit_event(&app, SHIELD_OPERATION_EVENT, ShieldOperationUpdate { private_address, source, status });
+    });
+}
+
+// Start shielding the identity's transparent funds into its private address. Returns the started operations;
+// results arrive as shield-operation events.
+pub async fn shield_funds<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    private_address: String,
+    include_coinbase: bool,
+) -> Result<ShieldResult, ShieldError> {
+    log::info!("Shielding transparent funds into {} (coinbase: {})", private_address, include_coinbase);
+    let sources = source_addresses(&rpc_user, &rpc_pass, rpc_port, &private_address).await?;
+    let mut operations = Vec::new();
+    if include_coinbase {
+        operations.extend(shield_coinbase(&rpc_user, &rpc_pass, rpc_port, &sources, &private_address).await?);
+    }
+    operations.extend(shield_transparent(&rpc_user, &rpc_pass, rpc_port, &sources, &private_address).await?);
+    if operations.is_empty() {
+        return Err(ShieldError::NothingToShield);
+    }
+
+    for operation in &operations {
+        log::info!("Started {:?} shielding of {} ({} UTXOs) as {}", operation.source, operation.value, operation.utxos, operation.opid);
+        track_operation(app, rpc_user.clone(), rpc_pass.clone(), rpc_port, private_address.clone(), operation);
+    }
+    Ok(ShieldResult { private_address, operations })
+}
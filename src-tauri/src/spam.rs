@@ -0,0 +1,400 @@
+// File: src-tauri/src/spam.rs
+// Description: Anti-spam filtering of polled messages and the reviewable "message requests" bucket.
+// Changes:
+// - Created file. Messages caught by the identity's SpamRules are held as message requests (store.json)
+//   instead of reaching conversations; the user can accept or dismiss them.
+// - Contacts-only mode holds every message from senders without a conversation (listed per sender).
+// - Gifts above auto_accept_gift_above from unknown senders bypass the queue and create a conversation.
+// - Requests from senders blocked after they were held are hidden.
+// - Added held_senders (candidate identities when recovering conversations from outgoing memos).
+// - Added without_held (chat history leaves out messages held as message requests).
+// - Auto-accepted conversations are added with update_conversations (to the current list, not the one read before the lookups).
+// - SpamError goes into the error log when a command returns it.
+// - Dropped the private now_secs in favour of clock::now_secs.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Runtime, State};
+use super::blocklist::is_blocked;
+use super::error_log::recorded_command_error;
+use super::events::{emit_event, CONVERSATION_AUTO_ACCEPTED_EVENT, MESSAGE_REQUESTS_UPDATED_EVENT};
+use super::identity_rpc::check_identity_eligibility;
+use super::message_rpc;
+use super::message_store::MessageStore;
+use super::settings::{read_conversations, read_spam_rules, update_conversations, ChatMessage, Conversation, SpamRules};
+use super::storage::{load_value, save_value};
+use super::clock::now_secs;
+
+const STORE_PATH: &str = "store.json";
+
+// Window for the per-sender rate limit
+const RATE_WINDOW_SECS: u64 = 3_600;
+
+// Receipt times of accepted messages per identity:sender (in memory; resets on restart)
+static RECENT_BY_SENDER: LazyLock<Mutex<HashMap<String, VecDeque<u64>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum SpamReason {
+    BelowMinimumGift, // Unknown sender without the required gift
+    RateLimited,      // Sender exceeded the per-hour limit
+    BlockedKeyword(String),
+    UnknownSender, // Contacts-only mode; listed separately from the other requests
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MessageRequest {
+    pub message: ChatMessage,
+    pub reason: SpamReason,
+    pub filtered_at: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MessageRequestsUpdate {
+    pub identity_i_address: String,
+    pub request_count: usize,
+    pub unknown_sender_count: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ConversationAutoAccepted {
+    pub identity_i_address: String,
+    pub conversation_id: String,
+    pub gift_txid: String,
+    pub amount: f64,
+}
+
+// Held messages of one unknown sender (contacts-only mode)
+#[derive(Serialize, Debug, Clone)]
+pub struct UnknownSenderRequests {
+    pub sender: String,
+    pub message_count: usize,
+    pub latest_filtered_at: u64,
+    pub requests: Vec<MessageRequest>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum SpamError {
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Settings error: {0}")]
+    Settings(String),
+    #[error("Message request not found: {0}")]
+    NotFound(String),
+}
+
+recorded_command_error!(SpamError, "spam");
+
+impl From<super::storage::StorageError> for SpamError {
+    fn from(error: super::storage::StorageError) -> Self {
+        SpamError::Storage(error.to_string())
+    }
+}
+
+impl From<super::settings::SettingsError> for SpamError {
+    fn from(error: super::settings::SettingsError) -> Self {
+        SpamError::Settings(error.to_string())
+    }
+}
+
+pub fn message_requests_key(identity_i_address: &str) -> String {
+    format!("message_requests_{}", identity_i_address)
+}
+
+fn read_requests<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Result<Vec<MessageRequest>, SpamError> {
+    Ok(load_value(app, STORE_PATH, &message_requests_key(identity_i_address))?.unwrap_or_default())
+}
+
+// Distinct senders of the held message requests
+pub fn held_senders<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str) -> Vec<String> {
+    let mut senders: Vec<String> = read_requests(app, identity_i_address)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|r| r.message.sender)
+        .collect();
+    senders.sort();
+    senders.dedup();
+    senders
+}
+
+// Leave out messages that are held as message requests (history shows them only once accepted)
+pub fn without_held<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    messages: Vec<message_rpc::ChatMessage>,
+) -> Vec<message_rpc::ChatMessage> {
+    let held: HashSet<String> = match read_requests(app, identity_i_address) {
+        Ok(requests) => requests.into_iter().map(|r| r.message.id).collect(),
+        Err(e) => {
+            log::warn!("Failed to load message requests, not excluding them from history: {}", e);
+            return messages;
+        }
+    };
+    if held.is_empty() {
+        return messages;
+    }
+    messages.into_iter().filter(|m| !held.contains(&m.id)).collect()
+}
+
+fn write_requests<R: Runtime>(app: &AppHandle<R>, identity_i_address: &str, requests: &[MessageRequest]) -> Result<(), SpamError> {
+    save_value(app, STORE_PATH, &message_requests_key(identity_i_address), &requests)?;
+    let unknown_sender_count = requests.iter().filter(|r| r.reason == SpamReason::UnknownSender).count();
+    emit_event(app, MESSAGE_REQUESTS_UPDATED_EVENT, MessageRequestsUpdate {
+        identity_i_address: identity_i_address.to_string(),
+        request_count: requests.len() - unknown_sender_count,
+        unknown_sender_count,
+    });
+    Ok(())
+}
+
+// Gifts large enough to skip the message requests queue
+fn qualifies_for_auto_accept(rules: &SpamRules, message: &message_rpc::ChatMessage) -> bool {
+    rules.auto_accept_gift_above.is_some_and(|min| message.amount > min)
+}
+
+// Decide whether a message is spam. Accepted messages count towards the sender's rate limit.
+fn evaluate(
+    rules: &SpamRules,
+    rate_key: &str,
+    known_sender: bool,
+    message: &message_rpc::ChatMessage,
+    now: u64,
+    initial_sync: bool,
+) -> Option<SpamReason> {
+    // Money talks: a large enough gift from a stranger is let through (and gets a conversation)
+    if !known_sender && qualifies_for_auto_accept(rules, message) {
+        return None;
+    }
+    if rules.contacts_only && !known_sender {
+        return Some(SpamReason::UnknownSender);
+    }
+    if !rules.enabled {
+        return None;
+    }
+
+    let text = message.text.to_lowercase();
+    if let Some(keyword) = rules
+        .blocked_keywords
+        .iter()
+        .find(|k| !k.trim().is_empty() && text.contains(&k.trim().to_lowercase()))
+    {
+        return Some(SpamReason::BlockedKeyword(keyword.clone()));
+    }
+
+    if !known_sender && rules.min_gift_from_unknown.is_some_and(|min| message.amount < min) {
+        return Some(SpamReason::BelowMinimumGift);
+    }
+
+    // History arriving in bulk on the first sync isn't a burst from the sender
+    if initial_sync {
+        return None;
+    }
+    let mut recent = RECENT_BY_SENDER.lock().unwrap_or_else(|e| e.into_inner());
+    let times = recent.entry(rate_key.to_string()).or_default();
+    while times.front().is_some_and(|t| now.saturating_sub(*t) >= RATE_WINDOW_SECS) {
+        times.pop_front();
+    }
+    if rules.max_messages_per_hour.is_some_and(|max| times.len() >= max as usize) {
+        return Some(SpamReason::RateLimited);
+    }
+    times.push_back(now);
+    None
+}
+
+// Apply the identity's spam rules to polled messages. Filtered messages are moved to the
+// message requests bucket; the rest are returned.
+pub fn filter_incoming<R: Runtime>(
+    app: &AppHandle<R>,
+    identity_i_address: &str,
+    messages: Vec<message_rpc::ChatMessage>,
+    initial_sync: bool,
+) -> Vec<message_rpc::ChatMessage> {
+    let rules = match read_spam_rules(app, identity_i_address) {
+        Ok(rules) if rules.enabled || rules.contacts_only => rules,
+        Ok(_) => return messages,
+        Err(e) => {
+            log::warn!("Failed to load spam rules, not filtering: {}", e);
+            return messages;
+        }
+    };
+    let known_senders: HashSet<String> = read_conversations(app, identity_i_address)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+
+    let now = now_secs();
+    let mut filtered = Vec::new();
+    let accepted = messages
+        .into_iter()
+        .filter(|message| {
+            let rate_key = format!("{}:{}", identity_i_address, message.sender);
+            match evaluate(&rules, &rate_key, known_senders.contains(&message.sender), message, now, initial_sync) {
+                Some(reason) => {
+                    log::info!("Message {} from {} held as a message request: {:?}", message.id, message.sender, reason);
+                    filtered.push(MessageRequest { message: message.clone().into(), reason, filtered_at: now });
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect();
+
+    if !filtered.is_empty() {
+        let result = read_requests(app, identity_i_address).and_then(|mut requests| {
+            let known: HashSet<String> = requests.iter().map(|r| r.message.id.clone()).collect();
+            requests.extend(filtered.into_iter().filter(|r| !known.contains(&r.message.id)));
+            write_requests(app, identity_i_address, &requests)
+        });
+        if let Err(e) = result {
+            log::warn!("Failed to store message requests: {}", e);
+        }
+    }
+    accepted
+}
+
+// Create conversations for unknown senders whose gifts passed the auto-accept threshold.
+// Runs after filter_incoming; the sender's private address is resolved from their identity.
+pub async fn accept_gifting_senders<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    identity_i_address: &str,
+    messages: &[message_rpc::ChatMessage],
+) {
+    let rules = match read_spam_rules(app, identity_i_address) {
+        Ok(rules) if (rules.enabled || rules.contacts_only) && rules.auto_accept_gift_above.is_some() => rules,
+        _ => return,
+    };
+    let mut conversations = read_conversations(app, identity_i_address).unwrap_or_default();
+
+    let mut added = Vec::new();
+    let mut accepted = Vec::new();
+    for message in messages.iter().filter(|m| m.direction == "received" && qualifies_for_auto_accept(&rules, m)) {
+        if conversations.iter().any(|c| c.id == message.sender) {
+            continue;
+        }
+        match check_identity_eligibility(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, message.sender.clone()).await {
+            Ok(identity) => {
+                log::info!("Auto-accepted conversation with {} after a gift of {}", message.sender, message.amount);
+                let conversation = Conversation {
+                    id: message.sender.clone(),
+                    name: identity.formatted_name,
+                    recipient_private_address: identity.private_address,
+                    unread: Some(true),
+                    archived: false,
+                };
+                conversations.push(conversation.clone());
+                added.push(conversation);
+                accepted.push(ConversationAutoAccepted {
+                    identity_i_address: identity_i_address.to_string(),
+                    conversation_id: message.sender.clone(),
+                    gift_txid: message.id.clone(),
+                    amount: message.amount,
+                });
+            }
+            Err(e) => log::warn!("Could not auto-accept {}: identity lookup failed: {:?}", message.sender, e),
+        }
+    }
+
+    if accepted.is_empty() {
+        return;
+    }
+    // The lookups took a while; add to the list as it is stored now
+    let result = update_conversations(app, identity_i_address, |conversations| {
+        for conversation in added {
+            if !conversations.iter().any(|c| c.id == conversation.id) {
+                conversations.push(conversation);
+            }
+        }
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to save auto-accepted conversations: {}", e);
+        return;
+    }
+    for event in accepted {
+        emit_event(app, CONVERSATION_AUTO_ACCEPTED_EVENT, event);
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub async fn get_message_requests<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+) -> Result<Vec<MessageRequest>, SpamError> {
+    log::debug!("get_message_requests command received for {}", identity_i_address);
+    let mut requests = read_requests(&app, &identity_i_address)?;
+    // Held before the sender was blocked; unknown senders are listed by get_unknown_sender_requests
+    requests.retain(|r| !is_blocked(&r.message.sender) && r.reason != SpamReason::UnknownSender);
+    Ok(requests)
+}
+
+// Messages held by contacts-only mode, grouped by sender (most recent first)
+#[tauri::command]
+pub async fn get_unknown_sender_requests<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+) -> Result<Vec<UnknownSenderRequests>, SpamError> {
+    log::debug!("get_unknown_sender_requests command received for {}", identity_i_address);
+    let mut by_sender: HashMap<String, Vec<MessageRequest>> = HashMap::new();
+    for request in read_requests(&app, &identity_i_address)? {
+        if request.reason == SpamReason::UnknownSender && !is_blocked(&request.message.sender) {
+            by_sender.entry(request.message.sender.clone()).or_default().push(request);
+        }
+    }
+
+    let mut senders: Vec<UnknownSenderRequests> = by_sender
+        .into_iter()
+        .map(|(sender, requests)| UnknownSenderRequests {
+            latest_filtered_at: requests.iter().map(|r| r.filtered_at).max().unwrap_or(0),
+            message_count: requests.len(),
+            sender,
+            requests,
+        })
+        .collect();
+    senders.sort_by_key(|s| std::cmp::Reverse(s.latest_filtered_at));
+    Ok(senders)
+}
+
+// Move a held message into its sender's conversation
+#[tauri::command]
+pub async fn accept_message_request<R: Runtime>(
+    app: AppHandle<R>,
+    store: State<'_, MessageStore>,
+    identity_i_address: String,
+    message_id: String,
+) -> Result<ChatMessage, SpamError> {
+    log::info!("accept_message_request command received for {} (user {})", message_id, identity_i_address);
+    let mut requests = read_requests(&app, &identity_i_address)?;
+    let position = requests
+        .iter()
+        .position(|r| r.message.id == message_id)
+        .ok_or_else(|| SpamError::NotFound(message_id.clone()))?;
+    let request = requests.remove(position);
+
+    store.merge(&app, &identity_i_address, &request.message.sender, vec![request.message.clone()])?;
+    write_requests(&app, &identity_i_address, &requests)?;
+    Ok(request.message)
+}
+
+#[tauri::command]
+pub async fn dismiss_message_request<R: Runtime>(
+    app: AppHandle<R>,
+    identity_i_address: String,
+    message_id: String,
+) -> Result<(), SpamError> {
+    log::info!("dismiss_message_request command received for {} (user {})", message_id, identity_i_address);
+    let mut requests = read_requests(&app, &identity_i_address)?;
+    let count = requests.len();
+    requests.retain(|r| r.message.id != message_id);
+    if requests.len() == count {
+        return Err(SpamError::NotFound(message_id));
+    }
+    write_requests(&app, &identity_i_address, &requests)
+}
@@ -0,0 +1,392 @@
+// File: src-tauri/src/ssh_tunnel.rs
+// Description: Optional SSH tunnel to a daemon on another machine (e.g. a headless server).
+// Changes:
+// - Created file. The system ssh client forwards a local port to the remote daemon's RPC port, so RPC calls keep
+//   going to localhost and the RPC port never has to be exposed. The tunnel config is persisted; make_rpc_call
+//   (re)establishes the tunnel before calls to its local port, and it is closed when the app exits.
+// - TunnelError goes into the error log when a command returns it.
+// - The server's host key is pinned: SshTunnelConfig requires host_key_fingerprint, the key matching it is written to
+//   the tunnel's own known_hosts file and ssh runs with StrictHostKeyChecking=yes against that file only.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use super::error_log::recorded_command_error;
+use super::storage::{load_value, save_value, StorageError};
+
+const TUNNEL_STORE_PATH: &str = "store.json";
+const TUNNEL_CONFIG_KEY: &str = "ssh_tunnel";
+
+const DEFAULT_SSH_PORT: u16 = 22;
+
+// Name the server's host key is stored under in the tunnel's own known_hosts file (in the app data dir)
+const HOST_KEY_ALIAS: &str = "nymia-ssh-tunnel";
+const KNOWN_HOSTS_FILE: &str = "ssh_known_hosts";
+const KEYSCAN_TIMEOUT_SECS: u64 = 10;
+
+// How long ssh gets to connect and open the forward
+const READY_TIMEOUT: Duration = Duration::from_secs(15);
+const READY_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SshTunnelConfig {
+    pub user: String,
+    pub host: String,
+    #[serde(default)]
+    pub ssh_port: Option<u16>,   // Default 22
+    #[serde(default)]
+    pub key_path: Option<String>, // Private key file; None uses the ssh agent / default keys
+    pub remote_rpc_port: u16,    // Daemon RPC port on the server
+    #[serde(default)]
+    pub local_port: Option<u16>, // Port RPC calls are made to; defaults to remote_rpc_port
+    // Required. The server's host key fingerprint, e.g. "SHA256:..." from `ssh-keygen -lf /etc/ssh/ssh_host_ed25519_key.pub`
+    // on the server. Defaulted only so configs saved before it existed still load (and are refused until it is set).
+    #[serde(default)]
+    pub host_key_fingerprint: String,
+}
+
+impl SshTunnelConfig {
+    pub fn effective_local_port(&self) -> u16 {
+        self.local_port.unwrap_or(self.remote_rpc_port)
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SshTunnelStatus {
+    pub config: Option<SshTunnelConfig>,
+    pub running: bool,
+    pub local_port: Option<u16>, // Port to save in the credentials
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum TunnelError {
+    #[error("Invalid SSH tunnel setting: {0}")]
+    InvalidConfig(String),
+    #[error("Local port {0} is already in use")]
+    PortInUse(u16),
+    #[error("Failed to start ssh: {0}")]
+    SpawnFailed(String),
+    #[error("ssh exited: {0}")]
+    Exited(String),
+    #[error("SSH tunnel not ready after {0} seconds")]
+    NotReady(u64),
+    #[error("SSH host key not verified: {0}")]
+    HostKey(String),
+    #[error("Storage error: {0}")]
+    Storage(String),
+}
+
+recorded_command_error!(TunnelError, "ssh_tunnel");
+
+impl From<StorageError> for TunnelError {
+    fn from(error: StorageError) -> Self {
+        TunnelError::Storage(error.to_string())
+    }
+}
+
+// Mirrors the persisted config so RPC calls don't need the store
+static CONFIG: Mutex<Option<SshTunnelConfig>> = Mutex::new(None);
+
+// Running ssh process
+static TUNNEL: Mutex<Option<Child>> = Mutex::new(None);
+
+// The tunnel's known_hosts file; set when the config is loaded
+static KNOWN_HOSTS_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// Held while a tunnel is being established, so concurrent calls don't each start one
+static ESTABLISHING: LazyLock<tokio::sync::Mutex<()>> = LazyLock::new(|| tokio::sync::Mutex::new(()));
+
+fn current_config() -> Option<SshTunnelConfig> {
+    CONFIG.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn is_running() -> bool {
+    let mut tunnel = TUNNEL.lock().unwrap_or_else(|e| e.into_inner());
+    match tunnel.as_mut().map(|child| child.try_wait()) {
+        Some(Ok(None)) => true,
+        Some(Ok(Some(status))) => {
+            log::warn!("SSH tunnel exited ({})", status);
+            *tunnel = None;
+            false
+        }
+        Some(Err(e)) => {
+            log::warn!("Failed to check SSH tunnel: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// User and host end up in ssh's arguments; refuse anything that could be read as an option
+fn validate(config: &SshTunnelConfig) -> Result<(), TunnelError> {
+    let is_plain = |value: &str| !value.is_empty() && !value.starts_with('-') && !value.contains(|c: char| c.is_whitespace() || c == '@');
+    if !is_plain(&config.user) {
+        return Err(TunnelError::InvalidConfig(format!("user '{}'", config.user)));
+    }
+    if !is_plain(&config.host) {
+        return Err(TunnelError::InvalidConfig(format!("host '{}'", config.host)));
+    }
+    let fingerprint = config.host_key_fingerprint.trim();
+    if !fingerprint.starts_with("SHA256:") || fingerprint.len() <= "SHA256:".len() || fingerprint.contains(char::is_whitespace) {
+        return Err(TunnelError::InvalidConfig("host key fingerprint (SHA256:...) is required".to_string()));
+    }
+    if config.remote_rpc_port == 0 || config.local_port == Some(0) || config.ssh_port == Some(0) {
+        return Err(TunnelError::InvalidConfig("ports must be above 0".to_string()));
+    }
+    if let Some(key_path) = &config.key_path {
+        if !std::path::Path::new(key_path).is_file() {
+            return Err(TunnelError::InvalidConfig(format!("key file {} not found", key_path)));
+        }
+    }
+    Ok(())
+}
+
+fn known_hosts_path() -> Result<PathBuf, TunnelError> {
+    KNOWN_HOSTS_PATH
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .ok_or_else(|| TunnelError::HostKey("no app data directory for the known_hosts file".to_string()))
+}
+
+// Fingerprint of one public key, as ssh-keygen prints it ("256 SHA256:... comment (ED25519)")
+async fn key_fingerprint(key_type: &str, key: &str) -> Result<String, TunnelError> {
+    let mut child = Command::new("ssh-keygen")
+        .args(["-l", "-E", "sha256", "-f", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| TunnelError::SpawnFailed(format!("ssh-keygen: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(format!("{} {}\n", key_type, key).as_bytes())
+            .await
+            .map_err(|e| TunnelError::SpawnFailed(format!("ssh-keygen: {}", e)))?;
+    }
+    let output = child.wait_with_output().await.map_err(|e| TunnelError::SpawnFailed(format!("ssh-keygen: {}", e)))?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .nth(1)
+        .map(str::to_string)
+        .ok_or_else(|| TunnelError::HostKey(format!("could not fingerprint the server's {} key", key_type)))
+}
+
+// Fetch the server's host keys and keep the one(s) matching the configured fingerprint, as known_hosts lines.
+// ssh then only accepts that key (StrictHostKeyChecking=yes against this file), so a server that can't present it
+// is refused instead of being trusted on first use.
+async fn verified_known_hosts(config: &SshTunnelConfig) -> Result<String, TunnelError> {
+    let output = Command::new("ssh-keyscan")
+        .args(["-T", &KEYSCAN_TIMEOUT_SECS.to_string()])
+        .args(["-p", &config.ssh_port.unwrap_or(DEFAULT_SSH_PORT).to_string()])
+        .arg(&config.host)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| TunnelError::SpawnFailed(format!("ssh-keyscan: {}", e)))?;
+    let scanned = String::from_utf8_lossy(&output.stdout);
+    // "host key-type base64-key"
+    let keys: Vec<(&str, &str)> = scanned
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            Some((fields.next()?, fields.next()?))
+        })
+        .collect();
+    if keys.is_empty() {
+        return Err(TunnelError::HostKey(format!("no host keys received from {}", config.host)));
+    }
+    let expected = config.host_key_fingerprint.trim();
+    let mut known_hosts = String::new();
+    for (key_type, key) in keys {
+        if key_fingerprint(key_type, key).await? == expected {
+            known_hosts.push_str(&format!("{} {} {}\n", HOST_KEY_ALIAS, key_type, key));
+        }
+    }
+    if known_hosts.is_empty() {
+        log::error!("No host key of {} matches the configured fingerprint {}", config.host, expected);
+        return Err(TunnelError::HostKey(format!("{} did not present a host key with fingerprint {}", config.host, expected)));
+    }
+    Ok(known_hosts)
+}
+
+fn ssh_command(config: &SshTunnelConfig, known_hosts: &std::path::Path) -> Command {
+    // Both known_hosts files point at ours, so keys trusted elsewhere on the machine don't count
+    let known_hosts = known_hosts.display().to_string().replace('"', "");
+    let mut command = Command::new("ssh");
+    command
+        .arg("-N")
+        .args(["-o", "BatchMode=yes"]) // Never prompt; the app has no terminal
+        .args(["-o", "ExitOnForwardFailure=yes"])
+        .args(["-o", "ServerAliveInterval=30"])
+        .args(["-o", "StrictHostKeyChecking=yes"])
+        .args(["-o", "UpdateHostKeys=no"])
+        .args(["-o", &format!("HostKeyAlias={}", HOST_KEY_ALIAS)])
+        .args(["-o", &format!("UserKnownHostsFile=\"{}\"", known_hosts)])
+        .args(["-o", &format!("GlobalKnownHostsFile=\"{}\"", known_hosts)])
+        .args(["-p", &config.ssh_port.unwrap_or(DEFAULT_SSH_PORT).to_string()])
+        .args(["-L", &format!("127.0.0.1:{}:127.0.0.1:{}", config.effective_local_port(), config.remote_rpc_port)]);
+    if let Some(key_path) = &config.key_path {
+        command.args(["-i", key_path, "-o", "IdentitiesOnly=yes"]);
+    }
+    command
+        .arg(format!("{}@{}", config.user, config.host))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    command
+}
+
+fn stop_tunnel() {
+    if let Some(mut child) = TUNNEL.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        log::info!("Closing SSH tunnel");
+        if let Err(e) = child.start_kill() {
+            log::warn!("Failed to stop ssh: {}", e);
+        }
+    }
+}
+
+// Start ssh and wait until the local end of the forward accepts connections
+async fn establish(config: &SshTunnelConfig) -> Result<(), TunnelError> {
+    validate(config)?;
+    // The old ssh has to release the local port first
+    let previous = TUNNEL.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(mut child) = previous {
+        let _ = child.start_kill();
+        let _ = tokio::time::timeout(Duration::from_secs(2), child.wait()).await;
+    }
+    let local_port = config.effective_local_port();
+    // A daemon (or another tunnel) on the local port would answer in the tunnel's place
+    if std::net::TcpListener::bind(("127.0.0.1", local_port)).is_err() {
+        return Err(TunnelError::PortInUse(local_port));
+    }
+    let known_hosts_path = known_hosts_path()?;
+    let known_hosts = verified_known_hosts(config).await?;
+    if let Some(dir) = known_hosts_path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| TunnelError::HostKey(format!("failed to write known_hosts: {}", e)))?;
+    }
+    std::fs::write(&known_hosts_path, known_hosts).map_err(|e| TunnelError::HostKey(format!("failed to write known_hosts: {}", e)))?;
+
+    log::info!("Opening SSH tunnel to {}@{} (local port {} -> {})", config.user, config.host, local_port, config.remote_rpc_port);
+    let mut child = ssh_command(config, &known_hosts_path).spawn().map_err(|e| TunnelError::SpawnFailed(e.to_string()))?;
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| TunnelError::SpawnFailed(e.to_string()))? {
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr).await;
+            }
+            let reason = if stderr.trim().is_empty() { status.to_string() } else { stderr.trim().to_string() };
+            log::error!("SSH tunnel failed: {}", reason);
+            return Err(TunnelError::Exited(reason));
+        }
+        if tokio::net::TcpStream::connect(("127.0.0.1", local_port)).await.is_ok() {
+            break;
+        }
+        if started.elapsed() > READY_TIMEOUT {
+            let _ = child.start_kill();
+            return Err(TunnelError::NotReady(READY_TIMEOUT.as_secs()));
+        }
+        tokio::time::sleep(READY_CHECK_INTERVAL).await;
+    }
+    log::info!("SSH tunnel ready after {:?}", started.elapsed());
+    // Keep reading ssh's messages (e.g. connection drops) so the pipe never fills up
+    if let Some(pipe) = child.stderr.take() {
+        tauri::async_runtime::spawn(async move {
+            let mut lines = BufReader::new(pipe).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                log::warn!("ssh: {}", line);
+            }
+        });
+    }
+    *TUNNEL.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+    Ok(())
+}
+
+// Called before RPC calls: (re)opens the configured tunnel when the call goes to its local port and ssh isn't
+// running (not started yet, or the connection dropped)
+pub async fn ensure_tunnel(rpc_port: u16) -> Result<(), TunnelError> {
+    let Some(config) = current_config().filter(|config| config.effective_local_port() == rpc_port) else {
+        return Ok(());
+    };
+    if is_running() {
+        return Ok(());
+    }
+    let _guard = ESTABLISHING.lock().await;
+    if is_running() {
+        return Ok(());
+    }
+    establish(&config).await
+}
+
+pub fn load_ssh_tunnel_config<R: Runtime>(app: &AppHandle<R>) {
+    match app.path().app_data_dir() {
+        Ok(dir) => *KNOWN_HOSTS_PATH.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir.join(KNOWN_HOSTS_FILE)),
+        Err(e) => log::warn!("No app data directory for the SSH tunnel's known_hosts: {}", e),
+    }
+    match load_value::<R, Option<SshTunnelConfig>>(app, TUNNEL_STORE_PATH, TUNNEL_CONFIG_KEY) {
+        Ok(config) => *CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = config.flatten(),
+        Err(e) => log::warn!("Failed to load SSH tunnel config: {}", e),
+    }
+}
+
+// App exit: ssh would otherwise outlive the app
+pub fn shutdown_ssh_tunnel() {
+    stop_tunnel();
+}
+
+fn status() -> SshTunnelStatus {
+    let config = current_config();
+    SshTunnelStatus {
+        local_port: config.as_ref().map(SshTunnelConfig::effective_local_port),
+        running: is_running(),
+        config,
+    }
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_ssh_tunnel() -> SshTunnelStatus {
+    log::debug!("get_ssh_tunnel command received");
+    status()
+}
+
+// Save a tunnel config and open the tunnel (the config is kept even if connecting fails, so the error can be
+// fixed on the server side and retried). None removes the tunnel.
+#[tauri::command]
+pub async fn set_ssh_tunnel<R: Runtime>(app: AppHandle<R>, config: Option<SshTunnelConfig>) -> Result<SshTunnelStatus, TunnelError> {
+    log::info!("set_ssh_tunnel command received");
+    if let Some(config) = &config {
+        validate(config)?;
+    }
+    let _guard = ESTABLISHING.lock().await;
+    save_value(&app, TUNNEL_STORE_PATH, TUNNEL_CONFIG_KEY, &config)?;
+    *CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = config.clone();
+    match config {
+        Some(config) => establish(&config).await?,
+        None => stop_tunnel(),
+    }
+    Ok(status())
+}
+
+// Reconnect the configured tunnel now (e.g. after the server rebooted)
+#[tauri::command]
+pub async fn restart_ssh_tunnel() -> Result<SshTunnelStatus, TunnelError> {
+    log::info!("restart_ssh_tunnel command received");
+    let config = current_config().ok_or_else(|| TunnelError::InvalidConfig("no SSH tunnel configured".to_string()))?;
+    let _guard = ESTABLISHING.lock().await;
+    establish(&config).await?;
+    Ok(status())
+}
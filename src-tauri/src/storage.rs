@@ -0,0 +1,58 @@
+// File: src-tauri/src/storage.rs
+// Description: Generic typed persistence helpers on top of tauri-plugin-store.
+// Changes:
+// - Created file with load_value / save_value helpers and StorageError.
+// - StorageError goes into the error log when a command returns it.
+
+use serde::{de::DeserializeOwned, Serialize};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_store::{StoreExt, Error as StoreError};
+use super::error_log::recorded_command_error;
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum StorageError {
+    #[error("Store plugin error: {0}")]
+    Store(String),
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+    #[error("Deserialization error: {0}")]
+    Deserialization(String),
+}
+
+recorded_command_error!(StorageError, "storage");
+
+impl From<StoreError> for StorageError {
+    fn from(error: StoreError) -> Self {
+        StorageError::Store(error.to_string())
+    }
+}
+
+// Load and deserialize a value. Returns Ok(None) when the key doesn't exist.
+pub fn load_value<R: Runtime, T: DeserializeOwned>(
+    app: &AppHandle<R>,
+    store_path: &str,
+    key: &str,
+) -> Result<Option<T>, StorageError> {
+    let store = app.store(store_path)?;
+    match store.get(key) {
+        Some(value) => serde_json::from_value::<T>(value)
+            .map(Some)
+            .map_err(|e| StorageError::Deserialization(format!("Failed to parse '{}': {}", key, e))),
+        None => Ok(None),
+    }
+}
+
+// Serialize and save a value, flushing the store file to disk
+pub fn save_value<R: Runtime, T: Serialize>(
+    app: &AppHandle<R>,
+    store_path: &str,
+    key: &str,
+    value: &T,
+) -> Result<(), StorageError> {
+    let store = app.store(store_path)?;
+    let json = serde_json::to_value(value).map_err(|e| StorageError::Serialization(e.to_string()))?;
+    store.set(key, json);
+    store.save()?;
+    Ok(())
+}
@@ -0,0 +1,142 @@
+// File: src-tauri/src/sync_status.rs
+// Description: Chain sync progress for the onboarding screen.
+// Changes:
+// - Created file. get_sync_status reads getblockchaininfo (blocks vs headers, verification progress) and estimates
+//   the time remaining from the block rate seen between samples. A background watcher emits sync-progress events
+//   until the daemon has caught up or the watcher is stopped.
+// - Sync status reports the block height it sees to the response cache (new blocks invalidate it)
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use super::events::{emit_event, SYNC_PROGRESS_EVENT};
+use super::network_rpc::SYNCED_VERIFICATION_PROGRESS;
+use super::response_cache::observe_tip_height;
+use super::rpc_client::{make_background_rpc_call, VerusRpcError};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+// Weight of the newest sample in the smoothed block rate
+const RATE_SMOOTHING: f64 = 0.3;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SyncStatus {
+    pub blocks: u64,
+    pub headers: u64,
+    pub blocks_remaining: u64,
+    pub verification_progress: f64, // 0.0 - 1.0
+    pub blocks_per_second: Option<f64>, // Smoothed; None until two samples were taken
+    pub estimated_seconds_remaining: Option<u64>,
+    pub synced: bool,
+}
+
+struct RateSample {
+    at: Instant,
+    blocks: u64,
+    blocks_per_second: Option<f64>,
+}
+
+// Last sample per RPC port, for the block rate
+static SAMPLES: LazyLock<Mutex<HashMap<u16, RateSample>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// RPC port -> generation of the running watcher; bumping it stops the old one
+static WATCHERS: LazyLock<Mutex<HashMap<u16, u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn update_rate(rpc_port: u16, blocks: u64) -> Option<f64> {
+    let mut samples = SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let previous = samples.get(&rpc_port);
+    let rate = match previous {
+        Some(sample) if blocks >= sample.blocks && now.duration_since(sample.at).as_secs_f64() >= 1.0 => {
+            let current = (blocks - sample.blocks) as f64 / now.duration_since(sample.at).as_secs_f64();
+            Some(match sample.blocks_per_second {
+                Some(smoothed) => smoothed * (1.0 - RATE_SMOOTHING) + current * RATE_SMOOTHING,
+                None => current,
+            })
+        }
+        // Too soon for a new measurement; keep the last rate and sample
+        Some(sample) if blocks >= sample.blocks => return sample.blocks_per_second,
+        _ => None, // First sample, or the daemon restarted with fewer blocks
+    };
+    samples.insert(rpc_port, RateSample { at: now, blocks, blocks_per_second: rate });
+    rate
+}
+
+pub async fn get_sync_status(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Result<SyncStatus, VerusRpcError> {
+    let info: Value = make_background_rpc_call(rpc_user, rpc_pass, rpc_port, "getblockchaininfo", vec![]).await?;
+    let blocks = info.get("blocks").and_then(|v| v.as_u64()).unwrap_or(0);
+    if blocks > 0 {
+        observe_tip_height(rpc_port, blocks);
+    }
+    let headers = info.get("headers").and_then(|v| v.as_u64()).unwrap_or(0);
+    let verification_progress = info.get("verificationprogress").and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let blocks_remaining = headers.saturating_sub(blocks);
+    let synced = headers > 0 && blocks >= headers && verification_progress >= SYNCED_VERIFICATION_PROGRESS;
+
+    let blocks_per_second = update_rate(rpc_port, blocks);
+    let estimated_seconds_remaining = if synced {
+        Some(0)
+    } else {
+        blocks_per_second.filter(|rate| *rate > 0.0).map(|rate| (blocks_remaining as f64 / rate).ceil() as u64)
+    };
+    Ok(SyncStatus {
+        blocks,
+        headers,
+        blocks_remaining,
+        verification_progress,
+        blocks_per_second,
+        estimated_seconds_remaining,
+        synced,
+    })
+}
+
+// Emit sync-progress events until the daemon is synced. Starting again replaces a running watcher.
+pub fn start_sync_watcher<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16) {
+    let generation = {
+        let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = watchers.get(&rpc_port).copied().unwrap_or(0) + 1;
+        watchers.insert(rpc_port, generation);
+        generation
+    };
+    let is_current = move || WATCHERS.lock().unwrap_or_else(|e| e.into_inner()).get(&rpc_port) == Some(&generation);
+    log::info!("Starting sync watcher for port {}", rpc_port);
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        while is_current() {
+            match get_sync_status(&rpc_user, &rpc_pass, rpc_port).await {
+                Ok(status) => {
+                    let synced = status.synced;
+                    emit_event(&app, SYNC_PROGRESS_EVENT, status);
+                    if synced {
+                        log::info!("Daemon on port {} is synced, stopping sync watcher", rpc_port);
+                        break;
+                    }
+                }
+                // The daemon may still be starting up (loading the block index); keep trying
+                Err(e) => log::debug!("Sync status unavailable: {:?}", e),
+            }
+            tokio::time::sleep(WATCH_INTERVAL).await;
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+// Stop the sync watcher (e.g. when the onboarding screen is left)
+#[tauri::command]
+pub fn stop_sync_watcher(rpc_port: Option<u16>) {
+    log::info!("stop_sync_watcher command received");
+    let mut watchers = WATCHERS.lock().unwrap_or_else(|e| e.into_inner());
+    match rpc_port {
+        Some(port) => {
+            if let Some(generation) = watchers.get_mut(&port) {
+                *generation += 1;
+            }
+        }
+        None => watchers.values_mut().for_each(|generation| *generation += 1),
+    }
+}
@@ -0,0 +1,150 @@
+// File: src-tauri/src/tasks.rs
+// Description: Registry of cancellable long-running backend tasks.
+// Changes:
+// - Created file with TaskRegistry (managed state), run_cancellable helper and cancel_task command.
+// - Emits task lifecycle events (started, completed, cancelled, failed) via the events module.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, State};
+use tokio_util::sync::CancellationToken;
+use super::events::{emit_event, TASK_LIFECYCLE_EVENT};
+
+#[derive(Debug, thiserror::Error, Serialize)]
+pub enum TaskError {
+    #[error("Task {0} was cancelled")]
+    Cancelled(String),
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskState {
+    Started,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TaskLifecycleEvent {
+    pub task_id: String,
+    pub kind: String, // e.g., "detection", "history", "balance"
+    pub state: TaskState,
+    pub error: Option<String>,
+}
+
+struct RegisteredTask {
+    generation: u64,
+    token: CancellationToken,
+}
+
+// Managed state tracking running tasks by frontend-supplied id
+#[derive(Default)]
+pub struct TaskRegistry {
+    tasks: Mutex<HashMap<String, RegisteredTask>>,
+    next_generation: Mutex<u64>,
+}
+
+impl TaskRegistry {
+    // Register a task, cancelling any previous task that used the same id
+    fn register(&self, task_id: &str) -> (u64, CancellationToken) {
+        let generation = {
+            let mut next = self.next_generation.lock().unwrap_or_else(|e| e.into_inner());
+            *next += 1;
+            *next
+        };
+        let token = CancellationToken::new();
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = tasks.insert(task_id.to_string(), RegisteredTask { generation, token: token.clone() }) {
+            log::info!("Task id {} reused, cancelling previous run", task_id);
+            previous.token.cancel();
+        }
+        (generation, token)
+    }
+
+    // Remove a finished task (only if it was not replaced in the meantime)
+    fn finish(&self, task_id: &str, generation: u64) {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if tasks.get(task_id).map(|t| t.generation) == Some(generation) {
+            tasks.remove(task_id);
+        }
+    }
+
+    // Cancel a running task. Returns false if no task with this id is running.
+    pub fn cancel(&self, task_id: &str) -> bool {
+        let tasks = self.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        match tasks.get(task_id) {
+            Some(task) => {
+                task.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn emit_lifecycle<R: Runtime>(app: &AppHandle<R>, task_id: &str, kind: &str, state: TaskState, error: Option<String>) {
+    emit_event(app, TASK_LIFECYCLE_EVENT, TaskLifecycleEvent {
+        task_id: task_id.to_string(),
+        kind: kind.to_string(),
+        state,
+        error,
+    });
+}
+
+// Run a future as a cancellable task when a task_id is supplied.
+// Without a task_id the future simply runs to completion (backwards compatible).
+pub async fn run_cancellable<R, T, E, F>(
+    app: &AppHandle<R>,
+    task_id: Option<String>,
+    kind: &str,
+    future: F,
+) -> Result<T, E>
+where
+    R: Runtime,
+    E: From<TaskError> + Display,
+    F: Future<Output = Result<T, E>>,
+{
+    let Some(task_id) = task_id else {
+        return future.await;
+    };
+
+    let registry = app.state::<TaskRegistry>();
+    let (generation, token) = registry.register(&task_id);
+    log::debug!("Task {} ({}) started", task_id, kind);
+    emit_lifecycle(app, &task_id, kind, TaskState::Started, None);
+
+    // Dropping the future on cancellation aborts any in-flight RPC requests
+    let result = tokio::select! {
+        _ = token.cancelled() => Err(E::from(TaskError::Cancelled(task_id.clone()))),
+        result = future => result,
+    };
+
+    registry.finish(&task_id, generation);
+
+    match &result {
+        Ok(_) => emit_lifecycle(app, &task_id, kind, TaskState::Completed, None),
+        Err(_) if token.is_cancelled() => {
+            log::info!("Task {} ({}) cancelled", task_id, kind);
+            emit_lifecycle(app, &task_id, kind, TaskState::Cancelled, None)
+        }
+        Err(e) => emit_lifecycle(app, &task_id, kind, TaskState::Failed, Some(e.to_string())),
+    }
+
+    result
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn cancel_task(registry: State<'_, TaskRegistry>, task_id: String) -> bool {
+    log::info!("cancel_task command received for {}", task_id);
+    let cancelled = registry.cancel(&task_id);
+    if !cancelled {
+        log::debug!("No running task with id {}", task_id);
+    }
+    cancelled
+}
@@ -0,0 +1,160 @@
+// File: src-tauri/src/transaction_rpc.rs
+// Description: Detail view of a single wallet transaction (message, gift, split, shielding...).
+// Changes:
+// - Created file with get_transaction_details. Confirmations, fee and block come from gettransaction, transparent
+//   inputs/outputs from decoding its hex, and shielded spends/outputs (with the memos our keys decrypt) from
+//   z_viewtransaction where the chain supports it.
+// - Transparent outputs list the currencies they carry (reserve outputs), by name instead of i-address.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use super::capabilities::{supports, Feature};
+use super::currency::currency_display_name;
+use super::memo_codec::decode_memo;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransparentInput {
+    pub prev_txid: Option<String>, // None for coinbase inputs
+    pub prev_vout: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransparentOutput {
+    pub index: u32,
+    pub value: f64,
+    pub addresses: Vec<String>,
+    pub currency_values: BTreeMap<String, f64>, // Other currencies carried by the output (currency name -> amount)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ShieldedSpend {
+    pub address: Option<String>,
+    pub value: f64,
+    pub prev_txid: Option<String>,
+    pub prev_output: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ShieldedOutput {
+    pub index: u32,
+    pub address: Option<String>,
+    pub value: f64,
+    pub outgoing: bool,       // Sent by us to someone else (decrypted with our outgoing viewing key)
+    pub memo: Option<String>, // Decoded memo text (decompressed if needed)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TransactionDetails {
+    pub txid: String,
+    pub confirmations: i64,
+    pub block_hash: Option<String>,
+    pub block_time: Option<u64>,
+    pub time: Option<u64>,     // When the wallet first saw the transaction
+    pub fee: Option<f64>,      // Only known for transactions we paid for
+    pub size_bytes: u64,
+    pub transparent_inputs: Vec<TransparentInput>,
+    pub transparent_outputs: Vec<TransparentOutput>,
+    pub shielded_spend_count: u32, // Spends/outputs in the transaction, including ones our keys can't see
+    pub shielded_output_count: u32,
+    pub shielded_spends: Vec<ShieldedSpend>,   // Only those our keys can decrypt
+    pub shielded_outputs: Vec<ShieldedOutput>,
+    pub shielded_visible: bool, // False if the chain lacks z_viewtransaction
+}
+
+fn as_u32(value: &Value, key: &str) -> Option<u32> {
+    value.get(key).and_then(|v| v.as_u64()).map(|v| v as u32)
+}
+
+fn as_string(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value.get(key).and_then(|v| v.as_array()).map(|a| a.as_slice()).unwrap_or_default()
+}
+
+// Details of a transaction in our wallet
+pub async fn get_transaction_details(rpc_user: String, rpc_pass: String, rpc_port: u16, txid: String) -> Result<TransactionDetails, VerusRpcError> {
+    log::info!("Fetching transaction details for {}", txid);
+    let tx: Value = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "gettransaction", vec![json!(txid)]).await?;
+    let hex = tx.get("hex").and_then(|v| v.as_str()).unwrap_or_default();
+    let decoded: Value = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "decoderawtransaction", vec![json!(hex)]).await?;
+
+    let transparent_inputs = array(&decoded, "vin")
+        .iter()
+        .map(|input| TransparentInput { prev_txid: as_string(input, "txid"), prev_vout: as_u32(input, "vout") })
+        .collect();
+    let mut transparent_outputs: Vec<TransparentOutput> = array(&decoded, "vout")
+        .iter()
+        .map(|output| TransparentOutput {
+            index: as_u32(output, "n").unwrap_or(0),
+            value: output.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            addresses: output
+                .pointer("/scriptPubKey/addresses")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default(),
+            currency_values: BTreeMap::new(),
+        })
+        .collect();
+    for (output, raw) in transparent_outputs.iter_mut().zip(array(&decoded, "vout")) {
+        let Some(values) = raw.pointer("/scriptPubKey/reserveoutput/currencyvalues").and_then(|v| v.as_object()) else { continue };
+        for (currency_id, amount) in values {
+            let Some(amount) = amount.as_f64() else { continue };
+            let name = currency_display_name(&rpc_user, &rpc_pass, rpc_port, currency_id).await;
+            *output.currency_values.entry(name).or_insert(0.0) += amount;
+        }
+    }
+
+    let mut details = TransactionDetails {
+        txid: txid.clone(),
+        confirmations: tx.get("confirmations").and_then(|v| v.as_i64()).unwrap_or(0),
+        block_hash: as_string(&tx, "blockhash"),
+        block_time: tx.get("blocktime").and_then(|v| v.as_u64()),
+        time: tx.get("time").and_then(|v| v.as_u64()),
+        // gettransaction reports our sends with a negative fee and has none for incoming transactions
+        fee: tx.get("fee").and_then(|v| v.as_f64()).map(f64::abs),
+        size_bytes: hex.len() as u64 / 2,
+        transparent_inputs,
+        transparent_outputs,
+        shielded_spend_count: array(&decoded, "vShieldedSpend").len() as u32,
+        shielded_output_count: array(&decoded, "vShieldedOutput").len() as u32,
+        shielded_spends: Vec::new(),
+        shielded_outputs: Vec::new(),
+        shielded_visible: false,
+    };
+
+    if !supports(rpc_port, Feature::ViewTransaction) {
+        return Ok(details);
+    }
+    let view: Value = match make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_viewtransaction", vec![json!(txid)]).await {
+        Ok(view) => view,
+        Err(e) => {
+            log::warn!("z_viewtransaction failed for {}, showing transparent details only: {:?}", txid, e);
+            return Ok(details);
+        }
+    };
+    details.shielded_visible = true;
+    details.shielded_spends = array(&view, "spends")
+        .iter()
+        .map(|spend| ShieldedSpend {
+            address: as_string(spend, "address"),
+            value: spend.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            prev_txid: as_string(spend, "txidPrev"),
+            prev_output: as_u32(spend, "outputPrev"),
+        })
+        .collect();
+    details.shielded_outputs = array(&view, "outputs")
+        .iter()
+        .map(|output| ShieldedOutput {
+            index: as_u32(output, "output").unwrap_or(0),
+            address: as_string(output, "address"),
+            value: output.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0),
+            outgoing: output.get("outgoing").and_then(|v| v.as_bool()).unwrap_or(false),
+            memo: decode_memo(output.get("memoStr").and_then(|v| v.as_str()), output.get("memo").and_then(|v| v.as_str())),
+        })
+        .collect();
+    Ok(details)
+}
@@ -0,0 +1,359 @@
+// File: src-tauri/src/utxo_maintenance.rs
+// Description: Keeps enough small notes around for Fast Messages by splitting a large note with a self-send.
+// Changes:
+// - Created file. When enabled for an address and its usable UTXO count (get_utxo_info) drops below the target,
+//   a self-send via sendcurrency creates split_count notes of split_amount each. Runs after polls (throttled,
+//   never while a previous split is unconfirmed) or on demand via run_utxo_maintenance.
+// - Operation waiting moved to wallet_rpc::wait_for_operation (shared with shielding).
+// - Added suggest_utxo_cleanup: a consolidation plan for dust notes (z_mergetoaddress batches, fees, and the split
+//   that restores Fast Messages capacity afterwards). Only a proposal; nothing is sent.
+// - run_maintenance_now (manual command) takes the same per-address running guard as the background check, so
+//   the two can't split the same address at once.
+// - UtxoMaintenanceError goes into the error log when a command returns it.
+// - Timestamps come from clock::now_secs (the local helper is gone).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Runtime};
+use super::capabilities::{supports, Feature};
+use super::error_log::recorded_command_error;
+use super::events::{emit_event, UTXO_SPLIT_EVENT};
+use super::message_rpc::DEFAULT_TX_FEE;
+use super::notes::read_reservations;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::storage::{load_value, save_value, StorageError};
+use super::wallet_rpc::{get_utxo_info, list_notes, wait_for_operation, NoteReservation, DUST_THRESHOLD};
+use super::clock::now_secs;
+
+const SPLIT_STORE_PATH: &str = "utxo_maintenance.json";
+
+// Smallest note get_utxo_info counts as usable
+const MIN_SPLIT_AMOUNT: f64 = DUST_THRESHOLD;
+const MAX_SPLIT_COUNT: u32 = 20;
+
+// Background checks after polls run at most this often per address
+const CHECK_INTERVAL_SECS: u64 = 10 * 60;
+
+// Sapling notes z_mergetoaddress spends per transaction by default
+const MERGE_NOTE_LIMIT: usize = 90;
+
+// Cleanup is only suggested once dust is worth a transaction
+const MIN_DUST_NOTES_FOR_CLEANUP: u32 = 10;
+
+// sendcurrency runs as an async operation; its result is awaited for this long
+const SPLIT_OPERATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UtxoSplitSettings {
+    pub enabled: bool,
+    pub target_usable_utxos: u32, // Split when fewer usable notes are left
+    pub split_count: u32,         // Notes created per split
+    pub split_amount: f64,        // Value of each new note
+}
+
+impl Default for UtxoSplitSettings {
+    fn default() -> Self {
+        UtxoSplitSettings { enabled: false, target_usable_utxos: 5, split_count: 5, split_amount: 0.001 }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UtxoSplitOutcome {
+    pub address: String,
+    pub usable_utxos: u32,
+    pub target_usable_utxos: u32,
+    pub split_txid: Option<String>,
+    pub skipped_reason: Option<String>, // Why no split was made (enough notes, pending split, no large note...)
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum UtxoMaintenanceError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error("Invalid split settings: {0}")]
+    InvalidSettings(String),
+    #[error("UTXO maintenance is already running for {0}")]
+    AlreadyRunning(String),
+}
+
+recorded_command_error!(UtxoMaintenanceError, "utxo_maintenance");
+
+impl From<VerusRpcError> for UtxoMaintenanceError {
+    fn from(error: VerusRpcError) -> Self {
+        UtxoMaintenanceError::Rpc(error)
+    }
+}
+
+impl From<StorageError> for UtxoMaintenanceError {
+    fn from(error: StorageError) -> Self {
+        UtxoMaintenanceError::Storage(error.to_string())
+    }
+}
+
+#[derive(Default)]
+struct MaintenanceState {
+    last_check: u64,
+    last_split_txid: Option<String>,
+    running: bool,
+}
+
+// Address -> maintenance state (in memory)
+static STATE: LazyLock<Mutex<HashMap<String, MaintenanceState>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn read_split_settings<R: Runtime>(app: &AppHandle<R>, address: &str) -> UtxoSplitSettings {
+    load_value(app, SPLIT_STORE_PATH, address)
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to read UTXO split settings for {}: {}", address, e);
+            None
+        })
+        .unwrap_or_default()
+}
+
+fn validate_settings(settings: &UtxoSplitSettings) -> Result<(), UtxoMaintenanceError> {
+    if !(2..=MAX_SPLIT_COUNT).contains(&settings.split_count) {
+        return Err(UtxoMaintenanceError::InvalidSettings(format!("split count must be 2 to {}", MAX_SPLIT_COUNT)));
+    }
+    if !settings.split_amount.is_finite() || settings.split_amount < MIN_SPLIT_AMOUNT {
+        return Err(UtxoMaintenanceError::InvalidSettings(format!("split amount must be at least {}", MIN_SPLIT_AMOUNT)));
+    }
+    if settings.target_usable_utxos == 0 {
+        return Err(UtxoMaintenanceError::InvalidSettings("target must be at least 1".to_string()));
+    }
+    Ok(())
+}
+
+// Mark maintenance of an address as running. With a throttle, a check within CHECK_INTERVAL_SECS of the last one
+// is refused too. Returns false if it can't start.
+fn try_start(address: &str, throttle: bool) -> bool {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let entry = state.entry(address.to_string()).or_default();
+    let now = now_secs();
+    if entry.running || (throttle && now.saturating_sub(entry.last_check) < CHECK_INTERVAL_SECS) {
+        return false;
+    }
+    entry.running = true;
+    entry.last_check = now;
+    true
+}
+
+fn finish(address: &str) {
+    if let Some(entry) = STATE.lock().unwrap_or_else(|e| e.into_inner()).get_mut(address) {
+        entry.running = false;
+    }
+}
+
+// Check the address and split a large note if Fast Messages capacity is below the target
+async fn maintain_capacity<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    address: &str,
+    settings: &UtxoSplitSettings,
+) -> Result<UtxoSplitOutcome, UtxoMaintenanceError> {
+    validate_settings(settings)?;
+    let info = get_utxo_info(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, address.to_string()).await?;
+    let mut outcome = UtxoSplitOutcome {
+        address: address.to_string(),
+        usable_utxos: info.usable_utxos,
+        target_usable_utxos: settings.target_usable_utxos,
+        split_txid: None,
+        skipped_reason: None,
+    };
+    if info.usable_utxos >= settings.target_usable_utxos {
+        outcome.skipped_reason = Some("enough usable notes".to_string());
+        return Ok(outcome);
+    }
+
+    // The notes of an unconfirmed split don't count as usable yet; splitting again would only lock up more funds
+    let notes = list_notes(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, address.to_string()).await?;
+    let last_split = STATE.lock().unwrap_or_else(|e| e.into_inner()).get(address).and_then(|s| s.last_split_txid.clone());
+    if let Some(txid) = last_split {
+        if notes.iter().any(|n| n.txid == txid && n.confirmations == 0) {
+            outcome.skipped_reason = Some(format!("previous split {} is unconfirmed", txid));
+            return Ok(outcome);
+        }
+    }
+
+    // The daemon selects the notes itself; this only checks that a suitable one exists (savings notes don't count)
+    let reservations = read_reservations(app, address).unwrap_or_else(|e| {
+        log::warn!("Failed to read note reservations for {}: {}", address, e);
+        HashMap::new()
+    });
+    let needed = settings.split_amount * settings.split_count as f64 + DEFAULT_TX_FEE;
+    let has_source = notes.iter().any(|n| {
+        n.spendable
+            && n.confirmations >= 1
+            && n.amount > needed
+            && reservations.get(&format!("{}:{}", n.txid, n.outindex)) != Some(&NoteReservation::Savings)
+    });
+    if !has_source {
+        outcome.skipped_reason = Some(format!("no spendable note larger than {}", needed));
+        return Ok(outcome);
+    }
+
+    log::info!(
+        "Splitting a note of {} into {} x {} ({} usable notes, target {})",
+        address, settings.split_count, settings.split_amount, info.usable_utxos, settings.target_usable_utxos
+    );
+    // z_sendmany rejects duplicate recipients; sendcurrency accepts several outputs to the same address
+    let outputs: Vec<Value> = (0..settings.split_count)
+        .map(|_| json!({ "address": address, "amount": settings.split_amount }))
+        .collect();
+    let opid: String = make_rpc_call(rpc_user, rpc_pass, rpc_port, "sendcurrency", vec![json!(address), json!(outputs), json!(1)]).await?;
+    let txid = wait_for_operation(rpc_user, rpc_pass, rpc_port, &opid, SPLIT_OPERATION_TIMEOUT).await?;
+    log::info!("UTXO split sent in {}", txid);
+
+    STATE.lock().unwrap_or_else(|e| e.into_inner()).entry(address.to_string()).or_default().last_split_txid = Some(txid.clone());
+    outcome.split_txid = Some(txid);
+    Ok(outcome)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UtxoCleanupPlan {
+    pub address: String,
+    pub dust_utxos: u32,
+    pub dust_value: f64,
+    pub usable_utxos: u32,
+    pub notes_merged: usize,  // z_mergetoaddress merges every spendable note, not just the dust
+    pub transactions: usize,  // Merge transactions needed (note limit per transaction)
+    pub total_fee: f64,
+    pub savings_notes_affected: usize, // Reserved notes that would be merged too
+    pub recommended: bool,
+    pub reasons: Vec<String>,
+    pub resplit: Option<UtxoSplitSettings>, // Split restoring the current Fast Messages capacity afterwards
+}
+
+// Propose how to get rid of dust notes. The daemon can't be told to merge only the dust, so the plan merges all
+// spendable notes of the address and re-splits afterwards.
+pub async fn suggest_utxo_cleanup<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    address: &str,
+) -> Result<UtxoCleanupPlan, UtxoMaintenanceError> {
+    let info = get_utxo_info(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, address.to_string()).await?;
+    let notes = list_notes(rpc_user.to_string(), rpc_pass.to_string(), rpc_port, address.to_string()).await?;
+    let reservations = read_reservations(app, address).unwrap_or_else(|e| {
+        log::warn!("Failed to read note reservations for {}: {}", address, e);
+        HashMap::new()
+    });
+
+    let mergeable: Vec<_> = notes.iter().filter(|n| n.spendable && n.confirmations >= 1).collect();
+    let savings_notes_affected = mergeable
+        .iter()
+        .filter(|n| reservations.get(&format!("{}:{}", n.txid, n.outindex)) == Some(&NoteReservation::Savings))
+        .count();
+    let transactions = mergeable.len().div_ceil(MERGE_NOTE_LIMIT);
+    let total_fee = transactions as f64 * DEFAULT_TX_FEE;
+
+    let mut reasons = Vec::new();
+    let mut recommended = true;
+    if info.dust_utxos < MIN_DUST_NOTES_FOR_CLEANUP {
+        recommended = false;
+        reasons.push(format!("only {} dust notes; cleanup starts to pay off from {}", info.dust_utxos, MIN_DUST_NOTES_FOR_CLEANUP));
+    }
+    if info.total_spendable_value < total_fee {
+        recommended = false;
+        reasons.push(format!("spendable funds don't cover the {} fee", total_fee));
+    }
+    if savings_notes_affected > 0 {
+        reasons.push(format!("{} savings notes would be merged as well", savings_notes_affected));
+    }
+    if recommended {
+        reasons.push(format!(
+            "{} dust notes ({:.8}) are merged in {} transaction(s) for {} in fees",
+            info.dust_utxos, info.dust_value, transactions, total_fee
+        ));
+    }
+
+    // Merging leaves one large note; split it back into as many usable notes as there are now
+    let current = read_split_settings(app, address);
+    let resplit_count = info.usable_utxos.clamp(2, MAX_SPLIT_COUNT);
+    let resplit = (recommended && info.usable_utxos > 1).then(|| UtxoSplitSettings {
+        enabled: current.enabled,
+        target_usable_utxos: resplit_count,
+        split_count: resplit_count,
+        split_amount: current.split_amount.max(MIN_SPLIT_AMOUNT),
+    });
+
+    Ok(UtxoCleanupPlan {
+        address: address.to_string(),
+        dust_utxos: info.dust_utxos,
+        dust_value: info.dust_value,
+        usable_utxos: info.usable_utxos,
+        notes_merged: mergeable.len(),
+        transactions,
+        total_fee,
+        savings_notes_affected,
+        recommended,
+        reasons,
+        resplit,
+    })
+}
+
+// Background check after a poll: only if enabled, supported and not checked recently
+pub fn spawn_maintenance<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, address: String) {
+    let settings = read_split_settings(app, &address);
+    if !settings.enabled || !supports(rpc_port, Feature::SendCurrency) {
+        return;
+    }
+    if !try_start(&address, true) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match maintain_capacity(&app, &rpc_user, &rpc_pass, rpc_port, &address, &settings).await {
+            Ok(outcome) => {
+                if outcome.split_txid.is_some() {
+                    emit_event(&app, UTXO_SPLIT_EVENT, outcome);
+                } else {
+                    log::debug!("UTXO maintenance for {}: {:?}", address, outcome.skipped_reason);
+                }
+            }
+            Err(e) => log::warn!("UTXO maintenance for {} failed: {}", address, e),
+        }
+        finish(&address);
+    });
+}
+
+// Manual check (run_utxo_maintenance): not throttled, but never alongside a running check of the same address
+pub async fn run_maintenance_now<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    address: &str,
+) -> Result<UtxoSplitOutcome, UtxoMaintenanceError> {
+    if !try_start(address, false) {
+        return Err(UtxoMaintenanceError::AlreadyRunning(address.to_string()));
+    }
+    let settings = read_split_settings(app, address);
+    let result = maintain_capacity(app, rpc_user, rpc_pass, rpc_port, address, &settings).await;
+    finish(address);
+    result
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_utxo_split_settings<R: Runtime>(app: AppHandle<R>, address: String) -> UtxoSplitSettings {
+    log::debug!("get_utxo_split_settings command received for {}", address);
+    read_split_settings(&app, &address)
+}
+
+#[tauri::command]
+pub fn set_utxo_split_settings<R: Runtime>(app: AppHandle<R>, address: String, settings: UtxoSplitSettings) -> Result<(), UtxoMaintenanceError> {
+    log::info!("set_utxo_split_settings command received for {}: {:?}", address, settings);
+    validate_settings(&settings)?;
+    save_value(&app, SPLIT_STORE_PATH, &address, &settings)?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+// File: src-tauri/src/verification_cache.rs
+// Description: Persisted cache of signature verification results keyed by transaction and memo.
+// Changes:
+// - Created file with VerificationCache (managed state) backed by verification_cache.json.
+// - Only successful verifications are cached; failures may be transient (daemon still indexing) and are retried.
+// - Failures with a retryable cause (daemon errors) are tracked as pending, with exponential backoff between attempts.
+// - Definitive failures (invalid signature, retries given up) are cached as rejected, so they aren't verified again.
+// - Added clear (emergency wipe drops the in-memory state so nothing is written back).
+// - Uses clock::now_secs instead of a local copy.
+// - persist keeps the cache marked dirty when saving fails, so the next call retries the write.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Runtime};
+use super::storage::{load_value, save_value, StorageError};
+use super::clock::now_secs;
+
+const CACHE_STORE_PATH: &str = "verification_cache.json";
+const CACHE_KEY: &str = "verified_memos";
+
+// Delay before retrying a failed verification, doubling per attempt up to the maximum
+const RETRY_BASE_DELAY_SECS: u64 = 60;
+const RETRY_MAX_DELAY_SECS: u64 = 3_600;
+
+// Verifications still failing after this many attempts are given up
+const MAX_RETRY_ATTEMPTS: u32 = 12;
+
+// A verification that failed for a retryable reason (e.g. the daemon's identity index still catching up)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingVerification {
+    pub txid: String,
+    pub receiving_address: String,
+    pub reason: String,
+    pub attempts: u32,
+    pub first_failed_at: u64,
+    pub next_retry_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct CacheState {
+    entries: HashMap<String, bool>, // cache key -> verification result (false: rejected for good)
+    #[serde(default)]
+    pending: HashMap<String, PendingVerification>, // cache key -> retry state
+    #[serde(skip)]
+    dirty: bool,
+}
+
+// Cheap to clone; all clones share the same entries
+#[derive(Clone, Default)]
+pub struct VerificationCache {
+    inner: Arc<Mutex<CacheState>>,
+}
+
+impl VerificationCache {
+    // Load the cache from disk (empty cache if missing or unreadable)
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Self {
+        let state = match load_value::<R, CacheState>(app, CACHE_STORE_PATH, CACHE_KEY) {
+            Ok(Some(state)) => {
+                log::info!("Loaded {} cached signature verifications", state.entries.len());
+                state
+            }
+            Ok(None) => CacheState::default(),
+            Err(e) => {
+                log::warn!("Failed to load verification cache, starting empty: {}", e);
+                CacheState::default()
+            }
+        };
+        VerificationCache { inner: Arc::new(Mutex::new(state)) }
+    }
+
+    // The verification result depends on the memo and (for recipient-bound memos) our receiving address
+    fn cache_key(txid: &str, memo: &str, receiving_address: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(memo.as_bytes());
+        hasher.update(receiving_address.as_bytes());
+        let digest = hex::encode(hasher.finalize());
+        format!("{}:{}", txid, &digest[..16])
+    }
+
+    pub fn get(&self, txid: &str, memo: &str, receiving_address: &str) -> Option<bool> {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.get(&Self::cache_key(txid, memo, receiving_address)).copied()
+    }
+
+    pub fn insert_verified(&self, txid: &str, memo: &str, receiving_address: &str) {
+        let key = Self::cache_key(txid, memo, receiving_address);
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if state.pending.remove(&key).is_some() {
+            log::info!("Verification of tx {} succeeded after retrying", txid);
+            state.dirty = true;
+        }
+        if state.entries.insert(key, true).is_none() {
+            state.dirty = true;
+        }
+    }
+
+    // A definitive negative outcome; the memo is never verified again
+    pub fn insert_rejected(&self, txid: &str, memo: &str, receiving_address: &str) {
+        let key = Self::cache_key(txid, memo, receiving_address);
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state.pending.remove(&key);
+        if state.entries.insert(key, false) != Some(false) {
+            state.dirty = true;
+        }
+    }
+
+    // Record a retryable failure. Failures before the scheduled retry time don't count as attempts.
+    pub fn record_retryable_failure(&self, txid: &str, memo: &str, receiving_address: &str, reason: &str) {
+        let key = Self::cache_key(txid, memo, receiving_address);
+        let now = now_secs();
+        let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let pending = state.pending.entry(key.clone()).or_insert_with(|| PendingVerification {
+            txid: txid.to_string(),
+            receiving_address: receiving_address.to_string(),
+            reason: String::new(),
+            attempts: 0,
+            first_failed_at: now,
+            next_retry_at: now,
+        });
+        pending.reason = reason.to_string();
+        if now >= pending.next_retry_at {
+            pending.attempts += 1;
+            let delay = RETRY_BASE_DELAY_SECS
+                .saturating_mul(1 << (pending.attempts - 1).min(16))
+                .min(RETRY_MAX_DELAY_SECS);
+            pending.next_retry_at = now + delay;
+            if pending.attempts > MAX_RETRY_ATTEMPTS {
+                log::warn!("Giving up verifying tx {} after {} attempts: {}", txid, MAX_RETRY_ATTEMPTS, reason);
+                state.pending.remove(&key);
+                state.entries.insert(key, false);
+            }
+        }
+        state.dirty = true;
+    }
+
+    // A retry is scheduled for later; verifying now would only repeat the failure
+    pub fn retry_deferred(&self, txid: &str, memo: &str, receiving_address: &str) -> bool {
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .pending
+            .get(&Self::cache_key(txid, memo, receiving_address))
+            .is_some_and(|p| p.next_retry_at > now_secs())
+    }
+
+    // Txids received on the address whose retry is due
+    pub fn due_retries(&self, receiving_address: &str) -> HashSet<String> {
+        let now = now_secs();
+        let state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        state
+            .pending
+            .values()
+            .filter(|p| p.receiving_address == receiving_address && p.next_retry_at <= now)
+            .map(|p| p.txid.clone())
+            .collect()
+    }
+
+    // Write new entries to disk (no-op if nothing changed)
+    pub fn persist<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), StorageError> {
+        let snapshot = {
+            let mut state = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.dirty {
+                return Ok(());
+            }
+            // Cleared up front so changes made while saving mark it again
+            state.dirty = false;
+            state.clone()
+        };
+        if let Err(e) = save_value(app, CACHE_STORE_PATH, CACHE_KEY, &snapshot) {
+            // Not on disk yet; the next persist tries again
+            self.inner.lock().unwrap_or_else(|e| e.into_inner()).dirty = true;
+            return Err(e);
+        }
+        log::debug!("Persisted verification cache ({} entries)", snapshot.entries.len());
+        Ok(())
+    }
+
+    // Drop everything in memory without persisting (emergency wipe)
+    pub fn clear(&self) {
+        *self.inner.lock().unwrap_or_else(|e| e.into_inner()) = Default::default();
+    }
+}
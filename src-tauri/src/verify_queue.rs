@@ -0,0 +1,146 @@
+// File: src-tauri/src/verify_queue.rs
+// Description: Low-priority background verification of a large received-memo backlog (first sync).
+// Changes:
+// - Created file. Polling verifies the most recent conversations first and queues the rest here; the worker
+//   verifies queued memos with low concurrency into the VerificationCache, and later polls deliver them as
+//   cache hits through the normal message pipeline.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, LazyLock, Mutex};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use super::events::{emit_event, VERIFICATION_BACKLOG_EVENT};
+use super::message_rpc::parse_and_verify_message;
+use super::tasks::{run_cancellable, TaskError};
+use super::verification_cache::VerificationCache;
+
+// Verifications in flight for the backlog (polling itself uses MAX_CONCURRENT_VERIFICATIONS)
+const BACKLOG_CONCURRENCY: usize = 2;
+
+// Progress is reported (and the cache persisted) after this many verifications
+const PROGRESS_INTERVAL: usize = 25;
+
+// Task id of the worker; cancel with cancel_task
+pub const BACKLOG_TASK_ID: &str = "verification-backlog";
+
+// A memo waiting for verification
+#[derive(Debug, Clone)]
+pub struct BacklogItem {
+    pub txid: String,
+    pub memo: String,
+}
+
+#[derive(Default)]
+struct Backlog {
+    items: VecDeque<BacklogItem>, // Highest priority first
+    running: bool,
+}
+
+// Receiving address -> queued memos
+static BACKLOGS: LazyLock<Mutex<HashMap<String, Backlog>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BacklogProgress {
+    pub own_private_address: String,
+    pub verified: usize,
+    pub remaining: usize,
+}
+
+// Replace the queue of an address with the memos the last poll deferred (in priority order)
+pub fn enqueue(receiving_address: &str, items: Vec<BacklogItem>) {
+    let mut backlogs = BACKLOGS.lock().unwrap_or_else(|e| e.into_inner());
+    let backlog = backlogs.entry(receiving_address.to_string()).or_default();
+    backlog.items = items.into();
+}
+
+fn take_batch(receiving_address: &str, size: usize) -> (Vec<BacklogItem>, usize) {
+    let mut backlogs = BACKLOGS.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(backlog) = backlogs.get_mut(receiving_address) else { return (Vec::new(), 0) };
+    let take = size.min(backlog.items.len());
+    let batch = backlog.items.drain(..take).collect();
+    (batch, backlog.items.len())
+}
+
+fn set_running(receiving_address: &str, running: bool) -> bool {
+    let mut backlogs = BACKLOGS.lock().unwrap_or_else(|e| e.into_inner());
+    let backlog = backlogs.entry(receiving_address.to_string()).or_default();
+    let was_running = backlog.running;
+    backlog.running = running;
+    was_running
+}
+
+fn persist_cache<R: Runtime>(app: &AppHandle<R>, cache: &VerificationCache) {
+    if let Err(e) = cache.persist(app) {
+        log::warn!("Failed to persist verification cache: {}", e);
+    }
+}
+
+async fn drain<R: Runtime>(
+    app: &AppHandle<R>,
+    rpc_user: &str,
+    rpc_pass: &str,
+    rpc_port: u16,
+    receiving_address: &str,
+) -> Result<usize, TaskError> {
+    let cache = app.state::<VerificationCache>().inner().clone();
+    let semaphore = Arc::new(Semaphore::new(BACKLOG_CONCURRENCY));
+    let mut verified = 0;
+    loop {
+        let (batch, remaining) = take_batch(receiving_address, PROGRESS_INTERVAL);
+        if batch.is_empty() {
+            break;
+        }
+        let mut join_set = JoinSet::new();
+        for item in batch {
+            let (semaphore, cache) = (semaphore.clone(), cache.clone());
+            let (rpc_user, rpc_pass, receiving_address) = (rpc_user.to_string(), rpc_pass.to_string(), receiving_address.to_string());
+            join_set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok();
+                // A valid signature lands in the cache; failures are tracked there as usual
+                parse_and_verify_message(&rpc_user, &rpc_pass, rpc_port, &item.memo, &item.txid, &receiving_address, &cache).await
+            });
+        }
+        while let Some(result) = join_set.join_next().await {
+            if let Ok(Some(_)) = result {
+                verified += 1;
+            }
+        }
+        persist_cache(app, &cache);
+        emit_event(app, VERIFICATION_BACKLOG_EVENT, BacklogProgress {
+            own_private_address: receiving_address.to_string(),
+            verified,
+            remaining,
+        });
+    }
+    log::info!("Verification backlog for {} drained ({} verified)", receiving_address, verified);
+    Ok(verified)
+}
+
+// Start the worker for an address unless it is already running or there is nothing queued
+pub fn spawn_backlog_worker<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, receiving_address: String) {
+    let queued = {
+        let backlogs = BACKLOGS.lock().unwrap_or_else(|e| e.into_inner());
+        backlogs.get(&receiving_address).map_or(0, |b| b.items.len())
+    };
+    if queued == 0 || set_running(&receiving_address, true) {
+        return;
+    }
+    log::info!("Verifying a backlog of {} memos for {} in the background", queued, receiving_address);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = run_cancellable(
+            &app,
+            Some(BACKLOG_TASK_ID.to_string()),
+            "verification-backlog",
+            drain(&app, &rpc_user, &rpc_pass, rpc_port, &receiving_address),
+        )
+        .await;
+        if let Err(e) = result {
+            log::info!("Verification backlog worker for {} stopped: {}", receiving_address, e);
+            persist_cache(&app, &app.state::<VerificationCache>().inner().clone());
+        }
+        set_running(&receiving_address, false);
+    });
+}
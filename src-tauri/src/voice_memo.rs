@@ -0,0 +1,285 @@
+// File: src-tauri/src/voice_memo.rs
+// Description: Short recorded audio clips sent as chunked memos (same transport as inline file attachments).
+// Changes:
+// - Created file. A voice memo is one transaction: a manifest memo (codec, duration, size, checksum) followed by
+//   chunk memos. Received chunks are collected in the message index and reassembled for playback.
+// - Chunking and reassembly use file_request's chunk transport; reassembly is bounded by MAX_VOICE_MEMO_BYTES and
+//   chunk indices beyond it are ignored (the manifest's size comes from the peer).
+// - VoiceMemoError goes into the error log when a command returns it.
+// - Reads the current time through clock::now_secs.
+// - Memo ids come from clock::new_id.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use tauri::State;
+use super::error_log::recorded_command_error;
+use super::file_request::{max_chunks, reassemble_chunks, split_into_chunks, ReassemblyError};
+use super::message_index::MessageIndex;
+use super::message_rpc::{send_signed_memos, ChatMessage};
+use super::rpc_client::VerusRpcError;
+use super::clock::{new_id, now_secs};
+
+// Memo text prefix marking a voice memo payload
+pub const VOICE_MEMO_PREFIX: &str = "nymia-voice:";
+
+// About 8 seconds of speech at 6 kbit/s opus (52 chunk memos, all in one transaction)
+pub const MAX_VOICE_MEMO_BYTES: usize = 6144;
+
+// Longer clips only fit at lower bitrates
+pub const MAX_VOICE_MEMO_DURATION_MS: u32 = 15_000;
+
+// Payload carried in the memo text. Field names are short to leave room in the 512-byte memo.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "t", rename_all = "snake_case")]
+enum VoiceMemo {
+    Manifest {
+        id: String,
+        #[serde(flatten)]
+        manifest: VoiceManifest,
+    },
+    Chunk {
+        id: String,
+        #[serde(rename = "i")]
+        index: u32,
+        #[serde(rename = "x")]
+        data: String, // Hex encoded bytes
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VoiceManifest {
+    #[serde(rename = "m")]
+    pub mime: String, // Container, e.g. audio/ogg; codecs=opus
+    #[serde(rename = "d")]
+    pub duration_ms: u32,
+    #[serde(rename = "s")]
+    pub size: u64,
+    #[serde(rename = "h")]
+    pub sha256: String, // Hex digest of the audio bytes
+    #[serde(rename = "n")]
+    pub chunks: u32,
+}
+
+// A sent or received voice memo, tracked in the message index by memo id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoiceMemoRecord {
+    pub memo_id: String,
+    pub sender: String, // VerusID of the sender
+    pub outgoing: bool,
+    pub txid: String,
+    pub manifest: Option<VoiceManifest>, // None while only chunks have been seen
+    #[serde(default)]
+    pub chunks: BTreeMap<u32, String>, // Audio chunks (hex)
+    pub complete: bool,
+    pub timestamp: u64,
+}
+
+impl VoiceMemoRecord {
+    fn refresh_complete(&mut self) {
+        self.complete = self.manifest.as_ref().is_some_and(|m| self.chunks.len() >= m.chunks as usize);
+    }
+}
+
+// Reassembled audio for the frontend to wrap in a Blob
+#[derive(Serialize, Debug, Clone)]
+pub struct VoiceMemoAudio {
+    pub memo_id: String,
+    pub mime: String,
+    pub duration_ms: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum VoiceMemoError {
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+    #[error("Voice memo is {0} bytes; the limit is {MAX_VOICE_MEMO_BYTES}")]
+    TooLarge(u64),
+    #[error("Voice memo is {0} ms long; the limit is {MAX_VOICE_MEMO_DURATION_MS} ms")]
+    TooLong(u32),
+    #[error("Audio is not an Ogg or WebM opus recording")]
+    UnsupportedFormat,
+    #[error("Unknown voice memo: {0}")]
+    UnknownMemo(String),
+    #[error("Voice memo incomplete: {0}")]
+    Incomplete(String),
+    #[error("Voice memo failed its integrity check")]
+    ChecksumMismatch,
+}
+
+recorded_command_error!(VoiceMemoError, "voice_memo");
+
+impl From<VerusRpcError> for VoiceMemoError {
+    fn from(error: VerusRpcError) -> Self {
+        VoiceMemoError::Rpc(error)
+    }
+}
+
+fn encode_voice_memo(memo: &VoiceMemo) -> String {
+    // Serializing these plain structs cannot fail
+    format!("{}{}", VOICE_MEMO_PREFIX, serde_json::to_string(memo).unwrap_or_default())
+}
+
+fn parse_voice_memo(text: &str) -> Option<VoiceMemo> {
+    let payload = text.strip_prefix(VOICE_MEMO_PREFIX)?;
+    match serde_json::from_str(payload) {
+        Ok(memo) => Some(memo),
+        Err(e) => {
+            log::debug!("Ignoring malformed voice memo: {}", e);
+            None
+        }
+    }
+}
+
+// Container of an opus recording, from its magic bytes (the recorder produces Ogg, Chromium-based webviews WebM)
+fn opus_mime(audio: &[u8]) -> Option<&'static str> {
+    if audio.starts_with(b"OggS") && audio.windows(8).any(|w| w == b"OpusHead") {
+        Some("audio/ogg; codecs=opus")
+    } else if audio.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) && audio.windows(6).any(|w| w == b"A_OPUS") {
+        Some("audio/webm; codecs=opus")
+    } else {
+        None
+    }
+}
+
+fn new_memo_id(sender_identity: &str, sha256: &str) -> String {
+    new_id(&[sender_identity.as_bytes(), sha256.as_bytes()])
+}
+
+// Record voice memo payloads from verified received messages in the index.
+// Chunk memos carry audio only and are removed from the returned list; the manifest stays as the message.
+pub fn ingest_voice_memos(index: &MessageIndex, messages: Vec<ChatMessage>) -> Vec<ChatMessage> {
+    messages
+        .into_iter()
+        .filter(|message| {
+            if message.direction != "received" {
+                return true;
+            }
+            let Some(memo) = parse_voice_memo(&message.text) else { return true };
+            if let VoiceMemo::Chunk { id, index, .. } = &memo {
+                if *index >= max_chunks(MAX_VOICE_MEMO_BYTES) {
+                    log::warn!("Ignoring voice memo chunk {} for {} from {}: beyond the size limit", index, id, message.sender);
+                    return false;
+                }
+            }
+            let (id, is_chunk) = match &memo {
+                VoiceMemo::Manifest { id, .. } => (id.clone(), false),
+                VoiceMemo::Chunk { id, .. } => (id.clone(), true),
+            };
+            let create = || VoiceMemoRecord {
+                memo_id: id.clone(),
+                sender: message.sender.clone(),
+                outgoing: false,
+                txid: message.id.clone(),
+                manifest: None,
+                chunks: BTreeMap::new(),
+                complete: false,
+                timestamp: message.timestamp,
+            };
+            index.update_voice_memo(&id, create, |record| {
+                // Memo ids are chosen by the sender; never let another identity add to a memo
+                if record.outgoing || record.sender != message.sender {
+                    log::warn!("Ignoring voice memo payload {} from {}", id, message.sender);
+                    return;
+                }
+                match memo {
+                    VoiceMemo::Manifest { manifest, .. } => record.manifest = Some(manifest),
+                    VoiceMemo::Chunk { index, data, .. } => {
+                        record.chunks.insert(index, data);
+                    }
+                }
+                record.refresh_complete();
+            });
+            !is_chunk
+        })
+        .collect()
+}
+
+// Send a recorded clip as one transaction (manifest + chunks). Returns the record and the manifest message
+// text, which is what shows up in the conversation.
+#[allow(clippy::too_many_arguments)]
+pub async fn send_voice_memo(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    recipient_z_address: String,
+    sender_identity: String,
+    audio: Vec<u8>,
+    duration_ms: u32,
+    index: &MessageIndex,
+) -> Result<(VoiceMemoRecord, String), VoiceMemoError> {
+    if audio.len() > MAX_VOICE_MEMO_BYTES {
+        return Err(VoiceMemoError::TooLarge(audio.len() as u64));
+    }
+    if duration_ms > MAX_VOICE_MEMO_DURATION_MS {
+        return Err(VoiceMemoError::TooLong(duration_ms));
+    }
+    let mime = opus_mime(&audio).ok_or(VoiceMemoError::UnsupportedFormat)?;
+
+    let sha256 = hex::encode(Sha256::digest(&audio));
+    let memo_id = new_memo_id(&sender_identity, &sha256);
+    let chunks = split_into_chunks(&audio);
+    let manifest = VoiceManifest {
+        mime: mime.to_string(),
+        duration_ms,
+        size: audio.len() as u64,
+        sha256,
+        chunks: chunks.len() as u32,
+    };
+    log::info!("Sending voice memo {} ({} ms, {} bytes, {} chunks)", memo_id, duration_ms, manifest.size, chunks.len());
+
+    let manifest_text = encode_voice_memo(&VoiceMemo::Manifest { id: memo_id.clone(), manifest: manifest.clone() });
+    let mut memo_texts = vec![manifest_text.clone()];
+    memo_texts.extend(chunks.iter().enumerate().map(|(i, data)| {
+        encode_voice_memo(&VoiceMemo::Chunk { id: memo_id.clone(), index: i as u32, data: data.clone() })
+    }));
+    let txid = send_signed_memos(rpc_user, rpc_pass, rpc_port, sender_z_address, recipient_z_address, sender_identity.clone(), memo_texts).await?;
+
+    // Our own copy is kept so the clip can be replayed in the conversation
+    let record = VoiceMemoRecord {
+        memo_id: memo_id.clone(),
+        sender: sender_identity,
+        outgoing: true,
+        txid,
+        manifest: Some(manifest),
+        chunks: chunks.into_iter().enumerate().map(|(i, data)| (i as u32, data)).collect(),
+        complete: true,
+        timestamp: now_secs(),
+    };
+    Ok((index.update_voice_memo(&memo_id, || record, |_| {}), manifest_text))
+}
+
+// Reassemble a voice memo and check it against the manifest's size and checksum
+fn assemble_voice_memo(record: &VoiceMemoRecord) -> Result<VoiceMemoAudio, VoiceMemoError> {
+    let manifest = record
+        .manifest
+        .as_ref()
+        .ok_or_else(|| VoiceMemoError::Incomplete("manifest not received yet".to_string()))?;
+    let data = reassemble_chunks(&record.chunks, manifest.chunks, manifest.size, &manifest.sha256, MAX_VOICE_MEMO_BYTES)
+        .map_err(|e| match e {
+            ReassemblyError::Incomplete { received, expected } => {
+                VoiceMemoError::Incomplete(format!("{} of {} chunks received", received, expected))
+            }
+            ReassemblyError::TooLarge(size) => VoiceMemoError::TooLarge(size),
+            ReassemblyError::ChecksumMismatch => VoiceMemoError::ChecksumMismatch,
+        })?;
+    Ok(VoiceMemoAudio {
+        memo_id: record.memo_id.clone(),
+        mime: manifest.mime.clone(),
+        duration_ms: manifest.duration_ms,
+        data,
+    })
+}
+
+// --- Tauri Commands ---
+
+// Playable audio of a voice memo (id from the manifest message's payload)
+#[tauri::command]
+pub fn get_voice_memo_audio(index: State<'_, MessageIndex>, memo_id: String) -> Result<VoiceMemoAudio, VoiceMemoError> {
+    log::debug!("get_voice_memo_audio command received for {}", memo_id);
+    let record = index.voice_memo(&memo_id).ok_or_else(|| VoiceMemoError::UnknownMemo(memo_id.clone()))?;
+    assemble_voice_memo(&record)
+}
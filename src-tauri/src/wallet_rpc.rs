@@ -5,10 +5,40 @@
 // - Added necessary use statements for rpc_client and serde_json.
 // - Added UtxoInfo struct and get_utxo_info function for Fast Messages feature
 // - Implemented z_listunspent RPC call with UTXO filtering and processing
+// - Balance and UTXO lookups use make_background_rpc_call so they pause while the daemon is overloaded
+// - Added wallet encryption status, encrypt_wallet (daemon restarts afterwards), unlock_wallet and lock_wallet
+// - Added list_notes (typed z_listunspent view of the address's sapling notes)
+// - Added estimate_send_fee (fee, change and note selection of a planned message/gift, before it is signed)
+// - Added async operation helpers (wait_for_operation, get_operation_status) for opid-returning wallet calls
+// - Added get_currency_balances (every currency held at an address via getcurrencybalance, not just the native coin)
+// - Failed operations map their error code like RPC errors (a locked wallet surfaces as WalletLocked)
+// - Added get_wallet_security_status (encryption, unlock time left, key availability; optional per-identity check)
+// - Added get_balance_summary (wallet-wide transparent/private totals and their unconfirmed parts from z_gettotalbalance)
+// - Added create_private_address (new sapling address, with the updateidentity call that attaches it to an identity)
+// - UtxoInfo reports dust (count and value of notes below the usable threshold, DUST_THRESHOLD)
+// - get_currency_balances names currencies the daemon reports by i-address (currency metadata cache)
+// - Wallet status reads the identity (cansignfor / canspendfor) through the block-aware response cache
+// - get_wallet_security_status is folded into get_wallet_encryption_status (one status struct, optional identity).
+// - create_private_address returns the new address even if the identity lookup fails (identity fields None).
+// - The fee estimate memo and the unlock countdown use clock::now_secs.
 
 use serde_json::{json, Value};
-use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::currency::currency_display_name;
+use super::response_cache::make_cached_rpc_call;
+use super::rpc_client::{error_from_code, make_background_rpc_call, make_rpc_call, VerusRpcError};
+use super::memo_codec::{encode_memo, MEMO_MAX_BYTES};
+use super::message_rpc::DEFAULT_TX_FEE;
+use super::protocol::{build_memo, PROTOCOL_VERSION};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use super::clock::now_secs;
+
+// Currency name -> amount held
+pub type CurrencyBalances = BTreeMap<String, f64>;
+
+// Notes below this amount are dust: they can't fund a Fast Message on their own
+pub const DUST_THRESHOLD: f64 = 0.0001;
 
 // UTXO information structure for Fast Messages feature
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +48,10 @@ pub struct UtxoInfo {
     pub total_spendable_value: f64, // Sum of usable UTXOs only
     pub largest_utxo: f64,          // Largest single UTXO amount
     pub smallest_utxo: f64,         // Smallest usable UTXO amount (>= 0.0001)
+    #[serde(default)]
+    pub dust_utxos: u32,            // Count below 0.0001
+    #[serde(default)]
+    pub dust_value: f64,            // Sum of dust UTXOs
 }
 
 // Function to connect and get block height
@@ -34,13 +68,123 @@ pub async fn connect_and_get_block_height(
 // Function to get balance for a z-address
 pub async fn get_private_balance(rpc_user: String, rpc_pass: String, rpc_port: u16, address: String) -> Result<f64, VerusRpcError> {
     log::info!("Fetching private balance for address: {}", address);
-    make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_getbalance", vec![json!(address)]).await
+    make_background_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_getbalance", vec![json!(address)]).await
 }
 
 // Function to get pending balance for a z-address (0 confirmations)
 pub async fn get_pending_balance(rpc_user: String, rpc_pass: String, rpc_port: u16, address: String) -> Result<f64, VerusRpcError> {
     log::info!("Fetching pending balance for address: {}", address);
-    make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_getbalance", vec![json!(address), json!(0)]).await
+    make_background_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_getbalance", vec![json!(address), json!(0)]).await
+}
+
+// All currencies held at an address (native coin and PBaaS currencies, by friendly name).
+// Currencies with a zero balance are left out.
+pub async fn get_currency_balances(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    address: String,
+    minconf: u32,
+) -> Result<CurrencyBalances, VerusRpcError> {
+    log::info!("Fetching currency balances for address: {} (minconf {})", address, minconf);
+    let result: Value = make_background_rpc_call(
+        &rpc_user,
+        &rpc_pass,
+        rpc_port,
+        "getcurrencybalance",
+        vec![json!(address), json!(minconf), json!(true)], // friendlynames
+    )
+    .await?;
+    let mut balances = CurrencyBalances::new();
+    for (currency, amount) in result.as_object().into_iter().flatten() {
+        let Some(amount) = amount.as_f64().filter(|amount| *amount > 0.0) else { continue };
+        // friendlynames covers most currencies; the rest still arrive as i-addresses
+        let name = currency_display_name(&rpc_user, &rpc_pass, rpc_port, currency).await;
+        *balances.entry(name).or_insert(0.0) += amount;
+    }
+    Ok(balances)
+}
+
+// Wallet-wide totals of the connected chain, confirmed and unconfirmed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceSummary {
+    pub chain_name: Option<String>, // From the capability probe; None before it ran
+    pub transparent: f64,
+    pub private: f64,
+    pub total: f64,
+    pub unconfirmed_transparent: f64, // Received but not yet confirmed (not part of the totals above)
+    pub unconfirmed_private: f64,
+    pub unconfirmed_total: f64,
+}
+
+// z_gettotalbalance reports amounts as strings on some daemon versions
+fn total_balance_field(balances: &Value, key: &str) -> f64 {
+    balances
+        .get(key)
+        .and_then(|v| v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+        .unwrap_or(0.0)
+}
+
+pub async fn get_balance_summary(rpc_user: String, rpc_pass: String, rpc_port: u16) -> Result<BalanceSummary, VerusRpcError> {
+    log::info!("Fetching wallet balance summary");
+    let confirmed: Value = make_background_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_gettotalbalance", vec![json!(1)]).await?;
+    let including_pending: Value = make_background_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_gettotalbalance", vec![json!(0)]).await?;
+    // Spending unconfirmed change can make the 0-conf figure smaller; never report negative pending amounts
+    let pending = |key: &str| (total_balance_field(&including_pending, key) - total_balance_field(&confirmed, key)).max(0.0);
+
+    Ok(BalanceSummary {
+        chain_name: super::capabilities::active_capabilities(rpc_port).map(|c| c.chain_name),
+        transparent: total_balance_field(&confirmed, "transparent"),
+        private: total_balance_field(&confirmed, "private"),
+        total: total_balance_field(&confirmed, "total"),
+        unconfirmed_transparent: pending("transparent"),
+        unconfirmed_private: pending("private"),
+        unconfirmed_total: pending("total"),
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewPrivateAddress {
+    pub address: String,
+    // For the identity asked about: the updateidentity call that makes this its private address.
+    // Not run automatically; updating an identity is an on-chain transaction the user should confirm.
+    pub identity_update_command: Option<String>,
+    pub identity_has_private_address: Option<bool>, // The identity already has one (attaching replaces it)
+}
+
+// Create a sapling address in the wallet (for users who have none yet)
+pub async fn create_private_address(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    identity: Option<String>,
+) -> Result<NewPrivateAddress, VerusRpcError> {
+    log::info!("Creating new private address (identity: {:?})", identity);
+    let address: String = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_getnewaddress", vec![json!("sapling")]).await?;
+    log::info!("Created private address {}", address);
+
+    let mut result = NewPrivateAddress { address, identity_update_command: None, identity_has_private_address: None };
+    let Some(identity) = identity else {
+        return Ok(result);
+    };
+    // The address exists in the wallet now; a failed lookup must not lose it, only the identity fields stay None
+    let identity_result: Value = match make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getidentity", vec![json!(identity)]).await {
+        Ok(identity_result) => identity_result,
+        Err(e) => {
+            log::warn!("Created {} but could not look up {}: {:?}", result.address, identity, e);
+            return Ok(result);
+        }
+    };
+    let Some(details) = identity_result.get("identity") else {
+        log::warn!("Created {} but getidentity returned no identity for {}", result.address, identity);
+        return Ok(result);
+    };
+    let name = details.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+    let parent = details.get("parent").and_then(|v| v.as_str()).unwrap_or_default();
+    result.identity_has_private_address = Some(details.get("privateaddress").and_then(|v| v.as_str()).is_some_and(|a| !a.is_empty()));
+    let update = json!({ "name": name, "parent": parent, "privateaddress": result.address });
+    result.identity_update_command = Some(format!("updateidentity '{}'", update));
+    Ok(result)
 }
 
 // NEW function to get UTXO information for Fast Messages
@@ -57,7 +201,7 @@ pub async fn get_utxo_info(
     // maxconf=9999999: All confirmed UTXOs  
     // watchonly=false: Only spendable UTXOs
     // addresses=[address]: Only for this specific address
-    let utxo_list: Value = make_rpc_call(
+    let utxo_list: Value = make_background_rpc_call(
         &rpc_user,
         &rpc_pass,
         rpc_port,
@@ -77,6 +221,8 @@ pub async fn get_utxo_info(
     let mut total_spendable_value = 0.0f64;
     let mut largest_utxo = 0.0f64;
     let mut smallest_utxo = f64::MAX;
+    let mut dust_utxos = 0u32;
+    let mut dust_value = 0.0f64;
 
     for utxo in utxos {
         let amount = utxo["amount"].as_f64().unwrap_or(0.0);
@@ -88,7 +234,7 @@ pub async fn get_utxo_info(
         }
 
         // Filter for usable UTXOs (amount >= 0.0001)
-        if amount >= 0.0001 {
+        if amount >= DUST_THRESHOLD {
             usable_utxos += 1;
             total_spendable_value += amount;
             
@@ -96,6 +242,9 @@ pub async fn get_utxo_info(
             if amount < smallest_utxo {
                 smallest_utxo = amount;
             }
+        } else {
+            dust_utxos += 1;
+            dust_value += amount;
         }
     }
 
@@ -110,16 +259,357 @@ pub async fn get_utxo_info(
         total_spendable_value,
         largest_utxo,
         smallest_utxo,
+        dust_utxos,
+        dust_value,
     };
 
     log::info!(
-        "UTXO analysis complete: {} total UTXOs, {} usable UTXOs, {:.4} total spendable, largest: {:.4}, smallest: {:.4}",
+        "UTXO analysis complete: {} total UTXOs, {} usable UTXOs, {:.4} total spendable, largest: {:.4}, smallest: {:.4}, dust: {} ({:.8})",
         utxo_info.total_utxos,
         utxo_info.usable_utxos,
         utxo_info.total_spendable_value,
         utxo_info.largest_utxo,
-        utxo_info.smallest_utxo
+        utxo_info.smallest_utxo,
+        utxo_info.dust_utxos,
+        utxo_info.dust_value
     );
 
     Ok(utxo_info)
-} 
\ No newline at end of file
+}
+
+// What a note is set aside for (user-assigned, see notes.rs)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteReservation {
+    FastMessages, // Small notes kept available for quick sends
+    Savings,      // Notes the user doesn't want spent on messaging
+}
+
+// A single sapling note from z_listunspent
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SaplingNote {
+    pub txid: String,
+    pub outindex: u32,
+    pub amount: f64,
+    pub confirmations: i64,
+    pub spendable: bool,
+    pub change: bool,               // Note is change from one of our own sends
+    pub reservation: Option<NoteReservation>,
+}
+
+// Lean view of a z_listunspent entry
+#[derive(Debug, Deserialize)]
+struct UnspentNoteEntry {
+    txid: String,
+    #[serde(default)]
+    outindex: u32,
+    amount: f64,
+    #[serde(default)]
+    confirmations: i64,
+    #[serde(default)]
+    spendable: bool,
+    #[serde(default)]
+    change: bool,
+}
+
+// All notes of an address, including unconfirmed ones (largest first)
+pub async fn list_notes(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    address: String,
+) -> Result<Vec<SaplingNote>, VerusRpcError> {
+    log::info!("Listing notes for address: {}", address);
+
+    // minconf=0 so notes of pending sends show up; watchonly=true so view-only notes are listed as unspendable
+    let entries: Vec<UnspentNoteEntry> = make_background_rpc_call(
+        &rpc_user,
+        &rpc_pass,
+        rpc_port,
+        "z_listunspent",
+        vec![json!(0), json!(9999999), json!(true), json!([address])],
+    ).await?;
+
+    let mut notes: Vec<SaplingNote> = entries
+        .into_iter()
+        .map(|entry| SaplingNote {
+            txid: entry.txid,
+            outindex: entry.outindex,
+            amount: entry.amount,
+            confirmations: entry.confirmations,
+            spendable: entry.spendable,
+            change: entry.change,
+            reservation: None,
+        })
+        .collect();
+    notes.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+    Ok(notes)
+}
+
+// Length of a base64 identity signature from signmessage (version, height and one compact signature)
+const SIGNATURE_CHARS_ESTIMATE: usize = 96;
+
+// Serialized sizes of sapling transaction parts, for the size estimate
+const TX_OVERHEAD_BYTES: u64 = 100;
+const SAPLING_SPEND_BYTES: u64 = 384;
+const SAPLING_OUTPUT_BYTES: u64 = 948; // Includes the encrypted 512-byte memo, so memo length doesn't change it
+
+// Expected cost of a planned send, shown before the user confirms
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SendFeeEstimate {
+    pub amount: f64,
+    pub fee: f64,
+    pub total: f64,                  // amount + fee
+    pub change: f64,                 // Returned to the sender's address
+    pub selected_notes: Vec<String>, // txid:outindex of the notes expected to be spent
+    pub sufficient_funds: bool,
+    pub memo_bytes: usize,           // Encoded size of the signed memo (after compression)
+    pub memo_fits: bool,             // Within the 512-byte memo field
+    pub output_count: usize,
+    pub estimated_size_bytes: u64,
+}
+
+// Estimate fee and change of a message/gift from the sender's confirmed notes.
+// Mirrors z_sendmany with the defaults used for sending (minconf 1, default fee); the daemon selects the notes
+// itself, so the selection here (largest first) is an approximation. memo_count > 1 covers chunked payloads.
+#[allow(clippy::too_many_arguments)]
+pub async fn estimate_send_fee(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    sender_z_address: String,
+    sender_identity: String,
+    amount: f64,
+    memo_text: String,
+    memo_count: usize,
+) -> Result<SendFeeEstimate, VerusRpcError> {
+    log::info!("Estimating send fee for amount {} from {}", amount, sender_z_address);
+    let notes = list_notes(rpc_user, rpc_pass, rpc_port, sender_z_address).await?;
+
+    let total = amount + DEFAULT_TX_FEE;
+    let mut selected_notes = Vec::new();
+    let mut selected_value = 0.0;
+    for note in notes.iter().filter(|n| n.spendable && n.confirmations >= 1) {
+        if selected_value >= total {
+            break;
+        }
+        selected_value += note.amount;
+        selected_notes.push(format!("{}:{}", note.txid, note.outindex));
+    }
+    let sufficient_funds = selected_value >= total;
+    let change = if sufficient_funds { selected_value - total } else { 0.0 };
+
+    // The signature isn't known before signing; a placeholder of typical length gives the memo size
+    let timestamp = now_secs();
+    let memo = build_memo(&memo_text, &sender_identity, timestamp, PROTOCOL_VERSION, &"A".repeat(SIGNATURE_CHARS_ESTIMATE));
+    let (memo_bytes, memo_fits) = match encode_memo(&memo) {
+        Ok(bytes) => (bytes.len(), true),
+        Err(VerusRpcError::MemoTooLong(len)) => (len, false),
+        Err(_) => (memo.len(), memo.len() <= MEMO_MAX_BYTES),
+    };
+
+    // Sapling transactions are padded to at least two outputs
+    let output_count = (memo_count.max(1) + usize::from(change > 0.0)).max(2);
+    let estimated_size_bytes = TX_OVERHEAD_BYTES
+        + SAPLING_SPEND_BYTES * selected_notes.len().max(1) as u64
+        + SAPLING_OUTPUT_BYTES * output_count as u64;
+
+    Ok(SendFeeEstimate {
+        amount,
+        fee: DEFAULT_TX_FEE,
+        total,
+        change,
+        selected_notes,
+        sufficient_funds,
+        memo_bytes,
+        memo_fits,
+        output_count,
+        estimated_size_bytes,
+    })
+}
+
+// How often async wallet operations are checked
+const OPERATION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// State of an async wallet operation (sendcurrency, z_shieldcoinbase, z_mergetoaddress...)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WalletOperationStatus {
+    pub opid: String,
+    pub status: String, // queued | executing | success | failed | cancelled
+    pub txid: Option<String>,
+    pub error: Option<String>,
+}
+
+impl WalletOperationStatus {
+    fn from_value(opid: &str, value: &Value) -> Self {
+        WalletOperationStatus {
+            opid: opid.to_string(),
+            status: value.get("status").and_then(|v| v.as_str()).unwrap_or("unknown").to_string(),
+            txid: value.pointer("/result/txid").and_then(|v| v.as_str()).map(String::from),
+            error: value.pointer("/error/message").and_then(|v| v.as_str()).map(String::from),
+        }
+    }
+}
+
+// Current state of operations (without removing finished ones from the daemon's list)
+pub async fn get_operation_status(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    opids: Vec<String>,
+) -> Result<Vec<WalletOperationStatus>, VerusRpcError> {
+    let results: Vec<Value> = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "z_getoperationstatus", vec![json!(opids)]).await?;
+    Ok(results
+        .iter()
+        .filter_map(|value| {
+            let opid = value.get("id").and_then(|v| v.as_str())?;
+            Some(WalletOperationStatus::from_value(opid, value))
+        })
+        .collect())
+}
+
+// Wait for an async wallet operation (opid) and return its txid
+pub async fn wait_for_operation(rpc_user: &str, rpc_pass: &str, rpc_port: u16, opid: &str, timeout: Duration) -> Result<String, VerusRpcError> {
+    let deadline = std::time::Instant::now() + timeout;
+    while std::time::Instant::now() < deadline {
+        tokio::time::sleep(OPERATION_POLL_INTERVAL).await;
+        let results: Vec<Value> = make_rpc_call(rpc_user, rpc_pass, rpc_port, "z_getoperationresult", vec![json!([opid])]).await?;
+        let Some(result) = results.first() else { continue }; // Still executing
+        let status = WalletOperationStatus::from_value(opid, result);
+        return match (status.status.as_str(), status.txid) {
+            ("success", Some(txid)) => Ok(txid),
+            ("success", None) => Err(VerusRpcError::ParseError("operation result has no txid".to_string())),
+            _ => Err(error_from_code(
+                result.pointer("/error/code").and_then(|v| v.as_i64()).unwrap_or(0) as i32,
+                status.error.unwrap_or_else(|| "operation failed".to_string()),
+            )),
+        };
+    }
+    Err(VerusRpcError::Timeout)
+}
+
+// Minimum passphrase length accepted for wallet encryption
+const MIN_WALLET_PASSPHRASE_LENGTH: usize = 12;
+
+// Wallet encryption state derived from getwalletinfo, plus what the login flow needs to know before the first send
+// (unlock time left, key availability, and optionally whether an identity can sign and spend)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletEncryptionStatus {
+    pub encrypted: bool,
+    pub locked: bool,                 // Encrypted and currently locked
+    pub unlocked_until: Option<u64>,  // Unix time the wallet relocks (None if unencrypted)
+    pub recommend_encryption: bool,   // Nudge the user towards encrypting
+    pub unlock_seconds_remaining: Option<u64>, // Only while an encrypted wallet is unlocked
+    pub private_keys_available: bool,          // False for wallets created without private keys
+    pub identity_can_sign: Option<bool>,       // cansignfor of the requested identity
+    pub identity_can_spend: Option<bool>,      // canspendfor of the requested identity
+    pub unlock_required: bool,                 // Sends will fail with WalletLocked until unlocked
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptWalletResult {
+    pub daemon_message: String,
+    pub restart_required: bool,
+    pub warnings: Vec<String>, // Shown prominently by the frontend
+}
+
+pub async fn get_wallet_encryption_status(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    identity: Option<String>,
+) -> Result<WalletEncryptionStatus, VerusRpcError> {
+    log::info!("Fetching wallet encryption status (identity: {:?})", identity);
+    let wallet_info: Value = make_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getwalletinfo", vec![]).await?;
+
+    // unlocked_until is only present for encrypted wallets; 0 means locked
+    let unlocked_until = wallet_info.get("unlocked_until").and_then(|v| v.as_u64());
+    let encrypted = unlocked_until.is_some();
+    let locked = unlocked_until == Some(0);
+    let now = now_secs();
+    let unlock_seconds_remaining = unlocked_until.filter(|until| *until > 0).map(|until| until.saturating_sub(now));
+    // Only reported by daemons that support key-less wallets
+    let private_keys_available = wallet_info.get("private_keys_enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+
+    let (identity_can_sign, identity_can_spend) = match identity {
+        Some(identity) => {
+            let result: Value = make_cached_rpc_call(&rpc_user, &rpc_pass, rpc_port, "getidentity", vec![json!(identity)]).await?;
+            (
+                Some(result.get("cansignfor").and_then(|v| v.as_bool()).unwrap_or(false)),
+                Some(result.get("canspendfor").and_then(|v| v.as_bool()).unwrap_or(false)),
+            )
+        }
+        None => (None, None),
+    };
+
+    Ok(WalletEncryptionStatus {
+        encrypted,
+        locked,
+        unlocked_until,
+        recommend_encryption: !encrypted,
+        unlock_seconds_remaining,
+        private_keys_available,
+        identity_can_sign,
+        identity_can_spend,
+        unlock_required: locked,
+    })
+}
+
+// Encrypt an unencrypted wallet. The daemon shuts itself down afterwards and must be restarted.
+pub async fn encrypt_wallet(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    passphrase: String,
+) -> Result<EncryptWalletResult, VerusRpcError> {
+    if passphrase.chars().count() < MIN_WALLET_PASSPHRASE_LENGTH {
+        return Err(VerusRpcError::WeakPassphrase(MIN_WALLET_PASSPHRASE_LENGTH));
+    }
+
+    let status = get_wallet_encryption_status(rpc_user.clone(), rpc_pass.clone(), rpc_port, None).await?;
+    if status.encrypted {
+        return Err(VerusRpcError::WalletEncryptionState("Wallet is already encrypted".to_string()));
+    }
+
+    log::warn!("Encrypting wallet. The daemon will shut down and must be restarted afterwards.");
+    let daemon_message = match make_rpc_call::<String>(&rpc_user, &rpc_pass, rpc_port, "encryptwallet", vec![json!(passphrase)]).await {
+        Ok(message) => message,
+        // The daemon may stop before the response is delivered
+        Err(VerusRpcError::NetworkError(e)) => {
+            log::warn!("Connection lost after encryptwallet (daemon stopping): {}", e);
+            "Wallet encrypted; daemon is stopping.".to_string()
+        }
+        Err(e) => return Err(e),
+    };
+    log::warn!("encryptwallet completed: {}", daemon_message);
+
+    Ok(EncryptWalletResult {
+        daemon_message,
+        restart_required: true,
+        warnings: vec![
+            "The Verus daemon is shutting down. Restart it before using Nymia again.".to_string(),
+            "If you forget this passphrase, your funds and messages are lost. It cannot be recovered.".to_string(),
+            "Make a new wallet backup now: backups made before encryption still hold unencrypted keys.".to_string(),
+        ],
+    })
+}
+
+// Unlock an encrypted wallet for the given number of seconds
+pub async fn unlock_wallet(
+    rpc_user: String,
+    rpc_pass: String,
+    rpc_port: u16,
+    passphrase: String,
+    timeout_secs: u64,
+) -> Result<(), VerusRpcError> {
+    log::info!("Unlocking wallet for {} seconds", timeout_secs);
+    make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "walletpassphrase", vec![json!(passphrase), json!(timeout_secs)])
+        .await
+        .map(|_| ())
+}
+
+pub async fn lock_wallet(rpc_user: String, rpc_pass: String, rpc_port: u16) -> Result<(), VerusRpcError> {
+    log::info!("Locking wallet");
+    make_rpc_call::<Value>(&rpc_user, &rpc_pass, rpc_port, "walletlock", vec![]).await.map(|_| ())
+}
+
@@ -0,0 +1,153 @@
+// File: src-tauri/src/wipe.rs
+// Description: Emergency wipe of all local Nymia data.
+// Changes:
+// - Created file. emergency_wipe overwrites and deletes everything in the app data, config, cache and log
+//   directories (stores, caches, crash logs, exports saved there), optionally locks the wallet, then exits.
+// - Pollers, sessions and watchers are stopped and the in-memory caches cleared before wiping, so nothing is
+//   written back afterwards. Loaded stores are closed by the relative path they were opened with, and nothing is
+//   logged once the files are gone (the log file would be recreated).
+// - WipeError goes into the error log when a command returns it.
+
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::StoreExt;
+use super::credentials::load_credentials;
+use super::error_log::recorded_command_error;
+use super::identity_cache::IdentityCache;
+use super::message_index::MessageIndex;
+use super::message_store::MessageStore;
+use super::verification_cache::VerificationCache;
+use super::wallet_rpc::lock_wallet;
+
+// Must be passed verbatim; guards against an accidental or scripted call
+pub const WIPE_CONFIRMATION_PHRASE: &str = "WIPE ALL NYMIA DATA";
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct WipeReport {
+    pub files_wiped: usize,
+    pub failures: Vec<String>, // Paths that could not be overwritten or removed
+    pub wallet_locked: bool,
+}
+
+#[derive(Debug, thiserror::Error, Serialize)]
+#[serde(remote = "Self")]
+pub enum WipeError {
+    #[error("Confirmation phrase does not match")]
+    ConfirmationMismatch,
+}
+
+recorded_command_error!(WipeError, "wipe");
+
+// All directories the app writes to (deduplicated; they coincide on some platforms)
+fn app_dirs<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let path = app.path();
+    let mut dirs: Vec<PathBuf> = [path.app_data_dir(), path.app_local_data_dir(), path.app_config_dir(), path.app_cache_dir(), path.app_log_dir()]
+        .into_iter()
+        .filter_map(Result::ok)
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => collect_files(&path, files),
+            Ok(t) if t.is_file() => files.push(path),
+            _ => {} // Symlinks are removed with the directory, never followed
+        }
+    }
+}
+
+// Stop everything that writes to the stores and drop what would be persisted again
+fn stop_background_work<R: Runtime>(app: &AppHandle<R>) {
+    super::sessions::close_all_sessions();
+    super::contact_watcher::stop_contact_watcher(None);
+    super::identity_watcher::stop_identity_watcher(None);
+    super::sync_status::stop_sync_watcher(None);
+    super::zmq_listener::stop_zmq_listener(None);
+    app.state::<MessageStore>().clear();
+    app.state::<VerificationCache>().clear();
+    app.state::<MessageIndex>().clear();
+    app.state::<IdentityCache>().clear();
+}
+
+// Stores are registered under the path they were opened with, relative to the app data directory
+fn close_store<R: Runtime>(app: &AppHandle<R>, file: &Path) {
+    let Ok(data_dir) = app.path().app_data_dir() else { return };
+    let Ok(relative) = file.strip_prefix(&data_dir) else { return };
+    if let Some(store) = app.get_store(relative) {
+        store.close_resource();
+    }
+}
+
+// Overwrite the contents with zeros before unlinking so the data isn't trivially recoverable.
+// Best effort: SSDs and copy-on-write filesystems may still keep old blocks.
+fn overwrite_and_remove(path: &Path) -> std::io::Result<()> {
+    let len = std::fs::metadata(path)?.len();
+    {
+        let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+        let zeros = [0u8; 8192];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(zeros.len() as u64) as usize;
+            file.write_all(&zeros[..chunk])?;
+            remaining -= chunk as u64;
+        }
+        file.sync_all()?;
+    }
+    std::fs::remove_file(path)
+}
+
+// --- Tauri Commands ---
+
+// Wipe all local data and exit. Individual failures don't stop the wipe; they are reported.
+#[tauri::command]
+pub async fn emergency_wipe<R: Runtime>(app: AppHandle<R>, confirmation: String, lock_wallet_first: bool) -> Result<WipeReport, WipeError> {
+    if confirmation != WIPE_CONFIRMATION_PHRASE {
+        return Err(WipeError::ConfirmationMismatch);
+    }
+    log::warn!("emergency_wipe command received (lock wallet: {})", lock_wallet_first);
+    let mut report = WipeReport::default();
+
+    // Needs the stored credentials, so before they are deleted
+    if lock_wallet_first {
+        match load_credentials(app.clone()).await {
+            Ok(creds) => match lock_wallet(creds.rpc_user, creds.rpc_pass, creds.rpc_port).await {
+                Ok(()) => report.wallet_locked = true,
+                Err(e) => log::warn!("Emergency wipe: failed to lock wallet: {:?}", e),
+            },
+            Err(e) => log::warn!("Emergency wipe: no credentials to lock the wallet with: {}", e),
+        }
+    }
+
+    stop_background_work(&app);
+    let dirs = app_dirs(&app);
+    let mut files = Vec::new();
+    for dir in &dirs {
+        collect_files(dir, &mut files);
+    }
+    log::warn!("Emergency wipe: wiping {} files", files.len());
+    // From here on nothing is logged; failures are only reported
+    for file in &files {
+        // Loaded stores would otherwise be written back to disk on exit
+        close_store(&app, file);
+        match overwrite_and_remove(file) {
+            Ok(()) => report.files_wiped += 1,
+            Err(_) => report.failures.push(file.display().to_string()),
+        }
+    }
+    for dir in &dirs {
+        if dir.exists() && std::fs::remove_dir_all(dir).is_err() {
+            report.failures.push(dir.display().to_string());
+        }
+    }
+
+    app.exit(0);
+    Ok(report)
+}
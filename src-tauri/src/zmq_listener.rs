@@ -0,0 +1,531 @@
+// File: src-tauri/src/zmq_listener.rs
+// Description: Push notifications from the daemon's ZMQ publisher (hashblock / hashtx) instead of timer-based polling.
+// Changes:
+// - Created file. A minimal ZMTP 3.0 subscriber (NULL security, tcp:// endpoints) on top of tokio, so no native
+//   libzmq is needed. The endpoint comes from the caller or getzmqnotifications. While connected, new blocks drop
+//   the block-aware response cache, daemon-notification events tell the frontend to refresh balances and messages,
+//   and background pollers (wait_for_next_poll) run on notifications instead of their timers. When ZMQ isn't
+//   enabled or the connection drops, pollers fall back to their timers and the listener keeps reconnecting.
+// - Connection state and notification counters are per RPC port: pollers only follow their own daemon's
+//   notifications (wait_for_next_poll takes the port).
+// - Dropped the private now_secs in favour of clock::now_secs.
+// - Unit tests for endpoint parsing, frame reading and the subscriber handshake (against a local fake publisher).
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Runtime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use super::events::{emit_event, DAEMON_NOTIFICATION_EVENT, ZMQ_STATUS_EVENT};
+use super::response_cache::invalidate_responses;
+use super::rpc_client::{make_rpc_call, VerusRpcError};
+use super::clock::now_secs;
+
+const TOPIC_BLOCK: &str = "hashblock";
+const TOPIC_TX: &str = "hashtx";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Blocks come about every minute; this long without any message means the connection is dead
+const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+const RECONNECT_MIN_DELAY: Duration = Duration::from_secs(30);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+// Frames above this are not notifications
+const MAX_FRAME_SIZE: u64 = 1024 * 1024;
+
+// Transaction events to the frontend are coalesced over this window
+const TX_EVENT_SPACING: Duration = Duration::from_secs(2);
+
+// Pollers driven by notifications: at most one poll per MIN_PUSH_POLL_SPACING, and one at least every
+// PUSH_SAFETY_INTERVAL in case a notification was missed
+const MIN_PUSH_POLL_SPACING: Duration = Duration::from_secs(10);
+const PUSH_SAFETY_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PushTrigger {
+    Block,
+    Transaction, // Any new transaction (mempool or block)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DaemonNotification {
+    pub rpc_port: u16,
+    pub kind: PushTrigger,
+    pub hash: String,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ZmqStatus {
+    pub rpc_port: u16,
+    pub endpoint: Option<String>,
+    pub connected: bool,
+    pub last_notification: Option<u64>,
+    pub error: Option<String>, // Why the listener isn't connected (None while connected)
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ZmqError {
+    #[error("ZMQ not enabled on the daemon")]
+    NotEnabled,
+    #[error("Unsupported ZMQ endpoint {0} (only tcp:// is supported)")]
+    UnsupportedEndpoint(String),
+    #[error("ZMQ connection failed: {0}")]
+    Io(String),
+    #[error("ZMQ protocol error: {0}")]
+    Protocol(String),
+    #[error("No ZMQ messages for {0} seconds")]
+    Stale(u64),
+    #[error("RPC error: {0}")]
+    Rpc(VerusRpcError),
+}
+
+impl From<std::io::Error> for ZmqError {
+    fn from(error: std::io::Error) -> Self {
+        ZmqError::Io(error.to_string())
+    }
+}
+
+struct Listener {
+    generation: u64,
+    status: ZmqStatus,
+}
+
+// RPC port -> listener; bumping the generation stops it
+static LISTENERS: LazyLock<Mutex<HashMap<u16, Listener>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Notification counters the pollers wait on
+struct Sequences {
+    block: watch::Sender<u64>,
+    tx: watch::Sender<u64>,
+}
+
+impl Default for Sequences {
+    fn default() -> Self {
+        Sequences { block: watch::channel(0).0, tx: watch::channel(0).0 }
+    }
+}
+
+// RPC port -> its notification counters (kept across listener restarts, so waiting pollers stay subscribed)
+static SEQUENCES: LazyLock<Mutex<HashMap<u16, Sequences>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn bump_sequence(rpc_port: u16, trigger: PushTrigger) {
+    let mut sequences = SEQUENCES.lock().unwrap_or_else(|e| e.into_inner());
+    let sequences = sequences.entry(rpc_port).or_default();
+    let sequence = match trigger {
+        PushTrigger::Block => &sequences.block,
+        PushTrigger::Transaction => &sequences.tx,
+    };
+    sequence.send_modify(|sequence| *sequence += 1);
+}
+
+fn push_connected(rpc_port: u16) -> bool {
+    LISTENERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&rpc_port)
+        .is_some_and(|listener| listener.status.connected)
+}
+
+// Sleep until the next poll of a background poller on a port: `interval` without push notifications from that
+// port's daemon; with them, until the next notification of the given kind (spaced by MIN_PUSH_POLL_SPACING, at
+// most PUSH_SAFETY_INTERVAL). Transactions arrive far more often than blocks, so pollers that shouldn't run more
+// often than their timer wait on blocks.
+pub async fn wait_for_next_poll(rpc_port: u16, interval: Duration, trigger: PushTrigger) {
+    if !push_connected(rpc_port) {
+        tokio::time::sleep(interval).await;
+        return;
+    }
+    let mut sequence = {
+        let mut sequences = SEQUENCES.lock().unwrap_or_else(|e| e.into_inner());
+        let sequences = sequences.entry(rpc_port).or_default();
+        match trigger {
+            PushTrigger::Block => sequences.block.subscribe(),
+            PushTrigger::Transaction => sequences.tx.subscribe(),
+        }
+    };
+    let started = Instant::now();
+    tokio::time::sleep(MIN_PUSH_POLL_SPACING).await;
+    if sequence.has_changed().unwrap_or(false) {
+        return;
+    }
+    let remaining = PUSH_SAFETY_INTERVAL.saturating_sub(started.elapsed());
+    let _ = tokio::time::timeout(remaining, sequence.changed()).await;
+}
+
+fn update_status<R: Runtime>(app: &AppHandle<R>, rpc_port: u16, generation: u64, update: impl FnOnce(&mut ZmqStatus)) {
+    let status = {
+        let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(listener) = listeners.get_mut(&rpc_port).filter(|listener| listener.generation == generation) else { return };
+        let was_connected = listener.status.connected;
+        update(&mut listener.status);
+        if listener.status.connected == was_connected && listener.status.error.is_none() {
+            return;
+        }
+        listener.status.clone()
+    };
+    emit_event(app, ZMQ_STATUS_EVENT, status);
+}
+
+// "tcp://host:port" -> "host:port"; wildcard bind addresses mean the local daemon
+fn tcp_address(endpoint: &str) -> Result<String, ZmqError> {
+    let address = endpoint
+        .trim()
+        .strip_prefix("tcp://")
+        .ok_or_else(|| ZmqError::UnsupportedEndpoint(endpoint.to_string()))?;
+    Ok(match address.rsplit_once(':') {
+        Some(("*", port)) | Some(("0.0.0.0", port)) => format!("127.0.0.1:{}", port),
+        _ => address.to_string(),
+    })
+}
+
+// Publisher address for our topics, as reported by the daemon. Blocks matter most (they also bump the transaction
+// pollers), so if the topics are published on different endpoints, the hashblock one is used.
+async fn discover_endpoint(rpc_user: &str, rpc_pass: &str, rpc_port: u16) -> Result<String, ZmqError> {
+    let notifications: Vec<Value> = match make_rpc_call(rpc_user, rpc_pass, rpc_port, "getzmqnotifications", vec![]).await {
+        Ok(notifications) => notifications,
+        // Daemons without the method can't report it
+        Err(VerusRpcError::Rpc { code: -32601, .. }) => return Err(ZmqError::NotEnabled),
+        Err(e) => return Err(ZmqError::Rpc(e)),
+    };
+    let address_of = |kind: &str| {
+        notifications
+            .iter()
+            .find(|n| n.get("type").and_then(|v| v.as_str()) == Some(kind))
+            .and_then(|n| n.get("address").and_then(|v| v.as_str()))
+            .map(String::from)
+    };
+    address_of("pubhashblock").or_else(|| address_of("pubhashtx")).ok_or(ZmqError::NotEnabled)
+}
+
+// --- ZMTP 3.0 ---
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+struct Frame {
+    more: bool,
+    command: bool,
+    body: Vec<u8>,
+}
+
+async fn write_frame(stream: &mut TcpStream, flags: u8, body: &[u8]) -> Result<(), ZmqError> {
+    let mut frame = Vec::with_capacity(body.len() + 9);
+    if body.len() > u8::MAX as usize {
+        frame.push(flags | FLAG_LONG);
+        frame.extend_from_slice(&(body.len() as u64).to_be_bytes());
+    } else {
+        frame.push(flags);
+        frame.push(body.len() as u8);
+    }
+    frame.extend_from_slice(body);
+    stream.write_all(&frame).await?;
+    Ok(())
+}
+
+async fn read_frame(stream: &mut TcpStream) -> Result<Frame, ZmqError> {
+    let flags = stream.read_u8().await?;
+    let size = if flags & FLAG_LONG != 0 { stream.read_u64().await? } else { stream.read_u8().await? as u64 };
+    if size > MAX_FRAME_SIZE {
+        return Err(ZmqError::Protocol(format!("frame of {} bytes", size)));
+    }
+    let mut body = vec![0u8; size as usize];
+    stream.read_exact(&mut body).await?;
+    Ok(Frame { more: flags & FLAG_MORE != 0, command: flags & FLAG_COMMAND != 0, body })
+}
+
+// Greeting and NULL handshake as a SUB socket, then subscribe to our topics
+async fn connect_subscriber(address: &str) -> Result<TcpStream, ZmqError> {
+    let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+        .await
+        .map_err(|_| ZmqError::Io(format!("connecting to {} timed out", address)))??;
+
+    let mut greeting = [0u8; 64];
+    greeting[0] = 0xFF; // Signature
+    greeting[9] = 0x7F;
+    greeting[10] = 3; // Version 3.0
+    greeting[12..16].copy_from_slice(b"NULL"); // Mechanism
+    stream.write_all(&greeting).await?;
+    let mut peer = [0u8; 64];
+    stream.read_exact(&mut peer).await?;
+    if peer[0] != 0xFF || peer[9] & 0x01 != 0x01 || peer[10] < 3 {
+        return Err(ZmqError::Protocol("peer doesn't speak ZMTP 3".to_string()));
+    }
+
+    let mut ready = vec![5u8];
+    ready.extend_from_slice(b"READY");
+    ready.push(11);
+    ready.extend_from_slice(b"Socket-Type");
+    ready.extend_from_slice(&3u32.to_be_bytes());
+    ready.extend_from_slice(b"SUB");
+    write_frame(&mut stream, FLAG_COMMAND, &ready).await?;
+    let reply = read_frame(&mut stream).await?;
+    let name_len = reply.body.first().copied().unwrap_or(0) as usize;
+    let name = reply.body.get(1..1 + name_len).unwrap_or_default();
+    if !reply.command || name != b"READY" {
+        return Err(ZmqError::Protocol(format!("handshake failed: {}", String::from_utf8_lossy(&reply.body))));
+    }
+
+    // ZMTP 3.0 subscriptions are messages: 0x01 followed by the topic
+    for topic in [TOPIC_BLOCK, TOPIC_TX] {
+        let mut subscription = vec![0x01];
+        subscription.extend_from_slice(topic.as_bytes());
+        write_frame(&mut stream, 0, &subscription).await?;
+    }
+    Ok(stream)
+}
+
+// Read notifications until the connection fails or the listener is stopped
+async fn listen<R: Runtime>(app: &AppHandle<R>, rpc_port: u16, generation: u64, mut stream: TcpStream, is_current: &impl Fn() -> bool) -> Result<(), ZmqError> {
+    let mut last_tx_event: Option<Instant> = None;
+    let mut parts: Vec<Vec<u8>> = Vec::new();
+    while is_current() {
+        let frame = tokio::time::timeout(STALE_AFTER, read_frame(&mut stream))
+            .await
+            .map_err(|_| ZmqError::Stale(STALE_AFTER.as_secs()))??;
+        if frame.command {
+            continue;
+        }
+        parts.push(frame.body);
+        if frame.more {
+            continue;
+        }
+        // topic, hash, sequence number
+        let message = std::mem::take(&mut parts);
+        let (Some(topic), Some(hash)) = (message.first(), message.get(1)) else { continue };
+        let kind = match topic.as_slice() {
+            t if t == TOPIC_BLOCK.as_bytes() => PushTrigger::Block,
+            t if t == TOPIC_TX.as_bytes() => PushTrigger::Transaction,
+            _ => continue,
+        };
+        let hash = hex::encode(hash);
+        update_status(app, rpc_port, generation, |status| status.last_notification = Some(now_secs()));
+        match kind {
+            PushTrigger::Block => {
+                log::debug!("ZMQ: new block {}", hash);
+                invalidate_responses(rpc_port);
+                bump_sequence(rpc_port, PushTrigger::Block);
+                // Blocks confirm transactions too
+                bump_sequence(rpc_port, PushTrigger::Transaction);
+            }
+            PushTrigger::Transaction => {
+                bump_sequence(rpc_port, PushTrigger::Transaction);
+                if last_tx_event.is_some_and(|at| at.elapsed() < TX_EVENT_SPACING) {
+                    continue;
+                }
+                last_tx_event = Some(Instant::now());
+            }
+        }
+        emit_event(app, DAEMON_NOTIFICATION_EVENT, DaemonNotification { rpc_port, kind, hash });
+    }
+    Ok(())
+}
+
+// Subscribe to a daemon's notifications. Without an endpoint it is asked from the daemon (getzmqnotifications);
+// if ZMQ isn't enabled the listener stops and polling stays timer-based. Starting again replaces a running listener.
+pub fn start_zmq_listener<R: Runtime>(app: &AppHandle<R>, rpc_user: String, rpc_pass: String, rpc_port: u16, endpoint: Option<String>) {
+    let generation = {
+        let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+        let generation = listeners.get(&rpc_port).map(|listener| listener.generation).unwrap_or(0) + 1;
+        let status = ZmqStatus { rpc_port, endpoint: endpoint.clone(), ..Default::default() };
+        listeners.insert(rpc_port, Listener { generation, status });
+        generation
+    };
+    let is_current = move || {
+        LISTENERS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&rpc_port)
+            .is_some_and(|listener| listener.generation == generation)
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let endpoint = match endpoint.filter(|e| !e.trim().is_empty()) {
+            Some(endpoint) => endpoint,
+            None => match discover_endpoint(&rpc_user, &rpc_pass, rpc_port).await {
+                Ok(endpoint) => endpoint,
+                Err(e) => {
+                    log::info!("ZMQ notifications unavailable on port {} ({}), polling stays timer-based", rpc_port, e);
+                    update_status(&app, rpc_port, generation, |status| status.error = Some(e.to_string()));
+                    return;
+                }
+            },
+        };
+        let address = match tcp_address(&endpoint) {
+            Ok(address) => address,
+            Err(e) => {
+                update_status(&app, rpc_port, generation, |status| status.error = Some(e.to_string()));
+                return;
+            }
+        };
+        update_status(&app, rpc_port, generation, |status| status.endpoint = Some(endpoint.clone()));
+
+        let mut delay = RECONNECT_MIN_DELAY;
+        while is_current() {
+            let result = match connect_subscriber(&address).await {
+                Ok(stream) => {
+                    log::info!("ZMQ notifications connected ({})", endpoint);
+                    delay = RECONNECT_MIN_DELAY;
+                    update_status(&app, rpc_port, generation, |status| {
+                        status.connected = true;
+                        status.error = None;
+                    });
+                    listen(&app, rpc_port, generation, stream, &is_current).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                log::warn!("ZMQ listener on {}: {}; polling on timers until it reconnects", endpoint, e);
+                update_status(&app, rpc_port, generation, |status| {
+                    status.connected = false;
+                    status.error = Some(e.to_string());
+                });
+            }
+            if !is_current() {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(RECONNECT_MAX_DELAY);
+        }
+        log::debug!("ZMQ listener for port {} stopped", rpc_port);
+    });
+}
+
+// --- Tauri Commands ---
+
+#[tauri::command]
+pub fn get_zmq_status() -> Vec<ZmqStatus> {
+    log::debug!("get_zmq_status command received");
+    LISTENERS.lock().unwrap_or_else(|e| e.into_inner()).values().map(|listener| listener.status.clone()).collect()
+}
+
+// Stop the listener of one port, or all of them (pollers go back to their timers)
+#[tauri::command]
+pub fn stop_zmq_listener(rpc_port: Option<u16>) {
+    log::info!("stop_zmq_listener command received");
+    let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+    listeners.retain(|port, _| rpc_port.is_some_and(|stop| stop != *port));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    // A listener on a free local port and its address
+    async fn local_listener() -> (TcpListener, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (listener, address)
+    }
+
+    // Publisher side of the handshake: greeting, then READY; returns the subscriber's READY body
+    async fn accept_subscriber(listener: &TcpListener) -> (TcpStream, Vec<u8>) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut greeting = [0u8; 64];
+        stream.read_exact(&mut greeting).await.unwrap();
+        let mut reply = [0u8; 64];
+        reply[0] = 0xFF;
+        reply[9] = 0x7F;
+        reply[10] = 3;
+        reply[12..16].copy_from_slice(b"NULL");
+        stream.write_all(&reply).await.unwrap();
+        let ready = read_frame(&mut stream).await.unwrap();
+        assert!(ready.command);
+        let mut body = vec![5u8];
+        body.extend_from_slice(b"READY");
+        write_frame(&mut stream, FLAG_COMMAND, &body).await.unwrap();
+        (stream, ready.body)
+    }
+
+    #[test]
+    fn tcp_address_strips_the_scheme() {
+        assert_eq!(tcp_address("tcp://192.168.1.5:28332").unwrap(), "192.168.1.5:28332");
+        assert_eq!(tcp_address(" tcp://localhost:28332 ").unwrap(), "localhost:28332");
+    }
+
+    #[test]
+    fn tcp_address_maps_wildcards_to_localhost() {
+        assert_eq!(tcp_address("tcp://*:28332").unwrap(), "127.0.0.1:28332");
+        assert_eq!(tcp_address("tcp://0.0.0.0:28332").unwrap(), "127.0.0.1:28332");
+    }
+
+    #[test]
+    fn tcp_address_rejects_other_transports() {
+        assert!(matches!(tcp_address("ipc:///tmp/verusd.sock"), Err(ZmqError::UnsupportedEndpoint(_))));
+        assert!(matches!(tcp_address("127.0.0.1:28332"), Err(ZmqError::UnsupportedEndpoint(_))));
+    }
+
+    #[tokio::test]
+    async fn read_frame_reads_short_and_long_frames() {
+        let (listener, address) = local_listener().await;
+        let long_body = vec![7u8; 300];
+        let expected = long_body.clone();
+        let writer = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(&[FLAG_MORE, 3, b'a', b'b', b'c']).await.unwrap();
+            write_frame(&mut stream, FLAG_COMMAND, &long_body).await.unwrap();
+        });
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+
+        let short = read_frame(&mut stream).await.unwrap();
+        assert!(short.more && !short.command);
+        assert_eq!(short.body, b"abc");
+        let long = read_frame(&mut stream).await.unwrap();
+        assert!(!long.more && long.command);
+        assert_eq!(long.body, expected);
+        writer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn read_frame_rejects_oversized_frames() {
+        let (listener, address) = local_listener().await;
+        let writer = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut header = vec![FLAG_LONG];
+            header.extend_from_slice(&(MAX_FRAME_SIZE + 1).to_be_bytes());
+            stream.write_all(&header).await.unwrap();
+        });
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+        assert!(matches!(read_frame(&mut stream).await, Err(ZmqError::Protocol(_))));
+        writer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn connect_subscriber_handshakes_and_subscribes() {
+        let (listener, address) = local_listener().await;
+        let publisher = tokio::spawn(async move {
+            let (mut stream, ready) = accept_subscriber(&listener).await;
+            let subscriptions = [read_frame(&mut stream).await.unwrap().body, read_frame(&mut stream).await.unwrap().body];
+            (ready, subscriptions)
+        });
+        let _stream = connect_subscriber(&address).await.unwrap();
+
+        let (ready, subscriptions) = publisher.await.unwrap();
+        assert_eq!(&ready[..6], b"\x05READY");
+        assert!(ready.ends_with(b"Socket-Type\x00\x00\x00\x03SUB"));
+        assert_eq!(subscriptions[0], b"\x01hashblock");
+        assert_eq!(subscriptions[1], b"\x01hashtx");
+    }
+
+    #[tokio::test]
+    async fn connect_subscriber_rejects_non_zmtp_peers() {
+        let (listener, address) = local_listener().await;
+        let peer = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 64];
+            stream.read_exact(&mut greeting).await.unwrap();
+            stream.write_all(&[b'H'; 64]).await.unwrap();
+        });
+        assert!(matches!(connect_subscriber(&address).await, Err(ZmqError::Protocol(_))));
+        peer.await.unwrap();
+    }
+}